@@ -0,0 +1,83 @@
+use chrono::{Duration, Utc};
+use parking_lot::Mutex;
+
+use crate::core::DateTime;
+
+/// Source of current time for engine.
+/// Allows to run engine with simulated time for backtesting without touching system time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime;
+}
+
+#[derive(Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime {
+        Utc::now()
+    }
+}
+
+/// System time shifted by constant offset
+pub struct OffsetClock {
+    offset: Duration,
+}
+
+impl OffsetClock {
+    pub fn new(offset: Duration) -> Self {
+        OffsetClock { offset }
+    }
+}
+
+impl Clock for OffsetClock {
+    fn now(&self) -> DateTime {
+        Utc::now() + self.offset
+    }
+}
+
+/// Clock which time changes only manually
+pub struct SimulatedClock {
+    now: Mutex<DateTime>,
+}
+
+impl SimulatedClock {
+    pub fn new(start: DateTime) -> Self {
+        SimulatedClock {
+            now: Mutex::new(start),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock();
+        *now = *now + duration;
+    }
+
+    pub fn set(&self, now: DateTime) {
+        *self.now.lock() = now;
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime {
+        *self.now.lock()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn simulated_clock_changes_only_manually() {
+        let start = Utc.ymd(2021, 1, 1).and_hms(0, 0, 0);
+        let clock = SimulatedClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::minutes(5));
+        assert_eq!(clock.now(), start + Duration::minutes(5));
+
+        clock.set(start);
+        assert_eq!(clock.now(), start);
+    }
+}
@@ -3,19 +3,32 @@ use std::{fmt::Debug, fs::File};
 use toml::value::Value;
 
 use crate::{
+    core::credentials_encryption::{
+        decrypt_credentials, encrypt_credentials, is_encrypted, CREDENTIALS_PASSPHRASE_ENV,
+    },
+    core::exchanges::common::ExchangeAccountId,
     core::settings::{AppSettings, BaseStrategySettings},
     hashmap,
 };
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use serde::Deserialize;
 use std::io::Read;
+use std::path::Path;
 
 pub static EXCHANGE_ACCOUNT_ID: &str = "exchange_account_id";
 pub static API_KEY: &str = "api_key";
 pub static SECRET_KEY: &str = "secret_key";
+// optional name of exchange which credentials belong to
+pub static CREDENTIALS_EXCHANGE: &str = "exchange";
 pub static CONFIG_PATH: &str = "config.toml";
 pub static CREDENTIALS_PATH: &str = "credentials.toml";
+pub static ENV_OVERRIDE_PREFIX: &str = "MMB__";
+pub static ENV_OVERRIDE_SEPARATOR: &str = "__";
 
+/// Load settings and overlay them with environment variables named `MMB__<SECTION>__<KEY>`,
+/// e.g. `MMB__CORE__EXCHANGES__BINANCE0__API_KEY`. Credentials file can be absent if all credentials
+/// are specified by environment variables. Encrypted credentials file is decrypted with passphrase
+/// from `MMB_CREDENTIALS_PASSPHRASE` environment variable
 pub fn load_settings<'a, TSettings>(
     config_path: &str,
     credentials_path: &str,
@@ -23,19 +36,69 @@ pub fn load_settings<'a, TSettings>(
 where
     TSettings: BaseStrategySettings + Clone + Debug + Deserialize<'a>,
 {
+    load_settings_with_env_vars(config_path, credentials_path, std::env::vars())
+}
+
+fn load_settings_with_env_vars<'a, TSettings>(
+    config_path: &str,
+    credentials_path: &str,
+    env_vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<AppSettings<TSettings>>
+where
+    TSettings: BaseStrategySettings + Clone + Debug + Deserialize<'a>,
+{
+    let env_vars = env_vars.into_iter().collect::<Vec<_>>();
+
     let mut settings = String::new();
     File::open(config_path)?.read_to_string(&mut settings)?;
 
-    let mut credentials = String::new();
-    File::open(credentials_path)?.read_to_string(&mut credentials)?;
+    let credentials = match Path::new(credentials_path).exists() {
+        true => {
+            let passphrase = env_vars
+                .iter()
+                .find(|(name, _)| name == CREDENTIALS_PASSPHRASE_ENV)
+                .map(|(_, value)| value.as_str());
+            read_credentials(credentials_path, passphrase)?
+        }
+        false => String::new(),
+    };
+
+    parse_settings_with_env_overrides(&settings, &credentials, env_vars)
+}
+
+fn read_credentials(credentials_path: &str, passphrase: Option<&str>) -> Result<String> {
+    let mut credentials = Vec::new();
+    File::open(credentials_path)?.read_to_end(&mut credentials)?;
+
+    if !is_encrypted(&credentials) {
+        return Ok(String::from_utf8(credentials)?);
+    }
 
-    parse_settings(&mut settings, &mut credentials)
+    let passphrase = passphrase.with_context(|| {
+        format!(
+            "Credentials file {} is encrypted but environment variable {} with passphrase is not set",
+            credentials_path, CREDENTIALS_PASSPHRASE_ENV
+        )
+    })?;
+    decrypt_credentials(&credentials, passphrase)
+        .with_context(|| format!("Unable to read credentials file {}", credentials_path))
 }
 
 pub fn parse_settings<'a, TSettings>(
     settings: &str,
     credentials: &str,
 ) -> Result<AppSettings<TSettings>>
+where
+    TSettings: BaseStrategySettings + Clone + Debug + Deserialize<'a>,
+{
+    parse_settings_with_env_overrides(settings, credentials, std::iter::empty())
+}
+
+fn parse_settings_with_env_overrides<'a, TSettings>(
+    settings: &str,
+    credentials: &str,
+    env_vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<AppSettings<TSettings>>
 where
     TSettings: BaseStrategySettings + Clone + Debug + Deserialize<'a>,
 {
@@ -57,34 +120,214 @@ where
             let exchange_account_id = exchange
                 .get(EXCHANGE_ACCOUNT_ID)
                 .and_then(|v| v.as_str())
+                .map(|v| v.to_owned())
                 .ok_or(anyhow!(
                     "Unable get exchange account id for Exchange in settings"
                 ))?;
 
-            let api_key = credentials
-                .get(exchange_account_id)
-                .and_then(|v| v.get(API_KEY))
-                .and_then(|v| v.as_str())
-                .ok_or(anyhow!("Unable get api_key for Exchange in settings"))?;
-            let secret_key = credentials
-                .get(exchange_account_id)
-                .and_then(|v| v.get(SECRET_KEY))
-                .and_then(|v| v.as_str())
-                .ok_or(anyhow!("Unable get secret_key for Exchange in settings"))?;
+            let exchange_credentials = credentials.get(exchange_account_id.as_str());
+            if let Some(exchange_credentials) = exchange_credentials {
+                validate_credentials_exchange(&exchange_account_id, exchange_credentials)?;
+            }
+
+            // missing credentials can be specified by environment variables
+            for key in [API_KEY, SECRET_KEY] {
+                let value = exchange_credentials
+                    .and_then(|v| v.get(key))
+                    .and_then(|v| v.as_str());
+                if let Some(value) = value {
+                    exchange.insert(key.to_owned(), value.into());
+                }
+            }
+        }
+    }
 
-            exchange.insert(API_KEY.to_owned(), api_key.into());
-            exchange.insert(SECRET_KEY.to_owned(), secret_key.into());
+    let overridden_by = apply_env_overrides(&mut settings, env_vars)?;
+
+    for exchange in get_exchanges_mut(&mut settings).into_iter().flatten() {
+        for key in [API_KEY, SECRET_KEY] {
+            if exchange.get(key).and_then(|v| v.as_str()).is_none() {
+                bail!("Unable get {} for Exchange in settings", key)
+            }
+        }
+    }
+
+    if overridden_by.is_empty() {
+        return settings
+            .try_into()
+            .context("Unable parse combined settings");
+    }
+
+    settings.try_into().with_context(|| {
+        format!(
+            "Unable parse combined settings with overrides from environment variables {}",
+            overridden_by.join(", ")
+        )
+    })
+}
+
+/// Credentials with specified exchange should be used only for accounts of the same exchange
+fn validate_credentials_exchange(exchange_account_id: &str, credentials: &Value) -> Result<()> {
+    let credentials_exchange = match credentials.get(CREDENTIALS_EXCHANGE) {
+        Some(credentials_exchange) => credentials_exchange.as_str().ok_or(anyhow!(
+            "Credentials {} of {} should be a string",
+            CREDENTIALS_EXCHANGE,
+            exchange_account_id
+        ))?,
+        None => return Ok(()),
+    };
+
+    let account_exchange = exchange_account_id
+        .parse::<ExchangeAccountId>()
+        .with_context(|| format!("Unable to validate credentials of {}", exchange_account_id))?;
+    if !credentials_exchange.eq_ignore_ascii_case(account_exchange.exchange_name()) {
+        bail!(
+            "Credentials of exchange {} are specified for exchange account {} of exchange {}",
+            credentials_exchange,
+            exchange_account_id,
+            account_exchange.exchange_name()
+        )
+    }
+
+    Ok(())
+}
+
+/// Returns names of applied environment variables
+fn apply_env_overrides(
+    settings: &mut Value,
+    env_vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Vec<String>> {
+    let mut overridden_by = Vec::new();
+    for (name, value) in env_vars {
+        let path = match name.strip_prefix(ENV_OVERRIDE_PREFIX) {
+            Some(path) => path
+                .split(ENV_OVERRIDE_SEPARATOR)
+                .map(|x| x.to_lowercase())
+                .collect::<Vec<_>>(),
+            None => continue,
+        };
+
+        if path.iter().any(|x| x.is_empty()) {
+            bail!("Invalid settings path in environment variable {}", name)
         }
+
+        override_value(settings, &path, &value, &name)?;
+        overridden_by.push(name);
     }
 
-    settings
-        .try_into()
-        .context("Unable parse combined settings")
+    overridden_by.sort();
+    Ok(overridden_by)
+}
+
+fn override_value(target: &mut Value, path: &[String], value: &str, env_var: &str) -> Result<()> {
+    let (segment, rest) = path.split_first().ok_or(anyhow!(
+        "Environment variable {} overrides a table",
+        env_var
+    ))?;
+
+    match target {
+        Value::Table(table) => {
+            let key = table
+                .keys()
+                .find(|x| x.eq_ignore_ascii_case(segment))
+                .cloned()
+                .unwrap_or_else(|| segment.clone());
+
+            if rest.is_empty() {
+                let value = parse_env_value(table.get(&key), value, env_var)?;
+                let _ = table.insert(key, value);
+                return Ok(());
+            }
+
+            let child = table
+                .entry(key)
+                .or_insert_with(|| Value::Table(Default::default()));
+            override_value(child, rest, value, env_var)
+        }
+        // array items are tables with exchange_account_id like core.exchanges or just indexes
+        Value::Array(items) => {
+            let index = items
+                .iter()
+                .position(|x| {
+                    x.get(EXCHANGE_ACCOUNT_ID)
+                        .and_then(|x| x.as_str())
+                        .map(|x| x.eq_ignore_ascii_case(segment))
+                        .unwrap_or(false)
+                })
+                .or_else(|| segment.parse().ok())
+                .filter(|index| *index < items.len())
+                .ok_or(anyhow!(
+                    "Unable to find item '{}' for environment variable {}",
+                    segment,
+                    env_var
+                ))?;
+
+            override_value(&mut items[index], rest, value, env_var)
+        }
+        _ => bail!(
+            "Environment variable {} overrides a field inside of {}",
+            env_var,
+            target.type_str()
+        ),
+    }
+}
+
+/// Parse value of environment variable with type of overridden value.
+/// Decimals are kept as strings because they are stored as strings in config
+fn parse_env_value(current: Option<&Value>, value: &str, env_var: &str) -> Result<Value> {
+    let parsed = match current {
+        Some(Value::String(_)) => Some(Value::String(value.to_owned())),
+        Some(Value::Integer(_)) => value.parse().ok().map(Value::Integer),
+        Some(Value::Float(_)) => value.parse().ok().map(Value::Float),
+        Some(Value::Boolean(_)) => value.parse().ok().map(Value::Boolean),
+        Some(current) => toml::from_str::<HashMap<String, Value>>(&format!("value = {}", value))
+            .ok()
+            .and_then(|mut x| x.remove("value"))
+            .filter(|x| x.same_type(current)),
+        None => Some(
+            value
+                .parse()
+                .map(Value::Integer)
+                .or_else(|_| value.parse().map(Value::Boolean))
+                .unwrap_or_else(|_| Value::String(value.to_owned())),
+        ),
+    };
+
+    // value itself isn't reported because it can be secret
+    parsed.ok_or(anyhow!(
+        "Unable to parse environment variable {} as {}",
+        env_var,
+        current.map(|x| x.type_str()).unwrap_or("value")
+    ))
 }
 
 pub fn save_settings(settings: &str, config_path: &str, credentials_path: &str) -> Result<()> {
+    let (main_config, credentials) = split_credentials(settings)?;
+
+    File::create(credentials_path)?.write_all(credentials.as_bytes())?;
+    File::create(config_path)?.write_all(main_config.as_bytes())?;
+
+    Ok(())
+}
+
+/// Same as `save_settings` but credentials file is encrypted with passphrase
+pub fn save_encrypted_credentials(
+    settings: &str,
+    config_path: &str,
+    credentials_path: &str,
+    passphrase: &str,
+) -> Result<()> {
+    let (main_config, credentials) = split_credentials(settings)?;
+
+    File::create(credentials_path)?.write_all(&encrypt_credentials(&credentials, passphrase)?)?;
+    File::create(config_path)?.write_all(main_config.as_bytes())?;
+
+    Ok(())
+}
+
+/// Take credentials out of settings, returns main config and credentials config
+fn split_credentials(settings: &str) -> Result<(String, String)> {
     let mut serialized_settings: toml::Value = toml::from_str(settings)?;
-    // Write credentials in their own config file
     let mut credentials_per_exchange = HashMap::new();
 
     let exchanges = get_exchanges_mut(&mut serialized_settings).ok_or(anyhow!(
@@ -112,13 +355,11 @@ pub fn save_settings(settings: &str, config_path: &str, credentials_path: &str)
     }
 
     let serialized_creds = Value::try_from(credentials_per_exchange)?;
-    let mut credentials_config = File::create(credentials_path)?;
-    credentials_config.write_all(&serialized_creds.to_string().as_bytes())?;
 
-    let mut main_config = File::create(config_path)?;
-    main_config.write_all(&serialized_settings.to_string().as_bytes())?;
-
-    Ok(())
+    Ok((
+        serialized_settings.to_string(),
+        serialized_creds.to_string(),
+    ))
 }
 
 fn get_credentials_data(
@@ -142,3 +383,208 @@ fn get_exchanges_mut(serialized: &mut Value) -> Option<&mut Vec<Value>> {
         .get_mut("exchanges")?
         .as_array_mut()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{Amount, CurrencyPair, ExchangeAccountId};
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use serde::Serialize;
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    struct TestStrategySettings {
+        spread: Decimal,
+        max_levels: u32,
+    }
+
+    impl BaseStrategySettings for TestStrategySettings {
+        fn exchange_account_id(&self) -> ExchangeAccountId {
+            "Envtest0".parse().expect("in test")
+        }
+
+        fn currency_pair(&self) -> CurrencyPair {
+            CurrencyPair::from_codes("eth".into(), "btc".into())
+        }
+
+        fn max_amount(&self) -> Amount {
+            dec!(1)
+        }
+    }
+
+    const CONFIG: &str = r#"[strategy]
+spread = "0.1"
+max_levels = 3
+
+[[core.exchanges]]
+exchange_account_id = "Envtest0"
+is_margin_trading = false
+web_socket_host = ""
+web_socket2_host = ""
+rest_host = ""
+websocket_channels = ["depth20"]
+subscribe_to_market_data = true
+currency_pairs = [{ base = "eth", quote = "btc" }]
+"#;
+
+    #[test]
+    fn load_settings_with_env_overrides() {
+        let config_path =
+            std::env::temp_dir().join(format!("mmb_env_config_{}.toml", uuid::Uuid::new_v4()));
+        File::create(&config_path)
+            .expect("in test")
+            .write_all(CONFIG.as_bytes())
+            .expect("in test");
+
+        // credentials and other settings only exist in environment variables
+        let env_vars = [
+            ("MMB__CORE__EXCHANGES__ENVTEST0__API_KEY", "env_api_key"),
+            (
+                "MMB__CORE__EXCHANGES__ENVTEST0__SECRET_KEY",
+                "env_secret_key",
+            ),
+            (
+                "MMB__CORE__EXCHANGES__ENVTEST0__REST_HOST",
+                "https://env.host",
+            ),
+        ];
+        let settings = load_settings_with_env_vars::<TestStrategySettings>(
+            &config_path.to_string_lossy(),
+            "absent_credentials.toml",
+            env_vars
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+        let _ = std::fs::remove_file(config_path);
+
+        let settings = settings.expect("in test");
+        let exchange_settings = &settings.core.exchanges[0];
+        assert_eq!(exchange_settings.api_key, "env_api_key");
+        assert_eq!(exchange_settings.secret_key, "env_secret_key");
+        assert_eq!(exchange_settings.rest_host, "https://env.host");
+        assert!(!exchange_settings.is_margin_trading);
+        assert_eq!(settings.strategy.spread, dec!(0.1));
+    }
+
+    #[test]
+    fn override_integer_and_decimal_fields() {
+        let credentials = r#"[Envtest0]
+api_key = "file_api_key"
+secret_key = "file_secret_key""#;
+        let env_vars = vec![
+            ("MMB__STRATEGY__SPREAD".to_owned(), "0.25".to_owned()),
+            ("MMB__STRATEGY__MAX_LEVELS".to_owned(), "5".to_owned()),
+            (
+                "MMB__CORE__EXCHANGES__ENVTEST0__API_KEY".to_owned(),
+                "env_api_key".to_owned(),
+            ),
+            ("OTHER_VARIABLE".to_owned(), "value".to_owned()),
+        ];
+
+        let settings: AppSettings<TestStrategySettings> =
+            parse_settings_with_env_overrides(CONFIG, credentials, env_vars).expect("in test");
+
+        assert_eq!(settings.strategy.spread, dec!(0.25));
+        assert_eq!(settings.strategy.max_levels, 5);
+        assert_eq!(settings.core.exchanges[0].api_key, "env_api_key");
+        assert_eq!(settings.core.exchanges[0].secret_key, "file_secret_key");
+    }
+
+    #[test]
+    fn name_variable_with_unparseable_override() {
+        let credentials = r#"[Envtest0]
+api_key = "file_api_key"
+secret_key = "file_secret_key""#;
+        let parse_with_override = |name: &str, value: &str| {
+            parse_settings_with_env_overrides::<TestStrategySettings>(
+                CONFIG,
+                credentials,
+                vec![(name.to_owned(), value.to_owned())],
+            )
+            .expect_err("in test")
+        };
+
+        let error = parse_with_override("MMB__STRATEGY__MAX_LEVELS", "many");
+        assert_eq!(
+            error.to_string(),
+            "Unable to parse environment variable MMB__STRATEGY__MAX_LEVELS as integer"
+        );
+
+        let error = parse_with_override("MMB__STRATEGY__SPREAD", "wide");
+        assert!(
+            error.to_string().contains("MMB__STRATEGY__SPREAD"),
+            "Unexpected error: {:#}",
+            error
+        );
+    }
+
+    #[test]
+    fn save_and_load_encrypted_credentials() {
+        let settings = CONFIG.replace(
+            "is_margin_trading = false",
+            "is_margin_trading = false\napi_key = \"file_api_key\"\nsecret_key = \"file_secret_key\"",
+        );
+        let temp_path = |name: &str| {
+            std::env::temp_dir()
+                .join(format!("mmb_{}_{}.toml", name, uuid::Uuid::new_v4()))
+                .to_str()
+                .expect("in test")
+                .to_owned()
+        };
+        let config_path = temp_path("encrypted_config");
+        let credentials_path = temp_path("encrypted_credentials");
+
+        save_encrypted_credentials(&settings, &config_path, &credentials_path, "passphrase")
+            .expect("in test");
+
+        let passphrase_env_var =
+            |passphrase: &str| [(CREDENTIALS_PASSPHRASE_ENV.to_owned(), passphrase.to_owned())];
+        let settings = load_settings_with_env_vars::<TestStrategySettings>(
+            &config_path,
+            &credentials_path,
+            passphrase_env_var("passphrase"),
+        );
+        let wrong_passphrase_error = load_settings_with_env_vars::<TestStrategySettings>(
+            &config_path,
+            &credentials_path,
+            passphrase_env_var("wrong"),
+        )
+        .expect_err("in test");
+
+        let main_config = std::fs::read_to_string(&config_path).expect("in test");
+        let _ = std::fs::remove_file(config_path);
+        let _ = std::fs::remove_file(credentials_path);
+
+        let settings = settings.expect("in test");
+        assert!(!main_config.contains("file_api_key"));
+        assert_eq!(settings.core.exchanges[0].api_key, "file_api_key");
+        assert_eq!(settings.core.exchanges[0].secret_key, "file_secret_key");
+        assert_eq!(
+            format!("{:#}", wrong_passphrase_error).split(": ").last(),
+            Some("wrong passphrase or corrupted data")
+        );
+    }
+
+    #[test]
+    fn validate_exchange_of_credentials() {
+        let parse_with_credentials_exchange = |exchange: &str| {
+            let credentials = format!(
+                r#"[Envtest0]
+exchange = "{}"
+api_key = "file_api_key"
+secret_key = "file_secret_key""#,
+                exchange
+            );
+            parse_settings::<TestStrategySettings>(CONFIG, &credentials)
+        };
+
+        let settings = parse_with_credentials_exchange("envtest").expect("in test");
+        assert_eq!(settings.core.exchanges[0].api_key, "file_api_key");
+
+        let error = parse_with_credentials_exchange("Kraken").expect_err("in test");
+        assert_eq!(
+            error.to_string(),
+            "Credentials of exchange Kraken are specified for exchange account Envtest0 of exchange Envtest"
+        );
+    }
+}
@@ -2,9 +2,11 @@ use crate::core::lifecycle::cancellation_token::CancellationToken;
 use crate::core::{
     connectivity::{
         connectivity_manager::WebSocketState::Disconnected,
+        reconnect_backoff::{ReconnectBackoff, ReconnectBackoffSettings},
         websocket_actor::{self, ForceClose, WebSocketActor, WebSocketParams},
     },
     exchanges::common::ExchangeAccountId,
+    nothing_to_do,
 };
 use actix::Addr;
 use anyhow::Result;
@@ -12,12 +14,14 @@ use futures::Future;
 use log::{error, info, log, trace, warn, Level};
 use parking_lot::Mutex;
 use std::pin::Pin;
+use std::time::Instant;
 use std::{
     borrow::Borrow,
     ops::DerefMut,
     sync::{Arc, Weak},
 };
 use tokio::sync::broadcast;
+use tokio::time::sleep;
 
 pub const MAX_RETRY_CONNECT_COUNT: u32 = 3;
 
@@ -30,6 +34,7 @@ pub enum WebSocketRole {
 struct WebSocketConnectivity {
     role: WebSocketRole,
     state: WebSocketState,
+    reconnect_backoff: ReconnectBackoff,
 }
 
 impl WebSocketConnectivity {
@@ -37,6 +42,7 @@ impl WebSocketConnectivity {
         WebSocketConnectivity {
             role,
             state: WebSocketState::Disconnected,
+            reconnect_backoff: ReconnectBackoff::default(),
         }
     }
 }
@@ -126,6 +132,16 @@ impl ConnectivityManager {
         *self.callback_msg_received.lock() = msg_received;
     }
 
+    pub fn set_reconnect_backoff_settings(&self, settings: ReconnectBackoffSettings) {
+        for role in [WebSocketRole::Main, WebSocketRole::Secondary].iter() {
+            self.websockets
+                .get_websocket_state(*role)
+                .lock()
+                .reconnect_backoff
+                .set_settings(settings.clone());
+        }
+    }
+
     fn set_callback_ws_params(&self, get_websocket_params: GetWSParamsCallback) {
         *self.callback_get_ws_params.lock() = get_websocket_params;
     }
@@ -255,6 +271,9 @@ impl ConnectivityManager {
             }
 
             websocket_state_guard.deref_mut().state = Disconnected;
+            websocket_state_guard
+                .reconnect_backoff
+                .on_disconnected(Instant::now());
         }
 
         self.callback_disconnected.lock().as_mut()(false);
@@ -299,11 +318,17 @@ impl ConnectivityManager {
 
                     match websocket_actor {
                         Ok(websocket_actor) => {
-                            websocket_connectivity.lock().deref_mut().state =
-                                WebSocketState::Connected {
+                            {
+                                let mut websocket_connectivity_guard =
+                                    websocket_connectivity.lock();
+                                websocket_connectivity_guard.state = WebSocketState::Connected {
                                     websocket_actor,
                                     finished_sender: finished_sender.clone(),
                                 };
+                                websocket_connectivity_guard
+                                    .reconnect_backoff
+                                    .on_connected(Instant::now());
+                            }
 
                             if attempt > 0 {
                                 info!(
@@ -353,6 +378,18 @@ impl ConnectivityManager {
                     role, error
                 ),
             }
+
+            let delay = websocket_connectivity.lock().reconnect_backoff.next_delay();
+            trace!(
+                "Waiting {} ms before next websocket {:?} connection attempt for {}",
+                delay.as_millis(),
+                role,
+                self.exchange_account_id
+            );
+            tokio::select! {
+                _ = sleep(delay) => nothing_to_do(),
+                _ = cancel_websocket_connecting.when_cancelled() => nothing_to_do(),
+            }
         }
 
         Self::set_disconnected_state(finished_sender, &websocket_connectivity);
@@ -1,2 +1,4 @@
 pub mod connectivity_manager;
+pub mod reconnect_backoff;
+pub mod subscription_limiter;
 pub mod websocket_actor;
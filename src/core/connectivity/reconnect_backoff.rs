@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ReconnectBackoffSettings {
+    pub base_delay_ms: u64,
+    pub multiplier: f64,
+    pub max_delay_ms: u64,
+    // connection is considered stable if it was alive at least this period,
+    // after disconnecting of stable connection backoff starts from base delay again
+    pub stable_period_ms: u64,
+}
+
+impl Default for ReconnectBackoffSettings {
+    fn default() -> Self {
+        ReconnectBackoffSettings {
+            base_delay_ms: 500,
+            multiplier: 2.0,
+            max_delay_ms: 30_000,
+            stable_period_ms: 60_000,
+        }
+    }
+}
+
+/// Calculates delays between websocket reconnection attempts
+pub struct ReconnectBackoff {
+    settings: ReconnectBackoffSettings,
+    attempt: u32,
+    connected_at: Option<Instant>,
+}
+
+impl ReconnectBackoff {
+    pub fn new(settings: ReconnectBackoffSettings) -> Self {
+        ReconnectBackoff {
+            settings,
+            attempt: 0,
+            connected_at: None,
+        }
+    }
+
+    pub fn settings(&self) -> &ReconnectBackoffSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: ReconnectBackoffSettings) {
+        self.settings = settings;
+        self.reset();
+    }
+
+    /// Returns delay before next reconnection attempt and moves backoff to next attempt
+    pub fn next_delay(&mut self) -> Duration {
+        let max_delay = Duration::from_millis(self.settings.max_delay_ms);
+
+        let factor = self.settings.multiplier.max(1.0).powi(self.attempt as i32);
+        let delay_ms = self.settings.base_delay_ms as f64 * factor;
+        let delay = if delay_ms.is_finite() && delay_ms < self.settings.max_delay_ms as f64 {
+            Duration::from_millis(delay_ms as u64)
+        } else {
+            max_delay
+        };
+
+        self.attempt = self.attempt.saturating_add(1);
+
+        delay
+    }
+
+    pub fn on_connected(&mut self, now: Instant) {
+        self.connected_at = Some(now);
+    }
+
+    pub fn on_disconnected(&mut self, now: Instant) {
+        if let Some(connected_at) = self.connected_at.take() {
+            let stable_period = Duration::from_millis(self.settings.stable_period_ms);
+            if now.saturating_duration_since(connected_at) >= stable_period {
+                self.reset();
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        ReconnectBackoff::new(ReconnectBackoffSettings::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_settings() -> ReconnectBackoffSettings {
+        ReconnectBackoffSettings {
+            base_delay_ms: 100,
+            multiplier: 2.0,
+            max_delay_ms: 1_000,
+            stable_period_ms: 10_000,
+        }
+    }
+
+    #[test]
+    fn delay_grows_and_caps_at_max() {
+        let mut backoff = ReconnectBackoff::new(test_settings());
+
+        let delays = (0..7).map(|_| backoff.next_delay()).collect::<Vec<_>>();
+
+        let expected = [100, 200, 400, 800, 1_000, 1_000, 1_000]
+            .iter()
+            .map(|&x| Duration::from_millis(x))
+            .collect::<Vec<_>>();
+        assert_eq!(delays, expected);
+    }
+
+    #[test]
+    fn unstable_connection_does_not_reset_delay() {
+        let mut backoff = ReconnectBackoff::new(test_settings());
+        let now = Instant::now();
+
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(200));
+
+        backoff.on_connected(now);
+        backoff.on_disconnected(now + Duration::from_secs(1));
+
+        assert_eq!(backoff.next_delay(), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn reset_after_stable_period() {
+        let mut backoff = ReconnectBackoff::new(test_settings());
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            let _ = backoff.next_delay();
+        }
+        assert_eq!(backoff.next_delay(), Duration::from_millis(1_000));
+
+        backoff.on_connected(now);
+        backoff.on_disconnected(now + Duration::from_secs(10));
+
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,126 @@
+use futures::{stream, Future, StreamExt};
+
+/// Run subscription futures keeping not more than `max_concurrent` of them in flight at once.
+/// Other subscriptions wait in queue. Results are returned in order of items
+pub async fn subscribe_with_limit<T, F, Fut>(
+    items: impl IntoIterator<Item = T>,
+    max_concurrent: Option<usize>,
+    subscribe: F,
+) -> Vec<Fut::Output>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future,
+{
+    let items: Vec<_> = items.into_iter().collect();
+    let max_concurrent = max_concurrent.unwrap_or(items.len()).max(1);
+
+    stream::iter(items)
+        .map(subscribe)
+        .buffered(max_concurrent)
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::sync::oneshot;
+    use tokio::time::{sleep, Duration};
+
+    #[derive(Default)]
+    struct ConcurrencyRecorder {
+        current: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    impl ConcurrencyRecorder {
+        async fn subscribe(&self, symbol: usize) -> usize {
+            let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max.fetch_max(current, Ordering::SeqCst);
+
+            sleep(Duration::from_millis(5)).await;
+
+            self.current.fetch_sub(1, Ordering::SeqCst);
+            symbol
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrency_never_exceeds_limit() {
+        let recorder = ConcurrencyRecorder::default();
+
+        let subscribed =
+            subscribe_with_limit(0..10, Some(3), |symbol| recorder.subscribe(symbol)).await;
+
+        assert_eq!(subscribed, (0..10).collect::<Vec<_>>());
+        assert_eq!(recorder.max.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn all_at_once_without_limit() {
+        let recorder = ConcurrencyRecorder::default();
+
+        let subscribed =
+            subscribe_with_limit(0..5, None, |symbol| recorder.subscribe(symbol)).await;
+
+        assert_eq!(subscribed.len(), 5);
+        assert_eq!(recorder.max.load(Ordering::SeqCst), 5);
+    }
+
+    /// Subscription is finished only when test releases it
+    #[derive(Default)]
+    struct ManualSubscriptions {
+        started: Mutex<Vec<usize>>,
+        releases: Mutex<HashMap<usize, oneshot::Sender<()>>>,
+    }
+
+    impl ManualSubscriptions {
+        async fn subscribe(self: Arc<Self>, symbol: usize) -> usize {
+            let (sender, receiver) = oneshot::channel();
+            let _ = self.releases.lock().insert(symbol, sender);
+            self.started.lock().push(symbol);
+
+            receiver.await.expect("in test");
+            symbol
+        }
+
+        fn release(&self, symbol: usize) {
+            let sender = self.releases.lock().remove(&symbol).expect("in test");
+            sender.send(()).expect("in test");
+        }
+
+        fn started(&self) -> Vec<usize> {
+            self.started.lock().clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn queued_subscriptions_start_only_after_previous_finished() {
+        let subscriptions = Arc::new(ManualSubscriptions::default());
+
+        let subscriptions_clone = subscriptions.clone();
+        let subscribed = tokio::spawn(subscribe_with_limit(0..5, Some(2), move |symbol| {
+            subscriptions_clone.clone().subscribe(symbol)
+        }));
+
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(subscriptions.started(), vec![0, 1]);
+
+        subscriptions.release(0);
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(subscriptions.started(), vec![0, 1, 2]);
+
+        subscriptions.release(1);
+        subscriptions.release(2);
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(subscriptions.started(), vec![0, 1, 2, 3, 4]);
+
+        subscriptions.release(3);
+        subscriptions.release(4);
+        assert_eq!(subscribed.await.expect("in test"), vec![0, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,110 @@
+use std::num::NonZeroU32;
+
+use anyhow::{anyhow, bail, Result};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+
+/// Environment variable with passphrase for encrypted credentials file
+pub static CREDENTIALS_PASSPHRASE_ENV: &str = "MMB_CREDENTIALS_PASSPHRASE";
+
+// encrypted file layout: MAGIC | salt | nonce | ciphertext with tag
+const MAGIC: &[u8] = b"MMBENC01";
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn create_key(passphrase: &str, salt: &[u8]) -> Result<LessSafeKey> {
+    let iterations = NonZeroU32::new(PBKDF2_ITERATIONS).expect("iterations count is not zero");
+    let mut key = [0u8; KEY_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key)
+        .map_err(|_| anyhow!("Unable to create credentials encryption key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Encrypt credentials with ChaCha20-Poly1305 by key derived from passphrase with random salt
+pub fn encrypt_credentials(credentials: &str, passphrase: &str) -> Result<Vec<u8>> {
+    let random = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    random
+        .fill(&mut salt)
+        .and_then(|_| random.fill(&mut nonce))
+        .map_err(|_| anyhow!("Unable to generate salt and nonce for credentials encryption"))?;
+
+    let mut ciphertext = credentials.as_bytes().to_vec();
+    create_key(passphrase, &salt)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(MAGIC),
+            &mut ciphertext,
+        )
+        .map_err(|_| anyhow!("Unable to encrypt credentials"))?;
+
+    Ok([MAGIC, &salt, &nonce, &ciphertext].concat())
+}
+
+pub fn decrypt_credentials(data: &[u8], passphrase: &str) -> Result<String> {
+    if !is_encrypted(data) || data.len() < MAGIC.len() + SALT_LEN + NONCE_LEN {
+        bail!("Credentials are not encrypted or their header is corrupted")
+    }
+
+    let (salt, rest) = data[MAGIC.len()..].split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| anyhow!("Invalid nonce in encrypted credentials"))?;
+
+    let mut ciphertext = ciphertext.to_vec();
+    let credentials = create_key(passphrase, salt)?
+        .open_in_place(nonce, Aad::from(MAGIC), &mut ciphertext)
+        .map_err(|_| {
+            anyhow!("Unable to decrypt credentials: wrong passphrase or corrupted data")
+        })?;
+
+    Ok(String::from_utf8(credentials.to_vec())?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CREDENTIALS: &str = r#"[Binance0]
+api_key = "test_api_key"
+secret_key = "test_secret_key""#;
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let encrypted = encrypt_credentials(CREDENTIALS, "passphrase").expect("in test");
+
+        assert!(is_encrypted(&encrypted));
+        assert!(!String::from_utf8_lossy(&encrypted).contains("test_secret_key"));
+        assert_eq!(
+            decrypt_credentials(&encrypted, "passphrase").expect("in test"),
+            CREDENTIALS
+        );
+    }
+
+    #[test]
+    fn fail_to_decrypt_with_wrong_passphrase() {
+        let encrypted = encrypt_credentials(CREDENTIALS, "passphrase").expect("in test");
+
+        let error = decrypt_credentials(&encrypted, "wrong").expect_err("in test");
+
+        assert_eq!(
+            error.to_string(),
+            "Unable to decrypt credentials: wrong passphrase or corrupted data"
+        );
+    }
+}
@@ -2,7 +2,6 @@ use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
 use anyhow::{anyhow, bail, Context, Result};
-use chrono::Utc;
 use futures::FutureExt;
 use itertools::Itertools;
 use log::{error, trace, warn};
@@ -32,6 +31,7 @@ use crate::core::orders::order::{
     OrderStatus, OrderType, ReservationId,
 };
 use crate::core::orders::pool::OrderRef;
+use crate::core::settings::LiveStrategySettings;
 use crate::core::{
     disposition_execution::trade_limit::is_enough_amount_and_cost, infrastructure::spawn_future,
 };
@@ -45,13 +45,16 @@ const ALLOWED_AMOUNT_DEVIATION_RATE: Decimal = dec!(0.001);
 const GROUP_REQUESTS_COUNT: usize = 4;
 
 struct DisplaySmallOrder {
-    price: Decimal,
+    price: Option<Decimal>,
     amount: Decimal,
 }
 
 impl Display for DisplaySmallOrder {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} ({})", self.price, self.amount)
+        match self.price {
+            Some(price) => write!(f, "{} ({})", price, self.amount),
+            None => write!(f, "market ({})", self.amount),
+        }
     }
 }
 
@@ -158,6 +161,10 @@ impl DispositionExecutor {
 
     pub async fn start(&mut self) -> Result<()> {
         let mut trading_context: Option<TradingContext> = None;
+        self.strategy.init(
+            &self.exchange_account_id,
+            &self.currency_pair_metadata.currency_pair(),
+        );
 
         loop {
             let event = tokio::select! {
@@ -177,7 +184,7 @@ impl DispositionExecutor {
         event: ExchangeEvent,
         last_trading_context: &mut Option<TradingContext>,
     ) -> Result<()> {
-        let now = now();
+        let now = self.exchange().now();
         let need_recalculate_trading_context = prepare_estimate_trading_context(&event, now);
 
         match event {
@@ -185,7 +192,9 @@ impl DispositionExecutor {
                 let _ = self.local_snapshots_service.update(order_book_event);
             }
             ExchangeEvent::OrderEvent(order_event) => {
-                if order_event.order.is_external_order() {
+                if order_event.order.is_external_order()
+                    || !self.is_order_of_trading_place(&order_event.order)
+                {
                     return Ok(());
                 }
 
@@ -270,6 +279,8 @@ impl DispositionExecutor {
 
                         // TODO save state to Database
                     }
+                    // escalation is already handled by exchange
+                    OrderEventType::CancelAttemptsExhausted => nothing_to_do(),
                 }
             }
             _ => nothing_to_do(),
@@ -277,7 +288,7 @@ impl DispositionExecutor {
 
         let mut new_trading_context = estimate_trading_context(
             need_recalculate_trading_context,
-            self.max_amount,
+            self.max_amount(),
             self.strategy.as_mut(),
             &self.local_snapshots_service,
             now,
@@ -404,7 +415,7 @@ impl DispositionExecutor {
                 .orders
                 .values()
                 .map(|or| or.order.fn_ref(|x| DisplaySmallOrder {
-                    price: x.price(),
+                    price: x.try_price(),
                     amount: x.amount()
                 }))
                 .join(", ")
@@ -594,8 +605,8 @@ impl DispositionExecutor {
         let new_price = new_disposition.order.price;
         let found = self.find_new_order_crossing_existing_orders(new_price, side);
         if let Some(crossed_order) = found {
-            let msg = format!("Finished `try_create_order` because there is order {} with price {} that crossing current price {}", crossed_order.client_order_id(),
-                                 crossed_order.price(),
+            let msg = format!("Finished `try_create_order` because there is order {} with price {:?} that crossing current price {}", crossed_order.client_order_id(),
+                                 crossed_order.try_price(),
                                  new_price
             );
             return log_trace(msg, explanation);
@@ -705,6 +716,8 @@ impl DispositionExecutor {
                     price: new_price,
                 };
 
+                // order is tracked in price slot by its client order id, so it can't be moved to
+                // failover account by `EngineContext::create_strategy_order`
                 exchange
                     .create_order(&order_creating, cancellation_token)
                     .await?;
@@ -729,8 +742,17 @@ impl DispositionExecutor {
         new_order_price: Price,
         side: OrderSide,
     ) -> Option<OrderRef> {
-        let buy_comparator = &|order: &OrderRef| order.price() <= new_order_price;
-        let sell_comparator = &|order: &OrderRef| new_order_price <= order.price();
+        // market orders have no price, so they can't cross
+        let buy_comparator = &|order: &OrderRef| {
+            order
+                .try_price()
+                .is_some_and(|price| price <= new_order_price)
+        };
+        let sell_comparator = &|order: &OrderRef| {
+            order
+                .try_price()
+                .is_some_and(|price| new_order_price <= price)
+        };
 
         let is_crossing: &dyn Fn(&OrderRef) -> bool = match side {
             OrderSide::Buy => buy_comparator,
@@ -770,6 +792,13 @@ impl DispositionExecutor {
         new_amount
     }
 
+    /// Events channel is shared by executors of all strategies, so orders of other
+    /// exchange accounts and currency pairs should be skipped
+    fn is_order_of_trading_place(&self, order: &OrderRef) -> bool {
+        order.exchange_account_id() == self.exchange_account_id
+            && order.currency_pair() == self.currency_pair_metadata.currency_pair()
+    }
+
     fn get_price_slot(&self, order: &OrderRef) -> Option<&PriceSlot> {
         let side = order.side();
         let price_slot = self.orders_state.by_side[side].find_price_slot(order);
@@ -842,6 +871,20 @@ impl DispositionExecutor {
             .value()
             .clone()
     }
+
+    fn strategy_settings(&self) -> Option<LiveStrategySettings> {
+        self.engine_ctx.strategy_settings(&TradePlaceAccount::new(
+            self.exchange_account_id.clone(),
+            self.currency_pair_metadata.currency_pair(),
+        ))
+    }
+
+    /// Max amount from live settings of strategy or from launch settings if strategy isn't registered in engine
+    fn max_amount(&self) -> Amount {
+        self.strategy_settings()
+            .map(|x| x.max_amount)
+            .unwrap_or(self.max_amount)
+    }
 }
 
 struct InitEstimation {
@@ -926,10 +969,6 @@ fn get_cancelling_orders<'a>(
     cancelling_orders
 }
 
-fn now() -> DateTime {
-    Utc::now()
-}
-
 #[inline(always)]
 fn log_trace<'a>(msg: impl AsRef<str>, explanation: &mut Explanation) -> Result<()> {
     let msg = msg.as_ref();
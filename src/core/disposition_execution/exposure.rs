@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use rust_decimal_macros::dec;
+
+use crate::core::exchanges::common::{Amount, CurrencyCode, Price};
+
+/// Source of rates for conversion amounts between currencies
+pub trait ConversionRates {
+    /// Price of 1 unit of `from` currency in `to` currency
+    fn rate(&self, from: &CurrencyCode, to: &CurrencyCode) -> Option<Price>;
+}
+
+impl ConversionRates for HashMap<(CurrencyCode, CurrencyCode), Price> {
+    fn rate(&self, from: &CurrencyCode, to: &CurrencyCode) -> Option<Price> {
+        if from == to {
+            return Some(dec!(1));
+        }
+
+        self.get(&(from.clone(), to.clone())).copied()
+    }
+}
+
+/// Signed exposure of position denominated in settlement currency
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exposure {
+    pub settlement_currency_code: CurrencyCode,
+    pub amount: Amount,
+}
+
+impl Exposure {
+    pub fn new(settlement_currency_code: CurrencyCode, amount: Amount) -> Self {
+        Exposure {
+            settlement_currency_code,
+            amount,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExposureReport {
+    pub by_settlement_currency: HashMap<CurrencyCode, Amount>,
+    /// Sum of exposures converted to `total_currency_code`.
+    /// Exposures in currencies from `missing_rates` are not included
+    pub total: Amount,
+    pub total_currency_code: CurrencyCode,
+    /// Settlement currencies without conversion rate to `total_currency_code`
+    pub missing_rates: Vec<CurrencyCode>,
+}
+
+impl ExposureReport {
+    pub fn is_complete(&self) -> bool {
+        self.missing_rates.is_empty()
+    }
+}
+
+/// Group exposures by settlement currency and convert them to unified total
+pub fn aggregate_exposure(
+    exposures: &[Exposure],
+    total_currency_code: CurrencyCode,
+    rates: &dyn ConversionRates,
+) -> ExposureReport {
+    let mut by_settlement_currency: HashMap<CurrencyCode, Amount> = HashMap::new();
+    for exposure in exposures {
+        *by_settlement_currency
+            .entry(exposure.settlement_currency_code.clone())
+            .or_insert(dec!(0)) += exposure.amount;
+    }
+
+    let mut total = dec!(0);
+    let mut missing_rates = Vec::new();
+    for (currency_code, amount) in &by_settlement_currency {
+        match rates.rate(currency_code, &total_currency_code) {
+            Some(rate) => total += amount * rate,
+            None => missing_rates.push(currency_code.clone()),
+        }
+    }
+    missing_rates.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+    ExposureReport {
+        by_settlement_currency,
+        total,
+        total_currency_code,
+        missing_rates,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rates() -> HashMap<(CurrencyCode, CurrencyCode), Price> {
+        let mut rates = HashMap::new();
+        rates.insert(("BTC".into(), "USDT".into()), dec!(50000));
+        rates
+    }
+
+    #[test]
+    fn aggregate_by_settlement_currency() {
+        let exposures = vec![
+            Exposure::new("USDT".into(), dec!(1000)),
+            Exposure::new("USDT".into(), dec!(-200)),
+            Exposure::new("BTC".into(), dec!(0.1)),
+        ];
+
+        let report = aggregate_exposure(&exposures, "USDT".into(), &rates());
+
+        assert_eq!(report.by_settlement_currency[&"USDT".into()], dec!(800));
+        assert_eq!(report.by_settlement_currency[&"BTC".into()], dec!(0.1));
+        assert_eq!(report.total, dec!(5800));
+        assert!(report.is_complete());
+    }
+
+    #[test]
+    fn flag_currency_without_rate() {
+        let exposures = vec![
+            Exposure::new("USDT".into(), dec!(1000)),
+            Exposure::new("ETH".into(), dec!(2)),
+        ];
+
+        let report = aggregate_exposure(&exposures, "USDT".into(), &rates());
+
+        assert_eq!(report.by_settlement_currency[&"ETH".into()], dec!(2));
+        assert_eq!(report.total, dec!(1000));
+        assert_eq!(report.missing_rates, vec![CurrencyCode::from("ETH")]);
+        assert!(!report.is_complete());
+    }
+}
@@ -0,0 +1,122 @@
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::core::orders::order::OrderSide;
+
+/// Calculate skew of quotes in basis points proportional to deviation of position from target.
+/// Positive skew means quotes should be moved up (prefer buying),
+/// negative skew means quotes should be moved down (prefer selling).
+/// Result is clamped by `max_skew_bps` in both directions
+pub fn inventory_skew(
+    position: Decimal,
+    target: Decimal,
+    max_position: Decimal,
+    max_skew_bps: i64,
+) -> i64 {
+    let max_skew_bps = max_skew_bps.abs();
+    let deviation = position - target;
+    if deviation.is_zero() || max_skew_bps == 0 {
+        return 0;
+    }
+
+    if max_position <= dec!(0) {
+        return if deviation.is_sign_positive() {
+            -max_skew_bps
+        } else {
+            max_skew_bps
+        };
+    }
+
+    let max_skew = Decimal::from(max_skew_bps);
+    let skew = (-deviation / max_position * max_skew)
+        .max(-max_skew)
+        .min(max_skew)
+        .round();
+
+    skew.to_i64()
+        .expect("skew is clamped by max_skew_bps so it always fits in i64")
+}
+
+/// Side and amount of order which closes net position. Returns None for flat position
+pub fn flattening_order(net_position: Decimal) -> Option<(OrderSide, Decimal)> {
+    if net_position.is_zero() {
+        return None;
+    }
+
+    let side = if net_position.is_sign_positive() {
+        OrderSide::Sell
+    } else {
+        OrderSide::Buy
+    };
+
+    Some((side, net_position.abs()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(dec!(5), dec!(0), dec!(10), 20, -10)]
+    #[case(dec!(10), dec!(0), dec!(10), 20, -20)]
+    #[case(dec!(25), dec!(0), dec!(10), 20, -20)]
+    #[case(dec!(7), dec!(2), dec!(10), 20, -10)]
+    fn long_inventory_skews_to_sell(
+        #[case] position: Decimal,
+        #[case] target: Decimal,
+        #[case] max_position: Decimal,
+        #[case] max_skew_bps: i64,
+        #[case] expected: i64,
+    ) {
+        assert_eq!(
+            inventory_skew(position, target, max_position, max_skew_bps),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(dec!(-5), dec!(0), dec!(10), 20, 10)]
+    #[case(dec!(-10), dec!(0), dec!(10), 20, 20)]
+    #[case(dec!(-25), dec!(0), dec!(10), 20, 20)]
+    #[case(dec!(-3), dec!(2), dec!(10), 20, 10)]
+    fn short_inventory_skews_to_buy(
+        #[case] position: Decimal,
+        #[case] target: Decimal,
+        #[case] max_position: Decimal,
+        #[case] max_skew_bps: i64,
+        #[case] expected: i64,
+    ) {
+        assert_eq!(
+            inventory_skew(position, target, max_position, max_skew_bps),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(dec!(0), dec!(0))]
+    #[case(dec!(3), dec!(3))]
+    #[case(dec!(-1.5), dec!(-1.5))]
+    fn zero_skew_at_target(#[case] position: Decimal, #[case] target: Decimal) {
+        assert_eq!(inventory_skew(position, target, dec!(10), 20), 0);
+    }
+
+    #[test]
+    fn flatten_long_position() {
+        assert_eq!(
+            flattening_order(dec!(1.5)),
+            Some((OrderSide::Sell, dec!(1.5)))
+        );
+    }
+
+    #[test]
+    fn flatten_short_position() {
+        assert_eq!(flattening_order(dec!(-2)), Some((OrderSide::Buy, dec!(2))));
+    }
+
+    #[test]
+    fn flat_position_needs_no_order() {
+        assert_eq!(flattening_order(dec!(0)), None);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod executor;
+pub mod exposure;
+pub mod inventory;
 pub mod trade_limit;
 mod trading_context_calculation;
 
@@ -14,10 +14,10 @@ use tokio::sync::broadcast;
 use super::support::BinanceOrderInfo;
 use crate::core::exchanges::events::ExchangeEvent;
 use crate::core::exchanges::rest_client::RestClient;
-use crate::core::exchanges::traits::ExchangeClientBuilderResult;
+use crate::core::exchanges::traits::{ExchangeClientBuilderResult, Support};
 use crate::core::exchanges::{
     common::CurrencyCode,
-    general::features::{ExchangeFeatures, OpenOrdersType},
+    general::features::{ExchangeCapabilities, ExchangeFeatures, OpenOrdersType},
     timeouts::requests_timeout_manager_factory::RequestTimeoutArguments,
 };
 use crate::core::exchanges::{common::CurrencyId, general::exchange::BoxExchangeClient};
@@ -84,6 +84,8 @@ impl Binance {
             specific_eosbtc => unified_eosbtc
         ];
 
+        let rest_client = RestClient::new(settings.rest_retry.clone());
+
         Self {
             id,
             order_created_callback: Mutex::new(Box::new(|_, _, _| {})),
@@ -96,7 +98,7 @@ impl Binance {
             settings,
             events_channel,
             application_manager,
-            rest_client: RestClient::new(),
+            rest_client,
         }
     }
 
@@ -128,6 +130,64 @@ impl Binance {
         todo!("is_websocket_reconnecting")
     }
 
+    /// Binance links order to broker by prefix of client order id
+    pub(super) fn to_server_client_order_id(&self, client_order_id: &ClientOrderId) -> String {
+        match &self.settings.broker_id {
+            Some(broker_id) => format!("x-{}{}", broker_id, client_order_id),
+            None => client_order_id.as_str().to_owned(),
+        }
+    }
+
+    pub(super) fn to_local_client_order_id<'a>(&self, client_order_id: &'a str) -> &'a str {
+        self.settings
+            .broker_id
+            .as_ref()
+            .and_then(|broker_id| {
+                client_order_id
+                    .strip_prefix("x-")
+                    .and_then(|x| x.strip_prefix(broker_id.as_str()))
+            })
+            .unwrap_or(client_order_id)
+    }
+
+    pub(super) fn get_create_order_params(&self, order: &OrderCreating) -> rest_client::HttpParams {
+        let specific_currency_pair = self.get_specific_currency_pair(&order.header.currency_pair);
+
+        let mut http_params = vec![
+            (
+                "symbol".to_owned(),
+                specific_currency_pair.as_str().to_owned(),
+            ),
+            (
+                "side".to_owned(),
+                Self::to_server_order_side(order.header.side),
+            ),
+            (
+                "type".to_owned(),
+                Self::to_server_order_type(order.header.order_type),
+            ),
+            ("quantity".to_owned(), order.header.amount.to_string()),
+            (
+                "newClientOrderId".to_owned(),
+                self.to_server_client_order_id(&order.header.client_order_id),
+            ),
+        ];
+
+        if order.header.order_type != OrderType::Market {
+            http_params.push(("timeInForce".to_owned(), "GTC".to_owned()));
+            http_params.push(("price".to_owned(), order.price.to_string()));
+        } else if order.header.execution_type == OrderExecutionType::MakerOnly {
+            http_params.push(("timeInForce".to_owned(), "GTX".to_owned()));
+        }
+
+        // reduce-only orders are supported by futures API only, on spot they are rejected by capabilities check
+        if order.header.reduce_only && self.settings.is_margin_trading {
+            http_params.push(("reduceOnly".to_owned(), "true".to_owned()));
+        }
+
+        http_params
+    }
+
     pub(super) fn to_server_order_side(side: OrderSide) -> String {
         match side {
             OrderSide::Buy => "BUY".to_owned(),
@@ -144,15 +204,17 @@ impl Binance {
         }
     }
 
-    fn to_local_order_status(status: &str) -> OrderStatus {
-        match status {
+    fn to_local_order_status(status: &str) -> ExchangeOrderStatus {
+        let status = match status {
             "NEW" | "PARTIALLY_FILLED" => OrderStatus::Created,
             "FILLED" => OrderStatus::Completed,
             "PENDING_CANCEL" => OrderStatus::Canceling,
-            "CANCELED" | "EXPIRED" | "REJECTED" => OrderStatus::Canceled,
-            // TODO just propagate and log there
-            _ => panic!("Unexpected order status"),
-        }
+            "CANCELED" | "EXPIRED" => OrderStatus::Canceled,
+            "REJECTED" => OrderStatus::Rejected,
+            _ => return ExchangeOrderStatus::UnknownStatus(status.to_owned()),
+        };
+
+        ExchangeOrderStatus::Known(status)
     }
 
     pub(super) fn to_server_order_type(order_type: OrderType) -> String {
@@ -205,7 +267,8 @@ impl Binance {
             self.get_unified_currency_pair(&specific.specific_currency_pair)
                 .expect("expected known currency pair"),
             specific.exchange_order_id.to_string().as_str().into(),
-            specific.client_order_id.clone(),
+            self.to_local_client_order_id(specific.client_order_id.as_str())
+                .into(),
             Self::to_local_order_side(&specific.side),
             Self::to_local_order_status(&specific.status),
             specific.price,
@@ -230,6 +293,7 @@ impl Binance {
         } else {
             original_client_order_id
         };
+        let client_order_id = self.to_local_client_order_id(client_order_id);
 
         let exchange_order_id = json_response["i"].to_string();
         let exchange_order_id = exchange_order_id.trim_matches('"');
@@ -395,6 +459,7 @@ impl ExchangeClientBuilder for BinanceBuilder {
         application_manager: Arc<ApplicationManager>,
     ) -> ExchangeClientBuilderResult {
         let exchange_account_id = exchange_settings.exchange_account_id.clone();
+        let is_margin_trading = exchange_settings.is_margin_trading;
 
         let events_rx = events_channel.subscribe();
         ExchangeClientBuilderResult {
@@ -410,6 +475,14 @@ impl ExchangeClientBuilder for BinanceBuilder {
                 false,
                 AllowedEventSourceType::All,
                 AllowedEventSourceType::All,
+                ExchangeCapabilities {
+                    post_only: true,
+                    oco: false,
+                    reduce_only: is_margin_trading,
+                    batch_orders: false,
+                    cancel_on_disconnect: false,
+                    amend_order: false,
+                },
             ),
             events_tx: events_channel,
             events_rx,
@@ -436,7 +509,11 @@ impl ExchangeClientBuilder for BinanceBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::exchanges::common::RestRequestOutcome;
     use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use awc::http::StatusCode;
+    use chrono::{TimeZone, Utc};
+    use rstest::rstest;
 
     #[test]
     fn generate_signature() {
@@ -482,4 +559,138 @@ mod tests {
         let right_value = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
         assert_eq!(http_string, right_value);
     }
+
+    fn binance_with_broker_id(broker_id: Option<&str>) -> Binance {
+        create_binance(broker_id, false)
+    }
+
+    fn create_binance(broker_id: Option<&str>, is_margin_trading: bool) -> Binance {
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+        let mut settings = ExchangeSettings::new_short(
+            exchange_account_id.clone(),
+            "test_api_key".into(),
+            "test_secret_key".into(),
+            is_margin_trading,
+        );
+        settings.broker_id = broker_id.map(|x| x.to_owned());
+
+        let (tx, _) = broadcast::channel(10);
+        Binance::new(
+            exchange_account_id,
+            settings,
+            tx,
+            ApplicationManager::new(CancellationToken::default()),
+        )
+    }
+
+    fn order_creating(client_order_id: &str) -> OrderCreating {
+        OrderCreating {
+            header: OrderHeader::new(
+                client_order_id.into(),
+                chrono::Utc::now(),
+                "Binance0".parse().expect("in test"),
+                CurrencyPair::from_codes("phb".into(), "btc".into()),
+                OrderType::Limit,
+                OrderSide::Buy,
+                rust_decimal_macros::dec!(1),
+                OrderExecutionType::None,
+                None,
+                None,
+                "StrategyInUnitTests".to_owned(),
+            ),
+            price: rust_decimal_macros::dec!(0.1),
+        }
+    }
+
+    fn new_client_order_id(params: &rest_client::HttpParams) -> &str {
+        params
+            .iter()
+            .find(|(key, _)| key == "newClientOrderId")
+            .map(|(_, value)| value.as_str())
+            .expect("in test")
+    }
+
+    #[test]
+    fn broker_id_included_in_create_order_params() {
+        let binance = binance_with_broker_id(Some("BROKER1"));
+
+        let params = binance.get_create_order_params(&order_creating("order1"));
+
+        assert_eq!(new_client_order_id(&params), "x-BROKER1order1");
+        assert_eq!(
+            binance.to_local_client_order_id("x-BROKER1order1"),
+            "order1"
+        );
+    }
+
+    #[test]
+    fn create_order_params_without_broker_id() {
+        let binance = binance_with_broker_id(None);
+
+        let params = binance.get_create_order_params(&order_creating("order1"));
+
+        assert_eq!(new_client_order_id(&params), "order1");
+        assert_eq!(binance.to_local_client_order_id("order1"), "order1");
+    }
+
+    #[rstest]
+    #[case(true, Some("true"))]
+    #[case(false, None)]
+    fn reduce_only_param_sent_for_futures(
+        #[case] is_margin_trading: bool,
+        #[case] expected: Option<&str>,
+    ) {
+        let binance = create_binance(None, is_margin_trading);
+        let mut order = order_creating("order1");
+        let mut header = (*order.header).clone();
+        header.reduce_only = true;
+        order.header = Arc::new(header);
+
+        let params = binance.get_create_order_params(&order);
+
+        let reduce_only = params
+            .iter()
+            .find(|(key, _)| key == "reduceOnly")
+            .map(|(_, value)| value.as_str());
+        assert_eq!(reduce_only, expected);
+    }
+
+    #[test]
+    fn keep_unrecognized_order_status_raw() {
+        assert_eq!(
+            Binance::to_local_order_status("FILLED"),
+            ExchangeOrderStatus::Known(OrderStatus::Completed)
+        );
+        assert_eq!(
+            Binance::to_local_order_status("REJECTED"),
+            ExchangeOrderStatus::Known(OrderStatus::Rejected)
+        );
+        assert_eq!(
+            Binance::to_local_order_status("PENDING_NEW"),
+            ExchangeOrderStatus::UnknownStatus("PENDING_NEW".to_owned())
+        );
+    }
+
+    #[test]
+    fn server_time_from_rest_response() {
+        let binance = create_binance(None, false);
+
+        let response = RestRequestOutcome::new(
+            r#"{"orderId":28,"transactTime":1507725176595}"#.to_owned(),
+            StatusCode::OK,
+        );
+        assert_eq!(
+            binance.get_server_time(&response),
+            Some(Utc.timestamp_millis(1507725176595))
+        );
+
+        let response = RestRequestOutcome::new(r#"{"orderId":28}"#.to_owned(), StatusCode::OK);
+        assert_eq!(binance.get_server_time(&response), None);
+
+        let response = RestRequestOutcome::new(
+            r#"{"orderId":28,"updateTime":1499827319559}"#.to_owned(),
+            StatusCode::OK,
+        );
+        assert_eq!(binance.get_server_time(&response), None);
+    }
 }
@@ -21,34 +21,7 @@ impl ExchangeClient for Binance {
     }
 
     async fn create_order(&self, order: &OrderCreating) -> Result<RestRequestOutcome> {
-        let specific_currency_pair = self.get_specific_currency_pair(&order.header.currency_pair);
-
-        let mut http_params = vec![
-            (
-                "symbol".to_owned(),
-                specific_currency_pair.as_str().to_owned(),
-            ),
-            (
-                "side".to_owned(),
-                Self::to_server_order_side(order.header.side),
-            ),
-            (
-                "type".to_owned(),
-                Self::to_server_order_type(order.header.order_type),
-            ),
-            ("quantity".to_owned(), order.header.amount.to_string()),
-            (
-                "newClientOrderId".to_owned(),
-                order.header.client_order_id.as_str().to_owned(),
-            ),
-        ];
-
-        if order.header.order_type != OrderType::Market {
-            http_params.push(("timeInForce".to_owned(), "GTC".to_owned()));
-            http_params.push(("price".to_owned(), order.price.to_string()));
-        } else if order.header.execution_type == OrderExecutionType::MakerOnly {
-            http_params.push(("timeInForce".to_owned(), "GTX".to_owned()));
-        }
+        let mut http_params = self.get_create_order_params(order);
         self.add_authentification_headers(&mut http_params)?;
 
         let url_path = match self.settings.is_margin_trading {
@@ -146,7 +119,7 @@ impl ExchangeClient for Binance {
             ),
             (
                 "origClientOrderId".to_owned(),
-                order.client_order_id().as_str().to_owned(),
+                self.to_server_client_order_id(&order.client_order_id()),
             ),
         ];
         self.add_authentification_headers(&mut http_params)?;
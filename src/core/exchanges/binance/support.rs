@@ -3,7 +3,7 @@ use std::sync::Arc;
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use awc::http::Uri;
-use chrono::Utc;
+use chrono::{TimeZone, Utc};
 use dashmap::DashMap;
 use itertools::Itertools;
 use log::{error, info};
@@ -16,6 +16,7 @@ use crate::core::connectivity::connectivity_manager::WebSocketRole;
 use crate::core::exchanges::common::SortedOrderData;
 use crate::core::exchanges::events::ExchangeEvent;
 use crate::core::exchanges::general::currency_pair_metadata::PrecisionType;
+use crate::core::exchanges::timeouts::rate_limit_rules::RateLimitTracker;
 use crate::core::exchanges::{
     common::CurrencyCode, common::CurrencyId,
     general::currency_pair_metadata::CurrencyPairMetadata,
@@ -30,6 +31,7 @@ use crate::core::{
         SpecificCurrencyPair,
     },
     orders::fill::EventSourceType,
+    DateTime,
 };
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -104,6 +106,15 @@ impl Support for Binance {
         Ok(ExchangeOrderId::new(id.into()))
     }
 
+    fn get_server_time(&self, response: &RestRequestOutcome) -> Option<DateTime> {
+        let response: Value = serde_json::from_str(&response.content).ok()?;
+        // `updateTime` of order info is time of the last order update, it isn't server time
+        let server_time = ["transactTime", "serverTime"]
+            .iter()
+            .find_map(|key| response[key].as_i64())?;
+        Some(Utc.timestamp_millis(server_time))
+    }
+
     fn clarify_error_type(&self, error: &mut ExchangeError) {
         // -1010 ERROR_MSG_RECEIVED
         // -2010 NEW_ORDER_REJECTED
@@ -186,6 +197,10 @@ impl Support for Binance {
         *self.handle_order_filled_callback.lock() = callback;
     }
 
+    fn set_rate_limit_tracker(&self, rate_limit_tracker: Arc<RateLimitTracker>) {
+        self.rest_client.set_rate_limit_tracker(rate_limit_tracker);
+    }
+
     fn is_enabled_websocket(&self, role: WebSocketRole) -> bool {
         match role {
             WebSocketRole::Main => true,
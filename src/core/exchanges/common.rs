@@ -26,6 +26,25 @@ type String15 = SmallString<[u8; 15]>;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExchangeIdParseError(String);
 
+impl Display for ExchangeIdParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Unable to parse exchange account id: {}", self.0)
+    }
+}
+
+impl std::error::Error for ExchangeIdParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrencyPairParseError(String);
+
+impl Display for CurrencyPairParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for CurrencyPairParseError {}
+
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
 pub struct ExchangeAccountId {
     pub exchange_id: ExchangeId,
@@ -43,6 +62,16 @@ impl ExchangeAccountId {
         }
     }
 
+    /// Name of exchange like `Binance` for `Binance0`
+    pub fn exchange_name(&self) -> &str {
+        self.exchange_id.as_str()
+    }
+
+    /// Number of account on exchange like `0` for `Binance0`
+    pub fn account_number(&self) -> u8 {
+        self.account_number
+    }
+
     pub fn to_string(&self) -> String {
         format!("{}", self)
     }
@@ -226,6 +255,40 @@ impl CurrencyPair {
         CurrencyPair([base.as_str(), quote.as_str()].join("/").into()) // convention from ccxt
     }
 
+    /// Parse pair like `FromStr` does and also pair without separator like `EOSBTC`
+    /// by looking for one of known quote currencies at the end of it
+    pub fn parse_with_quotes(
+        text: &str,
+        quotes: &[CurrencyCode],
+    ) -> std::result::Result<Self, CurrencyPairParseError> {
+        if text.contains(CURRENCY_PAIR_SEPARATORS) {
+            return text.parse();
+        }
+
+        let text = text.to_lowercase();
+        let quote = quotes
+            .iter()
+            .map(|x| x.as_str().to_lowercase())
+            .filter(|quote| text.len() > quote.len() && text.ends_with(quote.as_str()))
+            // the longest one to distinguish USDT from USD
+            .max_by_key(|quote| quote.len())
+            .ok_or_else(|| {
+                CurrencyPairParseError(format!(
+                    "Currency pair '{}' doesn't end with any of known quote currencies",
+                    text
+                ))
+            })?;
+
+        let base = &text[..text.len() - quote.len()];
+        Ok(CurrencyPair::from_codes(base.into(), quote.as_str().into()))
+    }
+
+    /// Base and quote currency codes of pair
+    pub fn to_codes(&self) -> (CurrencyCode, CurrencyCode) {
+        let (base, quote) = self.as_str().split_once('/').unwrap_or((self.as_str(), ""));
+        (base.into(), quote.into())
+    }
+
     /// Extracts a string slice containing the entire string.
     #[inline]
     pub fn as_str(&self) -> &str {
@@ -233,6 +296,36 @@ impl CurrencyPair {
     }
 }
 
+const CURRENCY_PAIR_SEPARATORS: &[char] = &['/', '-'];
+
+/// Parse pair like `EOS/BTC` or `EOS-BTC`, currency codes are lowercased like in configs
+impl FromStr for CurrencyPair {
+    type Err = CurrencyPairParseError;
+
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        if text.is_empty() {
+            return Err(CurrencyPairParseError(
+                "Currency pair should not be empty".into(),
+            ));
+        }
+
+        let codes = text
+            .split(CURRENCY_PAIR_SEPARATORS)
+            .map(|x| x.trim())
+            .collect_vec();
+        match codes.as_slice() {
+            [base, quote] if !base.is_empty() && !quote.is_empty() => Ok(CurrencyPair::from_codes(
+                base.to_lowercase().as_str().into(),
+                quote.to_lowercase().as_str().into(),
+            )),
+            _ => Err(CurrencyPairParseError(format!(
+                "Currency pair '{}' should consist of base and quote currencies separated by '/' or '-'",
+                text
+            ))),
+        }
+    }
+}
+
 impl Display for CurrencyPair {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.as_str())
@@ -314,6 +407,26 @@ pub enum ExchangeErrorType {
     ServiceUnavailable,
 }
 
+impl ExchangeErrorType {
+    /// Errors caused by exchange or connection state, not by request itself
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ExchangeErrorType::SendError
+                | ExchangeErrorType::RateLimit
+                | ExchangeErrorType::ServiceUnavailable
+        )
+    }
+
+    /// Errors meaning exchange refused order itself unlike transport or timeout failures
+    pub fn is_rejection(&self) -> bool {
+        matches!(
+            self,
+            ExchangeErrorType::InvalidOrder | ExchangeErrorType::InsufficientFunds
+        )
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum RestRequestError {
     IsInProgress,
@@ -348,6 +461,17 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn exchange_id_components() {
+        let exchange_account_id = "Binance0".parse::<ExchangeAccountId>().expect("in test");
+        assert_eq!(exchange_account_id.exchange_name(), "Binance");
+        assert_eq!(exchange_account_id.account_number(), 0);
+
+        let exchange_account_id = "Bitmex12".parse::<ExchangeAccountId>().expect("in test");
+        assert_eq!(exchange_account_id.exchange_name(), "Bitmex");
+        assert_eq!(exchange_account_id.account_number(), 12);
+    }
+
     #[test]
     pub fn exchange_id_parse_failed_exchange_id() {
         let exchange_account_id = "123".parse::<ExchangeAccountId>();
@@ -384,4 +508,59 @@ mod tests {
         let result = exchange_account_id.to_string();
         assert_eq!(result, "Binance1".to_string())
     }
+
+    #[test]
+    pub fn currency_pair_to_codes() {
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        assert_eq!(currency_pair.to_codes(), ("PHB".into(), "BTC".into()));
+    }
+
+    #[test]
+    pub fn currency_pair_parse_with_slash() {
+        assert_eq!(
+            "EOS/BTC".parse::<CurrencyPair>(),
+            Ok(CurrencyPair::from_codes("eos".into(), "btc".into()))
+        );
+    }
+
+    #[test]
+    pub fn currency_pair_parse_with_dash() {
+        assert_eq!(
+            "EOS-BTC".parse::<CurrencyPair>(),
+            Ok(CurrencyPair::from_codes("eos".into(), "btc".into()))
+        );
+    }
+
+    #[test]
+    pub fn currency_pair_parse_without_separator() {
+        let quotes = ["BTC".into(), "USD".into(), "USDT".into()];
+
+        assert_eq!(
+            CurrencyPair::parse_with_quotes("EOSBTC", &quotes),
+            Ok(CurrencyPair::from_codes("eos".into(), "btc".into()))
+        );
+        assert_eq!(
+            CurrencyPair::parse_with_quotes("ethusdt", &quotes),
+            Ok(CurrencyPair::from_codes("eth".into(), "usdt".into()))
+        );
+        assert!(CurrencyPair::parse_with_quotes("EOSETH", &quotes).is_err());
+        assert!("EOSBTC".parse::<CurrencyPair>().is_err());
+    }
+
+    #[test]
+    pub fn currency_pair_parse_failed_for_empty_and_single_token() {
+        assert_eq!(
+            "".parse::<CurrencyPair>(),
+            Err(CurrencyPairParseError(
+                "Currency pair should not be empty".into()
+            ))
+        );
+        assert_eq!(
+            "EOS/".parse::<CurrencyPair>(),
+            Err(CurrencyPairParseError(
+                "Currency pair 'EOS/' should consist of base and quote currencies separated by '/' or '-'".into()
+            ))
+        );
+        assert!("EOS/BTC/USD".parse::<CurrencyPair>().is_err());
+    }
 }
@@ -1,4 +1,5 @@
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
 
 use crate::core::exchanges::common::{
@@ -6,7 +7,9 @@ use crate::core::exchanges::common::{
 };
 use crate::core::order_book::event::OrderBookEvent;
 use crate::core::orders::event::OrderEvent;
+use crate::core::orders::fill::EventSourceType;
 use crate::core::orders::order::OrderSide;
+use crate::core::orders::sla_monitor::OrderSlaAlert;
 use crate::core::DateTime;
 
 pub const CHANNEL_MAX_EVENTS_COUNT: usize = 200_000;
@@ -97,6 +100,7 @@ pub enum ExchangeEvent {
     BalanceUpdate(BalanceUpdateEvent),
     LiquidationPrice(LiquidationPriceEvent),
     Trades(TradesEvent),
+    OrderSlaAlert(OrderSlaAlert),
 }
 
 pub(crate) struct ExchangeEvents {
@@ -125,3 +129,23 @@ impl Default for AllowedEventSourceType {
         AllowedEventSourceType::All
     }
 }
+
+/// Source of order creation confirmation which is authoritative for order status transition.
+/// Confirmations from other source are ignored to avoid double processing
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Deserialize, Serialize)]
+pub enum CreationConfirmationSource {
+    WebSocket,
+    Rest,
+    #[default]
+    Both,
+}
+
+impl CreationConfirmationSource {
+    pub fn is_accepted(self, source_type: EventSourceType) -> bool {
+        match self {
+            CreationConfirmationSource::Both => true,
+            CreationConfirmationSource::WebSocket => source_type == EventSourceType::WebSocket,
+            CreationConfirmationSource::Rest => source_type != EventSourceType::WebSocket,
+        }
+    }
+}
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use anyhow::{bail, Result};
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 
 use crate::core::{
     exchanges::common::Amount,
@@ -20,6 +21,53 @@ pub enum Round {
     ToNearest,
 }
 
+/// Rounding mode configurable in exchange settings
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+pub enum RoundingMode {
+    Floor,
+    Ceil,
+    HalfUp,
+    TowardZero,
+    /// Floor for buy order and ceil for sell order, so order doesn't become more aggressive
+    Passive,
+}
+
+impl RoundingMode {
+    fn to_round(self, value: Price, side: OrderSide) -> Round {
+        match self {
+            RoundingMode::Floor => Round::Floor,
+            RoundingMode::Ceil => Round::Ceiling,
+            RoundingMode::HalfUp => Round::ToNearest,
+            RoundingMode::Passive => match side {
+                OrderSide::Buy => Round::Floor,
+                OrderSide::Sell => Round::Ceiling,
+            },
+            RoundingMode::TowardZero => {
+                if value.is_sign_negative() {
+                    Round::Ceiling
+                } else {
+                    Round::Floor
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+pub struct RoundingSettings {
+    pub price: RoundingMode,
+    pub amount: RoundingMode,
+}
+
+impl Default for RoundingSettings {
+    fn default() -> Self {
+        RoundingSettings {
+            price: RoundingMode::Passive,
+            amount: RoundingMode::Floor,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum PrecisionType {
     ByFraction,
@@ -169,6 +217,20 @@ impl CurrencyPairMetadata {
         }
     }
 
+    /// Rounding of order price and amount to exchange precision with rounding modes from exchange settings
+    pub fn round_order(
+        &self,
+        price: Price,
+        amount: Amount,
+        side: OrderSide,
+        rounding: &RoundingSettings,
+    ) -> Result<(Price, Amount)> {
+        let price = self.price_round(price, rounding.price.to_round(price, side))?;
+        let amount = self.amount_round(amount, rounding.amount.to_round(amount, side))?;
+
+        Ok((price, amount))
+    }
+
     pub fn round_to_remove_amount_precision_error(&self, amount: Amount) -> Result<Amount> {
         // allowed machine error that is less then 0.01 * amount precision
         self.amount_round_pr(amount, Round::ToNearest, self.amount_precision + 2i8)
@@ -309,6 +371,10 @@ impl CurrencyPairMetadata {
 }
 
 impl Exchange {
+    pub fn set_rounding(&self, rounding: RoundingSettings) {
+        *self.rounding.lock() = rounding;
+    }
+
     pub fn get_currency_pair_metadata(
         &self,
         currency_pair: &CurrencyPair,
@@ -500,4 +566,105 @@ mod test {
 
         Ok(())
     }
+
+    fn create_metadata_for_rounding() -> CurrencyPairMetadata {
+        CurrencyPairMetadata::new(
+            false,
+            false,
+            "PHB".into(),
+            "PHB".into(),
+            "BTC".into(),
+            "BTC".into(),
+            None,
+            None,
+            0,
+            PrecisionType::ByFraction,
+            Some(dec!(0.1)),
+            "PHB".into(),
+            None,
+            None,
+            2,
+            PrecisionType::ByFraction,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[rstest]
+    #[case(dec!(1.26), RoundingMode::Floor, dec!(1.2))]
+    #[case(dec!(1.26), RoundingMode::Ceil, dec!(1.3))]
+    #[case(dec!(1.26), RoundingMode::HalfUp, dec!(1.3))]
+    #[case(dec!(1.25), RoundingMode::HalfUp, dec!(1.3))]
+    #[case(dec!(1.24), RoundingMode::HalfUp, dec!(1.2))]
+    #[case(dec!(1.26), RoundingMode::TowardZero, dec!(1.2))]
+    #[case(dec!(-1.26), RoundingMode::Floor, dec!(-1.3))]
+    #[case(dec!(-1.26), RoundingMode::Ceil, dec!(-1.2))]
+    #[case(dec!(-1.26), RoundingMode::TowardZero, dec!(-1.2))]
+    fn round_order_price(
+        #[case] price: Decimal,
+        #[case] mode: RoundingMode,
+        #[case] expected: Decimal,
+    ) -> Result<()> {
+        let rounding = RoundingSettings {
+            price: mode,
+            amount: RoundingMode::Floor,
+        };
+
+        let (rounded_price, _) = create_metadata_for_rounding().round_order(
+            price,
+            dec!(1),
+            OrderSide::Buy,
+            &rounding,
+        )?;
+
+        assert_eq!(rounded_price, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(OrderSide::Buy, dec!(1.2))]
+    #[case(OrderSide::Sell, dec!(1.3))]
+    fn round_order_price_passively(
+        #[case] side: OrderSide,
+        #[case] expected: Decimal,
+    ) -> Result<()> {
+        let rounding = RoundingSettings::default();
+
+        let (rounded_price, _) =
+            create_metadata_for_rounding().round_order(dec!(1.26), dec!(1), side, &rounding)?;
+
+        assert_eq!(rounded_price, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(dec!(12.3456), RoundingMode::Floor, dec!(12.34))]
+    #[case(dec!(12.3456), RoundingMode::Ceil, dec!(12.35))]
+    #[case(dec!(12.3456), RoundingMode::HalfUp, dec!(12.35))]
+    #[case(dec!(12.3456), RoundingMode::TowardZero, dec!(12.34))]
+    #[case(dec!(-12.3456), RoundingMode::TowardZero, dec!(-12.34))]
+    fn round_order_amount(
+        #[case] amount: Decimal,
+        #[case] mode: RoundingMode,
+        #[case] expected: Decimal,
+    ) -> Result<()> {
+        let rounding = RoundingSettings {
+            price: RoundingMode::HalfUp,
+            amount: mode,
+        };
+
+        let (_, rounded_amount) = create_metadata_for_rounding().round_order(
+            dec!(1),
+            amount,
+            OrderSide::Buy,
+            &rounding,
+        )?;
+
+        assert_eq!(rounded_amount, expected);
+
+        Ok(())
+    }
 }
@@ -1,26 +1,44 @@
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
 use anyhow::{bail, Context, Error, Result};
 use awc::http::StatusCode;
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use futures::FutureExt;
 use log::{error, info, warn, Level};
 use parking_lot::Mutex;
 use serde_json::Value;
-use tokio::sync::{broadcast, oneshot};
+use tokio::sync::{broadcast, mpsc, oneshot};
 
 use super::commission::Commission;
-use super::currency_pair_metadata::CurrencyPairMetadata;
+use super::currency_pair_metadata::{CurrencyPairMetadata, RoundingSettings};
+use super::handlers::handle_cancel_order_succeeded::CancelFillReconciliation;
+use super::order::modify::OrderModificationPreference;
+use super::price_rounder::PriceRounder;
+use super::time_sync::TimeSync;
+use super::trading_window::TradingWindow;
+use crate::core::clock::{Clock, SystemClock};
 use crate::core::connectivity::connectivity_manager::GetWSParamsCallback;
-use crate::core::exchanges::events::ExchangeEvent;
-use crate::core::exchanges::general::features::ExchangeFeatures;
+use crate::core::connectivity::reconnect_backoff::ReconnectBackoffSettings;
+use crate::core::exchanges::events::{CreationConfirmationSource, ExchangeEvent};
+use crate::core::exchanges::general::features::{ExchangeCapabilities, ExchangeFeatures};
 use crate::core::exchanges::general::order::cancel::CancelOrderResult;
+use crate::core::exchanges::general::order::cancel_escalation::CancelEscalationSettings;
 use crate::core::exchanges::general::order::create::CreateOrderResult;
+use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::exchanges::timeouts::rate_limit_rules::{
+    RateLimitRules, RateLimitTracker, REQUEST_WEIGHT,
+};
 use crate::core::exchanges::timeouts::timeout_manager::TimeoutManager;
+use crate::core::infrastructure::spawn_future;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
 use crate::core::orders::event::OrderEventType;
-use crate::core::orders::order::OrderHeader;
+use crate::core::orders::metrics::{CreationFailureReason, OrderMetricsHook};
+use crate::core::orders::oco::{OcoGroup, OcoRegistry};
+use crate::core::orders::order::{OrderHeader, OrderSide, OrderStatus};
 use crate::core::orders::pool::OrdersPool;
+use crate::core::orders::store::OrderStore;
 use crate::core::orders::{order::ExchangeOrderId, pool::OrderRef};
+use crate::core::DateTime;
 use crate::core::{
     connectivity::connectivity_manager::WebSocketRole,
     exchanges::common::ExchangeAccountId,
@@ -40,6 +58,8 @@ use crate::core::{
     orders::event::OrderEvent,
 };
 
+// weight of each REST request in rate limits, exchange specific weights aren't supported yet
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum RequestResult<T> {
     Success(T),
@@ -89,8 +109,8 @@ pub struct Exchange {
         ),
     >,
     pub(super) features: ExchangeFeatures,
-    pub(super) events_channel: broadcast::Sender<ExchangeEvent>,
-    application_manager: Arc<ApplicationManager>,
+    pub(crate) events_channel: broadcast::Sender<ExchangeEvent>,
+    pub(super) application_manager: Arc<ApplicationManager>,
     pub(crate) timeout_manager: Arc<TimeoutManager>,
     pub(super) commission: Commission,
     pub(super) supported_symbols: Mutex<Vec<Arc<CurrencyPairMetadata>>>,
@@ -100,6 +120,35 @@ pub struct Exchange {
     pub(super) wait_cancel_order: DashMap<ClientOrderId, broadcast::Sender<()>>,
     pub(super) orders_finish_events: DashMap<ClientOrderId, oneshot::Sender<()>>,
     pub(super) orders_created_events: DashMap<ClientOrderId, oneshot::Sender<()>>,
+    pub(super) oco_groups: DashMap<ClientOrderId, Arc<OcoGroup>>,
+    // orders linked by `oco_group_id`
+    pub(crate) oco_registry: OcoRegistry,
+    pub(super) price_rounders: DashMap<CurrencyPair, Arc<dyn PriceRounder>>,
+    pub(super) rounding: Mutex<RoundingSettings>,
+    pub(super) creation_confirmation_source: Mutex<CreationConfirmationSource>,
+    pub(super) creation_confirmation_timeout: Mutex<Option<chrono::Duration>>,
+    pub(super) order_modification_preference: Mutex<OrderModificationPreference>,
+    pub(super) cancel_fill_reconciliation: Mutex<CancelFillReconciliation>,
+    pub(super) trading_window: Mutex<TradingWindow>,
+    pub(super) max_positions: DashMap<CurrencyPair, Amount>,
+    // net position by pair accumulated from fills, pruning of orders pool doesn't affect it
+    pub(super) positions: DashMap<CurrencyPair, Amount>,
+    pub(super) min_order_notionals: DashMap<CurrencyPair, Price>,
+    pub(super) paused_pairs: DashSet<CurrencyPair>,
+    pub(super) orphan_grace_period: Mutex<chrono::Duration>,
+    pub(super) cancel_escalation: Mutex<Option<CancelEscalationSettings>>,
+    pub(super) min_replace_interval: Mutex<chrono::Duration>,
+    // time of the last cancel-replace of quote by pair and side, it can be in future for deferred one
+    pub(super) last_replace_times: DashMap<(CurrencyPair, OrderSide), DateTime>,
+    pub(super) max_retained_fills: Mutex<Option<usize>>,
+    rate_limit_tracker: Arc<RateLimitTracker>,
+    pub(super) time_sync: TimeSync,
+    order_metrics_hook: Mutex<Option<Arc<dyn OrderMetricsHook>>>,
+    order_store: Mutex<Option<Arc<dyn OrderStore>>>,
+    resubscription_sender: Mutex<Option<mpsc::UnboundedSender<CurrencyPair>>>,
+    clock: Mutex<Arc<dyn Clock>>,
+    // for spawning of exchange actions from handlers which have no `Arc<Exchange>`
+    pub(super) weak_self: Weak<Exchange>,
 }
 
 pub type BoxExchangeClient = Box<dyn ExchangeClient + Send + Sync + 'static>;
@@ -116,7 +165,7 @@ impl Exchange {
     ) -> Arc<Self> {
         let connectivity_manager = ConnectivityManager::new(exchange_account_id.clone());
 
-        let exchange = Arc::new(Self {
+        let exchange = Arc::new_cyclic(|weak_self| Self {
             exchange_account_id: exchange_account_id.clone(),
             exchange_client,
             orders: OrdersPool::new(),
@@ -135,6 +184,34 @@ impl Exchange {
             wait_cancel_order: DashMap::new(),
             orders_finish_events: DashMap::new(),
             orders_created_events: DashMap::new(),
+            oco_groups: DashMap::new(),
+            oco_registry: OcoRegistry::default(),
+            price_rounders: DashMap::new(),
+            rounding: Mutex::new(RoundingSettings::default()),
+            creation_confirmation_source: Mutex::new(CreationConfirmationSource::default()),
+            creation_confirmation_timeout: Mutex::new(None),
+            order_modification_preference: Mutex::new(OrderModificationPreference::default()),
+            cancel_fill_reconciliation: Mutex::new(CancelFillReconciliation::default()),
+            trading_window: Mutex::new(TradingWindow::default()),
+            max_positions: DashMap::new(),
+            positions: DashMap::new(),
+            min_order_notionals: DashMap::new(),
+            paused_pairs: DashSet::new(),
+            orphan_grace_period: Mutex::new(chrono::Duration::zero()),
+            cancel_escalation: Mutex::new(None),
+            min_replace_interval: Mutex::new(chrono::Duration::zero()),
+            last_replace_times: DashMap::new(),
+            max_retained_fills: Mutex::new(None),
+            rate_limit_tracker: Arc::new(RateLimitTracker::new(
+                RateLimitRules::default(),
+                std::time::Instant::now(),
+            )),
+            time_sync: TimeSync::new(exchange_account_id.clone(), None),
+            order_metrics_hook: Mutex::new(None),
+            order_store: Mutex::new(None),
+            resubscription_sender: Mutex::new(None),
+            clock: Mutex::new(Arc::new(SystemClock)),
+            weak_self: weak_self.clone(),
         });
 
         exchange.clone().setup_connectivity_manager();
@@ -153,11 +230,15 @@ impl Exchange {
     }
 
     fn setup_exchange_client(self: Arc<Self>) {
+        self.exchange_client
+            .set_rate_limit_tracker(self.rate_limit_tracker());
+
         let exchange_weak = Arc::downgrade(&self);
         self.exchange_client.set_order_created_callback(Box::new(
             move |client_order_id, exchange_order_id, source_type| match exchange_weak.upgrade() {
                 Some(exchange) => {
-                    exchange.raise_order_created(&client_order_id, &exchange_order_id, source_type)
+                    exchange.raise_order_created(&client_order_id, &exchange_order_id, source_type);
+                    exchange.schedule_auto_cancel(&client_order_id);
                 }
                 None => info!("Unable to upgrade weak reference to Exchange instance",),
             },
@@ -192,7 +273,6 @@ impl Exchange {
                 match exchange_weak.upgrade() {
                     Some(exchange) => {
                         let handle_outcome = exchange.handle_order_filled(event_data);
-
                         if let Err(error) = handle_outcome {
                             let error_message =
                                 format!("Error in handle_order_filled: {:?}", error);
@@ -242,6 +322,48 @@ impl Exchange {
         // TODO Reconnect
     }
 
+    /// Request to receive order book of currency pair again from the beginning.
+    /// Requests are handled by `handle_market_data_resubscriptions`
+    pub fn resubscribe_market_data(&self, currency_pair: CurrencyPair) {
+        warn!(
+            "Resubscribing to market data of {} on {}",
+            currency_pair, self.exchange_account_id
+        );
+
+        match &*self.resubscription_sender.lock() {
+            Some(sender) => {
+                let _ = sender.send(currency_pair);
+            }
+            None => error!(
+                "Unable to resubscribe to market data on {} because resubscriptions aren't handled",
+                self.exchange_account_id
+            ),
+        }
+    }
+
+    /// Market data streams are specified by websocket connection params, so websockets are
+    /// reconnected on resubscription. Requests received during reconnection are handled by it
+    pub async fn handle_market_data_resubscriptions(self: Arc<Self>) {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        *self.resubscription_sender.lock() = Some(sender);
+
+        let stop_token = self.application_manager.stop_token();
+        loop {
+            tokio::select! {
+                currency_pair = receiver.recv() => {
+                    if currency_pair.is_none() {
+                        return;
+                    }
+                }
+                _ = stop_token.when_cancelled() => return,
+            }
+
+            self.connectivity_manager.clone().disconnect().await;
+            while let Some(Some(_)) = receiver.recv().now_or_never() {}
+            self.clone().connect().await;
+        }
+    }
+
     async fn try_connect(self: Arc<Self>) {
         // TODO IsWebSocketConnecting()
         info!("Websocket: Connecting on {}", "test_exchange_id");
@@ -274,6 +396,111 @@ impl Exchange {
         // TODO all other logs and finish_connected
     }
 
+    pub fn set_reconnect_backoff_settings(&self, settings: ReconnectBackoffSettings) {
+        self.connectivity_manager
+            .set_reconnect_backoff_settings(settings);
+    }
+
+    pub fn set_creation_confirmation_source(&self, source: CreationConfirmationSource) {
+        *self.creation_confirmation_source.lock() = source;
+    }
+
+    pub fn set_creation_confirmation_timeout(&self, timeout: Option<chrono::Duration>) {
+        *self.creation_confirmation_timeout.lock() = timeout;
+    }
+
+    pub fn set_order_modification_preference(&self, preference: OrderModificationPreference) {
+        *self.order_modification_preference.lock() = preference;
+    }
+
+    pub fn set_cancel_fill_reconciliation(&self, reconciliation: CancelFillReconciliation) {
+        *self.cancel_fill_reconciliation.lock() = reconciliation;
+    }
+
+    pub fn set_trading_window(&self, trading_window: TradingWindow) {
+        *self.trading_window.lock() = trading_window;
+    }
+
+    pub fn time_sync(&self) -> &TimeSync {
+        &self.time_sync
+    }
+
+    /// Should be called on receiving server time from exchange to track clock drift
+    pub fn update_exchange_time(&self, exchange_time: DateTime) {
+        self.time_sync.update(self.now(), exchange_time);
+    }
+
+    /// Take time sample from response of order creation or cancellation. Order info responses
+    /// aren't used because they specify times of order updates only
+    pub(super) fn update_exchange_time_by_response(&self, response: &RestRequestOutcome) {
+        if let Some(server_time) = self.exchange_client.get_server_time(response) {
+            self.update_exchange_time(server_time);
+        }
+    }
+
+    pub fn set_clock(&self, clock: Arc<dyn Clock>) {
+        *self.clock.lock() = clock;
+    }
+
+    /// Current time of exchange from configured clock (system time by default)
+    pub fn now(&self) -> DateTime {
+        self.clock.lock().now()
+    }
+
+    pub fn set_rate_limit_rules(&self, rules: RateLimitRules) {
+        self.rate_limit_tracker
+            .set_rules(rules, std::time::Instant::now());
+    }
+
+    pub fn rate_limit_tracker(&self) -> Arc<RateLimitTracker> {
+        self.rate_limit_tracker.clone()
+    }
+
+    /// Wait until REST request fits in rate limits of exchange and account it
+    pub(crate) async fn acquire_rate_limit(&self, request_type: RequestType) {
+        self.rate_limit_tracker()
+            .acquire(
+                REQUEST_WEIGHT,
+                matches!(
+                    request_type,
+                    RequestType::CreateOrder | RequestType::AmendOrder
+                ),
+            )
+            .await;
+    }
+
+    pub fn set_order_metrics_hook(&self, hook: Arc<dyn OrderMetricsHook>) {
+        *self.order_metrics_hook.lock() = Some(hook);
+    }
+
+    /// Orders are saved to store on each change and removed from it when finished
+    pub fn set_order_store(&self, order_store: Arc<dyn OrderStore>) {
+        *self.order_store.lock() = Some(order_store);
+    }
+
+    fn persist_order(&self, order_ref: &OrderRef) {
+        if let Some(order_store) = &*self.order_store.lock() {
+            let persist_outcome = match order_ref.is_finished() {
+                true => order_store.remove(&order_ref.client_order_id()),
+                false => order_store.save(&order_ref.deep_clone()),
+            };
+            if let Err(error) = persist_outcome {
+                error!(
+                    "Unable to persist order {} on {}: {:?}",
+                    order_ref.client_order_id(),
+                    self.exchange_account_id,
+                    error
+                );
+            }
+        }
+    }
+
+    pub(super) fn on_order_rejected(&self, reason: CreationFailureReason) {
+        if let Some(hook) = &*self.order_metrics_hook.lock() {
+            hook.on_order_rejected(reason);
+        }
+    }
+
     pub(super) fn get_rest_error(&self, response: &RestRequestOutcome) -> Option<ExchangeError> {
         self.get_rest_error_main(response, None, None)
     }
@@ -398,6 +625,7 @@ impl Exchange {
     }
 
     pub async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> anyhow::Result<()> {
+        self.acquire_rate_limit(RequestType::CancelOrder).await;
         self.exchange_client
             .cancel_all_orders(currency_pair)
             .await?;
@@ -462,7 +690,9 @@ impl Exchange {
                 .orders
                 .not_finished
                 .remove(&order_ref.client_order_id());
+            self.oco_registry.on_finished(&order_ref.client_order_id());
         }
+        self.persist_order(order_ref);
 
         let event = ExchangeEvent::OrderEvent(OrderEvent::new(order_ref.clone(), event_type));
         self.events_channel
@@ -472,6 +702,122 @@ impl Exchange {
         Ok(())
     }
 
+    pub fn capabilities(&self) -> &ExchangeCapabilities {
+        &self.features.capabilities
+    }
+
+    pub fn register_oco_group(&self, group: Arc<OcoGroup>) {
+        let (first, second) = group.orders();
+        self.oco_groups
+            .insert(first.client_order_id(), group.clone());
+        self.oco_groups
+            .insert(second.client_order_id(), group.clone());
+    }
+
+    /// Link order with the other orders of the same `oco_group_id`
+    pub(super) fn register_oco_order(&self, order_ref: &OrderRef) {
+        if let Some(oco_group_id) = order_ref.fn_ref(|x| x.header.oco_group_id) {
+            self.oco_registry
+                .register(oco_group_id, order_ref.client_order_id());
+        }
+    }
+
+    /// Cancel other orders of filled order group registered by `oco_group_id`
+    pub(super) fn cancel_oco_siblings_on_fill(&self, order_ref: &OrderRef) {
+        let exchange = match self.weak_self.upgrade() {
+            Some(exchange) => exchange,
+            None => return,
+        };
+
+        for sibling_id in self.oco_registry.on_fill(&order_ref.client_order_id()) {
+            let order_to_cancel = match self.orders.cache_by_client_id.get(&sibling_id) {
+                Some(order_to_cancel) => order_to_cancel.clone(),
+                None => continue,
+            };
+            if order_to_cancel.is_finished() || order_to_cancel.status() == OrderStatus::Canceling {
+                continue;
+            }
+
+            info!(
+                "Order {} from OCO group filled on {}, canceling order {}",
+                order_ref.client_order_id(),
+                self.exchange_account_id,
+                sibling_id
+            );
+
+            let exchange = exchange.clone();
+            let action = async move {
+                exchange
+                    .start_cancel_order(&order_to_cancel, CancellationToken::default())
+                    .await
+                    .map(|_| ())
+            };
+            spawn_future("Cancel OCO sibling order", false, action.boxed());
+        }
+    }
+
+    pub fn register_price_rounder(
+        &self,
+        currency_pair: CurrencyPair,
+        price_rounder: Arc<dyn PriceRounder>,
+    ) {
+        let _ = self.price_rounders.insert(currency_pair, price_rounder);
+    }
+
+    /// Round price with rounder registered for currency pair.
+    /// Price is returned as is if there is no registered rounder
+    pub fn round_price(
+        &self,
+        currency_pair: &CurrencyPair,
+        price: Price,
+        side: OrderSide,
+    ) -> Price {
+        match self.price_rounders.get(currency_pair) {
+            Some(price_rounder) => price_rounder.round_price(price, side),
+            None => price,
+        }
+    }
+
+    pub(super) fn cancel_oco_sibling_if_completed(&self, exchange_order_id: &ExchangeOrderId) {
+        let order_ref = match self.orders.cache_by_exchange_id.get(exchange_order_id) {
+            Some(order_ref) => order_ref.clone(),
+            None => return,
+        };
+
+        if order_ref.status() != OrderStatus::Completed {
+            return;
+        }
+
+        let group = match self.oco_groups.get(&order_ref.client_order_id()) {
+            Some(group) => group.clone(),
+            None => return,
+        };
+
+        let order_to_cancel = group.on_order_completed(&order_ref.client_order_id());
+
+        let (first, second) = group.orders();
+        let _ = self.oco_groups.remove(&first.client_order_id());
+        let _ = self.oco_groups.remove(&second.client_order_id());
+
+        let exchange = self.weak_self.upgrade();
+        if let (Some(order_to_cancel), Some(exchange)) = (order_to_cancel, exchange) {
+            info!(
+                "Order {} from OCO group completed on {}, canceling order {}",
+                order_ref.client_order_id(),
+                self.exchange_account_id,
+                order_to_cancel.client_order_id()
+            );
+
+            let action = async move {
+                exchange
+                    .start_cancel_order(&order_to_cancel, CancellationToken::default())
+                    .await
+                    .map(|_| ())
+            };
+            spawn_future("Cancel OCO sibling order", false, action.boxed());
+        }
+    }
+
     pub async fn cancel_opened_orders(self: Arc<Self>) {
         // TODO should be implemented
     }
@@ -1,11 +1,14 @@
 use std::sync::Arc;
 
+use chrono::Duration;
 use itertools::Itertools;
 use log::error;
 use tokio::sync::broadcast;
 
 use super::{commission::Commission, currency_pair_metadata::CurrencyPairMetadata};
+use crate::core::clock::OffsetClock;
 use crate::core::exchanges::events::ExchangeEvent;
+use crate::core::exchanges::paper_trading::wrap_for_dry_run;
 use crate::core::lifecycle::application_manager::ApplicationManager;
 use crate::core::lifecycle::launcher::EngineBuildConfig;
 use crate::core::settings::{CurrencyPairSetting, ExchangeSettings};
@@ -18,6 +21,53 @@ use crate::core::{
     settings::CoreSettings,
 };
 
+/// Exchange settings which can be changed without restart of trading engine
+pub const LIVE_EXCHANGE_SETTINGS: &[&str] = &[
+    "creation_confirmation_source",
+    "creation_confirmation_timeout_ms",
+    "order_modification",
+    "cancel_fill_reconciliation",
+    "trading_window",
+    "max_clock_drift_ms",
+    "max_positions",
+    "min_order_notionals",
+    "orphan_grace_period_ms",
+    "cancel_escalation",
+    "min_replace_interval_ms",
+    "max_retained_fills_per_order",
+    "rounding",
+];
+
+/// Apply to running exchange all settings from `LIVE_EXCHANGE_SETTINGS`
+pub fn apply_live_settings(exchange: &Exchange, settings: &ExchangeSettings) {
+    exchange.set_creation_confirmation_source(settings.creation_confirmation_source);
+    exchange.set_creation_confirmation_timeout(
+        settings
+            .creation_confirmation_timeout_ms
+            .map(Duration::milliseconds),
+    );
+    exchange.set_order_modification_preference(settings.order_modification);
+    exchange.set_cancel_fill_reconciliation(settings.cancel_fill_reconciliation);
+    exchange.set_trading_window(settings.trading_window.clone());
+    exchange
+        .time_sync()
+        .set_max_drift(settings.max_clock_drift_ms.map(Duration::milliseconds));
+
+    exchange.max_positions.clear();
+    for (currency_pair, max_position) in &settings.max_positions {
+        exchange.set_max_position(currency_pair.clone(), *max_position);
+    }
+    exchange.min_order_notionals.clear();
+    for (currency_pair, min_order_notional) in &settings.min_order_notionals {
+        exchange.set_min_order_notional(currency_pair.clone(), *min_order_notional);
+    }
+    exchange.set_orphan_grace_period(Duration::milliseconds(settings.orphan_grace_period_ms));
+    exchange.set_cancel_escalation(settings.cancel_escalation.clone());
+    exchange.set_min_replace_interval(Duration::milliseconds(settings.min_replace_interval_ms));
+    exchange.set_max_retained_fills(settings.max_retained_fills_per_order);
+    exchange.set_rounding(settings.rounding);
+}
+
 pub fn create_timeout_manager(
     core_settings: &CoreSettings,
     build_settings: &EngineBuildConfig,
@@ -55,11 +105,19 @@ pub async fn create_exchange(
 
     let mut user_settings = user_settings.clone();
     exchange_client_builder.extend_settings(&mut user_settings);
-    let exchange_client = exchange_client_builder.create_exchange_client(
+    let mut exchange_client = exchange_client_builder.create_exchange_client(
         user_settings.clone(),
         events_channel.clone(),
         application_manager.clone(),
     );
+    if build_settings.dry_run {
+        exchange_client = wrap_for_dry_run(
+            exchange_client,
+            user_settings.exchange_account_id.clone(),
+            events_channel.subscribe(),
+            application_manager.stop_token(),
+        );
+    }
 
     let exchange = Exchange::new(
         user_settings.exchange_account_id.clone(),
@@ -71,8 +129,17 @@ pub async fn create_exchange(
         Commission::default(),
     );
 
+    exchange.set_reconnect_backoff_settings(user_settings.websocket_reconnect_backoff.clone());
+    exchange.set_rate_limit_rules(user_settings.rate_limits.clone());
+    apply_live_settings(&exchange, &user_settings);
+    if user_settings.time_offset_ms != 0 {
+        exchange.set_clock(Arc::new(OffsetClock::new(Duration::milliseconds(
+            user_settings.time_offset_ms,
+        ))));
+    }
     exchange.build_metadata().await;
     exchange.clone().connect().await;
+    let _handle = actix::spawn(exchange.clone().handle_market_data_resubscriptions());
 
     if let Some(currency_pairs) = &user_settings.currency_pairs {
         exchange.set_symbols(get_symbols(&exchange, &currency_pairs[..]))
@@ -1,5 +1,6 @@
 use crate::core::exchanges::common::{CurrencyCode, CurrencyId};
 use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::exchanges::general::request_type::RequestType;
 use anyhow::{bail, Result};
 use dashmap::DashMap;
 use itertools::Itertools;
@@ -60,6 +61,7 @@ impl Exchange {
     }
 
     async fn build_metadata_core(&self) -> Result<Vec<Arc<CurrencyPairMetadata>>> {
+        self.acquire_rate_limit(RequestType::GetMarkets).await;
         let response = self.exchange_client.request_metadata().await?;
 
         if let Some(error) = self.get_rest_error(&response) {
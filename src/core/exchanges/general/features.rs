@@ -9,12 +9,25 @@ pub enum OpenOrdersType {
     OneCurrencyPair,
 }
 
+/// Trading features supported by exchange
+#[derive(Debug, Default, Clone)]
+pub struct ExchangeCapabilities {
+    pub post_only: bool,
+    pub oco: bool,
+    pub reduce_only: bool,
+    pub batch_orders: bool,
+    pub cancel_on_disconnect: bool,
+    // in-place modification of price and amount of opened order
+    pub amend_order: bool,
+}
+
 pub struct ExchangeFeatures {
     pub open_orders_type: OpenOrdersType,
     pub empty_response_is_ok: bool,
     pub allows_to_get_order_info_by_client_order_id: bool,
     pub allowed_fill_event_source_type: AllowedEventSourceType,
     pub allowed_cancel_event_source_type: AllowedEventSourceType,
+    pub capabilities: ExchangeCapabilities,
 }
 
 impl ExchangeFeatures {
@@ -24,6 +37,7 @@ impl ExchangeFeatures {
         allows_to_get_order_info_by_client_order_id: bool,
         allowed_fill_event_source_type: AllowedEventSourceType,
         allowed_cancel_event_source_type: AllowedEventSourceType,
+        capabilities: ExchangeCapabilities,
     ) -> Self {
         Self {
             open_orders_type,
@@ -31,6 +45,7 @@ impl ExchangeFeatures {
             allows_to_get_order_info_by_client_order_id,
             allowed_fill_event_source_type,
             allowed_cancel_event_source_type,
+            capabilities,
         }
     }
 }
@@ -1,5 +1,4 @@
 use anyhow::Result;
-use chrono::Utc;
 use log::{error, warn};
 
 use crate::core::{
@@ -112,8 +111,12 @@ impl Exchange {
                     // TODO Some metrics
                 }
 
-                order.fn_mut(|order| order.set_status(OrderStatus::FailedToCancel, Utc::now()));
+                order.fn_mut(|order| {
+                    order.set_status(OrderStatus::FailedToCancel, self.now());
+                    order.internal_props.cancellation_attempts += 1;
+                });
                 self.add_event_on_order_change(&order, OrderEventType::CancelOrderFailed)?;
+                self.escalate_if_cancel_attempts_exhausted(order)?;
 
                 warn!(
                     "Order cancellation failed: {} {:?} on {} with error: {:?} {:?} {}",
@@ -151,6 +154,7 @@ mod test {
         },
         orders::pool::OrdersPool,
     };
+    use chrono::Utc;
     use parking_lot::RwLock;
     use rust_decimal_macros::dec;
     use std::mem::discriminant;
@@ -1,6 +1,8 @@
 use anyhow::{bail, Result};
-use chrono::Utc;
 use log::{error, info, warn};
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::core::{
     exchanges::common::Amount,
@@ -8,11 +10,24 @@ use crate::core::{
     exchanges::events::AllowedEventSourceType,
     exchanges::general::exchange::Exchange,
     orders::{
-        event::OrderEventType, fill::EventSourceType, order::ClientOrderId, order::ExchangeOrderId,
-        order::OrderStatus, pool::OrderRef,
+        event::OrderEventType, fill::EventSourceType, fill::OrderFill, fill::OrderFillType,
+        order::ClientOrderId, order::ExchangeOrderId, order::OrderRole, order::OrderStatus,
+        pool::OrderRef,
     },
 };
 
+/// What to do when cancel confirmation reports bigger filled amount than known locally
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Deserialize, Serialize)]
+pub enum CancelFillReconciliation {
+    // Add synthetic diff fill for missed amount so order filled amount matches exchange one.
+    // Synthetic fill isn't replaced by real fills, so it suits only exchanges which
+    // don't deliver fills after cancellation confirmation
+    SyntheticFill,
+    // Only remember reported filled amount and wait for missed fills from exchange
+    #[default]
+    Disabled,
+}
+
 impl Exchange {
     pub(crate) fn handle_cancel_order_succeeded(
         &self,
@@ -103,9 +118,15 @@ impl Exchange {
             // TODO some metrics
         }
 
+        if let Some(filled_amount) = filled_amount {
+            if *self.cancel_fill_reconciliation.lock() == CancelFillReconciliation::SyntheticFill {
+                self.reconcile_filled_amount(order_ref, filled_amount, source_type)?;
+            }
+        }
+
         let is_canceling_from_wait_cancel_order = order_ref.fn_mut(|order| {
             order.internal_props.filled_amount_after_cancellation = filled_amount;
-            order.set_status(OrderStatus::Canceled, Utc::now());
+            order.set_status(OrderStatus::Canceled, self.now());
             order.internal_props.cancellation_event_source_type = Some(source_type);
             order.internal_props.is_canceling_from_wait_cancel_order
         });
@@ -141,6 +162,66 @@ impl Exchange {
         Ok(())
     }
 
+    /// Cancel may race with fill, so exchange can report filled amount which fills weren't received
+    /// yet. Missed amount is added as synthetic fill by order price
+    fn reconcile_filled_amount(
+        &self,
+        order_ref: &OrderRef,
+        filled_amount: Amount,
+        source_type: EventSourceType,
+    ) -> Result<()> {
+        let missed_amount = filled_amount - order_ref.filled_amount();
+        if missed_amount <= dec!(0) {
+            return Ok(());
+        }
+
+        let currency_pair_metadata = self.get_currency_pair_metadata(&order_ref.currency_pair())?;
+        let price = order_ref.fn_ref(|x| x.price_or_error())?;
+        let cost = if currency_pair_metadata.is_derivative() {
+            missed_amount / price
+        } else {
+            missed_amount * price
+        };
+        let commission_currency_code =
+            currency_pair_metadata.get_commission_currency_code(order_ref.side());
+        let role = order_ref
+            .fn_ref(|order| order.props.role)
+            .unwrap_or(OrderRole::Maker);
+
+        warn!(
+            "Adding synthetic fill {} for order {} {:?} on {} because cancellation reported filled amount {} but {} known locally",
+            missed_amount,
+            order_ref.client_order_id(),
+            order_ref.exchange_order_id(),
+            self.exchange_account_id,
+            filled_amount,
+            order_ref.filled_amount()
+        );
+
+        let fill = OrderFill::new(
+            Uuid::new_v4(),
+            self.now(),
+            OrderFillType::UserTrade,
+            None,
+            price,
+            missed_amount,
+            cost,
+            role.into(),
+            commission_currency_code.clone(),
+            dec!(0),
+            dec!(0),
+            commission_currency_code,
+            dec!(0),
+            dec!(0),
+            true,
+            Some(source_type),
+            Some(order_ref.side()),
+        );
+        self.add_order_fill(order_ref, fill);
+
+        Ok(())
+    }
+
     fn log_cancel_handling_error_and_propagate(
         template: &str,
         args_to_log: &(
@@ -166,6 +247,7 @@ mod test {
         orders::order::OrderSide,
     };
     use anyhow::Context;
+    use chrono::Utc;
     use rstest::rstest;
     use rust_decimal_macros::dec;
 
@@ -336,6 +418,71 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn reconcile_filled_amount_reported_by_cancel() -> Result<()> {
+        let (exchange, _rx) = test_helper::get_test_exchange(false);
+        exchange.set_cancel_fill_reconciliation(CancelFillReconciliation::SyntheticFill);
+
+        let order_ref = test_helper::create_order_ref(
+            &ClientOrderId::unique_id(),
+            Some(OrderRole::Maker),
+            &exchange.exchange_account_id.clone(),
+            &CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            dec!(0.8),
+            dec!(12),
+            OrderSide::Buy,
+        );
+
+        let exchange_order_id = ExchangeOrderId::new("".into());
+        let filled_amount = Some(dec!(5));
+        exchange.try_update_local_order(
+            &order_ref,
+            filled_amount,
+            EventSourceType::Rest,
+            &exchange_order_id,
+        )?;
+
+        assert_eq!(order_ref.filled_amount(), dec!(5));
+        let fills = order_ref.get_fills().0;
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].amount(), dec!(5));
+        assert_eq!(fills[0].price(), dec!(0.8));
+        assert!(fills[0].is_diff());
+        assert_eq!(
+            order_ref.fn_ref(|x| x.internal_props.filled_amount_after_cancellation),
+            filled_amount
+        );
+        assert_eq!(order_ref.status(), OrderStatus::Canceled);
+
+        Ok(())
+    }
+
+    #[test]
+    fn no_reconciliation_by_default() -> Result<()> {
+        let (exchange, _rx) = test_helper::get_test_exchange(false);
+
+        let order_ref = test_helper::create_order_ref(
+            &ClientOrderId::unique_id(),
+            Some(OrderRole::Maker),
+            &exchange.exchange_account_id.clone(),
+            &CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            dec!(0.8),
+            dec!(12),
+            OrderSide::Buy,
+        );
+
+        exchange.try_update_local_order(
+            &order_ref,
+            Some(dec!(5)),
+            EventSourceType::Rest,
+            &ExchangeOrderId::new("".into()),
+        )?;
+
+        assert_eq!(order_ref.filled_amount(), dec!(0));
+
+        Ok(())
+    }
+
     #[test]
     fn canceled_not_from_wait_cancel_order() -> Result<()> {
         let (exchange, mut event_receiver) = test_helper::get_test_exchange(false);
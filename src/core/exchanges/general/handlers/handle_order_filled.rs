@@ -1,5 +1,4 @@
 use anyhow::{bail, Context, Result};
-use chrono::Utc;
 use log::{error, info, warn};
 use parking_lot::RwLock;
 use rust_decimal::Decimal;
@@ -17,8 +16,10 @@ use crate::core::{
     math::ConvertPercentToRate,
     orders::{
         event::OrderEventType, fill::EventSourceType, fill::OrderFill, fill::OrderFillType,
-        order::ClientOrderId, order::ExchangeOrderId, order::OrderRole, order::OrderSide,
-        order::OrderSnapshot, order::OrderStatus, order::OrderType, pool::OrderRef,
+        order::ClientOrderId, order::ExchangeOrderId, order::OrderExecutionType, order::OrderFills,
+        order::OrderHeader, order::OrderRole, order::OrderSide, order::OrderSimpleProps,
+        order::OrderSnapshot, order::OrderStatus, order::OrderStatusHistory, order::OrderType,
+        order::SystemInternalOrderProps, pool::OrderRef,
     },
 };
 
@@ -52,7 +53,37 @@ pub struct FillEventData {
 }
 
 impl Exchange {
-    pub fn handle_order_filled(&self, mut event_data: FillEventData) -> Result<()> {
+    pub fn set_max_retained_fills(&self, max_retained_fills: Option<usize>) {
+        *self.max_retained_fills.lock() = max_retained_fills;
+    }
+
+    /// Add fill to order aggregating its older fills if there are more than `max_retained_fills`.
+    /// Net position of order currency pair is updated by fill and other orders of its OCO group are canceled
+    pub(crate) fn add_order_fill(&self, order_ref: &OrderRef, fill: OrderFill) {
+        if fill.fill_type() != OrderFillType::Funding {
+            self.add_to_position(&order_ref.currency_pair(), order_ref.side(), fill.amount());
+        }
+
+        let max_retained_fills = *self.max_retained_fills.lock();
+        order_ref.fn_mut(|order| {
+            order.add_fill(fill.clone());
+            if let Some(max_retained_fills) = max_retained_fills {
+                order.fills.summarize_older_fills(max_retained_fills);
+            }
+        });
+        self.cancel_oco_siblings_on_fill(order_ref);
+    }
+
+    /// Add fill to order and cancel other order of its OCO group if order became completed
+    pub fn handle_order_filled(&self, event_data: FillEventData) -> Result<()> {
+        let exchange_order_id = event_data.exchange_order_id.clone();
+        self.add_fill_by_event(event_data)?;
+        self.cancel_oco_sibling_if_completed(&exchange_order_id);
+
+        Ok(())
+    }
+
+    fn add_fill_by_event(&self, mut event_data: FillEventData) -> Result<()> {
         let args_to_log = (
             self.exchange_account_id.clone(),
             event_data.trade_id.clone(),
@@ -86,15 +117,27 @@ impl Exchange {
         {
             None => {
                 if let Some(client_order_id) = &event_data.client_order_id {
-                    self.handle_create_order_succeeded(
+                    self.handle_create_order_confirmation(
                         &self.exchange_account_id,
                         client_order_id,
                         &event_data.exchange_order_id,
                         &event_data.source_type,
                     )?;
 
-                    let order_ref = self.orders.cache_by_exchange_id.get(&event_data.exchange_order_id).expect("Order should be inserted in orders.cache_by_exchange_id by handle_create_order_succeeded called above");
-                    return self.try_to_create_and_add_order_fill(&mut event_data, &order_ref);
+                    // confirmation is ignored e.g. for order which failed to create or from not preferred source
+                    return match self
+                        .orders
+                        .cache_by_exchange_id
+                        .get(&event_data.exchange_order_id)
+                    {
+                        Some(order_ref) => {
+                            self.try_to_create_and_add_order_fill(&mut event_data, &order_ref)
+                        }
+                        None => Self::log_fill_handling_error_and_propagate(
+                            "Received a fill for order which creation wasn't confirmed",
+                            &args_to_log,
+                        ),
+                    };
                 }
 
                 info!("Received a fill for not existing order {:?}", &args_to_log);
@@ -106,20 +149,9 @@ impl Exchange {
         }
     }
 
-    fn was_trade_already_received(
-        trade_id: &str,
-        order_fills: &Vec<OrderFill>,
-        order_ref: &OrderRef,
-    ) -> bool {
-        if !trade_id.is_empty()
-            && order_fills.iter().any(|fill| {
-                if let Some(fill_trade_id) = fill.trade_id() {
-                    return fill_trade_id == &trade_id;
-                }
-
-                false
-            })
-        {
+    fn was_trade_already_received(trade_id: &str, order_ref: &OrderRef) -> bool {
+        // summarized fills have to be checked too, so order fills are checked instead of their copy
+        if !trade_id.is_empty() && order_ref.fn_ref(|order| order.fills.contains_trade(trade_id)) {
             info!(
                 "Trade with {} was received already for order {:?}",
                 trade_id, order_ref
@@ -208,7 +240,17 @@ impl Exchange {
         };
 
         if !event_data.is_diff && order_fills.len() > 0 {
-            match Self::calculate_cost_diff(&order_fills, order_ref, last_fill_cost) {
+            let (total_filled_cost, current_commission) = order_ref.fn_ref(|order| {
+                (
+                    order.fills.total_cost(),
+                    order
+                        .total_commission()
+                        .iter()
+                        .map(|(_, amount)| *amount)
+                        .sum::<Decimal>(),
+                )
+            });
+            match Self::calculate_cost_diff(total_filled_cost, order_ref, last_fill_cost) {
                 None => return Ok(None),
                 Some(cost_diff) => {
                     let (price, amount, cost) = Self::calculate_last_fill_data(
@@ -221,7 +263,7 @@ impl Exchange {
                     last_fill_amount = amount;
                     last_fill_cost = cost;
 
-                    Self::try_set_commission_amount(event_data, order_fills);
+                    Self::try_set_commission_amount(event_data, current_commission);
                 }
             };
         }
@@ -240,12 +282,11 @@ impl Exchange {
     }
 
     fn calculate_cost_diff(
-        order_fills: &Vec<OrderFill>,
+        total_filled_cost: Decimal,
         order_ref: &OrderRef,
         last_fill_cost: Decimal,
     ) -> Option<Decimal> {
         // Diff should be calculated only if it is not the first fill
-        let total_filled_cost: Decimal = order_fills.iter().map(|fill| fill.cost()).sum();
         let cost_diff = last_fill_cost - total_filled_cost;
         if cost_diff <= dec!(0) {
             warn!(
@@ -280,18 +321,15 @@ impl Exchange {
         Ok((last_fill_price, last_fill_amount, last_fill_cost))
     }
 
-    fn try_set_commission_amount(event_data: &mut FillEventData, order_fills: &Vec<OrderFill>) {
+    fn try_set_commission_amount(event_data: &mut FillEventData, current_commission: Decimal) {
         if let Some(commission_amount) = event_data.commission_amount {
-            let current_commission: Decimal = order_fills
-                .iter()
-                .map(|fill| fill.commission_amount())
-                .sum();
             event_data.commission_amount = Some(commission_amount - current_commission);
         }
     }
 
     fn wrong_status_or_cancelled(order_ref: &OrderRef, event_data: &FillEventData) -> Result<()> {
         if order_ref.status() == OrderStatus::FailedToCreate
+            || order_ref.status() == OrderStatus::Rejected
             || order_ref.status() == OrderStatus::Completed
             || order_ref.was_cancellation_event_raised()
         {
@@ -485,7 +523,7 @@ impl Exchange {
     ) -> Result<()> {
         if order_filled_amount == order_ref.amount() {
             order_ref.fn_mut(|order| {
-                order.set_status(OrderStatus::Completed, Utc::now());
+                order.set_status(OrderStatus::Completed, self.now());
             });
 
             let cloned_order = Arc::new(order_ref.deep_clone());
@@ -532,7 +570,7 @@ impl Exchange {
             currency_pair_metadata.price_round(last_fill_price, Round::ToNearest)?;
         let order_fill = OrderFill::new(
             Uuid::new_v4(),
-            Utc::now(),
+            self.now(),
             fill_type,
             Some(trade_id.to_owned()),
             rounded_fill_price,
@@ -549,7 +587,7 @@ impl Exchange {
             None,
             None,
         );
-        order_ref.fn_mut(|order| order.add_fill(order_fill.clone()));
+        self.add_order_fill(order_ref, order_fill.clone());
 
         Ok(order_fill)
     }
@@ -561,7 +599,7 @@ impl Exchange {
     ) -> Result<()> {
         let (order_fills, order_filled_amount) = order_ref.get_fills();
 
-        if Self::was_trade_already_received(&event_data.trade_id, &order_fills, order_ref) {
+        if Self::was_trade_already_received(&event_data.trade_id, order_ref) {
             return Ok(());
         }
 
@@ -717,7 +755,7 @@ impl Exchange {
                     let order_ref = self.create_order_in_pool(event_data, OrderRole::Taker);
 
                     event_data.client_order_id = Some(order_ref.client_order_id());
-                    self.handle_create_order_succeeded(
+                    self.handle_create_order_confirmation(
                         &self.exchange_account_id,
                         &order_ref.client_order_id(),
                         &event_data.exchange_order_id,
@@ -746,17 +784,29 @@ impl Exchange {
 
         let client_order_id = ClientOrderId::unique_id();
 
-        let order_instance = OrderSnapshot::with_params(
+        let header = OrderHeader::new(
             client_order_id.clone(),
-            OrderType::Liquidation,
-            Some(order_role),
+            self.now(),
             self.exchange_account_id.clone(),
             currency_pair,
-            event_data.fill_price,
-            order_amount,
+            OrderType::Liquidation,
             order_side,
+            order_amount,
+            OrderExecutionType::None,
             None,
-            "Unknown order from handle_order_filled()",
+            None,
+            "Unknown order from handle_order_filled()".to_owned(),
+        );
+
+        let mut props = OrderSimpleProps::from_price(Some(event_data.fill_price));
+        props.role = Some(order_role);
+
+        let order_instance = OrderSnapshot::new(
+            header,
+            props,
+            OrderFills::default(),
+            OrderStatusHistory::default(),
+            SystemInternalOrderProps::default(),
         );
 
         self.orders
@@ -804,18 +854,17 @@ impl Exchange {
 #[cfg(test)]
 mod test {
     use chrono::Utc;
+    use rstest::rstest;
     use uuid::Uuid;
 
     use super::*;
     use crate::core::{
-        exchanges::common::CurrencyCode, exchanges::general::exchange::OrderBookTop,
+        clock::SimulatedClock, exchanges::common::CurrencyCode,
+        exchanges::events::CreationConfirmationSource, exchanges::general::exchange::OrderBookTop,
         exchanges::general::exchange::PriceLevel, exchanges::general::test_helper,
         exchanges::general::test_helper::create_order_ref,
         exchanges::general::test_helper::get_test_exchange, orders::fill::OrderFill,
-        orders::order::OrderExecutionType, orders::order::OrderFillRole, orders::order::OrderFills,
-        orders::order::OrderHeader, orders::order::OrderSimpleProps,
-        orders::order::OrderStatusHistory, orders::order::SystemInternalOrderProps,
-        orders::pool::OrdersPool,
+        orders::order::OrderFillRole, orders::pool::OrdersPool,
     };
 
     mod liquidation {
@@ -1006,6 +1055,50 @@ mod test {
             }
         }
 
+        #[test]
+        fn order_init_time_from_simulated_clock() {
+            let start = Utc::now() - chrono::Duration::days(30);
+            let clock = Arc::new(SimulatedClock::new(start));
+            let (exchange, _event_received) = get_test_exchange(false);
+            exchange.set_clock(clock.clone());
+
+            clock.advance(chrono::Duration::hours(1));
+
+            let event_data = FillEventData {
+                source_type: EventSourceType::WebSocket,
+                trade_id: String::new(),
+                client_order_id: None,
+                exchange_order_id: ExchangeOrderId::new("test".into()),
+                fill_price: dec!(0.2),
+                fill_amount: dec!(5),
+                is_diff: false,
+                total_filled_amount: None,
+                order_role: None,
+                commission_currency_code: None,
+                commission_rate: None,
+                commission_amount: None,
+                fill_type: OrderFillType::Liquidation,
+                trade_currency_pair: Some(CurrencyPair::from_codes("PHB".into(), "BTC".into())),
+                order_side: Some(OrderSide::Buy),
+                order_amount: Some(dec!(12)),
+            };
+            exchange.handle_order_filled(event_data).expect("in test");
+
+            let order = exchange
+                .orders
+                .cache_by_client_id
+                .iter()
+                .next()
+                .expect("order should be added already")
+                .clone();
+            let expected_time = start + chrono::Duration::hours(1);
+            assert_eq!(order.fn_ref(|x| x.header.init_time), expected_time);
+            assert_eq!(
+                order.fn_ref(|x| x.fills.fills[0].receive_time()),
+                expected_time
+            );
+        }
+
         #[test]
         fn empty_exchange_order_id() {
             let event_data = FillEventData {
@@ -1040,8 +1133,8 @@ mod test {
         }
     }
 
-    #[test]
-    fn ignore_if_trade_was_already_received() {
+    #[rstest]
+    fn ignore_if_trade_was_already_received(#[values(false, true)] is_fill_summarized: bool) {
         let (exchange, _event_receiver) = get_test_exchange(false);
 
         let client_order_id = ClientOrderId::unique_id();
@@ -1105,6 +1198,9 @@ mod test {
             None,
         );
         order.add_fill(order_fill);
+        if is_fill_summarized {
+            order.fills.summarize_older_fills(0);
+        }
         let order_pool = OrdersPool::new();
         let order_ref = order_pool.add_snapshot_initial(Arc::new(RwLock::new(order)));
 
@@ -3403,4 +3499,46 @@ mod test {
             Err(_) => assert!(false),
         }
     }
+
+    #[test]
+    fn fill_for_order_with_ignored_creation_confirmation_is_error() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+        exchange.set_creation_confirmation_source(CreationConfirmationSource::Rest);
+
+        let mut header = OrderHeader::test_default();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order_ref = exchange
+            .orders
+            .add_simple_initial(Arc::new(header), Some(dec!(0.8)));
+
+        let event_data = FillEventData {
+            source_type: EventSourceType::WebSocket,
+            trade_id: "trade_id".into(),
+            client_order_id: Some(order_ref.client_order_id()),
+            exchange_order_id: ExchangeOrderId::new("some_exchange_order_id".into()),
+            fill_price: dec!(0.8),
+            fill_amount: dec!(1),
+            is_diff: true,
+            total_filled_amount: None,
+            order_role: Some(OrderRole::Maker),
+            commission_currency_code: None,
+            commission_rate: None,
+            commission_amount: None,
+            fill_type: OrderFillType::UserTrade,
+            trade_currency_pair: None,
+            order_side: None,
+            order_amount: None,
+        };
+
+        let error = exchange
+            .handle_order_filled(event_data)
+            .expect_err("in test");
+
+        assert!(error
+            .to_string()
+            .contains("Received a fill for order which creation wasn't confirmed"));
+        assert_eq!(order_ref.status(), OrderStatus::Creating);
+        assert_eq!(order_ref.get_fills().1, dec!(0));
+    }
 }
@@ -6,6 +6,9 @@ pub mod exchange_metadata;
 pub mod features;
 pub mod handlers;
 pub mod order;
+pub mod price_rounder;
 pub mod request_type;
 #[cfg(test)]
 pub mod test_helper;
+pub mod time_sync;
+pub mod trading_window;
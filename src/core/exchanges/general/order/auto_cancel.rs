@@ -0,0 +1,129 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::FutureExt;
+use log::info;
+
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::infrastructure::spawn_future;
+use crate::core::orders::order::ClientOrderId;
+
+/// Max period between checks of exchange clock, so simulated time changes are noticed
+const AUTO_CANCEL_CHECK_PERIOD: Duration = Duration::from_millis(100);
+
+impl Exchange {
+    /// Cancel created order after `auto_cancel_after` from its header if it's still live by then.
+    /// Time is measured by exchange clock
+    pub(crate) fn schedule_auto_cancel(self: Arc<Self>, client_order_id: &ClientOrderId) {
+        let order = match self.orders.cache_by_client_id.get(client_order_id) {
+            Some(order) => order.clone(),
+            None => return,
+        };
+
+        let auto_cancel_after = match order.fn_ref(|x| x.header.auto_cancel_after) {
+            Some(auto_cancel_after) => auto_cancel_after,
+            None => return,
+        };
+
+        let deadline = match chrono::Duration::from_std(auto_cancel_after) {
+            Ok(auto_cancel_after) => self.now() + auto_cancel_after,
+            Err(_) => return,
+        };
+
+        let cancellation_token = self.application_manager.stop_token();
+        let action = async move {
+            loop {
+                let remaining = match (deadline - self.now()).to_std() {
+                    Ok(remaining) if remaining > Duration::ZERO => remaining,
+                    _ => break,
+                };
+
+                tokio::select! {
+                    _ = tokio::time::sleep(remaining.min(AUTO_CANCEL_CHECK_PERIOD)) => {}
+                    _ = cancellation_token.when_cancelled() => return Ok(()),
+                }
+
+                if order.is_finished() {
+                    return Ok(());
+                }
+            }
+
+            if order.is_finished() {
+                return Ok(());
+            }
+
+            info!(
+                "Order {} on {} is not finished in {:?}, canceling it automatically",
+                order.client_order_id(),
+                self.exchange_account_id,
+                auto_cancel_after
+            );
+            self.start_cancel_order(&order, cancellation_token)
+                .await
+                .map(|_| ())
+        };
+        let _handle = spawn_future("Auto cancel order", false, action.boxed());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::clock::{Clock, SimulatedClock};
+    use crate::core::exchanges::common::ExchangeAccountId;
+    use crate::core::exchanges::general::test_helper::{order_to_create, ScriptedExchangeBuilder};
+    use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use crate::core::orders::order::{OrderSide, OrderStatus};
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    #[actix_rt::test]
+    async fn cancel_not_filled_order_after_duration() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0)).build();
+        let clock = Arc::new(SimulatedClock::new(Utc::now()));
+        exchange.set_clock(clock.clone());
+
+        let mut order_to_create = order_to_create(&exchange, OrderSide::Buy, dec!(1), dec!(0.1));
+        Arc::make_mut(&mut order_to_create.header).auto_cancel_after =
+            Some(Duration::from_secs(60));
+
+        let order = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test");
+        assert_eq!(order.status(), OrderStatus::Created);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(order.status(), OrderStatus::Created);
+
+        clock.advance(chrono::Duration::seconds(61));
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        assert_eq!(order.status(), OrderStatus::Canceled);
+    }
+
+    #[actix_rt::test]
+    async fn do_not_cancel_order_filled_before_expiry() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0)).build();
+        let clock = Arc::new(SimulatedClock::new(Utc::now()));
+        exchange.set_clock(clock.clone());
+
+        let mut order_to_create = order_to_create(&exchange, OrderSide::Buy, dec!(1), dec!(0.1));
+        Arc::make_mut(&mut order_to_create.header).auto_cancel_after =
+            Some(Duration::from_secs(60));
+
+        let order = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test");
+        order.fn_mut(|x| x.set_status(OrderStatus::Completed, clock.now()));
+
+        clock.advance(chrono::Duration::seconds(61));
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        assert_eq!(order.status(), OrderStatus::Completed);
+        assert!(!order.fn_ref(|x| x.internal_props.was_cancellation_event_raised));
+    }
+}
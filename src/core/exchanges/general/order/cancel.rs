@@ -1,8 +1,8 @@
 use anyhow::{anyhow, Result};
-use chrono::Utc;
 use log::{error, info};
 use tokio::sync::oneshot;
 
+use crate::core::exchanges::general::request_type::RequestType;
 use crate::core::{
     exchanges::common::Amount,
     exchanges::common::ExchangeError,
@@ -74,7 +74,7 @@ impl Exchange {
                 Ok(None)
             }
             _ => {
-                order.fn_mut(|order| order.set_status(OrderStatus::Canceling, Utc::now()));
+                order.fn_mut(|order| order.set_status(OrderStatus::Canceling, self.now()));
 
                 info!(
                     "Submitting order cancellation {} {:?} on {}",
@@ -150,6 +150,7 @@ impl Exchange {
         self.order_cancellation_events
             .insert(exchange_order_id.clone(), (tx, None));
 
+        self.acquire_rate_limit(RequestType::CancelOrder).await;
         let order_cancel_future = self.exchange_client.request_cancel_order(&order);
 
         tokio::select! {
@@ -193,6 +194,7 @@ impl Exchange {
 
         match request_outcome {
             Ok(request_outcome) => {
+                self.update_exchange_time_by_response(request_outcome);
                 if let Some(rest_error) = self.get_rest_error_order(request_outcome, &order.header)
                 {
                     return CancelOrderResult::failed(rest_error, EventSourceType::Rest);
@@ -0,0 +1,184 @@
+use anyhow::Result;
+use futures::FutureExt;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::infrastructure::spawn_future;
+use crate::core::orders::event::OrderEventType;
+use crate::core::orders::order::OrderStatus;
+use crate::core::orders::pool::OrderRef;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CancelEscalationSettings {
+    pub max_cancel_attempts: u32,
+    // cancel all orders on currency pair of order which can't be canceled
+    #[serde(default)]
+    pub mass_cancel: bool,
+}
+
+impl Exchange {
+    pub fn set_cancel_escalation(&self, settings: Option<CancelEscalationSettings>) {
+        *self.cancel_escalation.lock() = settings;
+    }
+
+    /// Escalate once when order failed to cancel configured number of times:
+    /// log error, raise `CancelAttemptsExhausted` event and mass cancel orders on pair if enabled
+    pub(crate) fn escalate_if_cancel_attempts_exhausted(&self, order: &OrderRef) -> Result<()> {
+        let settings = match self.cancel_escalation.lock().clone() {
+            Some(settings) => settings,
+            None => return Ok(()),
+        };
+
+        let (status, cancellation_attempts) =
+            order.fn_ref(|x| (x.status(), x.internal_props.cancellation_attempts));
+        if status != OrderStatus::FailedToCancel
+            || cancellation_attempts != settings.max_cancel_attempts
+        {
+            return Ok(());
+        }
+
+        error!(
+            "Order {} {:?} on {} failed to cancel {} times, escalating",
+            order.client_order_id(),
+            order.exchange_order_id(),
+            self.exchange_account_id,
+            cancellation_attempts
+        );
+        self.add_event_on_order_change(order, OrderEventType::CancelAttemptsExhausted)?;
+
+        if settings.mass_cancel {
+            if let Some(exchange) = self.weak_self.upgrade() {
+                let currency_pair = order.currency_pair();
+                let action = async move { exchange.cancel_all_orders(currency_pair).await };
+                spawn_future("Mass cancel on escalated order", false, action.boxed());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{
+        CurrencyPair, ExchangeAccountId, ExchangeError, ExchangeErrorType,
+    };
+    use crate::core::exchanges::events::ExchangeEvent;
+    use crate::core::exchanges::general::test_helper::ScriptedExchangeBuilder;
+    use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use crate::core::orders::fill::EventSourceType;
+    use crate::core::orders::order::{OrderCreating, OrderSnapshot};
+    use awc::http::StatusCode;
+    use parking_lot::Mutex;
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::broadcast;
+
+    async fn create_order_with_escalation(
+        exchange: &Exchange,
+        currency_pair: &CurrencyPair,
+    ) -> OrderRef {
+        exchange.set_cancel_escalation(Some(CancelEscalationSettings {
+            max_cancel_attempts: 3,
+            mass_cancel: true,
+        }));
+
+        let mut header = (*OrderSnapshot::test_default().header).clone();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = currency_pair.clone();
+        let order_to_create = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.1),
+        };
+        exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test")
+    }
+
+    fn is_escalated(events_receiver: &mut broadcast::Receiver<ExchangeEvent>) -> bool {
+        std::iter::from_fn(|| events_receiver.try_recv().ok()).any(|x| {
+            matches!(x, ExchangeEvent::OrderEvent(event)
+                if matches!(event.event_type, OrderEventType::CancelAttemptsExhausted))
+        })
+    }
+
+    // mass cancel is spawned, so it's waited for a while
+    async fn wait_mass_canceled_pairs(
+        mass_canceled_pairs: &Mutex<Vec<CurrencyPair>>,
+    ) -> Vec<CurrencyPair> {
+        for _ in 0..100 {
+            if !mass_canceled_pairs.lock().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        mass_canceled_pairs.lock().clone()
+    }
+
+    #[actix_rt::test]
+    async fn escalate_after_configured_cancel_attempts() {
+        let builder = ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+            .cancel_order_status(StatusCode::SERVICE_UNAVAILABLE);
+        let mass_canceled_pairs = builder.mass_canceled_pairs();
+        let (exchange, mut events_receiver) = builder.build();
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order = create_order_with_escalation(&exchange, &currency_pair).await;
+
+        for _ in 0..2 {
+            let _ = exchange
+                .start_cancel_order(&order, CancellationToken::default())
+                .await
+                .expect("in test");
+        }
+        assert_eq!(order.status(), OrderStatus::FailedToCancel);
+        assert!(!is_escalated(&mut events_receiver));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(mass_canceled_pairs.lock().is_empty());
+
+        let _ = exchange
+            .start_cancel_order(&order, CancellationToken::default())
+            .await
+            .expect("in test");
+
+        assert!(is_escalated(&mut events_receiver));
+        assert_eq!(
+            wait_mass_canceled_pairs(&mass_canceled_pairs).await,
+            vec![currency_pair]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn escalate_on_cancel_failures_from_websocket() {
+        let builder = ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0));
+        let mass_canceled_pairs = builder.mass_canceled_pairs();
+        let (exchange, mut events_receiver) = builder.build();
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order = create_order_with_escalation(&exchange, &currency_pair).await;
+        let exchange_order_id = order.exchange_order_id().expect("in test");
+
+        for _ in 0..3 {
+            exchange
+                .handle_cancel_order_failed(
+                    &exchange_order_id,
+                    ExchangeError::new(
+                        ExchangeErrorType::ServiceUnavailable,
+                        "test_error".to_owned(),
+                        None,
+                    ),
+                    EventSourceType::WebSocket,
+                )
+                .expect("in test");
+        }
+
+        assert_eq!(order.status(), OrderStatus::FailedToCancel);
+        assert!(is_escalated(&mut events_receiver));
+        assert_eq!(
+            wait_mass_canceled_pairs(&mass_canceled_pairs).await,
+            vec![currency_pair]
+        );
+    }
+}
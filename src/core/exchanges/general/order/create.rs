@@ -1,11 +1,15 @@
+use std::sync::Arc;
+
 use anyhow::{anyhow, bail, Result};
-use chrono::Utc;
 use log::{error, info, warn};
+use rust_decimal_macros::dec;
 use tokio::sync::oneshot;
 
 use crate::core::exchanges::general::exchange::RequestResult::{Error, Success};
 use crate::core::nothing_to_do;
+use crate::core::orders::error::OrderError;
 use crate::core::orders::event::OrderEventType;
+use crate::core::orders::metrics::CreationFailureReason;
 use crate::core::{
     exchanges::common::ExchangeAccountId,
     exchanges::common::ExchangeError,
@@ -15,6 +19,7 @@ use crate::core::{
     lifecycle::cancellation_token::CancellationToken,
     orders::order::ClientOrderId,
     orders::order::ExchangeOrderId,
+    orders::order::OrderExecutionType,
     orders::order::OrderStatus,
     orders::order::OrderType,
     orders::pool::OrderRef,
@@ -50,8 +55,18 @@ impl Exchange {
         cancellation_token: CancellationToken,
     ) -> Result<OrderRef> {
         info!("Submitting order {:?}", order_to_create);
-        self.orders
+        let order_to_create = &match self.check_order_to_create(order_to_create) {
+            Ok(order_to_create) => order_to_create,
+            Err(error) => {
+                self.on_order_rejected(CreationFailureReason::from(&error));
+                return Err(error.into());
+            }
+        };
+
+        let order_ref = self
+            .orders
             .add_simple_initial(order_to_create.header.clone(), Some(order_to_create.price));
+        self.register_oco_order(&order_ref);
 
         let _linked_cancellation_token = cancellation_token.create_linked_token();
 
@@ -75,6 +90,141 @@ impl Exchange {
         }
     }
 
+    /// Local checks of order before sending it to exchange. Returns order with rounded price and amount
+    pub(super) fn check_order_to_create(
+        &self,
+        order_to_create: &OrderCreating,
+    ) -> Result<OrderCreating, OrderError> {
+        let order_to_create = self.with_rounded_price_and_amount(order_to_create);
+        self.check_rounded_amount(&order_to_create)?;
+        self.check_pair_paused(&order_to_create)?;
+        self.check_capabilities(&order_to_create)?;
+        self.check_trading_window(&order_to_create)?;
+        self.check_clock_drift(&order_to_create)?;
+        self.check_position_limit(&order_to_create)?;
+        self.check_min_notional(&order_to_create)?;
+
+        Ok(order_to_create)
+    }
+
+    fn check_capabilities(&self, order_to_create: &OrderCreating) -> Result<(), OrderError> {
+        let header = &order_to_create.header;
+        if header.execution_type == OrderExecutionType::MakerOnly && !self.capabilities().post_only
+        {
+            let error = OrderError::UnsupportedOrderType {
+                client_order_id: header.client_order_id.clone(),
+                order_type: header.order_type,
+                reason: format!(
+                    "post-only orders are not supported on {}",
+                    self.exchange_account_id
+                ),
+            };
+            error!("{}", error);
+            return Err(error);
+        }
+
+        if header.reduce_only && !self.capabilities().reduce_only {
+            let error = OrderError::UnsupportedOrderType {
+                client_order_id: header.client_order_id.clone(),
+                order_type: header.order_type,
+                reason: format!(
+                    "reduce-only orders are not supported on {}",
+                    self.exchange_account_id
+                ),
+            };
+            error!("{}", error);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    fn check_rounded_amount(&self, order_to_create: &OrderCreating) -> Result<(), OrderError> {
+        let amount = order_to_create.header.amount;
+        if amount <= dec!(0) {
+            let error = OrderError::InvalidAmount {
+                client_order_id: order_to_create.header.client_order_id.clone(),
+                amount,
+            };
+            warn!("{} after rounding on {}", error, self.exchange_account_id);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    fn check_trading_window(&self, order_to_create: &OrderCreating) -> Result<(), OrderError> {
+        let now = self.now();
+        if !self.trading_window.lock().is_open(now) {
+            let error = OrderError::OutsideTradingWindow {
+                client_order_id: order_to_create.header.client_order_id.clone(),
+                time: now,
+            };
+            warn!("{} on {}", error, self.exchange_account_id);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    fn check_clock_drift(&self, order_to_create: &OrderCreating) -> Result<(), OrderError> {
+        if self.time_sync.is_trading_halted() {
+            let error = OrderError::ClockDriftExceeded {
+                client_order_id: order_to_create.header.client_order_id.clone(),
+                drift: self.time_sync.drift(),
+            };
+            error!("{} on {}", error, self.exchange_account_id);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Price and amount are rounded to precision of currency pair with rounding modes from
+    /// exchange settings. Price rounder registered for currency pair takes precedence for price
+    fn with_rounded_price_and_amount(&self, order_to_create: &OrderCreating) -> OrderCreating {
+        let header = &order_to_create.header;
+        let unrounded = (order_to_create.price, header.amount);
+        let (price, amount) = match self.symbols.get(&header.currency_pair) {
+            Some(metadata) => metadata
+                .round_order(unrounded.0, unrounded.1, header.side, &self.rounding.lock())
+                .unwrap_or_else(|error| {
+                    warn!(
+                        "Unable to round order {} on {}: {:?}",
+                        header.client_order_id, self.exchange_account_id, error
+                    );
+                    unrounded
+                }),
+            None => unrounded,
+        };
+        let price = if self.price_rounders.contains_key(&header.currency_pair) {
+            self.round_price(&header.currency_pair, order_to_create.price, header.side)
+        } else {
+            price
+        };
+
+        if price != order_to_create.price {
+            info!(
+                "Price of order {} rounded from {} to {}",
+                header.client_order_id, order_to_create.price, price
+            );
+        }
+
+        let header = if amount != header.amount {
+            info!(
+                "Amount of order {} rounded from {} to {}",
+                header.client_order_id, header.amount, amount
+            );
+            let mut rounded_header = (**header).clone();
+            rounded_header.amount = amount;
+            Arc::new(rounded_header)
+        } else {
+            header.clone()
+        };
+
+        OrderCreating { header, price }
+    }
+
     fn match_created_order_outcome(
         &self,
         outcome: &RequestResult<ExchangeOrderId>,
@@ -137,6 +287,7 @@ impl Exchange {
 
         if let Some(created_order) = create_order_result {
             match &created_order.outcome {
+                // outcome from not preferred source is returned only as a fallback, so it's applied
                 Success(exchange_order_id) => {
                     self.handle_create_order_succeeded(
                         &self.exchange_account_id,
@@ -220,10 +371,10 @@ impl Exchange {
         let status = order_ref.status();
         match status {
             OrderStatus::Created => Self::log_error_and_propagate("Created", args_to_log),
-            OrderStatus::FailedToCreate => {
+            OrderStatus::FailedToCreate | OrderStatus::Rejected => {
                 warn!(
-                    "CreateOrderFailed was received for a FaildeToCreate order {:?}",
-                    args_to_log
+                    "CreateOrderFailed was received for a {:?} order {:?}",
+                    status, args_to_log
                 );
                 Ok(())
             }
@@ -234,10 +385,18 @@ impl Exchange {
                 Self::log_error_and_propagate("FailedToCancel", args_to_log)
             }
             OrderStatus::Creating => {
-                // TODO RestFallback and some metrics
+                // TODO RestFallback
+
+                self.on_order_rejected(CreationFailureReason::from(&exchange_error.error_type));
 
                 order_ref.fn_mut(|order| {
-                    order.set_status(OrderStatus::FailedToCreate, Utc::now());
+                    if exchange_error.error_type.is_rejection() {
+                        order.set_status(OrderStatus::Rejected, self.now());
+                        order.internal_props.rejection_reason =
+                            Some(exchange_error.message.clone());
+                    } else {
+                        order.set_status(OrderStatus::FailedToCreate, self.now());
+                    }
                     order.internal_props.last_creation_error_type =
                         Some(exchange_error.error_type.clone());
                     order.internal_props.last_creation_error_message =
@@ -271,6 +430,36 @@ impl Exchange {
         bail!("{}", error_msg)
     }
 
+    /// Handle order creation confirmation from websocket or REST.
+    /// Confirmation from source which is not preferred in exchange settings is ignored,
+    /// fallback to it after `creation_confirmation_timeout` is made by `create_order_core`
+    pub(crate) fn handle_create_order_confirmation(
+        &self,
+        exchange_account_id: &ExchangeAccountId,
+        client_order_id: &ClientOrderId,
+        exchange_order_id: &ExchangeOrderId,
+        source_type: &EventSourceType,
+    ) -> Result<()> {
+        let creation_confirmation_source = *self.creation_confirmation_source.lock();
+        if !creation_confirmation_source.is_accepted(*source_type) {
+            info!(
+                "Ignored creation confirmation from {:?} because {:?} is preferred for order {:?}",
+                source_type,
+                creation_confirmation_source,
+                (exchange_account_id, client_order_id, exchange_order_id)
+            );
+
+            return Ok(());
+        }
+
+        self.handle_create_order_succeeded(
+            exchange_account_id,
+            client_order_id,
+            exchange_order_id,
+            source_type,
+        )
+    }
+
     pub(crate) fn handle_create_order_succeeded(
         &self,
         exchange_account_id: &ExchangeAccountId,
@@ -312,6 +501,15 @@ impl Exchange {
                 order_ref.fn_mut(|order| {
                     order.props.exchange_order_id = Some(exchange_order_id.clone());
                 });
+                // order which is still creating is indexed on transition to Created status
+                let status = order_ref.status();
+                if !matches!(
+                    status,
+                    OrderStatus::Creating | OrderStatus::FailedToCreate | OrderStatus::Rejected
+                ) {
+                    self.orders
+                        .index_by_exchange_id(exchange_order_id.clone(), &order_ref);
+                }
                 self.react_on_status_when_succeed(&order_ref, args_to_log, source_type)
             }
         }
@@ -326,11 +524,11 @@ impl Exchange {
         let status = order_ref.status();
         let exchange_order_id = args_to_log.2;
         match status {
-            OrderStatus::FailedToCreate => {
+            OrderStatus::FailedToCreate | OrderStatus::Rejected => {
                 let error_msg = format!(
-                    "CreateOrderSucceeded was received for a FailedToCreate order.
-                                Probably FailedToCreate fallback was received before Creation Response {:?}",
-                                args_to_log
+                    "CreateOrderSucceeded was received for a {:?} order.
+                                Probably {:?} fallback was received before Creation Response {:?}",
+                    status, status, args_to_log
                 );
 
                 error!("{}", error_msg);
@@ -358,7 +556,7 @@ impl Exchange {
                 // TODO RestFallback and some metrics
 
                 order_ref.fn_mut(|order| {
-                    order.set_status(OrderStatus::Created, Utc::now());
+                    order.set_status(OrderStatus::Created, self.now());
                     order.internal_props.creation_event_source_type = Some(source_type.clone());
                 });
 
@@ -444,3 +642,524 @@ fn log_warn(
     );
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::clock::SimulatedClock;
+    use crate::core::exchanges::common::{CurrencyPair, Price};
+    use crate::core::exchanges::events::CreationConfirmationSource;
+    use crate::core::exchanges::general::currency_pair_metadata::{RoundingMode, RoundingSettings};
+    use crate::core::exchanges::general::test_helper::{
+        get_test_exchange, ScriptedExchangeBuilder,
+    };
+    use crate::core::exchanges::general::trading_window::{TradingTimeRange, TradingWindow};
+    use crate::core::exchanges::timeouts::rate_limit_rules::{
+        RateLimitBucket, RateLimitBucketType, RateLimitRules,
+    };
+    use crate::core::orders::metrics::OrderRejectionMetrics;
+    use crate::core::orders::order::{OrderHeader, OrderSide, OrderSnapshot};
+    use chrono::{NaiveTime, TimeZone, Utc};
+    use rstest::rstest;
+    use rust_decimal_macros::dec;
+
+    #[actix_rt::test]
+    async fn reject_post_only_order_if_not_supported() {
+        let (exchange, _rx) = get_test_exchange(false);
+        assert!(!exchange.capabilities().post_only);
+
+        let client_order_id = ClientOrderId::unique_id();
+        let header = OrderHeader::new(
+            client_order_id.clone(),
+            Utc::now(),
+            exchange.exchange_account_id.clone(),
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            OrderType::Limit,
+            OrderSide::Buy,
+            dec!(1),
+            OrderExecutionType::MakerOnly,
+            None,
+            None,
+            "StrategyInUnitTests".to_owned(),
+        );
+        let order_to_create = OrderCreating {
+            header,
+            price: dec!(0.1),
+        };
+
+        let error = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect_err("in test");
+
+        assert!(error
+            .to_string()
+            .contains("post-only orders are not supported"));
+        match error.downcast_ref::<OrderError>() {
+            Some(OrderError::UnsupportedOrderType {
+                client_order_id: rejected_client_order_id,
+                ..
+            }) => assert_eq!(rejected_client_order_id, &client_order_id),
+            error => panic!("Expected UnsupportedOrderType error but got {:?}", error),
+        }
+        assert!(exchange
+            .orders
+            .cache_by_client_id
+            .get(&client_order_id)
+            .is_none());
+    }
+
+    #[actix_rt::test]
+    async fn reject_reduce_only_order_if_not_supported() {
+        let (exchange, _rx) = get_test_exchange(false);
+        assert!(!exchange.capabilities().reduce_only);
+
+        let mut header = OrderHeader::test_default();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        header.reduce_only = true;
+        let client_order_id = header.client_order_id.clone();
+        let order_to_create = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.1),
+        };
+
+        let error = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect_err("in test");
+
+        assert!(error
+            .to_string()
+            .contains("reduce-only orders are not supported"));
+        match error.downcast_ref::<OrderError>() {
+            Some(OrderError::UnsupportedOrderType {
+                client_order_id: rejected_client_order_id,
+                ..
+            }) => assert_eq!(rejected_client_order_id, &client_order_id),
+            error => panic!("Expected UnsupportedOrderType error but got {:?}", error),
+        }
+        assert!(exchange
+            .orders
+            .cache_by_client_id
+            .get(&client_order_id)
+            .is_none());
+    }
+
+    fn create_order_at_noon(exchange: &Exchange) -> OrderCreating {
+        exchange.set_clock(Arc::new(SimulatedClock::new(
+            Utc.ymd(2021, 1, 4).and_hms(12, 0, 0),
+        )));
+
+        let header = OrderHeader::new(
+            ClientOrderId::unique_id(),
+            exchange.now(),
+            exchange.exchange_account_id.clone(),
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            OrderType::Limit,
+            OrderSide::Buy,
+            dec!(1),
+            OrderExecutionType::None,
+            None,
+            None,
+            "StrategyInUnitTests".to_owned(),
+        );
+
+        OrderCreating {
+            header,
+            price: dec!(0.1),
+        }
+    }
+
+    fn trading_window(from: u32, to: u32) -> TradingWindow {
+        TradingWindow::new(vec![TradingTimeRange {
+            from: NaiveTime::from_hms(from, 0, 0),
+            to: NaiveTime::from_hms(to, 0, 0),
+            weekdays: None,
+        }])
+    }
+
+    #[actix_rt::test]
+    async fn suppress_order_outside_trading_window() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let order_to_create = create_order_at_noon(&exchange);
+        exchange.set_trading_window(trading_window(14, 18));
+
+        let error = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect_err("in test");
+
+        assert!(matches!(
+            error.downcast_ref::<OrderError>(),
+            Some(OrderError::OutsideTradingWindow { .. })
+        ));
+        assert!(exchange
+            .orders
+            .cache_by_client_id
+            .get(&order_to_create.header.client_order_id)
+            .is_none());
+    }
+
+    #[test]
+    fn allow_order_inside_trading_window() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let order_to_create = create_order_at_noon(&exchange);
+        exchange.set_trading_window(trading_window(9, 17));
+
+        assert_eq!(exchange.check_trading_window(&order_to_create), Ok(()));
+    }
+
+    #[actix_rt::test]
+    async fn halt_orders_while_clock_drift_exceeded() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let order_to_create = create_order_at_noon(&exchange);
+        exchange
+            .time_sync()
+            .set_max_drift(Some(chrono::Duration::seconds(1)));
+
+        exchange.update_exchange_time(exchange.now() + chrono::Duration::seconds(30));
+        let error = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect_err("in test");
+        assert!(matches!(
+            error.downcast_ref::<OrderError>(),
+            Some(OrderError::ClockDriftExceeded { .. })
+        ));
+
+        exchange.update_exchange_time(exchange.now() + chrono::Duration::milliseconds(10));
+        assert_eq!(exchange.check_clock_drift(&order_to_create), Ok(()));
+    }
+
+    fn execution_report_new(order_to_create: &OrderCreating) -> String {
+        format!(
+            r#"{{"e":"executionReport","C":"","c":"{0}","i":"{0}","x":"NEW","X":"NEW","f":"GTC"}}"#,
+            order_to_create.header.client_order_id.as_str()
+        )
+    }
+
+    fn creation_event_source_type(exchange: &Exchange, order: &OrderRef) -> EventSourceType {
+        assert_eq!(order.status(), OrderStatus::Created);
+        assert!(exchange
+            .orders
+            .cache_by_exchange_id
+            .get(&order.exchange_order_id().expect("in test"))
+            .is_some());
+
+        order
+            .fn_ref(|order| order.internal_props.creation_event_source_type)
+            .expect("in test")
+    }
+
+    #[actix_rt::test]
+    async fn websocket_confirmation_preferred_over_earlier_rest_response() {
+        let (exchange, _rx) = ScriptedExchangeBuilder::new(ExchangeAccountId::new(
+            "local_exchange_account_id".into(),
+            0,
+        ))
+        .without_websocket_creation_confirmation()
+        .build();
+        exchange.set_creation_confirmation_source(CreationConfirmationSource::WebSocket);
+        let order_to_create = create_order_at_noon(&exchange);
+
+        let (created_order, _) = tokio::join!(
+            exchange.create_order(&order_to_create, CancellationToken::default()),
+            async {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                exchange
+                    .exchange_client
+                    .on_websocket_message(&execution_report_new(&order_to_create))
+                    .expect("in test");
+            }
+        );
+
+        let created_order = created_order.expect("in test");
+        assert_eq!(
+            creation_event_source_type(&exchange, &created_order),
+            EventSourceType::WebSocket
+        );
+    }
+
+    #[actix_rt::test]
+    async fn rest_response_applied_if_websocket_confirmation_not_received() {
+        let (exchange, _rx) = ScriptedExchangeBuilder::new(ExchangeAccountId::new(
+            "local_exchange_account_id".into(),
+            0,
+        ))
+        .without_websocket_creation_confirmation()
+        .build();
+        exchange.set_creation_confirmation_source(CreationConfirmationSource::WebSocket);
+        exchange.set_creation_confirmation_timeout(Some(chrono::Duration::milliseconds(50)));
+        let order_to_create = create_order_at_noon(&exchange);
+
+        let created_order = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test");
+        assert_eq!(
+            creation_event_source_type(&exchange, &created_order),
+            EventSourceType::Rest
+        );
+
+        // late confirmation doesn't change already created order
+        exchange
+            .exchange_client
+            .on_websocket_message(&execution_report_new(&order_to_create))
+            .expect("in test");
+        assert_eq!(
+            creation_event_source_type(&exchange, &created_order),
+            EventSourceType::Rest
+        );
+    }
+
+    #[actix_rt::test]
+    async fn rest_response_preferred_over_earlier_websocket_confirmation() {
+        let (exchange, _rx) = ScriptedExchangeBuilder::new(ExchangeAccountId::new(
+            "local_exchange_account_id".into(),
+            0,
+        ))
+        .build();
+        exchange.set_creation_confirmation_source(CreationConfirmationSource::Rest);
+        let order_to_create = create_order_at_noon(&exchange);
+
+        let created_order = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test");
+        assert_eq!(
+            creation_event_source_type(&exchange, &created_order),
+            EventSourceType::Rest
+        );
+    }
+
+    #[test]
+    fn confirmation_from_not_preferred_source_is_ignored_for_creating_order() {
+        let (exchange, _rx) = get_test_exchange(false);
+        exchange.set_creation_confirmation_source(CreationConfirmationSource::Rest);
+        let order_to_create = create_order_at_noon(&exchange);
+        let order_ref = exchange
+            .orders
+            .add_simple_initial(order_to_create.header.clone(), Some(order_to_create.price));
+
+        exchange
+            .handle_create_order_confirmation(
+                &exchange.exchange_account_id,
+                &order_ref.client_order_id(),
+                &ExchangeOrderId::new("test".into()),
+                &EventSourceType::WebSocket,
+            )
+            .expect("in test");
+
+        assert_eq!(order_ref.status(), OrderStatus::Creating);
+        assert!(exchange.orders.cache_by_exchange_id.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn creation_waits_for_rate_limit() {
+        let (exchange, _rx) = ScriptedExchangeBuilder::new(ExchangeAccountId::new(
+            "local_exchange_account_id".into(),
+            0,
+        ))
+        .build();
+        exchange.set_rate_limit_rules(RateLimitRules {
+            buckets: vec![RateLimitBucket {
+                bucket_type: RateLimitBucketType::Orders,
+                capacity: 2,
+                refill_period_ms: 200,
+                reserve: 1,
+            }],
+        });
+
+        let started = std::time::Instant::now();
+        for _ in 0..2 {
+            let _ = exchange
+                .create_order(
+                    &create_order_at_noon(&exchange),
+                    CancellationToken::default(),
+                )
+                .await
+                .expect("in test");
+        }
+
+        // only one order fits in bucket, so the second one waits for refill
+        assert!(started.elapsed() >= std::time::Duration::from_millis(90));
+    }
+
+    #[actix_rt::test]
+    async fn track_clock_drift_by_rest_response() {
+        let (exchange, _rx) = ScriptedExchangeBuilder::new(ExchangeAccountId::new(
+            "local_exchange_account_id".into(),
+            0,
+        ))
+        .build();
+        let order_to_create = create_order_at_noon(&exchange);
+        assert_eq!(exchange.time_sync().drift(), None);
+
+        let _ = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test");
+
+        // simulated clock of exchange is set to the past, so server time is far ahead
+        assert!(exchange.time_sync().drift().expect("in test") > chrono::Duration::days(1));
+    }
+
+    #[test]
+    fn rejections_counted_by_reason() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let metrics = Arc::new(OrderRejectionMetrics::default());
+        exchange.set_order_metrics_hook(metrics.clone());
+
+        let reject_order = |error_type| {
+            let mut header = OrderHeader::test_default();
+            header.exchange_account_id = exchange.exchange_account_id.clone();
+            header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+            let client_order_id = header.client_order_id.clone();
+            let _ = exchange
+                .orders
+                .add_simple_initial(Arc::new(header), Some(dec!(0.1)));
+
+            exchange
+                .handle_create_order_failed(
+                    &exchange.exchange_account_id,
+                    &client_order_id,
+                    &ExchangeError::new(error_type, "test error".to_owned(), None),
+                    &EventSourceType::Rest,
+                )
+                .expect("in test");
+        };
+
+        reject_order(ExchangeErrorType::InsufficientFunds);
+        reject_order(ExchangeErrorType::InvalidOrder);
+        reject_order(ExchangeErrorType::InsufficientFunds);
+
+        assert_eq!(metrics.count(CreationFailureReason::InsufficientFunds), 2);
+        assert_eq!(metrics.count(CreationFailureReason::InvalidOrder), 1);
+    }
+
+    #[test]
+    fn exchange_rejection_finishes_order_as_rejected() {
+        let (exchange, _rx) = get_test_exchange(false);
+
+        let fail_order = |error_type| {
+            let mut header = OrderHeader::test_default();
+            header.exchange_account_id = exchange.exchange_account_id.clone();
+            header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+            let client_order_id = header.client_order_id.clone();
+            let order_ref = exchange
+                .orders
+                .add_simple_initial(Arc::new(header), Some(dec!(0.1)));
+
+            exchange
+                .handle_create_order_failed(
+                    &exchange.exchange_account_id,
+                    &client_order_id,
+                    &ExchangeError::new(error_type, "post only order would cross".to_owned(), None),
+                    &EventSourceType::Rest,
+                )
+                .expect("in test");
+            order_ref
+        };
+
+        let rejected = fail_order(ExchangeErrorType::InvalidOrder);
+        assert_eq!(rejected.status(), OrderStatus::Rejected);
+        assert_eq!(
+            rejected.fn_ref(|x| x.internal_props.rejection_reason.clone()),
+            Some("post only order would cross".to_owned())
+        );
+
+        let failed = fail_order(ExchangeErrorType::ServiceUnavailable);
+        assert_eq!(failed.status(), OrderStatus::FailedToCreate);
+        assert_eq!(
+            failed.fn_ref(|x| x.internal_props.rejection_reason.clone()),
+            None
+        );
+    }
+
+    #[actix_rt::test]
+    async fn round_price_and_amount_with_rounding_modes_from_settings() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0)).build();
+        exchange.set_rounding(RoundingSettings {
+            price: RoundingMode::Ceil,
+            amount: RoundingMode::Floor,
+        });
+
+        let mut header = (*OrderSnapshot::test_default().header).clone();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        header.amount = dec!(1.7);
+        let order_to_create = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.12),
+        };
+
+        let order_ref = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test");
+
+        assert_eq!(order_ref.price(), dec!(0.2));
+        assert_eq!(order_ref.amount(), dec!(1));
+    }
+
+    #[rstest]
+    #[case(OrderSide::Buy, dec!(0.1))]
+    #[case(OrderSide::Sell, dec!(0.2))]
+    #[actix_rt::test]
+    async fn price_is_not_rounded_to_more_aggressive_by_default(
+        #[case] side: OrderSide,
+        #[case] expected_price: Price,
+    ) {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0)).build();
+
+        let mut header = OrderHeader::test_default();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        header.side = side;
+        let order_to_create = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.18),
+        };
+
+        let order_ref = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test");
+
+        assert_eq!(order_ref.price(), expected_price);
+    }
+
+    #[actix_rt::test]
+    async fn reject_order_with_amount_rounded_to_zero() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0)).build();
+
+        let mut header = OrderHeader::test_default();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        header.amount = dec!(0.7);
+        let client_order_id = header.client_order_id.clone();
+        let order_to_create = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.1),
+        };
+
+        let error = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect_err("in test");
+
+        match error.downcast_ref::<OrderError>() {
+            Some(OrderError::InvalidAmount { amount, .. }) => assert_eq!(amount, &dec!(0)),
+            error => panic!("Expected InvalidAmount error but got {:?}", error),
+        }
+        assert!(exchange
+            .orders
+            .cache_by_client_id
+            .get(&client_order_id)
+            .is_none());
+    }
+}
@@ -1,11 +1,16 @@
+use std::future::Future;
+use std::time::Duration;
+
 use anyhow::Result;
-use log::{error, info};
+use log::{error, info, warn};
 use tokio::sync::oneshot;
 
+use crate::core::exchanges::general::request_type::RequestType;
 use crate::core::{
     exchanges::common::ExchangeError,
     exchanges::common::ExchangeErrorType,
     exchanges::common::RestRequestOutcome,
+    exchanges::events::CreationConfirmationSource,
     exchanges::general::exchange::Exchange,
     exchanges::general::exchange::RequestResult,
     lifecycle::cancellation_token::CancellationToken,
@@ -30,10 +35,16 @@ impl Exchange {
         self.order_creation_events
             .insert(client_order_id.clone(), (tx, None));
 
+        self.acquire_rate_limit(RequestType::CreateOrder).await;
         let order_create_future = self.exchange_client.create_order(&order);
+        tokio::pin!(order_create_future);
+
+        let creation_confirmation_source = *self.creation_confirmation_source.lock();
+        let confirmation_timeout =
+            (*self.creation_confirmation_timeout.lock()).and_then(|timeout| timeout.to_std().ok());
 
         tokio::select! {
-            rest_request_outcome = order_create_future => {
+            rest_request_outcome = &mut order_create_future => {
                 let create_order_result = self.handle_create_order_response(&rest_request_outcome, &order);
                 match create_order_result.outcome {
                     RequestResult::Error(_) => {
@@ -41,9 +52,24 @@ impl Exchange {
                         return Some(create_order_result);
                     }
                     RequestResult::Success(_) => {
+                        if creation_confirmation_source == CreationConfirmationSource::Rest {
+                            self.order_creation_events.remove(&client_order_id);
+                            return Some(create_order_result);
+                        }
+
                         tokio::select! {
-                            websocket_outcome = &mut websocket_event_receiver => {
-                                return websocket_outcome.ok()
+                            websocket_outcome = wait_with_optional_timeout(confirmation_timeout, &mut websocket_event_receiver) => {
+                                match websocket_outcome {
+                                    Some(websocket_outcome) => websocket_outcome.ok(),
+                                    None => {
+                                        self.order_creation_events.remove(&client_order_id);
+                                        warn!(
+                                            "Creation confirmation from websocket wasn't received during {:?} for order {} on {}, falling back to REST response",
+                                            confirmation_timeout.unwrap_or_default(), client_order_id, self.exchange_account_id
+                                        );
+                                        Some(create_order_result)
+                                    }
+                                }
                             }
                             _ = cancellation_token.when_cancelled() => {
                                 return None;
@@ -56,9 +82,32 @@ impl Exchange {
                 return None;
             }
             websocket_outcome = &mut websocket_event_receiver => {
-                return websocket_outcome.ok();
+                let websocket_outcome = websocket_outcome.ok();
+                if creation_confirmation_source != CreationConfirmationSource::Rest {
+                    return websocket_outcome;
+                }
+
+                tokio::select! {
+                    rest_request_outcome = wait_with_optional_timeout(confirmation_timeout, &mut order_create_future) => {
+                        if let Some(rest_request_outcome) = rest_request_outcome {
+                            let create_order_result = self.handle_create_order_response(&rest_request_outcome, order);
+                            if let RequestResult::Success(_) = create_order_result.outcome {
+                                return Some(create_order_result);
+                            }
+                        }
+
+                        warn!(
+                            "Successful creation response from REST wasn't received during {:?} for order {} on {}, falling back to websocket confirmation",
+                            confirmation_timeout.unwrap_or_default(), client_order_id, self.exchange_account_id
+                        );
+                        websocket_outcome
+                    }
+                    _ = cancellation_token.when_cancelled() => {
+                        None
+                    }
+                }
             }
-        };
+        }
     }
 
     fn handle_create_order_response(
@@ -76,6 +125,7 @@ impl Exchange {
 
         match request_outcome {
             Ok(request_outcome) => {
+                self.update_exchange_time_by_response(request_outcome);
                 if let Some(rest_error) = self.get_rest_error_order(request_outcome, &order.header)
                 {
                     return CreateOrderResult::failed(rest_error, EventSourceType::Rest);
@@ -109,12 +159,36 @@ impl Exchange {
         exchange_order_id: &ExchangeOrderId,
         source_type: EventSourceType,
     ) {
-        if let Some((_, (tx, _))) = self.order_creation_events.remove(&client_order_id) {
-            if let Err(error) =
-                tx.send(CreateOrderResult::successed(exchange_order_id, source_type))
-            {
-                error!("Unable to send thru oneshot channel: {:?}", error);
+        match self.order_creation_events.remove(&client_order_id) {
+            Some((_, (tx, _))) => {
+                if let Err(error) =
+                    tx.send(CreateOrderResult::successed(exchange_order_id, source_type))
+                {
+                    error!("Unable to send thru oneshot channel: {:?}", error);
+                }
+            }
+            // order creation was already completed, e.g. by fallback to REST response
+            None => {
+                if let Err(error) = self.handle_create_order_confirmation(
+                    &self.exchange_account_id,
+                    client_order_id,
+                    exchange_order_id,
+                    &source_type,
+                ) {
+                    error!("Unable to handle order creation confirmation: {:?}", error);
+                }
             }
         }
     }
 }
+
+/// Without timeout future is just awaited, `None` is returned only if timeout is elapsed
+async fn wait_with_optional_timeout<F: Future>(
+    timeout: Option<Duration>,
+    future: F,
+) -> Option<F::Output> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, future).await.ok(),
+        None => Some(future.await),
+    }
+}
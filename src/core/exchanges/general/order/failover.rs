@@ -0,0 +1,140 @@
+use anyhow::Result;
+use log::warn;
+
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::exchanges::general::order::limit_then_market::OrderPlacementPolicy;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::orders::order::{ClientOrderId, OrderCreating};
+use crate::core::orders::pool::OrderRef;
+
+impl Exchange {
+    fn is_creation_retryable(&self, client_order_id: &ClientOrderId) -> bool {
+        self.orders
+            .cache_by_client_id
+            .get(client_order_id)
+            .and_then(|order| order.fn_ref(|x| x.internal_props.last_creation_error_type))
+            .is_some_and(|error_type| error_type.is_retryable())
+    }
+}
+
+/// Create order on primary exchange account. If creation failed with retryable error,
+/// same order with new client order id is created on secondary account.
+/// Order is placed on both accounts according to `policy`
+pub async fn create_order_with_failover(
+    primary: &Exchange,
+    secondary: Option<&Exchange>,
+    order_to_create: &OrderCreating,
+    policy: OrderPlacementPolicy,
+    cancellation_token: CancellationToken,
+) -> Result<OrderRef> {
+    let primary_error = match primary
+        .create_order_with_policy(order_to_create, policy, cancellation_token.clone())
+        .await
+    {
+        Ok(order_ref) => return Ok(order_ref),
+        Err(error) => error,
+    };
+
+    let secondary = match secondary {
+        Some(secondary) => secondary,
+        None => return Err(primary_error),
+    };
+
+    let client_order_id = &order_to_create.header.client_order_id;
+    if !primary.is_creation_retryable(client_order_id) {
+        return Err(primary_error);
+    }
+
+    let mut header = (*order_to_create.header).clone();
+    header.client_order_id = ClientOrderId::unique_id();
+    header.exchange_account_id = secondary.exchange_account_id.clone();
+    warn!(
+        "Order {} failed on {}: {}. Retrying as {} on {}",
+        client_order_id,
+        primary.exchange_account_id,
+        primary_error,
+        header.client_order_id,
+        secondary.exchange_account_id
+    );
+
+    let failover_order = OrderCreating {
+        header: header.into(),
+        price: order_to_create.price,
+    };
+    secondary
+        .create_order_with_policy(&failover_order, policy, cancellation_token)
+        .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{ExchangeAccountId, ExchangeErrorType};
+    use crate::core::exchanges::general::test_helper::{order_to_create, ScriptedExchangeBuilder};
+    use crate::core::orders::order::{OrderSide, OrderStatus};
+    use awc::http::StatusCode;
+    use rust_decimal_macros::dec;
+
+    #[actix_rt::test]
+    async fn fail_over_to_secondary_when_primary_rate_limited() {
+        let primary_account_id = ExchangeAccountId::new("Binance".into(), 0);
+        let secondary_account_id = ExchangeAccountId::new("Binance".into(), 1);
+        let (primary, _primary_rx) = ScriptedExchangeBuilder::new(primary_account_id)
+            .create_order_status(StatusCode::TOO_MANY_REQUESTS)
+            .build();
+        let (secondary, _secondary_rx) =
+            ScriptedExchangeBuilder::new(secondary_account_id.clone()).build();
+
+        let order = order_to_create(&primary, OrderSide::Buy, dec!(1), dec!(0.1));
+        let order_ref = create_order_with_failover(
+            &primary,
+            Some(&secondary),
+            &order,
+            OrderPlacementPolicy::Limit,
+            CancellationToken::default(),
+        )
+        .await
+        .expect("in test");
+
+        assert_eq!(order_ref.exchange_account_id(), secondary_account_id);
+        assert_eq!(order_ref.status(), OrderStatus::Created);
+        assert_ne!(order_ref.client_order_id(), order.header.client_order_id);
+
+        let primary_order = primary
+            .orders
+            .cache_by_client_id
+            .get(&order.header.client_order_id)
+            .expect("in test")
+            .clone();
+        assert_eq!(primary_order.status(), OrderStatus::FailedToCreate);
+        assert_eq!(
+            primary_order.fn_ref(|x| x.internal_props.last_creation_error_type),
+            Some(ExchangeErrorType::RateLimit)
+        );
+        assert!(secondary
+            .orders
+            .cache_by_client_id
+            .get(&order.header.client_order_id)
+            .is_none());
+    }
+
+    #[actix_rt::test]
+    async fn no_failover_without_secondary() {
+        let primary_account_id = ExchangeAccountId::new("Binance".into(), 0);
+        let (primary, _rx) = ScriptedExchangeBuilder::new(primary_account_id)
+            .create_order_status(StatusCode::TOO_MANY_REQUESTS)
+            .build();
+
+        let order = order_to_create(&primary, OrderSide::Buy, dec!(1), dec!(0.1));
+        let result = create_order_with_failover(
+            &primary,
+            None,
+            &order,
+            OrderPlacementPolicy::Limit,
+            CancellationToken::default(),
+        )
+        .await;
+
+        assert!(result.is_err());
+    }
+}
@@ -1,9 +1,11 @@
+use crate::core::exchanges::general::request_type::RequestType;
 use crate::core::{
     exchanges::common::ExchangeError, exchanges::common::ExchangeErrorType,
-    exchanges::general::exchange::Exchange, orders::order::OrderInfo, orders::pool::OrderRef,
+    exchanges::general::exchange::Exchange, orders::order::ExchangeOrderStatus,
+    orders::order::OrderInfo, orders::pool::OrderRef,
 };
 use anyhow::*;
-use log::info;
+use log::{info, warn};
 
 impl Exchange {
     pub async fn get_order_info(&self, order: &OrderRef) -> Result<OrderInfo, ExchangeError> {
@@ -29,6 +31,7 @@ impl Exchange {
             order.exchange_order_id(),
             self.exchange_account_id
         );
+        self.acquire_rate_limit(RequestType::GetOrderInfo).await;
         let request_outcome = self.exchange_client.request_order_info(order).await;
 
         match request_outcome {
@@ -43,7 +46,10 @@ impl Exchange {
                 let unified_order_info = self.exchange_client.parse_order_info(&request_outcome);
 
                 match unified_order_info {
-                    Ok(order_info) => Ok(order_info),
+                    Ok(order_info) => {
+                        self.record_unknown_order_status(order, &order_info.order_status);
+                        Ok(order_info)
+                    }
                     Err(error) => Err(ExchangeError::new(
                         ExchangeErrorType::OrderNotFound,
                         error.to_string(),
@@ -58,4 +64,59 @@ impl Exchange {
             )),
         }
     }
+
+    /// Status of order which isn't recognized is logged and recorded to order for diagnostics,
+    /// local status of order isn't changed
+    pub(crate) fn record_unknown_order_status(
+        &self,
+        order: &OrderRef,
+        order_status: &ExchangeOrderStatus,
+    ) {
+        if let ExchangeOrderStatus::UnknownStatus(raw_status) = order_status {
+            warn!(
+                "Unknown status {} of order {} on {}, local status {:?} is kept",
+                raw_status,
+                order.client_order_id(),
+                self.exchange_account_id,
+                order.status()
+            );
+            order.fn_mut(|x| x.internal_props.unknown_exchange_status = Some(raw_status.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::general::test_helper::get_test_exchange;
+    use crate::core::orders::order::{OrderSnapshot, OrderStatus};
+    use chrono::Utc;
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+
+    #[test]
+    fn keep_local_status_on_unknown_exchange_status() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let order = exchange
+            .orders
+            .add_snapshot_initial(Arc::new(RwLock::new(OrderSnapshot::test_default())));
+        order.fn_mut(|x| x.set_status(OrderStatus::Created, Utc::now()));
+
+        exchange.record_unknown_order_status(&order, &OrderStatus::Canceled.into());
+        assert_eq!(
+            order.fn_ref(|x| x.internal_props.unknown_exchange_status.clone()),
+            None
+        );
+
+        exchange.record_unknown_order_status(
+            &order,
+            &ExchangeOrderStatus::UnknownStatus("PENDING_NEW".to_owned()),
+        );
+
+        assert_eq!(order.status(), OrderStatus::Created);
+        assert_eq!(
+            order.fn_ref(|x| x.internal_props.unknown_exchange_status.clone()),
+            Some("PENDING_NEW".to_owned())
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use crate::core::exchanges::general::request_type::RequestType;
 use crate::core::{
     exchanges::general::exchange::Exchange, exchanges::general::features::OpenOrdersType,
     orders::order::OrderInfo,
@@ -22,6 +23,7 @@ impl Exchange {
             OpenOrdersType::AllCurrencyPair => {
                 // TODO implement in the future
                 //reserve_when_acailable().await
+                self.acquire_rate_limit(RequestType::GetOpenOrders).await;
                 let response = self.exchange_client.request_open_orders().await?;
 
                 info!(
@@ -0,0 +1,235 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use log::{info, warn};
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::timeout;
+
+use crate::core::exchanges::events::ExchangeEvent;
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::orders::order::{ClientOrderId, OrderCreating, OrderStatus, OrderType};
+use crate::core::orders::pool::OrderRef;
+
+/// How order is placed on exchange
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum OrderPlacementPolicy {
+    #[default]
+    Limit,
+    /// Place limit order and if it isn't filled in `timeout` cancel it and place market order
+    /// for remaining amount. Cancellation is waited for the same `timeout`
+    LimitThenMarket { timeout: Duration },
+}
+
+impl Exchange {
+    /// Returns limit order if it was completed before fallback or market order for remaining amount
+    pub async fn create_order_with_policy(
+        &self,
+        order_to_create: &OrderCreating,
+        policy: OrderPlacementPolicy,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        let fallback_timeout = match policy {
+            OrderPlacementPolicy::Limit => {
+                return self.create_order(order_to_create, cancellation_token).await
+            }
+            OrderPlacementPolicy::LimitThenMarket { timeout } => timeout,
+        };
+
+        let events_receiver = self.events_channel.subscribe();
+        let limit_order = self
+            .create_order(order_to_create, cancellation_token.clone())
+            .await?;
+
+        match timeout(
+            fallback_timeout,
+            self.wait_order_completed(&limit_order, events_receiver),
+        )
+        .await
+        {
+            Ok(Ok(())) => return Ok(limit_order),
+            Ok(Err(error)) => warn!("{:?}, falling back to market order", error),
+            Err(_) => {}
+        }
+
+        let status = if limit_order.is_finished() {
+            limit_order.status()
+        } else {
+            self.cancel_and_wait(
+                &limit_order.client_order_id(),
+                fallback_timeout,
+                cancellation_token.clone(),
+            )
+            .await?
+        };
+
+        let remaining_amount = limit_order.amount() - limit_order.filled_amount();
+        if status == OrderStatus::Completed || remaining_amount <= dec!(0) {
+            return Ok(limit_order);
+        }
+
+        let mut header = (*order_to_create.header).clone();
+        header.client_order_id = ClientOrderId::unique_id();
+        header.init_time = self.now();
+        header.order_type = OrderType::Market;
+        header.amount = remaining_amount;
+        info!(
+            "Limit order {} wasn't filled on {} in {:?}, placing market order {} for remaining amount {}",
+            limit_order.client_order_id(),
+            self.exchange_account_id,
+            fallback_timeout,
+            header.client_order_id,
+            remaining_amount
+        );
+
+        let market_order = OrderCreating {
+            header: Arc::new(header),
+            price: order_to_create.price,
+        };
+        self.create_order(&market_order, cancellation_token).await
+    }
+
+    async fn wait_order_completed(
+        &self,
+        order: &OrderRef,
+        mut events_receiver: broadcast::Receiver<ExchangeEvent>,
+    ) -> Result<()> {
+        loop {
+            match order.status() {
+                OrderStatus::Completed => return Ok(()),
+                OrderStatus::FailedToCreate | OrderStatus::Rejected | OrderStatus::Canceled => {
+                    bail!(
+                        "Order {} was finished on {} with status {:?} before it was completed",
+                        order.client_order_id(),
+                        self.exchange_account_id,
+                        order.status()
+                    )
+                }
+                _ => {}
+            }
+
+            match events_receiver.recv().await {
+                // Status is rechecked on each event, so lagged events are not a problem
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => bail!(
+                    "Events channel was closed while waiting completion of order {} on {}",
+                    order.client_order_id(),
+                    self.exchange_account_id
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+    use crate::core::exchanges::general::test_helper::ScriptedExchangeBuilder;
+    use crate::core::orders::fill::OrderFill;
+    use crate::core::orders::order::{OrderHeader, OrderSnapshot};
+
+    #[actix_rt::test]
+    async fn place_market_order_for_remaining_amount_after_timeout() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0)).build();
+        let mut header = OrderHeader::test_default();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        header.amount = dec!(10);
+        let order = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.1),
+        };
+        let policy = OrderPlacementPolicy::LimitThenMarket {
+            timeout: Duration::from_millis(100),
+        };
+
+        // limit order is filled partially only while policy is waiting
+        let partial_fill = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let limit_order = exchange
+                .orders
+                .cache_by_client_id
+                .get(&order.header.client_order_id)
+                .expect("in test")
+                .clone();
+            let fill = OrderFill::test_default().with_price_and_amount(order.price, dec!(4));
+            limit_order.fn_mut(|x| x.add_fill(fill.clone()));
+        };
+
+        let (market_order, _) = tokio::join!(
+            exchange.create_order_with_policy(&order, policy, CancellationToken::default()),
+            partial_fill
+        );
+        let market_order = market_order.expect("in test");
+
+        let limit_order = exchange
+            .orders
+            .cache_by_client_id
+            .get(&order.header.client_order_id)
+            .expect("in test")
+            .clone();
+        assert_eq!(limit_order.status(), OrderStatus::Canceled);
+        assert_ne!(market_order.client_order_id(), order.header.client_order_id);
+        assert_eq!(
+            market_order.fn_ref(|x| x.header.order_type),
+            OrderType::Market
+        );
+        assert_eq!(market_order.amount(), dec!(6));
+        assert_eq!(market_order.status(), OrderStatus::Created);
+    }
+
+    #[actix_rt::test]
+    async fn place_market_order_if_limit_order_canceled_while_waiting() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0)).build();
+        let mut header = (*OrderSnapshot::test_default().header).clone();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.1),
+        };
+        let policy = OrderPlacementPolicy::LimitThenMarket {
+            timeout: Duration::from_secs(10),
+        };
+
+        // limit order is canceled outside of policy long before timeout
+        let cancel = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let limit_order = exchange
+                .orders
+                .cache_by_client_id
+                .get(&order.header.client_order_id)
+                .expect("in test")
+                .clone();
+            let _ = exchange
+                .start_cancel_order(&limit_order, CancellationToken::default())
+                .await
+                .expect("in test");
+        };
+
+        let (market_order, _) = timeout(
+            Duration::from_secs(1),
+            futures::future::join(
+                exchange.create_order_with_policy(&order, policy, CancellationToken::default()),
+                cancel,
+            ),
+        )
+        .await
+        .expect("policy should not wait for timeout after limit order was canceled");
+        let market_order = market_order.expect("in test");
+
+        assert_ne!(market_order.client_order_id(), order.header.client_order_id);
+        assert_eq!(
+            market_order.fn_ref(|x| x.header.order_type),
+            OrderType::Market
+        );
+        assert_eq!(market_order.amount(), order.header.amount);
+    }
+}
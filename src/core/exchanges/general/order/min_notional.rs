@@ -0,0 +1,123 @@
+use log::warn;
+
+use crate::core::exchanges::common::{CurrencyPair, Price};
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::orders::error::OrderError;
+use crate::core::orders::order::{OrderCreating, OrderType};
+
+impl Exchange {
+    pub fn set_min_order_notional(&self, currency_pair: CurrencyPair, min_order_notional: Price) {
+        let _ = self
+            .min_order_notionals
+            .insert(currency_pair, min_order_notional);
+    }
+
+    /// Reject dust orders which `amount * price` is below configured minimum for pair.
+    /// Market orders are skipped because they have no price
+    pub(super) fn check_min_notional(
+        &self,
+        order_to_create: &OrderCreating,
+    ) -> Result<(), OrderError> {
+        let header = &order_to_create.header;
+        if header.order_type == OrderType::Market {
+            return Ok(());
+        }
+
+        let min_notional = match self.min_order_notionals.get(&header.currency_pair) {
+            Some(min_notional) => *min_notional,
+            None => return Ok(()),
+        };
+
+        let notional = header.amount * order_to_create.price;
+        if notional < min_notional {
+            let error = OrderError::BelowMinNotional {
+                client_order_id: header.client_order_id.clone(),
+                notional,
+                min_notional,
+            };
+            warn!("{} on {}", error, self.exchange_account_id);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::general::test_helper::{get_test_exchange, order_to_create};
+    use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use crate::core::orders::metrics::{CreationFailureReason, OrderRejectionMetrics};
+    use crate::core::orders::order::OrderSide;
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    fn currency_pair() -> CurrencyPair {
+        CurrencyPair::from_codes("PHB".into(), "BTC".into())
+    }
+
+    #[test]
+    fn allow_order_above_min_notional() {
+        let (exchange, _rx) = get_test_exchange(false);
+        exchange.set_min_order_notional(currency_pair(), dec!(1));
+
+        assert_eq!(
+            exchange.check_min_notional(&order_to_create(
+                &exchange,
+                OrderSide::Buy,
+                dec!(2),
+                dec!(0.5)
+            )),
+            Ok(())
+        );
+        assert_eq!(
+            exchange.check_min_notional(&order_to_create(
+                &exchange,
+                OrderSide::Buy,
+                dec!(3),
+                dec!(0.5)
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn allow_market_order_without_price() {
+        let (exchange, _rx) = get_test_exchange(false);
+        exchange.set_min_order_notional(currency_pair(), dec!(1));
+
+        let mut order = order_to_create(&exchange, OrderSide::Buy, dec!(3), dec!(0));
+        Arc::make_mut(&mut order.header).order_type = OrderType::Market;
+        assert_eq!(exchange.check_min_notional(&order), Ok(()));
+    }
+
+    #[actix_rt::test]
+    async fn reject_dust_order() {
+        let (exchange, _rx) = get_test_exchange(false);
+        exchange.set_min_order_notional(currency_pair(), dec!(1));
+        let metrics = Arc::new(OrderRejectionMetrics::default());
+        exchange.set_order_metrics_hook(metrics.clone());
+
+        let order = order_to_create(&exchange, OrderSide::Buy, dec!(1), dec!(0.5));
+        let error = exchange
+            .create_order(&order, CancellationToken::default())
+            .await
+            .expect_err("in test");
+
+        assert_eq!(
+            error.downcast_ref::<OrderError>(),
+            Some(&OrderError::BelowMinNotional {
+                client_order_id: order.header.client_order_id.clone(),
+                notional: dec!(0.5),
+                min_notional: dec!(1),
+            })
+        );
+        assert!(exchange
+            .orders
+            .cache_by_client_id
+            .get(&order.header.client_order_id)
+            .is_none());
+        assert_eq!(metrics.count(CreationFailureReason::InvalidOrder), 1);
+    }
+}
@@ -1,7 +1,17 @@
+pub mod auto_cancel;
 pub mod cancel;
+pub mod cancel_escalation;
 pub mod create;
 pub mod create_websocket_based;
+pub mod failover;
 pub mod get_info;
 pub mod get_open_orders;
+pub mod limit_then_market;
+pub mod min_notional;
+pub mod modify;
+pub mod pause;
+pub mod position_limit;
+pub mod reconcile;
+pub mod submission_queue;
 pub mod wait_cancel;
 pub mod wait_finish;
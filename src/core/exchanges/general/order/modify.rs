@@ -0,0 +1,555 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use chrono::Duration;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::orders::error::OrderError;
+use crate::core::{
+    exchanges::common::{Amount, CurrencyPair, Price},
+    exchanges::general::exchange::Exchange,
+    lifecycle::cancellation_token::CancellationToken,
+    orders::order::{ClientOrderId, OrderAmending, OrderCreating, OrderSide, OrderStatus},
+    orders::pool::OrderRef,
+};
+
+/// How opened order should be modified
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Deserialize, Serialize)]
+pub enum OrderModificationPreference {
+    // Amend order in-place if exchange supports it, otherwise cancel it and create new one
+    #[default]
+    AmendIfSupported,
+    CancelReplace,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OrderModificationMethod {
+    Amend,
+    CancelReplace,
+}
+
+impl Exchange {
+    pub fn order_modification_method(&self) -> OrderModificationMethod {
+        match *self.order_modification_preference.lock() {
+            OrderModificationPreference::AmendIfSupported if self.capabilities().amend_order => {
+                OrderModificationMethod::Amend
+            }
+            _ => OrderModificationMethod::CancelReplace,
+        }
+    }
+
+    pub fn set_min_replace_interval(&self, min_replace_interval: Duration) {
+        *self.min_replace_interval.lock() = min_replace_interval;
+    }
+
+    /// Schedule replacement of quote with pair and side not earlier than `min_replace_interval`
+    /// after previous one. Returns delay until scheduled time
+    fn schedule_replace(&self, currency_pair: CurrencyPair, side: OrderSide) -> Duration {
+        let now = self.now();
+        let min_replace_interval = *self.min_replace_interval.lock();
+
+        let mut last_replace_time = self
+            .last_replace_times
+            .entry((currency_pair, side))
+            .or_insert(now - min_replace_interval);
+        let replace_time = now.max(*last_replace_time + min_replace_interval);
+        *last_replace_time = replace_time;
+
+        replace_time - now
+    }
+
+    async fn wait_replace_interval(
+        &self,
+        order_ref: &OrderRef,
+        cancellation_token: &CancellationToken,
+    ) -> Result<()> {
+        let delay = self.schedule_replace(order_ref.currency_pair(), order_ref.side());
+        if delay <= Duration::zero() {
+            return Ok(());
+        }
+
+        info!(
+            "Replacement of order {} on {} is deferred for {}ms",
+            order_ref.client_order_id(),
+            self.exchange_account_id,
+            delay.num_milliseconds()
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(delay.to_std()?) => Ok(()),
+            _ = cancellation_token.when_cancelled() => bail!(
+                "Replacement of order {} on {} was cancelled",
+                order_ref.client_order_id(),
+                self.exchange_account_id
+            ),
+        }
+    }
+
+    /// Change price and amount of opened order. Finished orders can't be modified.
+    /// Amended order keeps its client order id, but after cancel-replace new order with new client
+    /// order id is returned
+    pub async fn modify_order(
+        &self,
+        client_order_id: &ClientOrderId,
+        new_price: Price,
+        new_amount: Amount,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        let order_ref = self
+            .orders
+            .cache_by_client_id
+            .get(client_order_id)
+            .map(|x| x.clone())
+            .ok_or_else(|| OrderError::NotFound {
+                client_order_id: client_order_id.clone(),
+            })?;
+
+        if order_ref.is_finished() {
+            bail!(
+                "Unable to modify order {} on {} because it has status {:?}",
+                client_order_id,
+                self.exchange_account_id,
+                order_ref.status()
+            )
+        }
+
+        match self.order_modification_method() {
+            OrderModificationMethod::Amend => {
+                self.amend_order(&order_ref, new_price, new_amount).await
+            }
+            OrderModificationMethod::CancelReplace => {
+                self.cancel_replace_order(&order_ref, new_price, new_amount, cancellation_token)
+                    .await
+            }
+        }
+    }
+
+    /// Modified price and amount are checked and rounded like for order creation
+    async fn amend_order(
+        &self,
+        order_ref: &OrderRef,
+        new_price: Price,
+        new_amount: Amount,
+    ) -> Result<OrderRef> {
+        let mut header = order_ref.fn_ref(|order| (*order.header).clone());
+        header.amount = new_amount;
+        let checked_order = self.check_order_to_create(&OrderCreating {
+            header: Arc::new(header),
+            price: new_price,
+        })?;
+        let (new_price, new_amount) = (checked_order.price, checked_order.header.amount);
+
+        let order_to_amend = order_ref
+            .fn_ref(|order| {
+                order
+                    .props
+                    .exchange_order_id
+                    .clone()
+                    .map(|exchange_order_id| OrderAmending {
+                        header: order.header.clone(),
+                        exchange_order_id,
+                        new_price,
+                        new_amount,
+                    })
+            })
+            .ok_or_else(|| OrderError::MissingExchangeOrderId {
+                client_order_id: order_ref.client_order_id(),
+            })?;
+
+        self.acquire_rate_limit(RequestType::AmendOrder).await;
+        let response = self
+            .exchange_client
+            .request_amend_order(&order_to_amend)
+            .await?;
+
+        if let Some(error) = self.get_rest_error_order(&response, &order_to_amend.header) {
+            let error = OrderError::ExchangeRejected {
+                client_order_id: order_to_amend.header.client_order_id.clone(),
+                error_type: error.error_type,
+                message: error.message,
+            };
+            error!(
+                "Unable to amend order on {}: {}",
+                self.exchange_account_id, error
+            );
+            return Err(error.into());
+        }
+
+        apply_amend(order_ref, new_price, new_amount);
+
+        info!(
+            "Order {} was amended on {} to price {} and amount {}",
+            order_to_amend.header.client_order_id, self.exchange_account_id, new_price, new_amount
+        );
+
+        Ok(order_ref.clone())
+    }
+
+    async fn cancel_replace_order(
+        &self,
+        order_ref: &OrderRef,
+        new_price: Price,
+        new_amount: Amount,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        self.wait_replace_interval(order_ref, &cancellation_token)
+            .await?;
+
+        self.wait_cancel_order(order_ref.clone(), None, true, cancellation_token.clone())
+            .await?;
+
+        if order_ref.status() != OrderStatus::Canceled {
+            bail!(
+                "Unable to replace order {} on {} because it has status {:?} after cancellation",
+                order_ref.client_order_id(),
+                self.exchange_account_id,
+                order_ref.status()
+            )
+        }
+
+        let mut header = order_ref.fn_ref(|order| (*order.header).clone());
+        header.client_order_id = ClientOrderId::unique_id();
+        header.init_time = self.now();
+        header.amount = new_amount;
+
+        let order_to_create = OrderCreating {
+            header: Arc::new(header),
+            price: new_price,
+        };
+
+        self.create_order(&order_to_create, cancellation_token)
+            .await
+    }
+}
+
+fn apply_amend(order_ref: &OrderRef, new_price: Price, new_amount: Amount) {
+    order_ref.fn_mut(|order| {
+        let mut header = (*order.header).clone();
+        header.amount = new_amount;
+
+        order.header = Arc::new(header);
+        order.props.raw_price = Some(new_price);
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::clock::SimulatedClock;
+    use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+    use crate::core::exchanges::general::features::ExchangeCapabilities;
+    use crate::core::exchanges::general::test_helper::{
+        create_order_ref, get_test_exchange, get_test_exchange_with_capabilities,
+        ScriptedExchangeBuilder,
+    };
+    use crate::core::exchanges::timeouts::requests_timeout_manager_factory::RequestTimeoutArguments;
+    use crate::core::orders::order::{OrderRole, OrderSnapshot};
+    use awc::http::StatusCode;
+    use chrono::{TimeZone, Utc};
+    use rstest::rstest;
+    use rust_decimal_macros::dec;
+
+    fn amend_capabilities() -> ExchangeCapabilities {
+        ExchangeCapabilities {
+            amend_order: true,
+            ..ExchangeCapabilities::default()
+        }
+    }
+
+    #[test]
+    fn amend_if_supported() {
+        let (exchange, _rx) = get_test_exchange_with_capabilities(false, amend_capabilities());
+
+        assert_eq!(
+            exchange.order_modification_method(),
+            OrderModificationMethod::Amend
+        );
+    }
+
+    #[test]
+    fn cancel_replace_if_amend_not_supported() {
+        let (exchange, _rx) = get_test_exchange(false);
+
+        assert_eq!(
+            exchange.order_modification_method(),
+            OrderModificationMethod::CancelReplace
+        );
+    }
+
+    #[test]
+    fn cancel_replace_if_preferred() {
+        let (exchange, _rx) = get_test_exchange_with_capabilities(false, amend_capabilities());
+        exchange.set_order_modification_preference(OrderModificationPreference::CancelReplace);
+
+        assert_eq!(
+            exchange.order_modification_method(),
+            OrderModificationMethod::CancelReplace
+        );
+    }
+
+    #[test]
+    fn amend_preserves_client_order_id() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let client_order_id = ClientOrderId::unique_id();
+        let order_ref = create_order_ref(
+            &client_order_id,
+            Some(OrderRole::Maker),
+            &exchange.exchange_account_id,
+            &CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            dec!(0.2),
+            dec!(5),
+            OrderSide::Buy,
+        );
+
+        apply_amend(&order_ref, dec!(0.3), dec!(7));
+
+        assert_eq!(order_ref.client_order_id(), client_order_id);
+        assert_eq!(order_ref.price(), dec!(0.3));
+        assert_eq!(order_ref.amount(), dec!(7));
+    }
+
+    #[actix_rt::test]
+    async fn defer_replacement_within_min_interval() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let clock = Arc::new(SimulatedClock::new(Utc.ymd(2021, 1, 4).and_hms(12, 0, 0)));
+        exchange.set_clock(clock.clone());
+        exchange.set_min_replace_interval(Duration::milliseconds(100));
+        let order_ref = create_order_ref(
+            &ClientOrderId::unique_id(),
+            Some(OrderRole::Maker),
+            &exchange.exchange_account_id,
+            &CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            dec!(0.2),
+            dec!(5),
+            OrderSide::Buy,
+        );
+        let cancellation_token = CancellationToken::default();
+
+        exchange
+            .wait_replace_interval(&order_ref, &cancellation_token)
+            .await
+            .expect("in test");
+
+        clock.advance(Duration::milliseconds(40));
+        let started = std::time::Instant::now();
+        exchange
+            .wait_replace_interval(&order_ref, &cancellation_token)
+            .await
+            .expect("in test");
+        assert!(started.elapsed() >= std::time::Duration::from_millis(60));
+
+        // next replacement is scheduled after deferred one
+        assert_eq!(
+            exchange.schedule_replace(order_ref.currency_pair(), OrderSide::Buy),
+            Duration::milliseconds(160)
+        );
+        assert_eq!(
+            exchange.schedule_replace(order_ref.currency_pair(), OrderSide::Sell),
+            Duration::zero()
+        );
+
+        clock.advance(Duration::milliseconds(500));
+        assert_eq!(
+            exchange.schedule_replace(order_ref.currency_pair(), OrderSide::Buy),
+            Duration::zero()
+        );
+    }
+
+    async fn create_order_to_modify(exchange: &Exchange) -> OrderRef {
+        let mut header = (*OrderSnapshot::test_default().header).clone();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        header.amount = dec!(5);
+        let order_to_create = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.2),
+        };
+
+        exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test")
+    }
+
+    async fn assert_modification_fails_without_changes(exchange: &Exchange, order_ref: &OrderRef) {
+        let status = order_ref.status();
+        let orders_count = exchange.orders.cache_by_client_id.len();
+
+        let result = exchange
+            .modify_order(
+                &order_ref.client_order_id(),
+                dec!(0.3),
+                dec!(7),
+                CancellationToken::default(),
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(order_ref.status(), status);
+        assert_eq!(order_ref.price(), dec!(0.2));
+        assert_eq!(order_ref.amount(), dec!(5));
+        assert_eq!(exchange.orders.cache_by_client_id.len(), orders_count);
+    }
+
+    #[rstest]
+    #[case(OrderStatus::Completed)]
+    #[case(OrderStatus::Canceled)]
+    #[actix_rt::test]
+    async fn finished_order_is_not_modified(#[case] status: OrderStatus) {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+                .capabilities(amend_capabilities())
+                .build();
+        let order_ref = create_order_to_modify(&exchange).await;
+        order_ref.fn_mut(|order| order.set_status(status, Utc::now()));
+
+        assert_modification_fails_without_changes(&exchange, &order_ref).await;
+    }
+
+    #[actix_rt::test]
+    async fn order_is_not_changed_if_amend_failed_on_exchange() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+                .capabilities(amend_capabilities())
+                .amend_order_status(StatusCode::BAD_REQUEST)
+                .build();
+        let order_ref = create_order_to_modify(&exchange).await;
+        assert_eq!(order_ref.status(), OrderStatus::Created);
+
+        assert_modification_fails_without_changes(&exchange, &order_ref).await;
+    }
+
+    #[actix_rt::test]
+    async fn amend_opened_order_on_exchange() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+                .capabilities(amend_capabilities())
+                .build();
+        let order_ref = create_order_to_modify(&exchange).await;
+
+        let amended = exchange
+            .modify_order(
+                &order_ref.client_order_id(),
+                dec!(0.3),
+                dec!(7),
+                CancellationToken::default(),
+            )
+            .await
+            .expect("in test");
+
+        assert_eq!(amended.client_order_id(), order_ref.client_order_id());
+        assert_eq!(order_ref.price(), dec!(0.3));
+        assert_eq!(order_ref.amount(), dec!(7));
+    }
+
+    #[actix_rt::test]
+    async fn amend_sends_single_request() {
+        let builder = ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+            .capabilities(amend_capabilities())
+            .requests_limit(RequestTimeoutArguments::unlimited());
+        let sent_requests = builder.sent_requests();
+        let (exchange, _rx) = builder.build();
+        let order_ref = create_order_to_modify(&exchange).await;
+        sent_requests.lock().clear();
+
+        let _ = exchange
+            .modify_order(
+                &order_ref.client_order_id(),
+                dec!(0.3),
+                dec!(7),
+                CancellationToken::default(),
+            )
+            .await
+            .expect("in test");
+
+        assert_eq!(*sent_requests.lock(), vec![RequestType::AmendOrder]);
+    }
+
+    #[actix_rt::test]
+    async fn cancel_replace_if_amend_not_supported_on_exchange() {
+        let builder = ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+            .capabilities(ExchangeCapabilities::default())
+            .requests_limit(RequestTimeoutArguments::unlimited());
+        let sent_requests = builder.sent_requests();
+        let (exchange, _rx) = builder.build();
+        let order_ref = create_order_to_modify(&exchange).await;
+        sent_requests.lock().clear();
+
+        let replaced = exchange
+            .modify_order(
+                &order_ref.client_order_id(),
+                dec!(0.3),
+                dec!(7),
+                CancellationToken::default(),
+            )
+            .await
+            .expect("in test");
+
+        assert_eq!(
+            *sent_requests.lock(),
+            vec![RequestType::CancelOrder, RequestType::CreateOrder]
+        );
+        assert_eq!(order_ref.status(), OrderStatus::Canceled);
+        assert_ne!(replaced.client_order_id(), order_ref.client_order_id());
+        assert_eq!(replaced.status(), OrderStatus::Created);
+        assert_eq!(replaced.price(), dec!(0.3));
+        assert_eq!(replaced.amount(), dec!(7));
+    }
+
+    #[actix_rt::test]
+    async fn amended_price_and_amount_are_rounded() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+                .capabilities(amend_capabilities())
+                .build();
+        let order_ref = create_order_to_modify(&exchange).await;
+
+        let _ = exchange
+            .modify_order(
+                &order_ref.client_order_id(),
+                dec!(0.37),
+                dec!(7.6),
+                CancellationToken::default(),
+            )
+            .await
+            .expect("in test");
+
+        assert_eq!(order_ref.price(), dec!(0.3));
+        assert_eq!(order_ref.amount(), dec!(7));
+    }
+
+    #[rstest]
+    #[case::amount_rounded_to_zero(dec!(0.5), None)]
+    #[case::position_limit_exceeded(dec!(7), Some(dec!(6)))]
+    #[actix_rt::test]
+    async fn invalid_amend_is_not_sent(
+        #[case] new_amount: Amount,
+        #[case] max_position: Option<Amount>,
+    ) {
+        let builder = ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+            .capabilities(amend_capabilities())
+            .requests_limit(RequestTimeoutArguments::unlimited());
+        let sent_requests = builder.sent_requests();
+        let (exchange, _rx) = builder.build();
+        let order_ref = create_order_to_modify(&exchange).await;
+        if let Some(max_position) = max_position {
+            exchange.set_max_position(order_ref.currency_pair(), max_position);
+        }
+        sent_requests.lock().clear();
+
+        let result = exchange
+            .modify_order(
+                &order_ref.client_order_id(),
+                dec!(0.3),
+                new_amount,
+                CancellationToken::default(),
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert!(sent_requests.lock().is_empty());
+        assert_eq!(order_ref.price(), dec!(0.2));
+        assert_eq!(order_ref.amount(), dec!(5));
+    }
+}
@@ -0,0 +1,97 @@
+use log::{info, warn};
+
+use crate::core::exchanges::common::CurrencyPair;
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::orders::error::OrderError;
+use crate::core::orders::order::OrderCreating;
+
+impl Exchange {
+    /// Suppress creation of new orders on pair. Already placed orders are kept
+    pub fn pause_pair(&self, currency_pair: &CurrencyPair) {
+        if self.paused_pairs.insert(currency_pair.clone()) {
+            info!(
+                "Orders placement on {} paused for {}",
+                currency_pair, self.exchange_account_id
+            );
+        }
+    }
+
+    pub fn resume_pair(&self, currency_pair: &CurrencyPair) {
+        if self.paused_pairs.remove(currency_pair).is_some() {
+            info!(
+                "Orders placement on {} resumed for {}",
+                currency_pair, self.exchange_account_id
+            );
+        }
+    }
+
+    pub fn is_pair_paused(&self, currency_pair: &CurrencyPair) -> bool {
+        self.paused_pairs.contains(currency_pair)
+    }
+
+    pub(super) fn check_pair_paused(
+        &self,
+        order_to_create: &OrderCreating,
+    ) -> Result<(), OrderError> {
+        let header = &order_to_create.header;
+        if self.is_pair_paused(&header.currency_pair) {
+            let error = OrderError::PairPaused {
+                client_order_id: header.client_order_id.clone(),
+                currency_pair: header.currency_pair.clone(),
+            };
+            warn!("{} on {}", error, self.exchange_account_id);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::ExchangeAccountId;
+    use crate::core::exchanges::general::test_helper::{order_to_create, ScriptedExchangeBuilder};
+    use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use crate::core::orders::order::{OrderSide, OrderStatus};
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    #[actix_rt::test]
+    async fn suppress_orders_only_on_paused_pair() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0)).build();
+        let paused_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let active_pair = CurrencyPair::from_codes("ETH".into(), "BTC".into());
+        exchange.pause_pair(&paused_pair);
+
+        let paused_order = order_to_create(&exchange, OrderSide::Buy, dec!(1), dec!(0.1));
+        let error = exchange
+            .create_order(&paused_order, CancellationToken::default())
+            .await
+            .expect_err("in test");
+        assert_eq!(
+            error.downcast_ref::<OrderError>(),
+            Some(&OrderError::PairPaused {
+                client_order_id: paused_order.header.client_order_id.clone(),
+                currency_pair: paused_pair.clone(),
+            })
+        );
+
+        let mut active_order = order_to_create(&exchange, OrderSide::Buy, dec!(1), dec!(0.1));
+        Arc::make_mut(&mut active_order.header).currency_pair = active_pair;
+        let order_ref = exchange
+            .create_order(&active_order, CancellationToken::default())
+            .await
+            .expect("in test");
+        assert_eq!(order_ref.status(), OrderStatus::Created);
+
+        exchange.resume_pair(&paused_pair);
+        let resumed_order = order_to_create(&exchange, OrderSide::Buy, dec!(1), dec!(0.1));
+        let order_ref = exchange
+            .create_order(&resumed_order, CancellationToken::default())
+            .await
+            .expect("in test");
+        assert_eq!(order_ref.status(), OrderStatus::Created);
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+use log::error;
+
+use crate::core::exchanges::common::{Amount, CurrencyPair};
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::orders::error::OrderError;
+use crate::core::orders::order::{ClientOrderId, OrderCreating, OrderSide};
+
+impl Exchange {
+    pub fn set_max_position(&self, currency_pair: CurrencyPair, max_position: Amount) {
+        let _ = self.max_positions.insert(currency_pair, max_position);
+    }
+
+    pub fn max_position(&self, currency_pair: &CurrencyPair) -> Option<Amount> {
+        self.max_positions.get(currency_pair).map(|x| *x)
+    }
+
+    pub(crate) fn add_to_position(
+        &self,
+        currency_pair: &CurrencyPair,
+        side: OrderSide,
+        filled_amount: Amount,
+    ) {
+        let mut position = self.positions.entry(currency_pair.clone()).or_default();
+        match side {
+            OrderSide::Buy => *position += filled_amount,
+            OrderSide::Sell => *position -= filled_amount,
+        }
+    }
+
+    /// Net position by fills of orders on pair: buys are positive, sells are negative.
+    /// Fills of orders removed from orders pool are accounted too
+    pub fn net_position(&self, currency_pair: &CurrencyPair) -> Amount {
+        self.positions
+            .get(currency_pair)
+            .map(|x| *x)
+            .unwrap_or_default()
+    }
+
+    /// Net positions of all pairs with fills, see `net_position`
+    pub fn net_positions(&self) -> HashMap<CurrencyPair, Amount> {
+        self.positions
+            .iter()
+            .map(|x| (x.key().clone(), *x.value()))
+            .collect()
+    }
+
+    /// Not filled amount of opened orders on pair and side except checked order
+    fn pending_amount(
+        &self,
+        currency_pair: &CurrencyPair,
+        side: OrderSide,
+        checked_order_id: &ClientOrderId,
+    ) -> Amount {
+        self.orders
+            .not_finished
+            .iter()
+            .filter_map(|order| {
+                order.fn_ref(|x| {
+                    (&x.header.currency_pair == currency_pair
+                        && x.header.side == side
+                        && &x.header.client_order_id != checked_order_id)
+                        .then(|| x.remaining_amount())
+                })
+            })
+            .sum()
+    }
+
+    /// Reject orders which would move position beyond configured maximum in either direction
+    /// if they and all opened orders of the same side were filled.
+    /// Orders reducing position are allowed even if position already exceeds maximum
+    pub(super) fn check_position_limit(
+        &self,
+        order_to_create: &OrderCreating,
+    ) -> Result<(), OrderError> {
+        let header = &order_to_create.header;
+        let max_position = match self.max_positions.get(&header.currency_pair) {
+            Some(max_position) => *max_position,
+            None => return Ok(()),
+        };
+
+        let position = self.net_position(&header.currency_pair);
+        // filled amount of modified order is accounted in position already
+        let filled_amount = self
+            .orders
+            .cache_by_client_id
+            .get(&header.client_order_id)
+            .map(|order_ref| order_ref.filled_amount())
+            .unwrap_or_default();
+        let amount = header.amount - filled_amount
+            + self.pending_amount(&header.currency_pair, header.side, &header.client_order_id);
+        let (prospective_position, is_exceeded) = match header.side {
+            OrderSide::Buy => {
+                let prospective = position + amount;
+                (prospective, prospective > max_position)
+            }
+            OrderSide::Sell => {
+                let prospective = position - amount;
+                (prospective, prospective < -max_position)
+            }
+        };
+
+        if is_exceeded {
+            let error = OrderError::PositionLimitExceeded {
+                client_order_id: header.client_order_id.clone(),
+                position: prospective_position,
+                max_position,
+            };
+            error!("{} on {}", error, self.exchange_account_id);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::general::test_helper::{
+        add_filled_order, get_test_exchange, order_to_create,
+    };
+    use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use crate::core::orders::order::OrderStatus;
+    use rust_decimal_macros::dec;
+
+    fn currency_pair() -> CurrencyPair {
+        CurrencyPair::from_codes("PHB".into(), "BTC".into())
+    }
+
+    #[test]
+    fn allow_order_up_to_limit() {
+        let (exchange, _rx) = get_test_exchange(false);
+        exchange.set_max_position(currency_pair(), dec!(5));
+        let _ = add_filled_order(&exchange, OrderSide::Buy, dec!(3), dec!(3));
+
+        let order = order_to_create(&exchange, OrderSide::Buy, dec!(2), dec!(0.1));
+
+        assert_eq!(exchange.net_position(&currency_pair()), dec!(3));
+        assert_eq!(exchange.check_position_limit(&order), Ok(()));
+    }
+
+    #[actix_rt::test]
+    async fn reject_order_beyond_limit() {
+        let (exchange, _rx) = get_test_exchange(false);
+        exchange.set_max_position(currency_pair(), dec!(5));
+        let _ = add_filled_order(&exchange, OrderSide::Buy, dec!(3), dec!(3));
+
+        let order = order_to_create(&exchange, OrderSide::Buy, dec!(3), dec!(0.1));
+        let error = exchange
+            .create_order(&order, CancellationToken::default())
+            .await
+            .expect_err("in test");
+
+        assert_eq!(
+            error.downcast_ref::<OrderError>(),
+            Some(&OrderError::PositionLimitExceeded {
+                client_order_id: order.header.client_order_id.clone(),
+                position: dec!(6),
+                max_position: dec!(5),
+            })
+        );
+    }
+
+    #[test]
+    fn allow_reducing_order_when_limit_exceeded() {
+        let (exchange, _rx) = get_test_exchange(false);
+        exchange.set_max_position(currency_pair(), dec!(5));
+        let _ = add_filled_order(&exchange, OrderSide::Sell, dec!(7), dec!(7));
+
+        let same_direction = order_to_create(&exchange, OrderSide::Sell, dec!(1), dec!(0.1));
+        let reducing = order_to_create(&exchange, OrderSide::Buy, dec!(1), dec!(0.1));
+
+        assert!(exchange.check_position_limit(&same_direction).is_err());
+        assert_eq!(exchange.check_position_limit(&reducing), Ok(()));
+    }
+
+    #[test]
+    fn position_is_kept_after_pruning_of_filled_orders() {
+        let (exchange, _rx) = get_test_exchange(false);
+        exchange.set_max_position(currency_pair(), dec!(5));
+        let order_ref = add_filled_order(&exchange, OrderSide::Buy, dec!(4), dec!(4));
+        order_ref.fn_mut(|x| {
+            x.set_status(
+                OrderStatus::Completed,
+                exchange.now() - chrono::Duration::hours(2),
+            )
+        });
+
+        let pruned = exchange
+            .orders
+            .prune_finished_orders(chrono::Duration::hours(1), exchange.now());
+
+        assert_eq!(pruned, 1);
+        assert_eq!(exchange.net_position(&currency_pair()), dec!(4));
+        let order = order_to_create(&exchange, OrderSide::Buy, dec!(2), dec!(0.1));
+        assert!(exchange.check_position_limit(&order).is_err());
+    }
+
+    #[test]
+    fn opened_orders_of_the_same_side_are_accounted() {
+        let (exchange, _rx) = get_test_exchange(false);
+        exchange.set_max_position(currency_pair(), dec!(5));
+        for _ in 0..2 {
+            let opened = order_to_create(&exchange, OrderSide::Buy, dec!(2), dec!(0.1));
+            let _ = exchange
+                .orders
+                .add_simple_initial(opened.header, Some(opened.price));
+        }
+        let opened_sell = order_to_create(&exchange, OrderSide::Sell, dec!(3), dec!(0.1));
+        let _ = exchange
+            .orders
+            .add_simple_initial(opened_sell.header, Some(opened_sell.price));
+
+        assert_eq!(
+            exchange.check_position_limit(&order_to_create(
+                &exchange,
+                OrderSide::Buy,
+                dec!(1),
+                dec!(0.1)
+            )),
+            Ok(())
+        );
+        assert!(exchange
+            .check_position_limit(&order_to_create(
+                &exchange,
+                OrderSide::Buy,
+                dec!(2),
+                dec!(0.1)
+            ))
+            .is_err());
+    }
+
+    #[actix_rt::test]
+    async fn position_limit_is_checked_for_rounded_amount() {
+        let (exchange, _rx) = get_test_exchange(false);
+        exchange.set_max_position(currency_pair(), dec!(5));
+        let _ = add_filled_order(&exchange, OrderSide::Buy, dec!(3), dec!(3));
+
+        // amount precision of pair is 0, so amount is floored to 2
+        let order = order_to_create(&exchange, OrderSide::Buy, dec!(2.7), dec!(0.1));
+        let checked_order = exchange.check_order_to_create(&order).expect("in test");
+
+        assert_eq!(checked_order.header.amount, dec!(2));
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+
+use chrono::Duration;
+use log::warn;
+
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::orders::order::{ClientOrderId, OrderInfo};
+
+/// Result of comparison local not finished orders with open orders on exchange
+#[derive(Debug, Default, Clone)]
+pub struct OrdersReconciliation {
+    // local orders which are open on exchange
+    pub matched: Vec<ClientOrderId>,
+    // local orders absent on exchange but created within grace period, so exchange may not reflect them yet
+    pub pending: Vec<ClientOrderId>,
+    // local orders absent on exchange after grace period
+    pub orphaned: Vec<ClientOrderId>,
+    // open orders on exchange unknown locally
+    pub unknown: Vec<OrderInfo>,
+}
+
+impl Exchange {
+    pub fn set_orphan_grace_period(&self, grace_period: Duration) {
+        *self.orphan_grace_period.lock() = grace_period;
+    }
+
+    /// Classify local not finished orders by open orders received from exchange.
+    /// Orders are matched by client order id or exchange order id
+    pub fn reconcile_orders(&self, open_orders: &[OrderInfo]) -> OrdersReconciliation {
+        let now = self.now();
+        let grace_period = *self.orphan_grace_period.lock();
+
+        let mut reconciliation = OrdersReconciliation::default();
+        let mut matched_open_orders = HashSet::new();
+        for order in self.orders.not_finished.iter() {
+            let (client_order_id, exchange_order_id, init_time) = order.fn_ref(|x| {
+                (
+                    x.header.client_order_id.clone(),
+                    x.props.exchange_order_id.clone(),
+                    x.header.init_time,
+                )
+            });
+
+            let open_order_index = open_orders.iter().position(|x| {
+                x.client_order_id == client_order_id
+                    || Some(&x.exchange_order_id) == exchange_order_id.as_ref()
+            });
+
+            match open_order_index {
+                Some(index) => {
+                    let _ = matched_open_orders.insert(index);
+                    reconciliation.matched.push(client_order_id);
+                }
+                None if now - init_time < grace_period => {
+                    reconciliation.pending.push(client_order_id)
+                }
+                None => {
+                    warn!(
+                        "Order {} on {} is not found among open orders on exchange",
+                        client_order_id, self.exchange_account_id
+                    );
+                    reconciliation.orphaned.push(client_order_id);
+                }
+            }
+        }
+
+        reconciliation.unknown = open_orders
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !matched_open_orders.contains(index))
+            .map(|(_, x)| x.clone())
+            .collect();
+
+        reconciliation
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::clock::SimulatedClock;
+    use crate::core::exchanges::general::test_helper::get_test_exchange;
+    use crate::core::orders::order::OrderSnapshot;
+    use chrono::{TimeZone, Utc};
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+
+    #[test]
+    fn classify_local_only_orders_by_grace_period() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let now = Utc.ymd(2021, 1, 4).and_hms(12, 0, 0);
+        exchange.set_clock(Arc::new(SimulatedClock::new(now)));
+        exchange.set_orphan_grace_period(Duration::seconds(5));
+
+        let add_order = |init_time| {
+            let mut order = OrderSnapshot::test_default();
+            let mut header = (*order.header).clone();
+            header.init_time = init_time;
+            order.header = Arc::new(header);
+            exchange
+                .orders
+                .add_snapshot_initial(Arc::new(RwLock::new(order)))
+                .client_order_id()
+        };
+        let recent = add_order(now - Duration::seconds(1));
+        let old = add_order(now - Duration::minutes(1));
+
+        let reconciliation = exchange.reconcile_orders(&[]);
+
+        assert_eq!(reconciliation.pending, vec![recent]);
+        assert_eq!(reconciliation.orphaned, vec![old]);
+        assert!(reconciliation.matched.is_empty());
+        assert!(reconciliation.unknown.is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use futures::FutureExt;
+use log::trace;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{oneshot, Notify};
+
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::infrastructure::spawn_future;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::orders::order::OrderCreating;
+use crate::core::orders::pool::OrderRef;
+
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize,
+)]
+pub enum OrderPriority {
+    // routine quote updates
+    Low,
+    #[default]
+    Normal,
+    // orders reducing risk (e.g. stop-losses)
+    High,
+}
+
+struct QueuedOrder {
+    priority: OrderPriority,
+    // orders with the same priority are dispatched in order of enqueuing
+    sequence: u64,
+    order: OrderCreating,
+    result_sender: oneshot::Sender<Result<OrderRef>>,
+}
+
+impl PartialEq for QueuedOrder {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueuedOrder {}
+
+impl PartialOrd for QueuedOrder {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedOrder {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    orders: BinaryHeap<QueuedOrder>,
+    next_sequence: u64,
+}
+
+/// Bounded queue of orders waiting for submission to exchange.
+/// Worker reserves request in exchange timeout manager and only then takes order with the highest
+/// priority, so orders enqueued while waiting for rate limit still can jump ahead
+pub struct OrderSubmissionQueue {
+    exchange: Arc<Exchange>,
+    capacity: usize,
+    state: Mutex<QueueState>,
+    order_enqueued: Notify,
+}
+
+impl OrderSubmissionQueue {
+    pub fn new(exchange: Arc<Exchange>, capacity: usize) -> Arc<Self> {
+        Arc::new(OrderSubmissionQueue {
+            exchange,
+            capacity,
+            state: Default::default(),
+            order_enqueued: Notify::new(),
+        })
+    }
+
+    /// Returns receiver of order creation result. Fails if queue is full
+    pub fn enqueue(
+        &self,
+        order: OrderCreating,
+        priority: OrderPriority,
+    ) -> Result<oneshot::Receiver<Result<OrderRef>>> {
+        let (result_sender, receiver) = oneshot::channel();
+        {
+            let mut state = self.state.lock();
+            if state.orders.len() >= self.capacity {
+                bail!(
+                    "Unable to enqueue order {} because submission queue for {} is full ({} orders)",
+                    order.header.client_order_id,
+                    self.exchange.exchange_account_id,
+                    self.capacity
+                );
+            }
+
+            let sequence = state.next_sequence;
+            state.next_sequence += 1;
+            state.orders.push(QueuedOrder {
+                priority,
+                sequence,
+                order,
+                result_sender,
+            });
+        }
+
+        self.order_enqueued.notify_one();
+        Ok(receiver)
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub async fn start(self: Arc<Self>, cancellation_token: CancellationToken) -> Result<()> {
+        loop {
+            while self.is_empty() {
+                tokio::select! {
+                    _ = self.order_enqueued.notified() => {}
+                    _ = cancellation_token.when_cancelled() => return Ok(()),
+                }
+            }
+
+            let reservation = self.exchange.timeout_manager.reserve_when_available(
+                &self.exchange.exchange_account_id,
+                RequestType::CreateOrder,
+                None,
+                cancellation_token.clone(),
+            )?;
+            let reservation_result = reservation.await.into_result();
+            if cancellation_token.is_cancellation_requested() {
+                return Ok(());
+            }
+
+            let queued_order = match self.state.lock().orders.pop() {
+                Some(queued_order) => queued_order,
+                None => continue,
+            };
+
+            if let Err(error) = reservation_result {
+                let _ = queued_order.result_sender.send(Err(error));
+                continue;
+            }
+
+            trace!(
+                "Dispatching order {} with priority {:?} from submission queue",
+                queued_order.order.header.client_order_id,
+                queued_order.priority
+            );
+
+            let exchange = self.exchange.clone();
+            let cancellation_token = cancellation_token.clone();
+            let action = async move {
+                let result = exchange
+                    .create_order(&queued_order.order, cancellation_token)
+                    .await;
+                let _ = queued_order.result_sender.send(result);
+                Ok(())
+            };
+            spawn_future("Create order from submission queue", false, action.boxed());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::ExchangeAccountId;
+    use crate::core::exchanges::general::test_helper::{order_to_create, ScriptedExchangeBuilder};
+    use crate::core::exchanges::timeouts::requests_timeout_manager_factory::RequestTimeoutArguments;
+    use crate::core::orders::order::{OrderSide, OrderStatus, OrderType};
+    use rust_decimal_macros::dec;
+    use tokio::sync::oneshot::error::TryRecvError;
+
+    #[actix_rt::test]
+    async fn dispatch_high_priority_order_first() {
+        // only 1 order can be dispatched during test
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+                .requests_limit(RequestTimeoutArguments::from_requests_per_hour(1))
+                .build();
+        let queue = OrderSubmissionQueue::new(exchange.clone(), 10);
+
+        let quote = order_to_create(&exchange, OrderSide::Sell, dec!(1), dec!(0.1));
+        let mut stop = order_to_create(&exchange, OrderSide::Sell, dec!(1), dec!(0.1));
+        Arc::make_mut(&mut stop.header).order_type = OrderType::StopLoss;
+        let mut quote_result = queue.enqueue(quote, OrderPriority::Low).expect("in test");
+        let stop_result = queue
+            .enqueue(stop.clone(), OrderPriority::High)
+            .expect("in test");
+
+        let cancellation_token = CancellationToken::new();
+        let _worker = tokio::spawn(queue.clone().start(cancellation_token.clone()));
+
+        let stop_order = stop_result.await.expect("in test").expect("in test");
+        assert_eq!(stop_order.client_order_id(), stop.header.client_order_id);
+        assert_eq!(stop_order.status(), OrderStatus::Created);
+
+        // quote waits for next requests period
+        assert_eq!(quote_result.try_recv().err(), Some(TryRecvError::Empty));
+        assert_eq!(queue.len(), 1);
+
+        cancellation_token.cancel();
+    }
+
+    #[test]
+    fn reject_order_when_queue_is_full() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+                .requests_limit(RequestTimeoutArguments::unlimited())
+                .build();
+        let queue = OrderSubmissionQueue::new(exchange.clone(), 1);
+
+        let _first_result = queue
+            .enqueue(
+                order_to_create(&exchange, OrderSide::Sell, dec!(1), dec!(0.1)),
+                OrderPriority::Normal,
+            )
+            .expect("in test");
+
+        assert!(queue
+            .enqueue(
+                order_to_create(&exchange, OrderSide::Sell, dec!(1), dec!(0.1)),
+                OrderPriority::High
+            )
+            .is_err());
+    }
+}
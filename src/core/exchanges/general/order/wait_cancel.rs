@@ -1,12 +1,12 @@
 use std::time::Duration;
 
-use anyhow::{bail, Result};
-use chrono::Utc;
+use anyhow::{anyhow, bail, Result};
 use dashmap::mapref::entry::Entry::{Occupied, Vacant};
 use log::{error, info, trace, warn};
 use scopeguard;
 use tokio::sync::broadcast;
-use tokio::time::sleep;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{sleep, timeout};
 
 use super::cancel::CancelOrderResult;
 use crate::core::exchanges::{
@@ -17,14 +17,76 @@ use crate::{
     core::orders::event::OrderEventType,
     core::{
         exchanges::common::ExchangeError, exchanges::common::ExchangeErrorType,
-        exchanges::events::AllowedEventSourceType, exchanges::general::exchange::Exchange,
-        exchanges::general::exchange::RequestResult,
-        lifecycle::cancellation_token::CancellationToken, orders::fill::EventSourceType,
-        orders::order::OrderStatus, orders::pool::OrderRef,
+        exchanges::events::AllowedEventSourceType, exchanges::events::ExchangeEvent,
+        exchanges::general::exchange::Exchange, exchanges::general::exchange::RequestResult,
+        lifecycle::cancellation_token::CancellationToken, orders::error::OrderError,
+        orders::fill::EventSourceType, orders::order::ClientOrderId,
+        orders::order::ExchangeOrderStatus, orders::order::OrderStatus, orders::pool::OrderRef,
     },
 };
 
 impl Exchange {
+    /// Cancel order and wait until it becomes Canceled or Completed (if it was filled before
+    /// cancellation landed). Returns final status of order or error if it isn't reached in timeout
+    pub async fn cancel_and_wait(
+        &self,
+        client_order_id: &ClientOrderId,
+        wait_timeout: Duration,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderStatus> {
+        let order = self
+            .orders
+            .cache_by_client_id
+            .get(client_order_id)
+            .map(|x| x.clone())
+            .ok_or_else(|| OrderError::NotFound {
+                client_order_id: client_order_id.clone(),
+            })?;
+
+        // Subscribe before cancellation request to not miss confirmation event
+        let events_receiver = self.events_channel.subscribe();
+
+        let cancel_and_confirm = async {
+            let _ = self.start_cancel_order(&order, cancellation_token).await?;
+            self.wait_cancel_confirmation(&order, events_receiver).await
+        };
+
+        timeout(wait_timeout, cancel_and_confirm)
+            .await
+            .map_err(|_| {
+                anyhow!(
+                    "Order {} was not canceled on {} in {:?}, current status {:?}",
+                    client_order_id,
+                    self.exchange_account_id,
+                    wait_timeout,
+                    order.status()
+                )
+            })?
+    }
+
+    async fn wait_cancel_confirmation(
+        &self,
+        order: &OrderRef,
+        mut events_receiver: broadcast::Receiver<ExchangeEvent>,
+    ) -> Result<OrderStatus> {
+        loop {
+            let status = order.status();
+            if let OrderStatus::Canceled | OrderStatus::Completed = status {
+                return Ok(status);
+            }
+
+            match events_receiver.recv().await {
+                // Status is rechecked on each event, so lagged events are not a problem
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => bail!(
+                    "Events channel was closed while waiting cancellation of order {} on {}",
+                    order.client_order_id(),
+                    self.exchange_account_id
+                ),
+            }
+        }
+    }
+
     pub async fn wait_cancel_order(
         &self,
         order: OrderRef,
@@ -292,7 +354,7 @@ impl Exchange {
             order.fn_mut(|order| {
                 order
                     .internal_props
-                    .last_order_cancellation_status_request_time = Some(Utc::now())
+                    .last_order_cancellation_status_request_time = Some(self.now())
             });
 
             self.timeout_manager
@@ -365,7 +427,7 @@ impl Exchange {
                 }
                 Ok(order_info) => {
                     match order_info.order_status {
-                        OrderStatus::Canceled => {
+                        ExchangeOrderStatus::Known(OrderStatus::Canceled) => {
                             if let Some(exchange_order_id) = order.exchange_order_id() {
                                 self.handle_cancel_order_succeeded(
                                     Some(&order.client_order_id()),
@@ -375,7 +437,7 @@ impl Exchange {
                                 )?;
                             }
                         }
-                        OrderStatus::Completed => {
+                        ExchangeOrderStatus::Known(OrderStatus::Completed) => {
                             // Looks like we've missed a fill while we were cancelling, it can happen in two scenarios:
                             // 1. Test ShouldCheckFillsForCompletedOrders. There we clear a completed order to be able to
                             // test a case of cancelling a completed order which involves calling CheckOrderFills in case of OrderCompleted
@@ -434,3 +496,135 @@ impl Exchange {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+    use crate::core::exchanges::general::test_helper::{
+        create_order_ref, get_test_exchange, try_add_snapshot_by_exchange_id,
+        ScriptedExchangeBuilder,
+    };
+    use crate::core::orders::order::{
+        ExchangeOrderId, OrderCreating, OrderRole, OrderSide, OrderSnapshot,
+    };
+    use awc::http::StatusCode;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    fn create_canceling_order(exchange: &Exchange) -> OrderRef {
+        let order_ref = create_order_ref(
+            &ClientOrderId::unique_id(),
+            Some(OrderRole::Maker),
+            &exchange.exchange_account_id,
+            &CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            dec!(0.2),
+            dec!(5),
+            OrderSide::Buy,
+        );
+        order_ref.fn_mut(|order| {
+            order.props.exchange_order_id = Some(ExchangeOrderId::new("test".into()));
+            order.set_status(OrderStatus::Canceling, Utc::now());
+        });
+        try_add_snapshot_by_exchange_id(exchange, &order_ref);
+        let _ = exchange
+            .orders
+            .cache_by_client_id
+            .insert(order_ref.client_order_id(), order_ref.clone());
+
+        order_ref
+    }
+
+    async fn create_order(exchange: &Exchange) -> OrderRef {
+        let mut header = (*OrderSnapshot::test_default().header).clone();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order_to_create = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.2),
+        };
+
+        exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test")
+    }
+
+    #[actix_rt::test]
+    async fn resolves_with_canceled_after_confirmation() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0)).build();
+        let order_ref = create_order(&exchange).await;
+
+        let status = exchange
+            .cancel_and_wait(
+                &order_ref.client_order_id(),
+                Duration::from_secs(1),
+                CancellationToken::new(),
+            )
+            .await
+            .expect("in test");
+
+        assert_eq!(status, OrderStatus::Canceled);
+        assert_eq!(order_ref.status(), OrderStatus::Canceled);
+    }
+
+    #[actix_rt::test]
+    async fn error_if_not_canceled_in_timeout() {
+        let (exchange, _rx) =
+            ScriptedExchangeBuilder::new(ExchangeAccountId::new("Binance".into(), 0))
+                .cancel_order_status(StatusCode::SERVICE_UNAVAILABLE)
+                .build();
+        let order_ref = create_order(&exchange).await;
+
+        let error = exchange
+            .cancel_and_wait(
+                &order_ref.client_order_id(),
+                Duration::from_millis(100),
+                CancellationToken::new(),
+            )
+            .await
+            .expect_err("in test");
+
+        assert!(error.to_string().contains("was not canceled"));
+        assert_eq!(order_ref.status(), OrderStatus::FailedToCancel);
+    }
+
+    #[tokio::test]
+    async fn already_completed_order_resolves_instantly() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let order_ref = create_canceling_order(&exchange);
+        order_ref.fn_mut(|order| order.set_status(OrderStatus::Completed, Utc::now()));
+
+        let status = exchange
+            .cancel_and_wait(
+                &order_ref.client_order_id(),
+                Duration::from_secs(1),
+                CancellationToken::new(),
+            )
+            .await
+            .expect("in test");
+
+        assert_eq!(status, OrderStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn unknown_order() {
+        let (exchange, _rx) = get_test_exchange(false);
+
+        let error = exchange
+            .cancel_and_wait(
+                &ClientOrderId::unique_id(),
+                Duration::from_secs(1),
+                CancellationToken::new(),
+            )
+            .await
+            .expect_err("in test");
+
+        assert!(matches!(
+            error.downcast_ref::<OrderError>(),
+            Some(OrderError::NotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{bail, Result};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::core::exchanges::common::Price;
+use crate::core::orders::order::OrderSide;
+
+/// Rounds order price before sending it to exchange.
+/// Can be registered for specific currency pair if exchange rounding rules differs between symbols
+pub trait PriceRounder: Send + Sync {
+    fn round_price(&self, price: Decimal, side: OrderSide) -> Decimal;
+}
+
+/// Rounds price to tick size so that price never becomes more aggressive:
+/// buy price is rounded down and sell price is rounded up
+pub struct TickSizeRounder {
+    tick: Price,
+}
+
+impl TickSizeRounder {
+    pub fn new(tick: Price) -> Result<Self> {
+        if tick <= dec!(0) {
+            bail!("Tick size should be positive but got {}", tick)
+        }
+
+        Ok(TickSizeRounder { tick })
+    }
+}
+
+impl PriceRounder for TickSizeRounder {
+    fn round_price(&self, price: Decimal, side: OrderSide) -> Decimal {
+        let ticks = price / self.tick;
+        let ticks = match side {
+            OrderSide::Buy => ticks.floor(),
+            OrderSide::Sell => ticks.ceil(),
+        };
+
+        ticks * self.tick
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::core::exchanges::common::CurrencyPair;
+    use crate::core::exchanges::general::test_helper::get_test_exchange;
+
+    struct ConstantRounder(Price);
+
+    impl PriceRounder for ConstantRounder {
+        fn round_price(&self, _price: Decimal, _side: OrderSide) -> Decimal {
+            self.0
+        }
+    }
+
+    #[test]
+    fn tick_size_rounder() {
+        let rounder = TickSizeRounder::new(dec!(0.05)).expect("in test");
+
+        assert_eq!(rounder.round_price(dec!(1.23), OrderSide::Buy), dec!(1.20));
+        assert_eq!(rounder.round_price(dec!(1.23), OrderSide::Sell), dec!(1.25));
+        assert_eq!(rounder.round_price(dec!(1.25), OrderSide::Buy), dec!(1.25));
+    }
+
+    #[test]
+    fn tick_size_rounder_with_non_positive_tick() {
+        assert!(TickSizeRounder::new(dec!(0)).is_err());
+    }
+
+    #[test]
+    fn custom_rounder_used_only_for_registered_pair() {
+        let (exchange, _) = get_test_exchange(false);
+        let registered_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let other_pair = CurrencyPair::from_codes("ETH".into(), "BTC".into());
+
+        exchange
+            .register_price_rounder(registered_pair.clone(), Arc::new(ConstantRounder(dec!(42))));
+
+        assert_eq!(
+            exchange.round_price(&registered_pair, dec!(1.2345), OrderSide::Buy),
+            dec!(42)
+        );
+        assert_eq!(
+            exchange.round_price(&other_pair, dec!(1.2345), OrderSide::Buy),
+            dec!(1.2345)
+        );
+    }
+}
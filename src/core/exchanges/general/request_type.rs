@@ -2,6 +2,7 @@
 pub enum RequestType {
     CreateOrder,
     CancelOrder,
+    AmendOrder,
     GetOrderInfo,
     GetBalance,
     GetOpenOrders,
@@ -1,16 +1,38 @@
 #![cfg(test)]
 use std::{collections::HashMap, sync::Arc};
 
-use parking_lot::RwLock;
+use anyhow::Result;
+use async_trait::async_trait;
+use awc::http::{StatusCode, Uri};
+use chrono::Utc;
+use dashmap::DashMap;
+use parking_lot::{Mutex, RwLock};
 use rust_decimal_macros::dec;
 use tokio::sync::broadcast;
 
+use super::exchange::BoxExchangeClient;
+use super::handlers::handle_order_filled::FillEventData;
+use super::request_type::RequestType;
 use super::{currency_pair_metadata::CurrencyPairMetadata, exchange::Exchange};
+use crate::core::connectivity::connectivity_manager::WebSocketRole;
 use crate::core::exchanges::binance::binance::BinanceBuilder;
+use crate::core::exchanges::common::{
+    CurrencyCode, CurrencyId, ExchangeError, RestRequestOutcome, SpecificCurrencyPair,
+};
 use crate::core::exchanges::events::ExchangeEvent;
-use crate::core::exchanges::traits::ExchangeClientBuilder;
+use crate::core::exchanges::general::features::ExchangeCapabilities;
+use crate::core::exchanges::timeouts::rate_limit_rules::RateLimitTracker;
+use crate::core::exchanges::timeouts::requests_timeout_manager_factory::{
+    RequestTimeoutArguments, RequestsTimeoutManagerFactory,
+};
+use crate::core::exchanges::traits::{ExchangeClient, ExchangeClientBuilder, Support};
 use crate::core::lifecycle::application_manager::ApplicationManager;
 use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::orders::fill::{EventSourceType, OrderFill};
+use crate::core::orders::order::{
+    ExchangeOrderId, OrderAmending, OrderCancelling, OrderCreating, OrderHeader, OrderInfo,
+};
+use crate::core::DateTime;
 use crate::core::{
     exchanges::binance::binance::Binance, exchanges::common::Amount,
     exchanges::common::CurrencyPair, exchanges::common::ExchangeAccountId,
@@ -25,8 +47,147 @@ use crate::core::{
 
 pub(crate) fn get_test_exchange(
     is_derivative: bool,
+) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
+    get_test_exchange_with_capabilities(is_derivative, ExchangeCapabilities::default())
+}
+
+pub(crate) fn get_test_exchange_with_capabilities(
+    is_derivative: bool,
+    capabilities: ExchangeCapabilities,
 ) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
     let exchange_account_id = ExchangeAccountId::new("local_exchange_account_id".into(), 0);
+    get_test_exchange_with_client(
+        exchange_account_id,
+        is_derivative,
+        capabilities,
+        None,
+        |binance| Box::new(binance),
+    )
+}
+
+/// Builder of test exchange with stubbed REST order creation, cancellation and amending.
+/// By default all requests succeed, successful creation and any cancellation are confirmed
+/// immediately as if they were received from websocket
+pub(crate) struct ScriptedExchangeBuilder {
+    exchange_account_id: ExchangeAccountId,
+    capabilities: ExchangeCapabilities,
+    requests_limit: Option<RequestTimeoutArguments>,
+    create_order_status: StatusCode,
+    cancel_order_status: StatusCode,
+    amend_order_status: StatusCode,
+    is_creation_confirmed_by_websocket: bool,
+    mass_canceled_pairs: Arc<Mutex<Vec<CurrencyPair>>>,
+    sent_requests: Arc<Mutex<Vec<RequestType>>>,
+}
+
+impl ScriptedExchangeBuilder {
+    pub(crate) fn new(exchange_account_id: ExchangeAccountId) -> Self {
+        ScriptedExchangeBuilder {
+            exchange_account_id,
+            capabilities: ExchangeCapabilities::default(),
+            requests_limit: None,
+            create_order_status: StatusCode::OK,
+            cancel_order_status: StatusCode::OK,
+            amend_order_status: StatusCode::OK,
+            is_creation_confirmed_by_websocket: true,
+            mass_canceled_pairs: Default::default(),
+            sent_requests: Default::default(),
+        }
+    }
+
+    pub(crate) fn capabilities(mut self, capabilities: ExchangeCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Requests are limited by timeout manager
+    pub(crate) fn requests_limit(mut self, requests_limit: RequestTimeoutArguments) -> Self {
+        self.requests_limit = Some(requests_limit);
+        self
+    }
+
+    pub(crate) fn create_order_status(mut self, create_order_status: StatusCode) -> Self {
+        self.create_order_status = create_order_status;
+        self
+    }
+
+    pub(crate) fn cancel_order_status(mut self, cancel_order_status: StatusCode) -> Self {
+        self.cancel_order_status = cancel_order_status;
+        self
+    }
+
+    pub(crate) fn amend_order_status(mut self, amend_order_status: StatusCode) -> Self {
+        self.amend_order_status = amend_order_status;
+        self
+    }
+
+    /// Successful creation is confirmed by REST response only
+    pub(crate) fn without_websocket_creation_confirmation(mut self) -> Self {
+        self.is_creation_confirmed_by_websocket = false;
+        self
+    }
+
+    /// Currency pairs of requested mass cancellations
+    pub(crate) fn mass_canceled_pairs(&self) -> Arc<Mutex<Vec<CurrencyPair>>> {
+        self.mass_canceled_pairs.clone()
+    }
+
+    /// Types of sent order requests
+    pub(crate) fn sent_requests(&self) -> Arc<Mutex<Vec<RequestType>>> {
+        self.sent_requests.clone()
+    }
+
+    pub(crate) fn build(self) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
+        let ScriptedExchangeBuilder {
+            exchange_account_id,
+            capabilities,
+            requests_limit,
+            create_order_status,
+            cancel_order_status,
+            amend_order_status,
+            is_creation_confirmed_by_websocket,
+            mass_canceled_pairs,
+            sent_requests,
+        } = self;
+
+        get_test_exchange_with_client(
+            exchange_account_id,
+            false,
+            capabilities,
+            requests_limit,
+            move |binance| {
+                Box::new(ScriptedExchangeClient {
+                    binance,
+                    create_order_status,
+                    cancel_order_status,
+                    amend_order_status,
+                    mass_canceled_pairs,
+                    is_creation_confirmed_by_websocket,
+                    sent_requests,
+                })
+            },
+        )
+    }
+}
+
+pub(crate) fn get_test_exchange_with_client(
+    exchange_account_id: ExchangeAccountId,
+    is_derivative: bool,
+    capabilities: ExchangeCapabilities,
+    requests_limit: Option<RequestTimeoutArguments>,
+    create_client: impl FnOnce(Binance) -> BoxExchangeClient,
+) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
+    let mut timeout_managers = HashMap::new();
+    if let Some(requests_limit) = requests_limit {
+        let _ = timeout_managers.insert(
+            exchange_account_id.clone(),
+            RequestsTimeoutManagerFactory::from_requests_per_period(
+                requests_limit,
+                exchange_account_id.clone(),
+            ),
+        );
+    }
+
     let mut settings = settings::ExchangeSettings::new_short(
         exchange_account_id.clone(),
         "test_api_key".into(),
@@ -41,7 +202,7 @@ pub(crate) fn get_test_exchange(
     settings.web_socket_host = "host".into();
     settings.web_socket2_host = "host2".into();
 
-    let binance = Box::new(Binance::new(
+    let binance = create_client(Binance::new(
         "Binance0".parse().expect("in test"),
         settings.clone(),
         tx.clone(),
@@ -62,10 +223,11 @@ pub(crate) fn get_test_exchange(
             true,
             AllowedEventSourceType::default(),
             AllowedEventSourceType::default(),
+            capabilities,
         ),
         tx,
         application_manager,
-        TimeoutManager::new(HashMap::new()),
+        TimeoutManager::new(timeout_managers),
         commission,
     );
     let base_currency_code = "PHB";
@@ -147,3 +309,217 @@ pub(crate) fn try_add_snapshot_by_exchange_id(exchange: &Exchange, order_ref: &O
             .insert(exchange_order_id.clone(), order_ref.clone());
     }
 }
+
+/// `OrderHeader::test_default` order for PHB/BTC on the given test exchange
+pub(crate) fn order_to_create(
+    exchange: &Exchange,
+    side: OrderSide,
+    amount: Amount,
+    price: Price,
+) -> OrderCreating {
+    let mut header = OrderHeader::test_default();
+    header.exchange_account_id = exchange.exchange_account_id.clone();
+    header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+    header.side = side;
+    header.amount = amount;
+    OrderCreating {
+        header: Arc::new(header),
+        price,
+    }
+}
+
+/// Adds `order_to_create` order to the exchange and fills it by user trade on `filled_amount`
+pub(crate) fn add_filled_order(
+    exchange: &Exchange,
+    side: OrderSide,
+    amount: Amount,
+    filled_amount: Amount,
+) -> OrderRef {
+    let order = order_to_create(exchange, side, amount, dec!(0.1));
+    let order_ref = exchange
+        .orders
+        .add_simple_initial(order.header, Some(order.price));
+    let fill = OrderFill::test_default()
+        .with_price_and_amount(order.price, filled_amount)
+        .with_side(side);
+    exchange.add_order_fill(&order_ref, fill);
+    order_ref
+}
+
+/// Binance client with stubbed REST order creation, cancellation and amending, see `ScriptedExchangeBuilder`
+struct ScriptedExchangeClient {
+    binance: Binance,
+    create_order_status: StatusCode,
+    cancel_order_status: StatusCode,
+    amend_order_status: StatusCode,
+    mass_canceled_pairs: Arc<Mutex<Vec<CurrencyPair>>>,
+    is_creation_confirmed_by_websocket: bool,
+    sent_requests: Arc<Mutex<Vec<RequestType>>>,
+}
+
+#[async_trait]
+impl ExchangeClient for ScriptedExchangeClient {
+    async fn request_metadata(&self) -> Result<RestRequestOutcome> {
+        self.binance.request_metadata().await
+    }
+
+    async fn create_order(&self, order: &OrderCreating) -> Result<RestRequestOutcome> {
+        self.sent_requests.lock().push(RequestType::CreateOrder);
+        if self.create_order_status != StatusCode::OK {
+            return Ok(RestRequestOutcome::new(
+                "Request rejected".to_owned(),
+                self.create_order_status,
+            ));
+        }
+
+        let exchange_order_id = ExchangeOrderId::new(order.header.client_order_id.as_str().into());
+        if self.is_creation_confirmed_by_websocket {
+            self.binance.order_created_callback.lock()(
+                order.header.client_order_id.clone(),
+                exchange_order_id.clone(),
+                EventSourceType::WebSocket,
+            );
+        }
+
+        Ok(RestRequestOutcome::new(
+            format!(
+                r#"{{"orderId":"{}","transactTime":{}}}"#,
+                exchange_order_id.as_str(),
+                Utc::now().timestamp_millis()
+            ),
+            StatusCode::OK,
+        ))
+    }
+
+    async fn request_cancel_order(&self, order: &OrderCancelling) -> Result<RestRequestOutcome> {
+        self.sent_requests.lock().push(RequestType::CancelOrder);
+        if self.cancel_order_status != StatusCode::OK {
+            return Ok(RestRequestOutcome::new(
+                "Request rejected".to_owned(),
+                self.cancel_order_status,
+            ));
+        }
+
+        self.binance.order_cancelled_callback.lock()(
+            order.header.client_order_id.clone(),
+            order.exchange_order_id.clone(),
+            EventSourceType::WebSocket,
+        );
+
+        Ok(RestRequestOutcome::new(
+            format!(r#"{{"orderId":"{}"}}"#, order.exchange_order_id.as_str()),
+            StatusCode::OK,
+        ))
+    }
+
+    async fn request_amend_order(&self, order: &OrderAmending) -> Result<RestRequestOutcome> {
+        self.sent_requests.lock().push(RequestType::AmendOrder);
+        if self.amend_order_status != StatusCode::OK {
+            return Ok(RestRequestOutcome::new(
+                "Request rejected".to_owned(),
+                self.amend_order_status,
+            ));
+        }
+
+        Ok(RestRequestOutcome::new(
+            format!(r#"{{"orderId":"{}"}}"#, order.exchange_order_id.as_str()),
+            StatusCode::OK,
+        ))
+    }
+
+    async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()> {
+        self.mass_canceled_pairs.lock().push(currency_pair);
+        Ok(())
+    }
+
+    async fn request_open_orders(&self) -> Result<RestRequestOutcome> {
+        self.binance.request_open_orders().await
+    }
+
+    async fn request_order_info(&self, order: &OrderRef) -> Result<RestRequestOutcome> {
+        self.binance.request_order_info(order).await
+    }
+}
+
+#[async_trait]
+impl Support for ScriptedExchangeClient {
+    fn is_rest_error_code(&self, response: &RestRequestOutcome) -> Result<(), ExchangeError> {
+        self.binance.is_rest_error_code(response)
+    }
+
+    fn get_order_id(&self, response: &RestRequestOutcome) -> Result<ExchangeOrderId> {
+        self.binance.get_order_id(response)
+    }
+
+    fn get_server_time(&self, response: &RestRequestOutcome) -> Option<DateTime> {
+        self.binance.get_server_time(response)
+    }
+
+    fn clarify_error_type(&self, error: &mut ExchangeError) {
+        self.binance.clarify_error_type(error)
+    }
+
+    fn on_websocket_message(&self, msg: &str) -> Result<()> {
+        self.binance.on_websocket_message(msg)
+    }
+
+    fn set_order_created_callback(
+        &self,
+        callback: Box<dyn FnMut(ClientOrderId, ExchangeOrderId, EventSourceType) + Send + Sync>,
+    ) {
+        self.binance.set_order_created_callback(callback)
+    }
+
+    fn set_order_cancelled_callback(
+        &self,
+        callback: Box<dyn FnMut(ClientOrderId, ExchangeOrderId, EventSourceType) + Send + Sync>,
+    ) {
+        self.binance.set_order_cancelled_callback(callback)
+    }
+
+    fn set_handle_order_filled_callback(
+        &self,
+        callback: Box<dyn FnMut(FillEventData) + Send + Sync>,
+    ) {
+        self.binance.set_handle_order_filled_callback(callback)
+    }
+
+    fn set_rate_limit_tracker(&self, rate_limit_tracker: Arc<RateLimitTracker>) {
+        self.binance.set_rate_limit_tracker(rate_limit_tracker)
+    }
+
+    fn is_enabled_websocket(&self, role: WebSocketRole) -> bool {
+        self.binance.is_enabled_websocket(role)
+    }
+
+    async fn create_ws_url(&self, role: WebSocketRole) -> Result<Uri> {
+        self.binance.create_ws_url(role).await
+    }
+
+    fn get_specific_currency_pair(&self, currency_pair: &CurrencyPair) -> SpecificCurrencyPair {
+        self.binance.get_specific_currency_pair(currency_pair)
+    }
+
+    fn get_supported_currencies(&self) -> &DashMap<CurrencyId, CurrencyCode> {
+        self.binance.get_supported_currencies()
+    }
+
+    fn should_log_message(&self, message: &str) -> bool {
+        self.binance.should_log_message(message)
+    }
+
+    fn parse_open_orders(&self, response: &RestRequestOutcome) -> Result<Vec<OrderInfo>> {
+        self.binance.parse_open_orders(response)
+    }
+
+    fn parse_order_info(&self, response: &RestRequestOutcome) -> Result<OrderInfo> {
+        self.binance.parse_order_info(response)
+    }
+
+    fn parse_metadata(
+        &self,
+        response: &RestRequestOutcome,
+    ) -> Result<Vec<Arc<CurrencyPairMetadata>>> {
+        self.binance.parse_metadata(response)
+    }
+}
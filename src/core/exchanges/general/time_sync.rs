@@ -0,0 +1,113 @@
+use chrono::Duration;
+use log::{error, info};
+use parking_lot::Mutex;
+
+use crate::core::exchanges::common::ExchangeAccountId;
+use crate::core::DateTime;
+
+struct TimeSyncState {
+    max_drift: Option<Duration>,
+    drift: Option<Duration>,
+    is_trading_halted: bool,
+}
+
+/// Tracks drift of local clock relative to exchange clock.
+/// Trading is halted while absolute drift exceeds configured maximum
+pub struct TimeSync {
+    exchange_account_id: ExchangeAccountId,
+    state: Mutex<TimeSyncState>,
+}
+
+impl TimeSync {
+    pub fn new(exchange_account_id: ExchangeAccountId, max_drift: Option<Duration>) -> Self {
+        TimeSync {
+            exchange_account_id,
+            state: Mutex::new(TimeSyncState {
+                max_drift,
+                drift: None,
+                is_trading_halted: false,
+            }),
+        }
+    }
+
+    pub fn set_max_drift(&self, max_drift: Option<Duration>) {
+        self.state.lock().max_drift = max_drift;
+    }
+
+    /// Measure drift as difference between time reported by exchange and local time
+    pub fn update(&self, local_time: DateTime, exchange_time: DateTime) {
+        let drift = exchange_time - local_time;
+
+        let mut state = self.state.lock();
+        state.drift = Some(drift);
+
+        let is_exceeded = match state.max_drift {
+            Some(max_drift) => drift > max_drift || drift < -max_drift,
+            None => false,
+        };
+
+        if is_exceeded && !state.is_trading_halted {
+            error!(
+                "Trading on {} is halted because clock drift {}ms exceeds maximum {:?}ms",
+                self.exchange_account_id,
+                drift.num_milliseconds(),
+                state.max_drift.map(|x| x.num_milliseconds())
+            );
+        } else if !is_exceeded && state.is_trading_halted {
+            info!(
+                "Trading on {} is resumed because clock drift {}ms recovered",
+                self.exchange_account_id,
+                drift.num_milliseconds()
+            );
+        }
+
+        state.is_trading_halted = is_exceeded;
+    }
+
+    pub fn drift(&self) -> Option<Duration> {
+        self.state.lock().drift
+    }
+
+    pub fn is_trading_halted(&self) -> bool {
+        self.state.lock().is_trading_halted
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Utc;
+
+    fn time_sync(max_drift_ms: i64) -> TimeSync {
+        TimeSync::new(
+            ExchangeAccountId::new("Binance".into(), 0),
+            Some(Duration::milliseconds(max_drift_ms)),
+        )
+    }
+
+    #[test]
+    fn halt_on_large_drift_and_resume_on_recovery() {
+        let time_sync = time_sync(500);
+        let now = Utc::now();
+
+        time_sync.update(now, now + Duration::milliseconds(100));
+        assert!(!time_sync.is_trading_halted());
+
+        time_sync.update(now, now - Duration::seconds(2));
+        assert!(time_sync.is_trading_halted());
+        assert_eq!(time_sync.drift(), Some(-Duration::seconds(2)));
+
+        time_sync.update(now, now - Duration::milliseconds(200));
+        assert!(!time_sync.is_trading_halted());
+    }
+
+    #[test]
+    fn no_halt_without_max_drift() {
+        let time_sync = TimeSync::new(ExchangeAccountId::new("Binance".into(), 0), None);
+        let now = Utc::now();
+
+        time_sync.update(now, now + Duration::hours(1));
+
+        assert!(!time_sync.is_trading_halted());
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::{Datelike, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::core::DateTime;
+
+/// UTC time range when trading is allowed. Range with `from` greater than `to` passes midnight
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TradingTimeRange {
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+    // all weekdays if not specified
+    #[serde(default)]
+    pub weekdays: Option<Vec<Weekday>>,
+}
+
+impl TradingTimeRange {
+    pub fn contains(&self, time: DateTime) -> bool {
+        if let Some(weekdays) = &self.weekdays {
+            if !weekdays.contains(&time.weekday()) {
+                return false;
+            }
+        }
+
+        let time = time.time();
+        if self.from <= self.to {
+            self.from <= time && time < self.to
+        } else {
+            self.from <= time || time < self.to
+        }
+    }
+}
+
+/// Set of time ranges when orders can be placed. Empty window doesn't restrict trading
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct TradingWindow {
+    pub ranges: Vec<TradingTimeRange>,
+}
+
+impl TradingWindow {
+    pub fn new(ranges: Vec<TradingTimeRange>) -> Self {
+        TradingWindow { ranges }
+    }
+
+    pub fn is_open(&self, time: DateTime) -> bool {
+        self.ranges.is_empty() || self.ranges.iter().any(|range| range.contains(time))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use rstest::rstest;
+
+    fn range(from: u32, to: u32, weekdays: Option<Vec<Weekday>>) -> TradingTimeRange {
+        TradingTimeRange {
+            from: NaiveTime::from_hms(from, 0, 0),
+            to: NaiveTime::from_hms(to, 0, 0),
+            weekdays,
+        }
+    }
+
+    #[rstest]
+    // 2021-01-04 is Monday
+    #[case(range(9, 17, None), 12, true)]
+    #[case(range(9, 17, None), 17, false)]
+    #[case(range(22, 2, None), 23, true)]
+    #[case(range(22, 2, None), 1, true)]
+    #[case(range(22, 2, None), 12, false)]
+    #[case(range(9, 17, Some(vec![Weekday::Mon])), 12, true)]
+    #[case(range(9, 17, Some(vec![Weekday::Sat, Weekday::Sun])), 12, false)]
+    fn range_contains(#[case] range: TradingTimeRange, #[case] hour: u32, #[case] expected: bool) {
+        let time = Utc.ymd(2021, 1, 4).and_hms(hour, 0, 0);
+
+        assert_eq!(range.contains(time), expected);
+    }
+
+    #[test]
+    fn empty_window_is_always_open() {
+        assert!(TradingWindow::default().is_open(Utc::now()));
+    }
+
+    #[test]
+    fn deserialize_window() {
+        let window: TradingWindow =
+            serde_json::from_str(r#"[{"from":"09:00:00","to":"17:00:00","weekdays":["Mon"]}]"#)
+                .expect("in test");
+
+        assert_eq!(
+            window,
+            TradingWindow::new(vec![range(9, 17, Some(vec![Weekday::Mon]))])
+        );
+    }
+}
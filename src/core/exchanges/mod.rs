@@ -4,6 +4,8 @@ pub mod common;
 pub mod events;
 pub mod exchange_blocker;
 pub mod general;
+pub mod paper_trading;
 pub mod rest_client;
+pub mod rest_retry;
 pub mod timeouts;
 pub mod traits;
@@ -0,0 +1,533 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use awc::http::{StatusCode, Uri};
+use dashmap::DashMap;
+use futures::FutureExt;
+use log::info;
+use parking_lot::Mutex;
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+
+use super::common::{
+    CurrencyCode, CurrencyId, CurrencyPair, ExchangeAccountId, ExchangeError, ExchangeErrorType,
+    Price, RestRequestOutcome, SpecificCurrencyPair, TradePlace,
+};
+use super::events::ExchangeEvent;
+use super::general::currency_pair_metadata::CurrencyPairMetadata;
+use super::general::exchange::BoxExchangeClient;
+use super::general::handlers::handle_order_filled::FillEventData;
+use super::traits::{ExchangeClient, ExchangeClientBuilderResult, Support};
+use crate::core::connectivity::connectivity_manager::WebSocketRole;
+use crate::core::exchanges::timeouts::rate_limit_rules::RateLimitTracker;
+use crate::core::infrastructure::spawn_future;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
+use crate::core::orders::fill::{EventSourceType, OrderFillType};
+use crate::core::orders::order::{
+    ClientOrderId, ExchangeOrderId, OrderCancelling, OrderCreating, OrderInfo, OrderRole,
+    OrderSide, OrderStatus, OrderType,
+};
+use crate::core::orders::pool::OrderRef;
+
+type OrderCallback = Box<dyn FnMut(ClientOrderId, ExchangeOrderId, EventSourceType) + Send + Sync>;
+
+struct SimulatedOrder {
+    order: OrderCreating,
+    exchange_order_id: ExchangeOrderId,
+    status: OrderStatus,
+}
+
+impl SimulatedOrder {
+    /// Price of fill if opposite side of order book crosses the order
+    fn fill_price(&self, top_ask: Option<Price>, top_bid: Option<Price>) -> Option<Price> {
+        let header = &self.order.header;
+        let is_market = header.order_type == OrderType::Market;
+        match header.side {
+            OrderSide::Buy => top_ask
+                .filter(|&ask| is_market || ask <= self.order.price)
+                .map(|ask| if is_market { ask } else { self.order.price }),
+            OrderSide::Sell => top_bid
+                .filter(|&bid| is_market || bid >= self.order.price)
+                .map(|bid| if is_market { bid } else { self.order.price }),
+        }
+    }
+
+    fn order_info(&self) -> OrderInfo {
+        let header = &self.order.header;
+        let (average_fill_price, filled_amount) = match self.status {
+            OrderStatus::Completed => (self.order.price, header.amount),
+            _ => (dec!(0), dec!(0)),
+        };
+
+        OrderInfo::new(
+            header.currency_pair.clone(),
+            self.exchange_order_id.clone(),
+            header.client_order_id.clone(),
+            header.side,
+            self.status.into(),
+            self.order.price,
+            header.amount,
+            average_fill_price,
+            filled_amount,
+            None,
+            None,
+            None,
+        )
+    }
+}
+
+struct PaperTradingState {
+    exchange_account_id: ExchangeAccountId,
+    snapshots: Mutex<LocalSnapshotsService>,
+    orders: Mutex<HashMap<ClientOrderId, SimulatedOrder>>,
+    order_created_callback: Mutex<OrderCallback>,
+    order_cancelled_callback: Mutex<OrderCallback>,
+    handle_order_filled_callback: Mutex<Box<dyn FnMut(FillEventData) + Send + Sync>>,
+}
+
+impl PaperTradingState {
+    /// Fill completely all opened orders on pair crossed by last observed order book
+    fn match_orders(&self, currency_pair: &CurrencyPair) {
+        let trade_place = TradePlace::new(
+            self.exchange_account_id.exchange_id.clone(),
+            currency_pair.clone(),
+        );
+        let (top_ask, top_bid) = match self.snapshots.lock().get_snapshot(trade_place) {
+            Some(snapshot) => (
+                snapshot.get_top_ask().map(|x| x.0),
+                snapshot.get_top_bid().map(|x| x.0),
+            ),
+            None => return,
+        };
+
+        // callbacks are called without lock because they are handled by exchange synchronously
+        let fills = self
+            .orders
+            .lock()
+            .values_mut()
+            .filter(|x| x.status == OrderStatus::Created)
+            .filter(|x| &x.order.header.currency_pair == currency_pair)
+            .filter_map(|x| {
+                let fill_price = x.fill_price(top_ask, top_bid)?;
+                x.status = OrderStatus::Completed;
+                x.order.price = fill_price;
+                Some(simulated_fill(x, fill_price))
+            })
+            .collect::<Vec<_>>();
+
+        for fill in fills {
+            info!(
+                "Dry-run order {:?} on {} is filled at {}",
+                fill.client_order_id, self.exchange_account_id, fill.fill_price
+            );
+            self.handle_order_filled_callback.lock()(fill);
+        }
+    }
+
+    fn cancel_orders(&self, should_cancel: impl Fn(&SimulatedOrder) -> bool) -> usize {
+        let canceled = self
+            .orders
+            .lock()
+            .values_mut()
+            .filter(|x| x.status == OrderStatus::Created && should_cancel(x))
+            .map(|x| {
+                x.status = OrderStatus::Canceled;
+                (
+                    x.order.header.client_order_id.clone(),
+                    x.exchange_order_id.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (client_order_id, exchange_order_id) in &canceled {
+            self.order_cancelled_callback.lock()(
+                client_order_id.clone(),
+                exchange_order_id.clone(),
+                EventSourceType::WebSocket,
+            );
+        }
+
+        canceled.len()
+    }
+}
+
+fn simulated_fill(order: &SimulatedOrder, fill_price: Price) -> FillEventData {
+    let header = &order.order.header;
+    let order_role = match header.order_type {
+        OrderType::Market => OrderRole::Taker,
+        _ => OrderRole::Maker,
+    };
+
+    FillEventData {
+        source_type: EventSourceType::WebSocket,
+        trade_id: uuid::Uuid::new_v4().to_string(),
+        client_order_id: Some(header.client_order_id.clone()),
+        exchange_order_id: order.exchange_order_id.clone(),
+        fill_price,
+        fill_amount: header.amount,
+        is_diff: true,
+        total_filled_amount: Some(header.amount),
+        order_role: Some(order_role),
+        commission_currency_code: None,
+        commission_rate: None,
+        commission_amount: None,
+        fill_type: OrderFillType::UserTrade,
+        trade_currency_pair: Some(header.currency_pair.clone()),
+        order_side: Some(header.side),
+        order_amount: Some(header.amount),
+    }
+}
+
+fn response_field(response: &RestRequestOutcome, field: &str) -> Result<String> {
+    let content: Value = serde_json::from_str(&response.content)?;
+    match content[field].as_str() {
+        Some(value) => Ok(value.to_owned()),
+        None => bail!(
+            "Field {} is missing in dry-run response {}",
+            field,
+            response.content
+        ),
+    }
+}
+
+/// Client of dry-run mode which never sends orders to exchange. Metadata and market data
+/// are received by wrapped real client while orders are simulated locally:
+/// - creation and cancellation are confirmed immediately like from websocket
+/// - limit order is filled completely at its price as maker when observed order book crosses it
+///   (top ask <= buy price or top bid >= sell price), including order book observed before creation
+/// - market order is filled completely at top opposite price of observed order book as taker
+///
+/// Orders progress through usual statuses, so strategies work the same way as with real client
+pub struct PaperExchangeClient {
+    inner: BoxExchangeClient,
+    state: Arc<PaperTradingState>,
+}
+
+impl PaperExchangeClient {
+    pub fn new(inner: BoxExchangeClient, exchange_account_id: ExchangeAccountId) -> Self {
+        PaperExchangeClient {
+            inner,
+            state: Arc::new(PaperTradingState {
+                exchange_account_id,
+                snapshots: Mutex::new(LocalSnapshotsService::default()),
+                orders: Default::default(),
+                order_created_callback: Mutex::new(Box::new(|_, _, _| {})),
+                order_cancelled_callback: Mutex::new(Box::new(|_, _, _| {})),
+                handle_order_filled_callback: Mutex::new(Box::new(|_| {})),
+            }),
+        }
+    }
+}
+
+/// Observe order book and fill crossed orders
+fn on_order_book_event(state: &PaperTradingState, event: ExchangeEvent) {
+    let order_book_event = match event {
+        ExchangeEvent::OrderBookEvent(order_book_event) => order_book_event,
+        _ => return,
+    };
+
+    let trade_place_account = state.snapshots.lock().update(order_book_event);
+    if let Some(trade_place_account) = trade_place_account {
+        if trade_place_account.exchange_account_id == state.exchange_account_id {
+            state.match_orders(&trade_place_account.currency_pair);
+        }
+    }
+}
+
+/// Replace client of exchange with `PaperExchangeClient` observing order books from `events_receiver`
+/// until `cancellation_token` is cancelled. In-place amending isn't simulated, so orders are modified
+/// by cancel-replace
+pub fn wrap_for_dry_run(
+    mut exchange_client: ExchangeClientBuilderResult,
+    exchange_account_id: ExchangeAccountId,
+    mut events_receiver: broadcast::Receiver<ExchangeEvent>,
+    cancellation_token: CancellationToken,
+) -> ExchangeClientBuilderResult {
+    info!(
+        "Orders on {} are simulated in dry-run mode",
+        exchange_account_id
+    );
+
+    let client = PaperExchangeClient::new(exchange_client.client, exchange_account_id);
+    let state = client.state.clone();
+    let action = async move {
+        loop {
+            let event = tokio::select! {
+                event = events_receiver.recv() => event,
+                _ = cancellation_token.when_cancelled() => return Ok(()),
+            };
+
+            match event {
+                Ok(event) => on_order_book_event(&state, event),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+        }
+    };
+    let _handle = spawn_future("dry-run order book observing", false, action.boxed());
+
+    exchange_client.client = Box::new(client);
+    exchange_client.features.capabilities.amend_order = false;
+    exchange_client
+}
+
+#[async_trait]
+impl ExchangeClient for PaperExchangeClient {
+    async fn request_metadata(&self) -> Result<RestRequestOutcome> {
+        self.inner.request_metadata().await
+    }
+
+    async fn create_order(&self, order: &OrderCreating) -> Result<RestRequestOutcome> {
+        let client_order_id = order.header.client_order_id.clone();
+        let exchange_order_id = ExchangeOrderId::new(format!("paper_{}", client_order_id).into());
+        let _ = self.state.orders.lock().insert(
+            client_order_id.clone(),
+            SimulatedOrder {
+                order: order.clone(),
+                exchange_order_id: exchange_order_id.clone(),
+                status: OrderStatus::Created,
+            },
+        );
+
+        self.state.order_created_callback.lock()(
+            client_order_id,
+            exchange_order_id.clone(),
+            EventSourceType::WebSocket,
+        );
+        self.state.match_orders(&order.header.currency_pair);
+
+        Ok(RestRequestOutcome::new(
+            json!({ "orderId": exchange_order_id.as_str() }).to_string(),
+            StatusCode::OK,
+        ))
+    }
+
+    async fn request_cancel_order(&self, order: &OrderCancelling) -> Result<RestRequestOutcome> {
+        let client_order_id = &order.header.client_order_id;
+        if self
+            .state
+            .cancel_orders(|x| &x.order.header.client_order_id == client_order_id)
+            == 0
+        {
+            return Ok(RestRequestOutcome::new(
+                json!({ "msg": format!("Order {} is not opened", client_order_id) }).to_string(),
+                StatusCode::BAD_REQUEST,
+            ));
+        }
+
+        Ok(RestRequestOutcome::new(
+            json!({ "orderId": order.exchange_order_id.as_str() }).to_string(),
+            StatusCode::OK,
+        ))
+    }
+
+    async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()> {
+        let _ = self
+            .state
+            .cancel_orders(|x| x.order.header.currency_pair == currency_pair);
+        Ok(())
+    }
+
+    async fn request_open_orders(&self) -> Result<RestRequestOutcome> {
+        // opened orders are taken from simulated ones on parsing
+        Ok(RestRequestOutcome::new(
+            json!({ "dryRun": true }).to_string(),
+            StatusCode::OK,
+        ))
+    }
+
+    async fn request_order_info(&self, order: &OrderRef) -> Result<RestRequestOutcome> {
+        Ok(RestRequestOutcome::new(
+            json!({ "clientOrderId": order.client_order_id().as_str() }).to_string(),
+            StatusCode::OK,
+        ))
+    }
+}
+
+#[async_trait]
+impl Support for PaperExchangeClient {
+    fn is_rest_error_code(&self, response: &RestRequestOutcome) -> Result<(), ExchangeError> {
+        if response.status != StatusCode::OK {
+            return Err(ExchangeError::new(
+                ExchangeErrorType::OrderNotFound,
+                response_field(response, "msg").unwrap_or_else(|_| response.content.clone()),
+                None,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn get_order_id(&self, response: &RestRequestOutcome) -> Result<ExchangeOrderId> {
+        let exchange_order_id = response_field(response, "orderId")?;
+        Ok(ExchangeOrderId::new(exchange_order_id.into()))
+    }
+
+    fn clarify_error_type(&self, _error: &mut ExchangeError) {}
+
+    fn on_websocket_message(&self, msg: &str) -> Result<()> {
+        self.inner.on_websocket_message(msg)
+    }
+
+    fn set_order_created_callback(&self, callback: OrderCallback) {
+        *self.state.order_created_callback.lock() = callback;
+    }
+
+    fn set_order_cancelled_callback(&self, callback: OrderCallback) {
+        *self.state.order_cancelled_callback.lock() = callback;
+    }
+
+    fn set_handle_order_filled_callback(
+        &self,
+        callback: Box<dyn FnMut(FillEventData) + Send + Sync>,
+    ) {
+        *self.state.handle_order_filled_callback.lock() = callback;
+    }
+
+    fn set_rate_limit_tracker(&self, rate_limit_tracker: Arc<RateLimitTracker>) {
+        self.inner.set_rate_limit_tracker(rate_limit_tracker)
+    }
+
+    fn is_enabled_websocket(&self, role: WebSocketRole) -> bool {
+        self.inner.is_enabled_websocket(role)
+    }
+
+    async fn create_ws_url(&self, role: WebSocketRole) -> Result<Uri> {
+        self.inner.create_ws_url(role).await
+    }
+
+    fn get_specific_currency_pair(&self, currency_pair: &CurrencyPair) -> SpecificCurrencyPair {
+        self.inner.get_specific_currency_pair(currency_pair)
+    }
+
+    fn get_supported_currencies(&self) -> &DashMap<CurrencyId, CurrencyCode> {
+        self.inner.get_supported_currencies()
+    }
+
+    fn should_log_message(&self, message: &str) -> bool {
+        self.inner.should_log_message(message)
+    }
+
+    fn parse_open_orders(&self, _response: &RestRequestOutcome) -> Result<Vec<OrderInfo>> {
+        Ok(self
+            .state
+            .orders
+            .lock()
+            .values()
+            .filter(|x| x.status == OrderStatus::Created)
+            .map(|x| x.order_info())
+            .collect())
+    }
+
+    fn parse_order_info(&self, response: &RestRequestOutcome) -> Result<OrderInfo> {
+        let client_order_id = ClientOrderId::new(response_field(response, "clientOrderId")?.into());
+        match self.state.orders.lock().get(&client_order_id) {
+            Some(order) => Ok(order.order_info()),
+            None => bail!("Dry-run order {} is not found", client_order_id),
+        }
+    }
+
+    fn parse_metadata(
+        &self,
+        response: &RestRequestOutcome,
+    ) -> Result<Vec<Arc<CurrencyPairMetadata>>> {
+        self.inner.parse_metadata(response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{Amount, SortedOrderData};
+    use crate::core::exchanges::general::exchange::Exchange;
+    use crate::core::exchanges::general::features::ExchangeCapabilities;
+    use crate::core::exchanges::general::test_helper::{
+        get_test_exchange_with_client, order_to_create,
+    };
+    use crate::core::order_book::event::{EventType, OrderBookEvent};
+    use crate::core::order_book::order_book_data::OrderBookData;
+    use chrono::Utc;
+
+    fn currency_pair() -> CurrencyPair {
+        CurrencyPair::from_codes("PHB".into(), "BTC".into())
+    }
+
+    fn paper_exchange() -> (
+        Arc<Exchange>,
+        Arc<PaperTradingState>,
+        broadcast::Receiver<ExchangeEvent>,
+    ) {
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+        let state = Mutex::new(None);
+        let (exchange, rx) = get_test_exchange_with_client(
+            exchange_account_id.clone(),
+            false,
+            ExchangeCapabilities::default(),
+            None,
+            |binance| {
+                let client = PaperExchangeClient::new(Box::new(binance), exchange_account_id);
+                *state.lock() = Some(client.state.clone());
+                Box::new(client)
+            },
+        );
+
+        let state = state.into_inner().expect("in test");
+        (exchange, state, rx)
+    }
+
+    fn order_book(ask: Price, bid: Price) -> ExchangeEvent {
+        let level = |price: Price, amount: Amount| -> SortedOrderData {
+            vec![(price, amount)].into_iter().collect()
+        };
+
+        ExchangeEvent::OrderBookEvent(OrderBookEvent::new(
+            Utc::now(),
+            "Binance0".parse().expect("in test"),
+            currency_pair(),
+            "1".to_owned(),
+            EventType::Snapshot,
+            OrderBookData::new(level(ask, dec!(5)), level(bid, dec!(5))),
+        ))
+    }
+
+    #[actix_rt::test]
+    async fn fill_limit_order_when_order_book_crosses_it() {
+        let (exchange, state, _rx) = paper_exchange();
+        on_order_book_event(&state, order_book(dec!(11), dec!(9)));
+
+        let order = order_to_create(&exchange, OrderSide::Buy, dec!(2), dec!(10));
+        let order_ref = exchange
+            .create_order(&order, CancellationToken::default())
+            .await
+            .expect("in test");
+
+        assert_eq!(order_ref.status(), OrderStatus::Created);
+        assert_eq!(order_ref.filled_amount(), dec!(0));
+
+        on_order_book_event(&state, order_book(dec!(9.5), dec!(9)));
+
+        assert_eq!(order_ref.status(), OrderStatus::Completed);
+        assert_eq!(order_ref.filled_amount(), dec!(2));
+        assert_eq!(order_ref.fn_ref(|x| x.average_fill_price()), Some(dec!(10)));
+    }
+
+    #[actix_rt::test]
+    async fn fill_market_order_on_creation_by_observed_order_book() {
+        let (exchange, state, _rx) = paper_exchange();
+        on_order_book_event(&state, order_book(dec!(11), dec!(9)));
+
+        let mut order = order_to_create(&exchange, OrderSide::Sell, dec!(2), dec!(0));
+        Arc::make_mut(&mut order.header).order_type = OrderType::Market;
+        let order_ref = exchange
+            .create_order(&order, CancellationToken::default())
+            .await
+            .expect("in test");
+
+        assert_eq!(order_ref.status(), OrderStatus::Completed);
+        assert_eq!(order_ref.fn_ref(|x| x.average_fill_price()), Some(dec!(9)));
+    }
+}
@@ -1,26 +1,88 @@
 use super::common::*;
+use super::rest_retry::{send_with_retry, RestRetrySettings};
+use super::timeouts::rate_limit_rules::{RateLimitTracker, REQUEST_WEIGHT};
 use anyhow::{bail, Context, Result};
 use hyper::client::HttpConnector;
 use hyper::{Body, Client, Error, Request, Response, Uri};
 use hyper_tls::HttpsConnector;
+use parking_lot::Mutex;
 use std::convert::TryInto;
+use std::sync::Arc;
 
 pub type HttpParams = Vec<(String, String)>;
 
 pub struct RestClient {
     client: Client<HttpsConnector<HttpConnector>>,
+    retry_settings: RestRetrySettings,
+    rate_limit_tracker: Mutex<Option<Arc<RateLimitTracker>>>,
 }
 
 const KEEP_ALIVE: &'static str = "keep-alive";
 
 impl RestClient {
-    pub fn new() -> Self {
+    pub fn new(retry_settings: RestRetrySettings) -> Self {
         Self {
             client: create_client(),
+            retry_settings,
+            rate_limit_tracker: Mutex::new(None),
+        }
+    }
+
+    /// Retries are accounted in rate limits of exchange, first attempt is accounted by caller
+    pub fn set_rate_limit_tracker(&self, rate_limit_tracker: Arc<RateLimitTracker>) {
+        *self.rate_limit_tracker.lock() = Some(rate_limit_tracker);
+    }
+
+    async fn acquire_rate_limit(&self, is_order: bool) {
+        let rate_limit_tracker = self.rate_limit_tracker.lock().clone();
+        if let Some(rate_limit_tracker) = rate_limit_tracker {
+            rate_limit_tracker.acquire(REQUEST_WEIGHT, is_order).await;
         }
     }
 
     pub async fn get(&self, url: Uri, api_key: &str) -> Result<RestRequestOutcome> {
+        let max_retries = self.retry_settings.max_retries;
+        send_with_retry(
+            &self.retry_settings,
+            max_retries,
+            "GET",
+            || self.acquire_rate_limit(false),
+            || self.get_once(url.clone(), api_key),
+        )
+        .await
+    }
+
+    pub async fn post(
+        &self,
+        url: Uri,
+        api_key: &str,
+        http_params: &HttpParams,
+    ) -> Result<RestRequestOutcome> {
+        let max_retries = self.retry_settings.max_post_retries;
+        // POST requests of exchanges are order placements
+        send_with_retry(
+            &self.retry_settings,
+            max_retries,
+            "POST",
+            || self.acquire_rate_limit(true),
+            || self.post_once(url.clone(), api_key, http_params),
+        )
+        .await
+    }
+
+    pub async fn delete(&self, url: Uri, api_key: &str) -> Result<RestRequestOutcome> {
+        let max_retries = self.retry_settings.max_retries;
+        send_with_retry(
+            &self.retry_settings,
+            max_retries,
+            "DELETE",
+            || self.acquire_rate_limit(false),
+            || self.delete_once(url.clone(), api_key),
+        )
+        .await
+    }
+
+    async fn get_once(&self, url: Uri, api_key: &str) -> Result<RestRequestOutcome> {
         let req = Request::get(url)
             .header(hyper::header::CONNECTION, KEEP_ALIVE)
             .header("X-MBX-APIKEY", api_key)
@@ -32,7 +94,7 @@ impl RestClient {
         handle_response(response, "GET").await
     }
 
-    pub async fn post(
+    async fn post_once(
         &self,
         url: Uri,
         api_key: &str,
@@ -53,7 +115,7 @@ impl RestClient {
         handle_response(response, "POST").await
     }
 
-    pub async fn delete(&self, url: Uri, api_key: &str) -> Result<RestRequestOutcome> {
+    async fn delete_once(&self, url: Uri, api_key: &str) -> Result<RestRequestOutcome> {
         let req = Request::delete(url)
             .header(hyper::header::CONNECTION, KEEP_ALIVE)
             .header("X-MBX-APIKEY", api_key)
@@ -0,0 +1,194 @@
+use std::future::Future;
+
+use anyhow::Result;
+use hyper::StatusCode;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::common::RestRequestOutcome;
+use crate::core::connectivity::reconnect_backoff::{ReconnectBackoff, ReconnectBackoffSettings};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RestRetrySettings {
+    // retries of idempotent GET and DELETE requests after first attempt,
+    // so request is sent at most `max_retries + 1` times
+    pub max_retries: u32,
+    // POST requests aren't idempotent (e.g. order creation accepted by exchange before timeout
+    // would be duplicated), so they are retried only if explicitly configured
+    #[serde(default)]
+    pub max_post_retries: u32,
+    #[serde(default)]
+    pub backoff: ReconnectBackoffSettings,
+}
+
+impl Default for RestRetrySettings {
+    fn default() -> Self {
+        RestRetrySettings {
+            max_retries: 2,
+            max_post_retries: 0,
+            backoff: ReconnectBackoffSettings::default(),
+        }
+    }
+}
+
+/// Server errors are transient while client errors will be repeated on retry
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error()
+}
+
+/// Request errors without response (network ones) are considered transient
+pub fn is_retryable(request_outcome: &Result<RestRequestOutcome>) -> bool {
+    match request_outcome {
+        Ok(outcome) => is_retryable_status(outcome.status),
+        Err(_) => true,
+    }
+}
+
+/// Send request until it gets non-retryable outcome or `max_retries` are exhausted.
+/// Each retry waits for `acquire_rate_limit` because first attempt is already accounted
+/// in rate limits by caller. Outcome of the last attempt is returned as is
+pub async fn send_with_retry<A, AFut, F, Fut>(
+    settings: &RestRetrySettings,
+    max_retries: u32,
+    rest_action: &str,
+    mut acquire_rate_limit: A,
+    mut send_request: F,
+) -> Result<RestRequestOutcome>
+where
+    A: FnMut() -> AFut,
+    AFut: Future<Output = ()>,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<RestRequestOutcome>>,
+{
+    let mut backoff = ReconnectBackoff::new(settings.backoff.clone());
+    let mut attempt = 0;
+    loop {
+        let request_outcome = send_request().await;
+        if attempt >= max_retries || !is_retryable(&request_outcome) {
+            return request_outcome;
+        }
+
+        attempt += 1;
+        let delay = backoff.next_delay();
+        match &request_outcome {
+            Ok(outcome) => warn!(
+                "{} request failed with status {}, retry {} of {} in {}ms",
+                rest_action,
+                outcome.status,
+                attempt,
+                max_retries,
+                delay.as_millis()
+            ),
+            Err(error) => warn!(
+                "{} request failed: {:?}, retry {} of {} in {}ms",
+                rest_action,
+                error,
+                attempt,
+                max_retries,
+                delay.as_millis()
+            ),
+        }
+
+        tokio::time::sleep(delay).await;
+        acquire_rate_limit().await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parking_lot::Mutex;
+
+    fn test_settings() -> RestRetrySettings {
+        RestRetrySettings {
+            max_retries: 3,
+            max_post_retries: 0,
+            backoff: ReconnectBackoffSettings {
+                base_delay_ms: 1,
+                multiplier: 2.0,
+                max_delay_ms: 10,
+                stable_period_ms: 0,
+            },
+        }
+    }
+
+    async fn send_scripted(
+        max_retries: u32,
+        statuses: Vec<StatusCode>,
+    ) -> (RestRequestOutcome, usize) {
+        let (outcome, attempts, _) = send_scripted_with_rate_limit(max_retries, statuses).await;
+        (outcome, attempts)
+    }
+
+    /// Returns outcome, count of attempts and count of rate limit acquisitions
+    async fn send_scripted_with_rate_limit(
+        max_retries: u32,
+        statuses: Vec<StatusCode>,
+    ) -> (RestRequestOutcome, usize, usize) {
+        let statuses = Mutex::new(statuses.into_iter());
+        let attempts = Mutex::new(0);
+        let acquisitions = Mutex::new(0);
+
+        let outcome = send_with_retry(
+            &test_settings(),
+            max_retries,
+            "GET",
+            || {
+                *acquisitions.lock() += 1;
+                async {}
+            },
+            || {
+                *attempts.lock() += 1;
+                let status = statuses.lock().next().expect("in test");
+                async move { Ok(RestRequestOutcome::new(String::new(), status)) }
+            },
+        )
+        .await
+        .expect("in test");
+
+        let attempts = *attempts.lock();
+        let acquisitions = *acquisitions.lock();
+        (outcome, attempts, acquisitions)
+    }
+
+    #[actix_rt::test]
+    async fn acquire_rate_limit_before_each_retry() {
+        let statuses = vec![
+            StatusCode::SERVICE_UNAVAILABLE,
+            StatusCode::BAD_GATEWAY,
+            StatusCode::OK,
+        ];
+        let (outcome, attempts, acquisitions) = send_scripted_with_rate_limit(3, statuses).await;
+
+        assert_eq!(outcome.status, StatusCode::OK);
+        assert_eq!(attempts, 3);
+        assert_eq!(acquisitions, 2);
+    }
+
+    #[actix_rt::test]
+    async fn retry_service_unavailable_to_success() {
+        let (outcome, attempts) =
+            send_scripted(3, vec![StatusCode::SERVICE_UNAVAILABLE, StatusCode::OK]).await;
+
+        assert_eq!(outcome.status, StatusCode::OK);
+        assert_eq!(attempts, 2);
+    }
+
+    #[actix_rt::test]
+    async fn do_not_retry_bad_request() {
+        let (outcome, attempts) = send_scripted(3, vec![StatusCode::BAD_REQUEST]).await;
+
+        assert_eq!(outcome.status, StatusCode::BAD_REQUEST);
+        assert_eq!(attempts, 1);
+    }
+
+    #[actix_rt::test]
+    async fn do_not_retry_post_by_default() {
+        let max_post_retries = RestRetrySettings::default().max_post_retries;
+        let (outcome, attempts) =
+            send_scripted(max_post_retries, vec![StatusCode::SERVICE_UNAVAILABLE]).await;
+
+        assert_eq!(outcome.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(attempts, 1);
+    }
+}
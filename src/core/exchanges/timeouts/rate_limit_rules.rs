@@ -0,0 +1,204 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+// weight of each REST request in `RateLimitBucketType::RequestWeight` bucket
+pub(crate) const REQUEST_WEIGHT: u32 = 1;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum RateLimitBucketType {
+    // consumes weight of each request
+    RequestWeight,
+    // consumes 1 for each order placement request
+    Orders,
+}
+
+/// Rate limit of exchange which refills uniformly up to `capacity` during `refill_period_ms`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RateLimitBucket {
+    pub bucket_type: RateLimitBucketType,
+    pub capacity: u32,
+    pub refill_period_ms: u64,
+    // part of capacity which is never used to stay away from exhaustion
+    #[serde(default)]
+    pub reserve: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RateLimitRules {
+    pub buckets: Vec<RateLimitBucket>,
+}
+
+impl RateLimitRules {
+    pub fn validate(&self) -> Result<()> {
+        for bucket in &self.buckets {
+            if bucket.reserve >= bucket.capacity || bucket.refill_period_ms == 0 {
+                bail!(
+                    "Invalid {:?} rate limit bucket: reserve should be less than capacity and refill period should be positive",
+                    bucket.bucket_type
+                )
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct BucketState {
+    bucket: RateLimitBucket,
+    available: f64,
+    updated_at: Instant,
+}
+
+impl BucketState {
+    fn usable_capacity(&self) -> f64 {
+        (self.bucket.capacity - self.bucket.reserve) as f64
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at);
+        let refilled = self.bucket.capacity as f64 * elapsed.as_secs_f64()
+            / Duration::from_millis(self.bucket.refill_period_ms).as_secs_f64();
+        self.available = (self.available + refilled).min(self.usable_capacity());
+        self.updated_at = self.updated_at.max(now);
+    }
+
+    fn cost(&self, weight: u32, is_order: bool) -> f64 {
+        match self.bucket.bucket_type {
+            RateLimitBucketType::RequestWeight => weight as f64,
+            RateLimitBucketType::Orders if is_order => 1.0,
+            RateLimitBucketType::Orders => 0.0,
+        }
+    }
+
+    fn delay_for(&self, cost: f64) -> Duration {
+        let shortage = cost.min(self.usable_capacity()) - self.available;
+        if shortage <= 0.0 {
+            return Duration::ZERO;
+        }
+
+        Duration::from_millis(self.bucket.refill_period_ms).mul_f64(shortage) / self.bucket.capacity
+    }
+}
+
+/// Accounts weights of requests in rate limit buckets
+pub struct RateLimitTracker {
+    buckets: Mutex<Vec<BucketState>>,
+}
+
+impl RateLimitTracker {
+    pub fn new(rules: RateLimitRules, now: Instant) -> Self {
+        RateLimitTracker {
+            buckets: Mutex::new(Self::create_buckets(rules, now)),
+        }
+    }
+
+    /// Replace rules keeping tracker shared with its users, buckets are started full
+    pub fn set_rules(&self, rules: RateLimitRules, now: Instant) {
+        *self.buckets.lock() = Self::create_buckets(rules, now);
+    }
+
+    fn create_buckets(rules: RateLimitRules, now: Instant) -> Vec<BucketState> {
+        rules
+            .buckets
+            .into_iter()
+            .map(|bucket| BucketState {
+                available: (bucket.capacity - bucket.reserve.min(bucket.capacity)) as f64,
+                bucket,
+                updated_at: now,
+            })
+            .collect()
+    }
+
+    /// Account request in all buckets if each of them has enough weight,
+    /// otherwise nothing is accounted and delay until refill is returned
+    pub fn try_acquire(&self, weight: u32, is_order: bool, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock();
+        for bucket in buckets.iter_mut() {
+            bucket.refill(now);
+        }
+
+        let delay = buckets
+            .iter()
+            .map(|x| x.delay_for(x.cost(weight, is_order)))
+            .max()
+            .unwrap_or(Duration::ZERO);
+        if delay > Duration::ZERO {
+            return Err(delay);
+        }
+
+        for bucket in buckets.iter_mut() {
+            bucket.available -= bucket.cost(weight, is_order);
+        }
+
+        Ok(())
+    }
+
+    /// Wait until request can be sent without exhausting any bucket and account it
+    pub async fn acquire(&self, weight: u32, is_order: bool) {
+        while let Err(delay) = self.try_acquire(weight, is_order, Instant::now()) {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rules() -> RateLimitRules {
+        RateLimitRules {
+            buckets: vec![
+                RateLimitBucket {
+                    bucket_type: RateLimitBucketType::RequestWeight,
+                    capacity: 12,
+                    refill_period_ms: 120,
+                    reserve: 2,
+                },
+                RateLimitBucket {
+                    bucket_type: RateLimitBucketType::Orders,
+                    capacity: 100,
+                    refill_period_ms: 1000,
+                    reserve: 0,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn delay_requests_exceeding_bucket_until_refill() {
+        let now = Instant::now();
+        let tracker = RateLimitTracker::new(rules(), now);
+
+        assert_eq!(tracker.try_acquire(6, true, now), Ok(()));
+        // 4 of usable 10 are left, so 2 more should be refilled with 10ms per unit
+        let delay = tracker.try_acquire(6, false, now).expect_err("in test");
+        assert_eq!(delay, Duration::from_millis(20));
+
+        assert!(tracker
+            .try_acquire(6, false, now + Duration::from_millis(10))
+            .is_err());
+        assert_eq!(tracker.try_acquire(6, false, now + delay), Ok(()));
+    }
+
+    #[actix_rt::test]
+    async fn acquire_waits_for_refill() {
+        let tracker = RateLimitTracker::new(rules(), Instant::now());
+        tracker.acquire(10, false).await;
+
+        let started = Instant::now();
+        tracker.acquire(3, false).await;
+
+        assert!(started.elapsed() >= Duration::from_millis(25));
+    }
+
+    #[test]
+    fn reject_reserve_not_less_than_capacity() {
+        let mut rules = rules();
+        rules.buckets[0].reserve = 12;
+
+        assert!(rules.validate().is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use dashmap::DashMap;
 use log::info;
@@ -14,6 +14,7 @@ use super::{
     },
     general::currency_pair_metadata::CurrencyPairMetadata,
     general::handlers::handle_order_filled::FillEventData,
+    timeouts::rate_limit_rules::RateLimitTracker,
     timeouts::requests_timeout_manager_factory::RequestTimeoutArguments,
 };
 use crate::core::connectivity::connectivity_manager::WebSocketRole;
@@ -22,9 +23,10 @@ use crate::core::exchanges::general::features::ExchangeFeatures;
 use crate::core::lifecycle::application_manager::ApplicationManager;
 use crate::core::orders::fill::EventSourceType;
 use crate::core::orders::order::{
-    ClientOrderId, ExchangeOrderId, OrderCancelling, OrderCreating, OrderInfo,
+    ClientOrderId, ExchangeOrderId, OrderAmending, OrderCancelling, OrderCreating, OrderInfo,
 };
 use crate::core::settings::ExchangeSettings;
+use crate::core::DateTime;
 use crate::core::{exchanges::general::exchange::BoxExchangeClient, orders::pool::OrderRef};
 use awc::http::Uri;
 
@@ -37,6 +39,14 @@ pub trait ExchangeClient: Support {
 
     async fn request_cancel_order(&self, _order: &OrderCancelling) -> Result<RestRequestOutcome>;
 
+    // Should be implemented only by exchanges with ExchangeCapabilities::amend_order
+    async fn request_amend_order(&self, order: &OrderAmending) -> Result<RestRequestOutcome> {
+        Err(anyhow!(
+            "Amending is not supported for order {}",
+            order.header.client_order_id
+        ))
+    }
+
     async fn cancel_all_orders(&self, _currency_pair: CurrencyPair) -> Result<()>;
 
     async fn request_open_orders(&self) -> Result<RestRequestOutcome>;
@@ -48,6 +58,11 @@ pub trait ExchangeClient: Support {
 pub trait Support: Send + Sync {
     fn is_rest_error_code(&self, response: &RestRequestOutcome) -> Result<(), ExchangeError>;
     fn get_order_id(&self, response: &RestRequestOutcome) -> Result<ExchangeOrderId>;
+
+    /// Server time of exchange if it is specified in REST response
+    fn get_server_time(&self, _response: &RestRequestOutcome) -> Option<DateTime> {
+        None
+    }
     fn clarify_error_type(&self, error: &mut ExchangeError);
 
     fn on_websocket_message(&self, msg: &str) -> Result<()>;
@@ -67,6 +82,9 @@ pub trait Support: Send + Sync {
         callback: Box<dyn FnMut(FillEventData) + Send + Sync>,
     );
 
+    /// Tracker of exchange rate limits to account REST requests retried by client itself
+    fn set_rate_limit_tracker(&self, _rate_limit_tracker: Arc<RateLimitTracker>) {}
+
     fn is_enabled_websocket(&self, role: WebSocketRole) -> bool;
 
     async fn create_ws_url(&self, role: WebSocketRole) -> Result<Uri>;
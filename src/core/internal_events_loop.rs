@@ -63,6 +63,7 @@ impl InternalEventsLoop {
                 }
                 ExchangeEvent::LiquidationPrice(_) => {}
                 ExchangeEvent::Trades(_) => {}
+                ExchangeEvent::OrderSlaAlert(_) => {}
             }
         }
     }
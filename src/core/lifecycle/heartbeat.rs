@@ -0,0 +1,165 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use itertools::Itertools;
+use log::{info, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::lifecycle::trading_engine::Service;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum HeartbeatMetric {
+    OpenOrders,
+    NetPosition,
+    Uptime,
+}
+
+fn default_heartbeat_metrics() -> Vec<HeartbeatMetric> {
+    vec![
+        HeartbeatMetric::OpenOrders,
+        HeartbeatMetric::NetPosition,
+        HeartbeatMetric::Uptime,
+    ]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HeartbeatSettings {
+    pub interval_ms: u64,
+    // metrics included in summary in specified order, all of them if not specified
+    #[serde(default = "default_heartbeat_metrics")]
+    pub metrics: Vec<HeartbeatMetric>,
+}
+
+/// Periodically logs one line summary of engine state
+pub struct HeartbeatService {
+    settings: HeartbeatSettings,
+    started_at: Instant,
+    work_finished_receiver: Mutex<Option<oneshot::Receiver<Result<()>>>>,
+}
+
+impl HeartbeatService {
+    pub fn new(settings: HeartbeatSettings) -> Arc<Self> {
+        Arc::new(HeartbeatService {
+            settings,
+            started_at: Instant::now(),
+            work_finished_receiver: Default::default(),
+        })
+    }
+
+    pub fn summary(&self, exchanges: &[Arc<Exchange>]) -> String {
+        let parts = self
+            .settings
+            .metrics
+            .iter()
+            .map(|metric| match metric {
+                HeartbeatMetric::OpenOrders => {
+                    let open_orders: usize =
+                        exchanges.iter().map(|x| x.orders.not_finished.len()).sum();
+                    format!("open orders {}", open_orders)
+                }
+                HeartbeatMetric::NetPosition => {
+                    let positions = exchanges
+                        .iter()
+                        .flat_map(|exchange| {
+                            exchange
+                                .net_positions()
+                                .into_iter()
+                                .sorted_by(|a, b| a.0.as_str().cmp(b.0.as_str()))
+                                .map(|(currency_pair, position)| {
+                                    format!(
+                                        "{} {} {}",
+                                        exchange.exchange_account_id, currency_pair, position
+                                    )
+                                })
+                                .collect_vec()
+                        })
+                        .join(", ");
+                    format!("net position [{}]", positions)
+                }
+                HeartbeatMetric::Uptime => {
+                    format!("uptime {}s", self.started_at.elapsed().as_secs())
+                }
+            })
+            .join(", ");
+
+        format!("Heartbeat: {}", parts)
+    }
+
+    pub async fn start(
+        self: Arc<Self>,
+        exchanges: Vec<Arc<Exchange>>,
+        cancellation_token: CancellationToken,
+    ) -> Result<()> {
+        let (work_finished_sender, receiver) = oneshot::channel();
+        *self.work_finished_receiver.lock() = Some(receiver);
+
+        let interval = Duration::from_millis(self.settings.interval_ms);
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = cancellation_token.when_cancelled() => {
+                    let _ = work_finished_sender.send(Ok(()));
+                    return Ok(());
+                }
+            }
+
+            info!("{}", self.summary(&exchanges));
+        }
+    }
+}
+
+impl Service for HeartbeatService {
+    fn name(&self) -> &str {
+        "HeartbeatService"
+    }
+
+    fn graceful_shutdown(self: Arc<Self>) -> Option<oneshot::Receiver<Result<()>>> {
+        let work_finished_receiver = self.work_finished_receiver.lock().take();
+        if work_finished_receiver.is_none() {
+            warn!("'work_finished_receiver' wasn't created when started graceful shutdown in HeartbeatService");
+        }
+
+        work_finished_receiver
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::general::test_helper::get_test_exchange;
+    use crate::core::logger::{init_logger, recent_logs};
+    use crate::core::orders::order::OrderSnapshot;
+    use parking_lot::RwLock;
+
+    #[actix_rt::test]
+    async fn log_heartbeat_with_open_orders_count() {
+        init_logger();
+        let (exchange, _rx) = get_test_exchange(false);
+        let _ = exchange
+            .orders
+            .add_snapshot_initial(Arc::new(RwLock::new(OrderSnapshot::test_default())));
+
+        let service = HeartbeatService::new(HeartbeatSettings {
+            interval_ms: 10,
+            metrics: vec![HeartbeatMetric::OpenOrders, HeartbeatMetric::Uptime],
+        });
+        let cancellation_token = CancellationToken::default();
+        let action = service
+            .clone()
+            .start(vec![exchange.clone()], cancellation_token.clone());
+        let handle = tokio::spawn(action);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancellation_token.cancel();
+        handle.await.expect("in test").expect("in test");
+
+        assert!(recent_logs().iter().any(|x| x.level == log::Level::Info
+            && x.message.starts_with("Heartbeat: open orders 1, uptime ")));
+        assert!(service.graceful_shutdown().is_some());
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::core::lifecycle::application_manager::ApplicationManager;
+
+fn default_poll_interval_ms() -> u64 {
+    1000
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct KillSwitchSettings {
+    // appearance of file with this path triggers graceful shutdown
+    pub path: String,
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+/// Poll kill switch file until it appears and start graceful shutdown then.
+/// File existing already on startup triggers shutdown on the first check
+pub async fn watch_kill_switch(
+    settings: KillSwitchSettings,
+    application_manager: Arc<ApplicationManager>,
+) -> Result<()> {
+    let stop_token = application_manager.stop_token();
+    let poll_interval = Duration::from_millis(settings.poll_interval_ms);
+    loop {
+        if Path::new(&settings.path).exists() {
+            warn!("Kill switch file {} is found", settings.path);
+            let _ = application_manager
+                .spawn_graceful_shutdown(format!("Kill switch file {} appeared", settings.path));
+            return Ok(());
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(poll_interval) => {}
+            _ = stop_token.when_cancelled() => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::events::ExchangeEvents;
+    use crate::core::exchanges::timeouts::timeout_manager::TimeoutManager;
+    use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use crate::core::lifecycle::trading_engine::EngineContext;
+    use crate::core::orders::store::InMemoryOrderStore;
+    use crate::core::settings::CoreSettings;
+    use dashmap::DashMap;
+    use std::collections::HashMap;
+    use tokio::sync::{broadcast, oneshot};
+
+    #[actix_rt::test]
+    async fn start_graceful_shutdown_when_file_appears() {
+        let path = std::env::temp_dir().join(format!("mmb_kill_switch_{}", uuid::Uuid::new_v4()));
+        let application_manager = ApplicationManager::new(CancellationToken::new());
+        let (events_sender, _) = broadcast::channel(10);
+        let (finish_graceful_shutdown_sender, finish_graceful_shutdown_receiver) =
+            oneshot::channel();
+        let _context = EngineContext::new(
+            CoreSettings::default(),
+            DashMap::new(),
+            ExchangeEvents::new(events_sender),
+            finish_graceful_shutdown_sender,
+            TimeoutManager::new(HashMap::new()),
+            application_manager.clone(),
+            Arc::new(InMemoryOrderStore::default()),
+        );
+
+        let settings = KillSwitchSettings {
+            path: path.to_string_lossy().to_string(),
+            poll_interval_ms: 10,
+        };
+        let handle = tokio::spawn(watch_kill_switch(settings, application_manager.clone()));
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(!application_manager.stop_token().is_cancellation_requested());
+
+        std::fs::write(&path, "").expect("in test");
+        handle.await.expect("in test").expect("in test");
+        let _ = std::fs::remove_file(&path);
+
+        assert!(application_manager.stop_token().is_cancellation_requested());
+        tokio::time::timeout(Duration::from_secs(5), finish_graceful_shutdown_receiver)
+            .await
+            .expect("in test")
+            .expect("in test");
+    }
+}
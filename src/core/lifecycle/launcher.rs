@@ -1,4 +1,5 @@
 use crate::core::config::load_settings;
+use crate::core::connectivity::subscription_limiter::subscribe_with_limit;
 use crate::core::exchanges::binance::binance::BinanceBuilder;
 use crate::core::exchanges::common::ExchangeId;
 use crate::core::exchanges::events::{ExchangeEvent, ExchangeEvents, CHANNEL_MAX_EVENTS_COUNT};
@@ -10,9 +11,19 @@ use crate::core::exchanges::traits::ExchangeClientBuilder;
 use crate::core::internal_events_loop::InternalEventsLoop;
 use crate::core::lifecycle::application_manager::ApplicationManager;
 use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::lifecycle::heartbeat::HeartbeatService;
+use crate::core::lifecycle::kill_switch::watch_kill_switch;
+use crate::core::lifecycle::shutdown_policy::ShutdownPolicy;
 use crate::core::lifecycle::trading_engine::{EngineContext, TradingEngine};
 use crate::core::logger::init_logger;
 use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
+use crate::core::orders::metrics::{
+    LogMetricsSink, MetricsFlushService, MetricsSink, OrderRejectionMetrics,
+};
+use crate::core::orders::order::{ClientOrderId, ReservationId};
+use crate::core::orders::pruning::prune_finished_orders_periodically;
+use crate::core::orders::sla_monitor::OrderSlaMonitor;
+use crate::core::orders::store::{InMemoryOrderStore, OrderStore};
 use crate::core::settings::{AppSettings, BaseStrategySettings, CoreSettings};
 use crate::core::{
     disposition_execution::executor::DispositionExecutorService,
@@ -21,11 +32,12 @@ use crate::core::{
 use crate::hashmap;
 use crate::rest_api::control_panel::ControlPanel;
 use crate::strategies::disposition_strategy::DispositionStrategy;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use core::fmt::Debug;
 use dashmap::DashMap;
-use futures::{future::join_all, FutureExt};
-use log::{error, info};
+use futures::FutureExt;
+use itertools::Itertools;
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::identity;
@@ -34,6 +46,10 @@ use tokio::sync::{broadcast, oneshot};
 
 pub struct EngineBuildConfig {
     pub supported_exchange_clients: HashMap<ExchangeId, Box<dyn ExchangeClientBuilder + 'static>>,
+    pub order_store: Arc<dyn OrderStore>,
+    pub metrics_sink: Arc<dyn MetricsSink>,
+    // orders are simulated by `PaperExchangeClient` instead of sending them to exchanges
+    pub dry_run: bool,
 }
 
 impl EngineBuildConfig {
@@ -44,8 +60,29 @@ impl EngineBuildConfig {
 
         EngineBuildConfig {
             supported_exchange_clients,
+            order_store: Arc::new(InMemoryOrderStore::default()),
+            metrics_sink: Arc::new(LogMetricsSink),
+            dry_run: false,
+        }
+    }
+
+    /// Standard config in dry-run mode: real market data with simulated orders and fills
+    pub fn paper() -> Self {
+        EngineBuildConfig {
+            dry_run: true,
+            ..Self::standard()
         }
     }
+
+    pub fn with_order_store(mut self, order_store: Arc<dyn OrderStore>) -> Self {
+        self.order_store = order_store;
+        self
+    }
+
+    pub fn with_metrics_sink(mut self, metrics_sink: Arc<dyn MetricsSink>) -> Self {
+        self.metrics_sink = metrics_sink;
+        self
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -57,11 +94,114 @@ where
     Load(String, String),
 }
 
+fn load_and_validate_settings<'a, TStrategySettings>(
+    init_user_settings: InitSettings<TStrategySettings>,
+) -> Result<AppSettings<TStrategySettings>>
+where
+    TStrategySettings: BaseStrategySettings + Clone + Debug + Deserialize<'a>,
+{
+    let settings = match init_user_settings {
+        InitSettings::Directly(v) => v,
+        InitSettings::Load(config_path, credentials_path) => {
+            load_settings::<TStrategySettings>(&config_path, &credentials_path)?
+        }
+    };
+
+    settings.validate()?;
+
+    Ok(settings)
+}
+
+/// Load settings and validate them without building of trading engine and connecting to exchanges
+pub fn validate_settings<'a, TStrategySettings>(
+    init_user_settings: InitSettings<TStrategySettings>,
+) -> Result<()>
+where
+    TStrategySettings: BaseStrategySettings + Clone + Debug + Deserialize<'a>,
+{
+    load_and_validate_settings(init_user_settings).map(|_| ())
+}
+
+/// Strategy launched in engine with its own settings, exchange clients and services of engine
+/// are shared between all strategies.
+/// Strategy is keyed by exchange account id and currency pair of its settings: own disposition
+/// executor is started for this trade place and only orders created for price slots of the executor
+/// are attributed to the strategy, so trade places of strategies in one engine should be unique
+pub struct StrategyRegistration {
+    pub settings: Box<dyn BaseStrategySettings + Send + Sync>,
+    pub strategy: Box<dyn DispositionStrategy>,
+}
+
+impl StrategyRegistration {
+    pub fn new(
+        settings: impl BaseStrategySettings + Send + Sync + 'static,
+        strategy: Box<dyn DispositionStrategy>,
+    ) -> Self {
+        StrategyRegistration {
+            settings: Box::new(settings),
+            strategy,
+        }
+    }
+}
+
+fn validate_strategies(
+    core_settings: &CoreSettings,
+    strategies: &[StrategyRegistration],
+) -> Result<()> {
+    if strategies.is_empty() {
+        bail!("At least one strategy should be launched in trading engine")
+    }
+
+    for registration in strategies {
+        core_settings.validate_strategy(registration.settings.as_ref())?;
+    }
+
+    let duplicates = strategies
+        .iter()
+        .map(|x| {
+            format!(
+                "{} {}",
+                x.settings.exchange_account_id(),
+                x.settings.currency_pair()
+            )
+        })
+        .duplicates()
+        .collect_vec();
+    if !duplicates.is_empty() {
+        bail!(
+            "Trade places used by more than one strategy: {}",
+            duplicates.iter().join(", ")
+        )
+    }
+
+    Ok(())
+}
+
 pub async fn launch_trading_engine<'a, TStrategySettings>(
     build_settings: &EngineBuildConfig,
     init_user_settings: InitSettings<TStrategySettings>,
     build_strategy: impl Fn(&AppSettings<TStrategySettings>) -> Box<dyn DispositionStrategy + 'static>,
 ) -> Result<TradingEngine>
+where
+    TStrategySettings:
+        BaseStrategySettings + Clone + Debug + Deserialize<'a> + Serialize + Send + Sync + 'static,
+{
+    launch_trading_engine_with_strategies(build_settings, init_user_settings, |settings| {
+        vec![StrategyRegistration::new(
+            settings.strategy.clone(),
+            build_strategy(settings),
+        )]
+    })
+    .await
+}
+
+/// Launch several strategies in one engine. Section `strategy` of settings is only passed
+/// to `build_strategies`, launched strategies are specified by returned registrations
+pub async fn launch_trading_engine_with_strategies<'a, TStrategySettings>(
+    build_settings: &EngineBuildConfig,
+    init_user_settings: InitSettings<TStrategySettings>,
+    build_strategies: impl FnOnce(&AppSettings<TStrategySettings>) -> Vec<StrategyRegistration>,
+) -> Result<TradingEngine>
 where
     TStrategySettings: BaseStrategySettings + Clone + Debug + Deserialize<'a> + Serialize,
 {
@@ -70,12 +210,28 @@ where
     info!("*****************************");
     info!("TradingEngine starting");
 
-    let settings = match init_user_settings {
-        InitSettings::Directly(v) => v,
-        InitSettings::Load(config_path, credentials_path) => {
-            load_settings::<TStrategySettings>(&config_path, &credentials_path)?
-        }
-    };
+    let settings = load_and_validate_settings(init_user_settings)?;
+    ClientOrderId::set_env_prefix(
+        settings
+            .core
+            .client_order_id_env_prefix
+            .as_deref()
+            .unwrap_or_default(),
+    )?;
+
+    let strategies = build_strategies(&settings);
+    validate_strategies(&settings.core, &strategies)?;
+
+    // reservation ids of persisted orders shouldn't be generated again after restart
+    let max_reservation_id = build_settings
+        .order_store
+        .load_all()?
+        .iter()
+        .filter_map(|x| x.header.reservation_id.as_ref().map(|id| id.value()))
+        .max();
+    if let Some(max_reservation_id) = max_reservation_id {
+        ReservationId::set_floor(max_reservation_id);
+    }
 
     let application_manager = ApplicationManager::new(CancellationToken::new());
     keep_application_manager(application_manager.clone());
@@ -91,6 +247,14 @@ where
     )
     .await;
 
+    let rejection_metrics = Arc::new(OrderRejectionMetrics::default());
+    for exchange in &exchanges {
+        exchange.set_order_metrics_hook(rejection_metrics.clone());
+        exchange.set_order_store(build_settings.order_store.clone());
+    }
+    let metrics_flush_service =
+        MetricsFlushService::new(rejection_metrics, build_settings.metrics_sink.clone());
+
     let exchanges_map: DashMap<_, _> = exchanges
         .into_iter()
         .map(|exchange| (exchange.exchange_account_id.clone(), exchange))
@@ -106,12 +270,14 @@ where
         finish_graceful_shutdown_tx,
         timeout_manager,
         application_manager.clone(),
+        build_settings.order_store.clone(),
     );
 
     let internal_events_loop = InternalEventsLoop::new();
+    let settings_value = toml::Value::try_from(settings.clone())?;
     let control_panel = ControlPanel::new(
         "127.0.0.1:8080",
-        toml::Value::try_from(settings.clone())?.to_string(),
+        settings_value.to_string(),
         application_manager,
     );
 
@@ -125,27 +291,100 @@ where
         let _ = spawn_future("internal_events_loop start", true, action.boxed());
     }
 
+    let order_sla_monitor = settings.core.order_sla.as_ref().map(|order_sla| {
+        let order_sla_monitor =
+            OrderSlaMonitor::new(chrono::Duration::milliseconds(order_sla.max_lifetime_ms));
+        let action = order_sla_monitor.clone().start(
+            engine_context
+                .exchanges
+                .iter()
+                .map(|x| x.value().clone())
+                .collect(),
+            std::time::Duration::from_millis(order_sla.check_period_ms),
+            engine_context.application_manager.stop_token(),
+        );
+        spawn_future("order_sla_monitor start", true, action.boxed());
+        order_sla_monitor
+    });
+
+    let heartbeat_service = settings.core.heartbeat.as_ref().map(|heartbeat| {
+        let heartbeat_service = HeartbeatService::new(heartbeat.clone());
+        let action = heartbeat_service.clone().start(
+            engine_context
+                .exchanges
+                .iter()
+                .map(|x| x.value().clone())
+                .collect(),
+            engine_context.application_manager.stop_token(),
+        );
+        spawn_future("heartbeat_service start", true, action.boxed());
+        heartbeat_service
+    });
+
+    if let Some(orders_pruning) = &settings.core.orders_pruning {
+        let action = prune_finished_orders_periodically(
+            engine_context
+                .exchanges
+                .iter()
+                .map(|x| x.value().clone())
+                .collect(),
+            orders_pruning.clone(),
+            engine_context.application_manager.stop_token(),
+        );
+        let _handle = spawn_future("prune_finished_orders", true, action.boxed());
+    }
+
+    if let Some(kill_switch) = &settings.core.kill_switch {
+        let action = watch_kill_switch(
+            kill_switch.clone(),
+            engine_context.application_manager.clone(),
+        );
+        let _handle = spawn_future("watch_kill_switch", true, action.boxed());
+    }
+
     if let Err(error) = control_panel.clone().start() {
         error!("Unable to start rest api: {}", error);
     }
 
-    let disposition_strategy = build_strategy(&settings);
-    let disposition_executor_service = create_disposition_executor_service(
-        &settings.strategy,
-        &engine_context,
-        disposition_strategy,
-    );
+    let shutdown_policy = strategies
+        .iter()
+        .map(|x| x.strategy.shutdown_policy())
+        .reduce(ShutdownPolicy::combine)
+        .unwrap_or_default();
+    engine_context.set_shutdown_policy(shutdown_policy);
+    for registration in strategies {
+        engine_context.set_strategy_settings(registration.settings.as_ref());
+        let disposition_executor_service = create_disposition_executor_service(
+            registration.settings.as_ref(),
+            &engine_context,
+            registration.strategy,
+        );
+        engine_context
+            .shutdown_service
+            .register_service(disposition_executor_service);
+    }
 
     engine_context.shutdown_service.register_services(&[
         control_panel,
         internal_events_loop,
-        disposition_executor_service,
+        metrics_flush_service,
     ]);
+    if let Some(order_sla_monitor) = order_sla_monitor {
+        engine_context
+            .shutdown_service
+            .register_service(order_sla_monitor);
+    }
+    if let Some(heartbeat_service) = heartbeat_service {
+        engine_context
+            .shutdown_service
+            .register_service(heartbeat_service);
+    }
 
     info!("TradingEngine started");
     Ok(TradingEngine::new(
         engine_context,
         finish_graceful_shutdown_rx,
+        settings_value,
     ))
 }
 
@@ -154,10 +393,28 @@ fn create_disposition_executor_service(
     engine_context: &Arc<EngineContext>,
     disposition_strategy: Box<dyn DispositionStrategy>,
 ) -> Arc<DispositionExecutorService> {
+    let mut local_snapshots_service = LocalSnapshotsService::default();
+    let engine_context_weak = Arc::downgrade(engine_context);
+    local_snapshots_service.set_resubscribe_callback(Box::new(move |trade_place_account| {
+        let exchange = engine_context_weak.upgrade().and_then(|engine_context| {
+            engine_context
+                .exchanges
+                .get(&trade_place_account.exchange_account_id)
+                .map(|x| x.clone())
+        });
+        match exchange {
+            Some(exchange) => exchange.resubscribe_market_data(trade_place_account.currency_pair),
+            None => warn!(
+                "Unable to resubscribe to market data of {:?} because exchange isn't found",
+                trade_place_account
+            ),
+        }
+    }));
+
     DispositionExecutorService::new(
         engine_context.clone(),
         engine_context.get_events_channel(),
-        LocalSnapshotsService::default(),
+        local_snapshots_service,
         base_settings.exchange_account_id(),
         base_settings.currency_pair(),
         base_settings.max_amount(),
@@ -173,14 +430,174 @@ pub async fn create_exchanges(
     application_manager: Arc<ApplicationManager>,
     timeout_manager: &Arc<TimeoutManager>,
 ) -> Vec<Arc<Exchange>> {
-    join_all(core_settings.exchanges.iter().map(|x| {
-        create_exchange(
-            x,
-            build_settings,
-            events_channel.clone(),
-            application_manager.clone(),
-            timeout_manager.clone(),
-        )
-    }))
+    subscribe_with_limit(
+        core_settings.exchanges.iter(),
+        core_settings.max_concurrent_subscriptions,
+        |x| {
+            create_exchange(
+                x,
+                build_settings,
+                events_channel.clone(),
+                application_manager.clone(),
+                timeout_manager.clone(),
+            )
+        },
+    )
     .await
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{Amount, CurrencyPair, ExchangeAccountId};
+    use crate::core::settings::ExchangeSettings;
+    use crate::strategies::disposition_strategy::ExampleStrategy;
+    use rust_decimal_macros::dec;
+    use std::io::Write;
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    struct TestStrategySettings {}
+
+    impl BaseStrategySettings for TestStrategySettings {
+        fn exchange_account_id(&self) -> ExchangeAccountId {
+            "Binance0".parse().expect("in test")
+        }
+
+        fn currency_pair(&self) -> CurrencyPair {
+            CurrencyPair::from_codes("eth".into(), "btc".into())
+        }
+
+        fn max_amount(&self) -> Amount {
+            dec!(1)
+        }
+    }
+
+    #[test]
+    fn validate_correct_settings() {
+        let init_settings = InitSettings::<TestStrategySettings>::Load(
+            "tests/lifecycle.toml".to_owned(),
+            "tests/lifecycle.cred.toml".to_owned(),
+        );
+
+        validate_settings(init_settings).expect("in test");
+    }
+
+    #[test]
+    fn validate_malformed_settings() {
+        let config_path = std::env::temp_dir().join(format!(
+            "mmb_malformed_config_{}.toml",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::File::create(&config_path)
+            .expect("in test")
+            .write_all(b"[strategy]\n[[core.exchanges]\nexchange_account_id = \"Binance0\"")
+            .expect("in test");
+
+        let init_settings = InitSettings::<TestStrategySettings>::Load(
+            config_path.to_string_lossy().to_string(),
+            "tests/lifecycle.cred.toml".to_owned(),
+        );
+
+        let error = validate_settings(init_settings).expect_err("in test");
+        let _ = std::fs::remove_file(config_path);
+
+        assert!(
+            format!("{:#}", error).contains("line 2"),
+            "Unexpected error: {:#}",
+            error
+        );
+    }
+
+    #[actix_rt::test]
+    async fn launch_with_empty_credentials() {
+        let mut settings = AppSettings::<TestStrategySettings> {
+            strategy: TestStrategySettings {},
+            core: CoreSettings::default(),
+        };
+        settings.core.exchanges.push(ExchangeSettings::new_short(
+            "Binance0".parse().expect("in test"),
+            "api_key".into(),
+            "".into(),
+            false,
+        ));
+
+        let launch_result = launch_trading_engine(
+            &EngineBuildConfig::standard(),
+            InitSettings::Directly(settings),
+            |_| panic!("Strategy should not be built with invalid settings"),
+        )
+        .await;
+
+        let error = match launch_result {
+            Ok(_) => panic!("Engine should not be launched with empty credentials"),
+            Err(error) => error,
+        };
+        assert_eq!(
+            format!("{:#}", error),
+            "Invalid credentials for Binance0: secret_key should not be empty"
+        );
+    }
+
+    #[test]
+    fn validate_settings_with_unknown_strategy_exchange() {
+        let mut settings = AppSettings::<TestStrategySettings> {
+            strategy: TestStrategySettings {},
+            core: CoreSettings::default(),
+        };
+        settings.core.exchanges.push(ExchangeSettings::new_short(
+            "Binance1".parse().expect("in test"),
+            "api_key".into(),
+            "secret_key".into(),
+            false,
+        ));
+
+        let error = validate_settings(InitSettings::Directly(settings)).expect_err("in test");
+
+        assert!(error.to_string().contains("Binance0"));
+    }
+
+    #[test]
+    fn reject_too_long_client_order_id_env_prefix() {
+        let mut settings = AppSettings::<TestStrategySettings> {
+            strategy: TestStrategySettings {},
+            core: CoreSettings::default(),
+        };
+        settings.core.client_order_id_env_prefix = Some("production".to_owned());
+
+        let error = validate_settings(InitSettings::Directly(settings)).expect_err("in test");
+
+        assert_eq!(
+            format!("{:#}", error),
+            "Invalid core.client_order_id_env_prefix: Client order id environment prefix 'production' is longer than 7 symbols"
+        );
+    }
+
+    #[test]
+    fn reject_strategies_with_same_trade_place() {
+        let mut core_settings = CoreSettings::default();
+        core_settings.exchanges.push(ExchangeSettings::new_short(
+            "Binance0".parse().expect("in test"),
+            "api_key".into(),
+            "secret_key".into(),
+            false,
+        ));
+        let registration = || {
+            StrategyRegistration::new(
+                TestStrategySettings {},
+                Box::new(ExampleStrategy::new(
+                    "Binance0".parse().expect("in test"),
+                    CurrencyPair::from_codes("eth".into(), "btc".into()),
+                )),
+            )
+        };
+
+        validate_strategies(&core_settings, &[registration()]).expect("in test");
+        let error = validate_strategies(&core_settings, &[registration(), registration()])
+            .expect_err("in test");
+        assert_eq!(
+            error.to_string(),
+            "Trade places used by more than one strategy: Binance0 eth/btc"
+        );
+        assert!(validate_strategies(&core_settings, &[]).is_err());
+    }
+}
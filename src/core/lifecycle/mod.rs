@@ -1,5 +1,8 @@
 pub mod application_manager;
 pub mod cancellation_token;
+pub mod heartbeat;
+pub mod kill_switch;
 pub mod launcher;
 pub mod shutdown;
+pub mod shutdown_policy;
 pub mod trading_engine;
@@ -6,12 +6,14 @@ use anyhow::Result;
 use futures::future::join_all;
 use futures::FutureExt;
 use itertools::Itertools;
-use log::{error, info, trace};
+use log::{error, log, trace, warn, Level};
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::oneshot;
 use tokio::sync::oneshot::Sender;
-use tokio::time::{sleep, Duration};
+use tokio::sync::{oneshot, Notify};
+use tokio::time::{sleep_until, Duration, Instant};
 
 #[derive(Message)]
 #[rtype(result = "()")]
@@ -19,26 +21,132 @@ pub struct GracefulShutdownMsg {
     pub service_finished: Sender<Result<()>>,
 }
 
+/// Receives actor name and shutdown message which couldn't be delivered to actor mailbox
+pub type DeadLetterCallback = Box<dyn Fn(&str, GracefulShutdownMsg) + Send + Sync>;
+
+struct ServiceInfo {
+    service: Arc<dyn Service>,
+    // services with higher priority are shut down first
+    priority: i32,
+}
+
 struct ActorInfo {
     name: String,
     actor: Recipient<GracefulShutdownMsg>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ShutdownSettings {
+    // how many times graceful shutdown is repeated for services that wasn't dropped
+    // before actor system is stopped forcibly
+    pub max_graceful_attempts: u32,
+    // level of messages about major shutdown steps, details are always logged with trace level
+    #[serde(default = "default_shutdown_log_level")]
+    pub shutdown_log_level: Level,
+    // how long to wait finishing of services during whole graceful shutdown
+    #[serde(default = "default_graceful_shutdown_timeout_ms")]
+    pub graceful_shutdown_timeout_ms: u64,
+}
+
+fn default_shutdown_log_level() -> Level {
+    Level::Info
+}
+
+fn default_graceful_shutdown_timeout_ms() -> u64 {
+    3_000
+}
+
+impl Default for ShutdownSettings {
+    fn default() -> Self {
+        ShutdownSettings {
+            max_graceful_attempts: 1,
+            shutdown_log_level: default_shutdown_log_level(),
+            graceful_shutdown_timeout_ms: default_graceful_shutdown_timeout_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownReport {
+    pub not_dropped_services: Vec<String>,
+    pub attempts: u32,
+    // actor system was stopped while some services still wasn't dropped
+    pub is_forced: bool,
+}
+
 #[derive(Default)]
 struct State {
-    services: Vec<Arc<dyn Service>>,
+    services: Vec<ServiceInfo>,
     actors: Vec<ActorInfo>,
+    completion_waiters: Vec<Sender<ShutdownReport>>,
+    report: Option<ShutdownReport>,
 }
 
-#[derive(Default)]
 pub struct ShutdownService {
     state: Mutex<State>,
+    max_graceful_attempts: u32,
+    log_level: Level,
+    // how long to wait finishing of services during whole graceful shutdown
+    timeout: Duration,
+    dead_letter_callback: Mutex<Option<DeadLetterCallback>>,
+    is_in_progress: AtomicBool,
+    is_force_requested: AtomicBool,
+    force_notify: Notify,
+}
+
+impl Default for ShutdownService {
+    fn default() -> Self {
+        let settings = ShutdownSettings::default();
+        let timeout = Duration::from_millis(settings.graceful_shutdown_timeout_ms);
+        Self::from_settings(&settings, timeout)
+    }
 }
 
 impl ShutdownService {
+    pub fn new(settings: &ShutdownSettings) -> Arc<Self> {
+        Self::with_timeout(
+            settings,
+            Duration::from_millis(settings.graceful_shutdown_timeout_ms),
+        )
+    }
+
+    pub fn with_timeout(settings: &ShutdownSettings, timeout: Duration) -> Arc<Self> {
+        Arc::new(Self::from_settings(settings, timeout))
+    }
+
+    fn from_settings(settings: &ShutdownSettings, timeout: Duration) -> Self {
+        Self {
+            state: Default::default(),
+            max_graceful_attempts: settings.max_graceful_attempts.max(1),
+            log_level: settings.shutdown_log_level,
+            timeout,
+            dead_letter_callback: Default::default(),
+            is_in_progress: AtomicBool::new(false),
+            is_force_requested: AtomicBool::new(false),
+            force_notify: Notify::new(),
+        }
+    }
+
+    pub fn set_dead_letter_callback(&self, callback: DeadLetterCallback) {
+        *self.dead_letter_callback.lock() = Some(callback);
+    }
+
     pub fn register_service(self: &Arc<Self>, service: Arc<dyn Service>) {
-        trace!("Registered in ShutdownService service '{}'", service.name());
-        self.state.lock().services.push(service);
+        self.register_service_with_priority(service, 0);
+    }
+
+    /// Services are shut down by tiers in descending order of priority,
+    /// next tier is started after previous one finished. Actors are shut down with priority 0
+    pub fn register_service_with_priority(&self, service: Arc<dyn Service>, priority: i32) {
+        trace!(
+            "Registered in ShutdownService service '{}' with priority {}",
+            service.name(),
+            priority
+        );
+        self.state
+            .lock()
+            .services
+            .push(ServiceInfo { service, priority });
     }
 
     pub fn register_services(self: &Arc<Self>, services: &[Arc<dyn Service>]) {
@@ -52,31 +160,176 @@ impl ShutdownService {
         self.state.lock().actors.push(ActorInfo { name, actor });
     }
 
+    /// Returns receiver that resolves with report when graceful shutdown finished.
+    /// If shutdown is already finished receiver resolves immediately
+    pub fn completion(&self) -> oneshot::Receiver<ShutdownReport> {
+        let (sender, receiver) = oneshot::channel();
+
+        let mut state_guard = self.state.lock();
+        match &state_guard.report {
+            Some(report) => {
+                let _ = sender.send(report.clone());
+            }
+            None => state_guard.completion_waiters.push(sender),
+        }
+
+        receiver
+    }
+
+    /// Stop waiting services in graceful shutdown which is in progress and stop actor system immediately.
+    /// Does nothing if graceful shutdown isn't started
+    pub fn force_shutdown(&self) {
+        if !self.is_in_progress.load(Ordering::SeqCst) {
+            warn!("Force shutdown is requested while graceful shutdown isn't in progress");
+            return;
+        }
+
+        warn!("Force shutdown is requested");
+        self.is_force_requested.store(true, Ordering::SeqCst);
+        self.force_notify.notify_one();
+    }
+
+    fn is_force_requested(&self) -> bool {
+        self.is_force_requested.load(Ordering::SeqCst)
+    }
+
     pub(crate) async fn graceful_shutdown(&self) -> Vec<String> {
-        let mut finish_receivers = Vec::new();
+        self.is_in_progress.store(true, Ordering::SeqCst);
+        let deadline = Instant::now() + self.timeout;
 
-        trace!("Prepare to drop services in ShutdownService started");
+        let mut attempts = 1;
+        let mut not_dropped_services = self.graceful_shutdown_attempt(true, deadline).await;
 
+        while !not_dropped_services.is_empty()
+            && attempts < self.max_graceful_attempts
+            && !self.is_force_requested()
         {
-            trace!("Running graceful shutdown for actors started");
+            attempts += 1;
+            warn!(
+                "Repeat graceful shutdown (attempt {} of {}) for services that wasn't dropped: {}",
+                attempts,
+                self.max_graceful_attempts,
+                not_dropped_services
+                    .iter()
+                    .map(|x| x.service.name())
+                    .join(", ")
+            );
+
+            self.state.lock().services = not_dropped_services;
+            not_dropped_services = self.graceful_shutdown_attempt(false, deadline).await;
+        }
 
-            let state_guard = self.state.lock();
-            for actor_info in &state_guard.actors {
-                let (service_finished, receiver) = oneshot::channel::<Result<()>>();
-                let _ = actor_info
-                    .actor
-                    .try_send(GracefulShutdownMsg { service_finished });
+        let not_dropped_services = not_dropped_services
+            .into_iter()
+            .map(|x| x.service.name().to_string())
+            .collect_vec();
 
-                let actor_name = format!("actor {}", actor_info.name);
+        let is_forced = !not_dropped_services.is_empty();
+        if is_forced {
+            error!(
+                "After {} graceful shutdown attempts follow services wasn't dropped, actor system will be stopped forcibly:{}{}",
+                attempts,
+                text::LINE_ENDING,
+                not_dropped_services.join(text::LINE_ENDING)
+            )
+        } else {
+            log!(
+                self.log_level,
+                "After graceful shutdown all services dropped completely"
+            )
+        }
 
-                trace!("Waiting graceful shutdown finishing for {}", actor_name);
-                finish_receivers.push((actor_name, receiver));
+        log!(self.log_level, "Stopping actor system");
+        System::current().stop();
+
+        self.is_in_progress.store(false, Ordering::SeqCst);
+        self.notify_completion(ShutdownReport {
+            not_dropped_services: not_dropped_services.clone(),
+            attempts,
+            is_forced,
+        });
+
+        not_dropped_services
+    }
+
+    /// Returns services that wasn't dropped after graceful shutdown
+    async fn graceful_shutdown_attempt(
+        &self,
+        with_actors: bool,
+        deadline: Instant,
+    ) -> Vec<ServiceInfo> {
+        log!(
+            self.log_level,
+            "Prepare to drop services in ShutdownService started"
+        );
+
+        let mut actors_finish_receivers = Vec::new();
+        let services;
+        {
+            let state_guard = self.state.lock();
+            if with_actors {
+                log!(
+                    self.log_level,
+                    "Running graceful shutdown for actors started"
+                );
+
+                for actor_info in &state_guard.actors {
+                    let (service_finished, receiver) = oneshot::channel::<Result<()>>();
+                    if let Err(error) = actor_info
+                        .actor
+                        .try_send(GracefulShutdownMsg { service_finished })
+                    {
+                        warn!(
+                            "Unable to send graceful shutdown message to actor {}: {}",
+                            actor_info.name, error
+                        );
+                        if let Some(callback) = &*self.dead_letter_callback.lock() {
+                            callback(&actor_info.name, error.into_inner());
+                        }
+                    }
+
+                    let actor_name = format!("actor {}", actor_info.name);
+
+                    trace!("Waiting graceful shutdown finishing for {}", actor_name);
+                    actors_finish_receivers.push((actor_name, receiver));
+                }
+
+                log!(
+                    self.log_level,
+                    "Running graceful shutdown for actors finished"
+                );
             }
 
-            trace!("Running graceful shutdown for actors finished");
+            services = state_guard
+                .services
+                .iter()
+                .map(|x| (x.priority, x.service.clone()))
+                .collect_vec();
+        }
+
+        let mut priorities = services.iter().map(|(priority, _)| *priority).collect_vec();
+        if !actors_finish_receivers.is_empty() {
+            priorities.push(0);
+        }
+        priorities.sort_unstable_by(|a, b| b.cmp(a));
+        priorities.dedup();
 
-            trace!("Running graceful shutdown for services started");
-            for service in &state_guard.services {
+        for priority in priorities {
+            if self.is_force_requested() {
+                break;
+            }
+
+            let mut finish_receivers = match priority {
+                0 => std::mem::take(&mut actors_finish_receivers),
+                _ => Vec::new(),
+            };
+
+            log!(
+                self.log_level,
+                "Running graceful shutdown for services with priority {} started",
+                priority
+            );
+            for (_, service) in services.iter().filter(|(x, _)| *x == priority) {
                 let receiver = service.clone().graceful_shutdown();
 
                 if let Some(receiver) = receiver {
@@ -91,9 +344,51 @@ impl ShutdownService {
                     )
                 }
             }
-            trace!("Running graceful shutdown for services finished");
+            log!(
+                self.log_level,
+                "Running graceful shutdown for services with priority {} finished",
+                priority
+            );
+
+            self.wait_finishing(finish_receivers, deadline).await;
+        }
+        drop(services);
+
+        log!(
+            self.log_level,
+            "Prepare to drop services in ShutdownService finished"
+        );
+
+        log!(self.log_level, "Drop services in ShutdownService started");
+
+        let weak_services;
+        {
+            let mut state_guard = self.state.lock();
+            weak_services = state_guard
+                .services
+                .drain(..)
+                .map(|x| (Arc::downgrade(&x.service), x.priority))
+                .collect_vec();
         }
 
+        log!(self.log_level, "Drop services in ShutdownService finished");
+
+        weak_services
+            .iter()
+            .filter_map(|(weak_service, priority)| {
+                weak_service.upgrade().map(|service| ServiceInfo {
+                    service,
+                    priority: *priority,
+                })
+            })
+            .collect_vec()
+    }
+
+    async fn wait_finishing(
+        &self,
+        finish_receivers: Vec<(String, oneshot::Receiver<Result<()>>)>,
+        deadline: Instant,
+    ) {
         // log errors when its came
         let finishing_services_futures = finish_receivers
             .into_iter()
@@ -127,63 +422,34 @@ impl ShutdownService {
             })
             .collect_vec();
 
-        const TIMEOUT: Duration = Duration::from_secs(3);
         tokio::select! {
-            _ = join_all(finishing_services_futures) => trace!("All services sent finished marker at given time"),
-            _ = sleep(TIMEOUT) => error!("Not all services finished after timeout ({} sec)", TIMEOUT.as_secs()),
+            _ = join_all(finishing_services_futures) => log!(self.log_level, "All services sent finished marker at given time"),
+            _ = sleep_until(deadline) => error!("Not all services finished after timeout ({} ms)", self.timeout.as_millis()),
+            _ = self.force_notify.notified() => warn!("Waiting services finishing is interrupted by force shutdown"),
         }
+    }
 
-        trace!("Prepare to drop services in ShutdownService finished");
-
-        trace!("Stopping actor system");
-        System::current().stop();
-
-        trace!("Drop services in ShutdownService started");
-
-        let weak_services;
+    fn notify_completion(&self, report: ShutdownReport) {
+        let waiters;
         {
             let mut state_guard = self.state.lock();
-            weak_services = state_guard
-                .services
-                .drain(..)
-                .map(|x| Arc::downgrade(&x))
-                .collect_vec();
+            state_guard.report = Some(report.clone());
+            waiters = state_guard.completion_waiters.drain(..).collect_vec();
         }
 
-        trace!("Drop services in ShutdownService finished");
-
-        let not_dropped_services = weak_services
-            .iter()
-            .filter_map(|weak_service| {
-                if weak_service.strong_count() > 0 {
-                    weak_service
-                        .upgrade()
-                        .map(|service| service.name().to_string())
-                } else {
-                    None
-                }
-            })
-            .collect_vec();
-
-        if not_dropped_services.is_empty() {
-            info!("After graceful shutdown all services dropped completely")
-        } else {
-            error!(
-                "After graceful shutdown follow services wasn't dropped:{}{}",
-                text::LINE_ENDING,
-                not_dropped_services.join(text::LINE_ENDING)
-            )
+        for waiter in waiters {
+            let _ = waiter.send(report.clone());
         }
-
-        not_dropped_services
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::logger::init_logger;
+    use crate::core::logger::{init_logger, recent_logs};
+    use actix::{Actor, ActorContext, Context, Handler};
     use tokio::sync::oneshot::Receiver;
+    use tokio::time::sleep;
 
     #[actix_rt::test]
     pub async fn success() {
@@ -253,4 +519,306 @@ mod tests {
         let not_dropped_services = shutdown_service.graceful_shutdown().await;
         assert_eq!(not_dropped_services, vec![REF_TEST_SERVICE.to_string()]);
     }
+
+    #[actix_rt::test]
+    pub async fn completion_resolves_with_report() {
+        init_logger();
+
+        const REF_TEST_SERVICE: &str = "RefTestService";
+        pub struct RefTestService(Mutex<Option<Arc<RefTestService>>>);
+
+        impl Service for RefTestService {
+            fn name(&self) -> &str {
+                REF_TEST_SERVICE
+            }
+
+            fn graceful_shutdown(self: Arc<Self>) -> Option<Receiver<Result<()>>> {
+                None
+            }
+        }
+
+        let shutdown_service = Arc::new(ShutdownService::default());
+
+        let test = Arc::new(RefTestService(Mutex::new(None)));
+        *test.0.lock() = Some(test.clone());
+        shutdown_service.clone().register_service(test);
+
+        let completion = shutdown_service.completion();
+        let supervisor = tokio::spawn(async move { completion.await.expect("in test") });
+
+        let not_dropped_services = shutdown_service.graceful_shutdown().await;
+
+        let report = supervisor.await.expect("in test");
+        assert_eq!(report.not_dropped_services, not_dropped_services);
+        assert_eq!(
+            report.not_dropped_services,
+            vec![REF_TEST_SERVICE.to_string()]
+        );
+
+        assert!(report.is_forced);
+
+        let late_report = shutdown_service.completion().await.expect("in test");
+        assert_eq!(late_report, report);
+    }
+
+    #[actix_rt::test]
+    pub async fn repeat_attempts_before_forced_stop() {
+        init_logger();
+
+        const MAX_GRACEFUL_ATTEMPTS: u32 = 3;
+
+        pub struct NeverDroppedService {
+            shutdown_calls: Mutex<u32>,
+        }
+
+        impl Service for NeverDroppedService {
+            fn name(&self) -> &str {
+                "NeverDroppedService"
+            }
+
+            fn graceful_shutdown(self: Arc<Self>) -> Option<Receiver<Result<()>>> {
+                *self.shutdown_calls.lock() += 1;
+                None
+            }
+        }
+
+        let shutdown_service = ShutdownService::new(&ShutdownSettings {
+            max_graceful_attempts: MAX_GRACEFUL_ATTEMPTS,
+            ..ShutdownSettings::default()
+        });
+
+        // test holds reference to service, so it is never dropped by ShutdownService
+        let test = Arc::new(NeverDroppedService {
+            shutdown_calls: Mutex::new(0),
+        });
+        shutdown_service.clone().register_service(test.clone());
+
+        let not_dropped_services = shutdown_service.graceful_shutdown().await;
+        assert_eq!(
+            not_dropped_services,
+            vec!["NeverDroppedService".to_string()]
+        );
+        assert_eq!(*test.shutdown_calls.lock(), MAX_GRACEFUL_ATTEMPTS);
+
+        let report = shutdown_service.completion().await.expect("in test");
+        assert_eq!(report.attempts, MAX_GRACEFUL_ATTEMPTS);
+        assert!(report.is_forced);
+    }
+
+    #[actix_rt::test]
+    pub async fn log_milestones_with_configured_level() {
+        init_logger();
+
+        let shutdown_service = ShutdownService::new(&ShutdownSettings {
+            shutdown_log_level: Level::Warn,
+            ..ShutdownSettings::default()
+        });
+
+        let _ = shutdown_service.graceful_shutdown().await;
+
+        let milestones = recent_logs()
+            .into_iter()
+            .filter(|x| x.level == Level::Warn && x.message.contains("ShutdownService"))
+            .map(|x| x.message)
+            .collect_vec();
+        assert!(
+            milestones.contains(&"Prepare to drop services in ShutdownService started".to_owned())
+        );
+        assert!(milestones.contains(&"Drop services in ShutdownService finished".to_owned()));
+    }
+
+    #[actix_rt::test]
+    pub async fn dead_letter_callback_for_undelivered_message() {
+        init_logger();
+
+        struct StoppedActor;
+
+        impl Actor for StoppedActor {
+            type Context = Context<Self>;
+
+            fn started(&mut self, ctx: &mut Self::Context) {
+                ctx.stop();
+            }
+        }
+
+        impl Handler<GracefulShutdownMsg> for StoppedActor {
+            type Result = ();
+
+            fn handle(&mut self, msg: GracefulShutdownMsg, _: &mut Self::Context) {
+                let _ = msg.service_finished.send(Ok(()));
+            }
+        }
+
+        let shutdown_service = Arc::new(ShutdownService::default());
+        let dead_letters = Arc::new(Mutex::new(Vec::new()));
+        let dead_letters_clone = dead_letters.clone();
+        shutdown_service.set_dead_letter_callback(Box::new(move |name, msg| {
+            dead_letters_clone.lock().push(name.to_owned());
+            // alternative teardown of actor
+            let _ = msg.service_finished.send(Ok(()));
+        }));
+
+        let actor = StoppedActor.start();
+        // let actor stop so its mailbox rejects messages
+        sleep(Duration::from_millis(10)).await;
+        shutdown_service.register_actor("StoppedActor".to_owned(), actor.recipient());
+
+        let _ = shutdown_service.graceful_shutdown().await;
+
+        assert_eq!(*dead_letters.lock(), vec!["StoppedActor".to_owned()]);
+    }
+
+    pub struct DelayedService(Duration);
+
+    impl Service for DelayedService {
+        fn name(&self) -> &str {
+            "DelayedService"
+        }
+
+        fn graceful_shutdown(self: Arc<Self>) -> Option<Receiver<Result<()>>> {
+            let (sender, receiver) = oneshot::channel();
+            let delay = self.0;
+            let _handle = tokio::spawn(async move {
+                sleep(delay).await;
+                let _ = sender.send(Ok(()));
+            });
+
+            Some(receiver)
+        }
+    }
+
+    #[actix_rt::test]
+    pub async fn wait_services_within_configured_timeout() {
+        init_logger();
+
+        let delay = Duration::from_millis(100);
+        let shutdown_service = ShutdownService::new(&ShutdownSettings {
+            graceful_shutdown_timeout_ms: 1_000,
+            ..ShutdownSettings::default()
+        });
+        shutdown_service.register_service(Arc::new(DelayedService(delay)));
+
+        let started = std::time::Instant::now();
+        let not_dropped_services = shutdown_service.graceful_shutdown().await;
+
+        assert!(not_dropped_services.is_empty());
+        assert!(started.elapsed() >= delay);
+    }
+
+    #[actix_rt::test]
+    pub async fn stop_waiting_services_after_configured_timeout() {
+        init_logger();
+
+        let delay = Duration::from_secs(2);
+        let shutdown_service = ShutdownService::new(&ShutdownSettings {
+            graceful_shutdown_timeout_ms: 20,
+            ..ShutdownSettings::default()
+        });
+        shutdown_service.register_service(Arc::new(DelayedService(delay)));
+
+        let started = std::time::Instant::now();
+        let _ = shutdown_service.graceful_shutdown().await;
+
+        assert!(started.elapsed() < delay);
+        assert!(recent_logs().iter().any(|x| x.level == Level::Error
+            && x.message == "Not all services finished after timeout (20 ms)"));
+    }
+
+    #[actix_rt::test]
+    pub async fn timeout_is_shared_by_all_priority_tiers() {
+        init_logger();
+
+        let timeout = Duration::from_millis(100);
+        let shutdown_service = ShutdownService::with_timeout(&ShutdownSettings::default(), timeout);
+        for priority in [10, 0, -1] {
+            shutdown_service.register_service_with_priority(
+                Arc::new(DelayedService(Duration::from_secs(2))),
+                priority,
+            );
+        }
+
+        let started = std::time::Instant::now();
+        let _ = shutdown_service.graceful_shutdown().await;
+
+        assert!(started.elapsed() < timeout * 2);
+    }
+
+    pub struct OrderedService {
+        name: String,
+        shutdown_order: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Service for OrderedService {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn graceful_shutdown(self: Arc<Self>) -> Option<Receiver<Result<()>>> {
+            self.shutdown_order.lock().push(self.name.clone());
+
+            let (sender, receiver) = oneshot::channel();
+            let _handle = tokio::spawn(async move {
+                sleep(Duration::from_millis(20)).await;
+                self.shutdown_order
+                    .lock()
+                    .push(format!("{} finished", self.name));
+                let _ = sender.send(Ok(()));
+            });
+
+            Some(receiver)
+        }
+    }
+
+    #[actix_rt::test]
+    pub async fn shutdown_services_by_priority_tiers() {
+        init_logger();
+
+        let shutdown_order = Arc::new(Mutex::new(Vec::new()));
+        let shutdown_service =
+            ShutdownService::with_timeout(&ShutdownSettings::default(), Duration::from_secs(1));
+        for (name, priority) in [("Low", -1), ("Default", 0), ("High", 10)] {
+            let service = Arc::new(OrderedService {
+                name: name.to_owned(),
+                shutdown_order: shutdown_order.clone(),
+            });
+            shutdown_service.register_service_with_priority(service, priority);
+        }
+
+        let not_dropped_services = shutdown_service.graceful_shutdown().await;
+
+        assert!(not_dropped_services.is_empty());
+        assert_eq!(
+            *shutdown_order.lock(),
+            vec![
+                "High",
+                "High finished",
+                "Default",
+                "Default finished",
+                "Low",
+                "Low finished"
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    pub async fn force_shutdown_interrupts_waiting() {
+        init_logger();
+
+        let timeout = Duration::from_secs(10);
+        let shutdown_service = ShutdownService::with_timeout(&ShutdownSettings::default(), timeout);
+        shutdown_service.register_service(Arc::new(DelayedService(timeout * 2)));
+
+        let started = std::time::Instant::now();
+        let shutdown_service_clone = shutdown_service.clone();
+        let shutdown =
+            tokio::spawn(async move { shutdown_service_clone.graceful_shutdown().await });
+
+        sleep(Duration::from_millis(50)).await;
+        shutdown_service.force_shutdown();
+        let _ = shutdown.await.expect("in test");
+
+        assert!(started.elapsed() < Duration::from_secs(1));
+        let report = shutdown_service.completion().await.expect("in test");
+        assert_eq!(report.attempts, 1);
+    }
 }
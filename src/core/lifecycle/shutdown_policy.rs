@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use futures::future::join_all;
+use log::{error, info, warn};
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use crate::core::disposition_execution::inventory::flattening_order;
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::orders::order::{
+    ClientOrderId, OrderCreating, OrderExecutionType, OrderHeader, OrderType,
+};
+
+pub(crate) const FLATTENING_STRATEGY_NAME: &str = "ShutdownFlattening";
+
+/// What engine should do with orders and positions of strategy on graceful shutdown
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ShutdownPolicy {
+    pub cancel_all_on_shutdown: bool,
+    // close net position by reduce-only market orders after cancellation of opened orders
+    pub flatten_on_shutdown: bool,
+}
+
+impl Default for ShutdownPolicy {
+    fn default() -> Self {
+        ShutdownPolicy {
+            cancel_all_on_shutdown: true,
+            flatten_on_shutdown: false,
+        }
+    }
+}
+
+impl ShutdownPolicy {
+    /// Policy of engine with several strategies: orders are canceled and positions are flattened
+    /// if any of strategies requires it, because cancellation and flattening are done for all exchanges
+    pub fn combine(self, other: ShutdownPolicy) -> ShutdownPolicy {
+        ShutdownPolicy {
+            cancel_all_on_shutdown: self.cancel_all_on_shutdown || other.cancel_all_on_shutdown,
+            flatten_on_shutdown: self.flatten_on_shutdown || other.flatten_on_shutdown,
+        }
+    }
+}
+
+/// Market orders which close all net positions on exchange.
+/// Orders are reduce-only if exchange supports it
+pub fn flattening_orders(exchange: &Exchange) -> Vec<OrderCreating> {
+    let reduce_only = exchange.capabilities().reduce_only;
+    exchange
+        .net_positions()
+        .into_iter()
+        .filter_map(|(currency_pair, position)| {
+            let (side, amount) = flattening_order(position)?;
+
+            let mut header = (*OrderHeader::new(
+                ClientOrderId::unique_id(),
+                exchange.now(),
+                exchange.exchange_account_id.clone(),
+                currency_pair,
+                OrderType::Market,
+                side,
+                amount,
+                OrderExecutionType::None,
+                None,
+                None,
+                FLATTENING_STRATEGY_NAME.to_owned(),
+            ))
+            .clone();
+            header.reduce_only = reduce_only;
+
+            Some(OrderCreating {
+                header: Arc::new(header),
+                price: dec!(0),
+            })
+        })
+        .collect()
+}
+
+pub(crate) async fn flatten_positions(exchange: Arc<Exchange>) {
+    if !exchange.capabilities().reduce_only {
+        warn!(
+            "Positions on {} are flattened by plain market orders because reduce-only orders are not supported",
+            exchange.exchange_account_id
+        );
+    }
+
+    let orders = flattening_orders(&exchange);
+    let results = join_all(
+        orders
+            .iter()
+            .map(|order| exchange.create_order(order, CancellationToken::default())),
+    )
+    .await;
+
+    for (order, result) in orders.iter().zip(results) {
+        match result {
+            Ok(_) => info!(
+                "Flattening order {} was created on {}",
+                order.header.client_order_id, exchange.exchange_account_id
+            ),
+            Err(error) => error!(
+                "Unable to create flattening order {} on {}: {:?}",
+                order.header.client_order_id, exchange.exchange_account_id, error
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::general::features::ExchangeCapabilities;
+    use crate::core::exchanges::general::test_helper::{
+        add_filled_order, get_test_exchange_with_capabilities, ScriptedExchangeBuilder,
+    };
+    use crate::core::orders::order::{OrderSide, OrderStatus};
+    use rstest::rstest;
+
+    fn reduce_only_capabilities() -> ExchangeCapabilities {
+        ExchangeCapabilities {
+            reduce_only: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flatten_net_long_position_by_reduce_only_sell() {
+        let (exchange, _rx) =
+            get_test_exchange_with_capabilities(false, reduce_only_capabilities());
+        let _ = add_filled_order(&exchange, OrderSide::Buy, dec!(5), dec!(5));
+        let _ = add_filled_order(&exchange, OrderSide::Sell, dec!(2), dec!(2));
+
+        let orders = flattening_orders(&exchange);
+
+        assert_eq!(orders.len(), 1);
+        let header = &orders[0].header;
+        assert_eq!(header.side, OrderSide::Sell);
+        assert_eq!(header.amount, dec!(3));
+        assert_eq!(header.order_type, OrderType::Market);
+        assert!(header.reduce_only);
+    }
+
+    #[test]
+    fn no_flattening_for_flat_position() {
+        let (exchange, _rx) =
+            get_test_exchange_with_capabilities(false, reduce_only_capabilities());
+        let _ = add_filled_order(&exchange, OrderSide::Buy, dec!(2), dec!(2));
+        let _ = add_filled_order(&exchange, OrderSide::Sell, dec!(2), dec!(2));
+
+        assert!(flattening_orders(&exchange).is_empty());
+    }
+
+    #[rstest]
+    #[case(true)]
+    #[case(false)]
+    #[actix_rt::test]
+    async fn flatten_positions_on_exchange(#[case] is_reduce_only_supported: bool) {
+        let capabilities = ExchangeCapabilities {
+            reduce_only: is_reduce_only_supported,
+            ..Default::default()
+        };
+        let (exchange, _rx) = ScriptedExchangeBuilder::new("Binance0".parse().expect("in test"))
+            .capabilities(capabilities)
+            .build();
+        let _ = add_filled_order(&exchange, OrderSide::Buy, dec!(5), dec!(5));
+        let _ = add_filled_order(&exchange, OrderSide::Sell, dec!(2), dec!(2));
+
+        flatten_positions(exchange.clone()).await;
+
+        let flattening_orders = exchange
+            .orders
+            .cache_by_client_id
+            .iter()
+            .filter(|x| x.fn_ref(|x| x.header.strategy_name == FLATTENING_STRATEGY_NAME))
+            .map(|x| x.value().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(flattening_orders.len(), 1);
+        let order = &flattening_orders[0];
+        assert_eq!(order.status(), OrderStatus::Created);
+        let header = order.fn_ref(|x| x.header.clone());
+        assert_eq!(header.side, OrderSide::Sell);
+        assert_eq!(header.amount, dec!(3));
+        assert_eq!(header.order_type, OrderType::Market);
+        assert_eq!(header.reduce_only, is_reduce_only_supported);
+    }
+}
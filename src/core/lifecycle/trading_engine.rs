@@ -1,23 +1,41 @@
+use std::collections::BTreeSet;
+use std::fmt::Debug;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use dashmap::DashMap;
 use futures::future::join_all;
 use itertools::Itertools;
 use log::info;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use tokio::sync::{broadcast, oneshot};
+use toml::Value;
 
+use crate::core::config::{load_settings, EXCHANGE_ACCOUNT_ID};
 use crate::core::exchanges::block_reasons;
-use crate::core::exchanges::common::ExchangeAccountId;
+use crate::core::exchanges::common::{Amount, CurrencyPair, ExchangeAccountId, TradePlaceAccount};
 use crate::core::exchanges::events::{ExchangeEvent, ExchangeEvents};
 use crate::core::exchanges::exchange_blocker::BlockType;
 use crate::core::exchanges::exchange_blocker::ExchangeBlocker;
 use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::exchanges::general::exchange_creation::{
+    apply_live_settings, LIVE_EXCHANGE_SETTINGS,
+};
+use crate::core::exchanges::general::order::failover::create_order_with_failover;
 use crate::core::exchanges::timeouts::timeout_manager::TimeoutManager;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
 use crate::core::lifecycle::shutdown::ShutdownService;
-use crate::core::settings::CoreSettings;
+use crate::core::lifecycle::shutdown_policy::{flatten_positions, ShutdownPolicy};
+use crate::core::orders::order::{ExchangeOrderId, OrderCreating, OrderSnapshot};
+use crate::core::orders::pool::OrderRef;
+use crate::core::orders::reservation::ReservationRegistry;
+use crate::core::orders::store::OrderStore;
+use crate::core::settings::{
+    AppSettings, BaseStrategySettings, CoreSettings, LiveStrategySettings,
+};
 use crate::core::{
     infrastructure::unset_application_manager, lifecycle::application_manager::ApplicationManager,
 };
@@ -36,7 +54,11 @@ pub struct EngineContext {
     pub exchange_blocker: Arc<ExchangeBlocker>,
     pub application_manager: Arc<ApplicationManager>,
     pub timeout_manager: Arc<TimeoutManager>,
+    pub reservation_registry: Arc<ReservationRegistry>,
+    pub order_store: Arc<dyn OrderStore>,
     is_graceful_shutdown_started: AtomicBool,
+    shutdown_policy: Mutex<ShutdownPolicy>,
+    strategy_settings: DashMap<TradePlaceAccount, LiveStrategySettings>,
     exchange_events: ExchangeEvents,
     finish_graceful_shutdown_sender: Mutex<Option<oneshot::Sender<()>>>,
 }
@@ -49,6 +71,7 @@ impl EngineContext {
         finish_graceful_shutdown_sender: oneshot::Sender<()>,
         timeout_manager: Arc<TimeoutManager>,
         application_manager: Arc<ApplicationManager>,
+        order_store: Arc<dyn OrderStore>,
     ) -> Arc<Self> {
         let exchange_account_ids = app_settings
             .exchanges
@@ -56,14 +79,21 @@ impl EngineContext {
             .map(|x| x.exchange_account_id.clone())
             .collect_vec();
 
+        let reservation_registry = ReservationRegistry::new(&app_settings.reservations);
+        let shutdown_service = ShutdownService::new(&app_settings.shutdown);
+
         let engine_context = Arc::new(EngineContext {
             app_settings,
             exchanges,
-            shutdown_service: Default::default(),
+            shutdown_service,
             exchange_blocker: ExchangeBlocker::new(exchange_account_ids),
             application_manager: application_manager.clone(),
             timeout_manager,
+            reservation_registry,
+            order_store,
             is_graceful_shutdown_started: Default::default(),
+            shutdown_policy: Mutex::new(ShutdownPolicy::default()),
+            strategy_settings: DashMap::new(),
             exchange_events,
             finish_graceful_shutdown_sender: Mutex::new(Some(finish_graceful_shutdown_sender)),
         });
@@ -94,11 +124,18 @@ impl EngineContext {
 
         self.application_manager.stop_token().cancel();
 
+        // orders are canceled and positions are flattened while exchange services are still running
+        let shutdown_policy = *self.shutdown_policy.lock();
+        if shutdown_policy.cancel_all_on_shutdown {
+            cancel_opened_orders(&self.exchanges).await;
+        }
+        if shutdown_policy.flatten_on_shutdown {
+            flatten_opened_positions(&self.exchanges).await;
+        }
+
         self.shutdown_service.graceful_shutdown().await;
         self.exchange_blocker.stop_blocker().await;
 
-        cancel_opened_orders(&self.exchanges).await;
-
         self.finish_graceful_shutdown_sender
             .lock()
             .take()
@@ -111,9 +148,178 @@ impl EngineContext {
         info!("Graceful shutdown finished");
     }
 
+    pub fn set_shutdown_policy(&self, shutdown_policy: ShutdownPolicy) {
+        *self.shutdown_policy.lock() = shutdown_policy;
+    }
+
+    /// Register or update live settings of strategy launched on its trade place
+    pub fn set_strategy_settings(&self, settings: &dyn BaseStrategySettings) {
+        let trade_place =
+            TradePlaceAccount::new(settings.exchange_account_id(), settings.currency_pair());
+        let _ = self
+            .strategy_settings
+            .insert(trade_place, LiveStrategySettings::new(settings));
+    }
+
+    pub fn strategy_settings(
+        &self,
+        trade_place: &TradePlaceAccount,
+    ) -> Option<LiveStrategySettings> {
+        self.strategy_settings.get(trade_place).map(|x| x.clone())
+    }
+
     pub fn get_events_channel(&self) -> broadcast::Receiver<ExchangeEvent> {
         self.exchange_events.get_events_channel()
     }
+
+    /// Net position on pair over all exchange accounts by filled amounts of orders:
+    /// buys are positive, sells are negative, funding fills don't change position
+    pub fn net_position(&self, currency_pair: &CurrencyPair) -> Amount {
+        self.exchanges
+            .iter()
+            .map(|x| x.net_position(currency_pair))
+            .sum()
+    }
+
+    /// Create order of strategy on its exchange account, if creation failed with retryable error
+    /// order is created on failover account from live settings of strategy.
+    /// Failover order has its own client order id, so caller should track the returned order
+    pub async fn create_strategy_order(
+        &self,
+        order_to_create: &OrderCreating,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        let header = &order_to_create.header;
+        let exchange = self
+            .exchanges
+            .get(&header.exchange_account_id)
+            .with_context(|| format!("Unknown exchange {}", header.exchange_account_id))?
+            .clone();
+        let strategy_settings = self.strategy_settings(&TradePlaceAccount::new(
+            header.exchange_account_id.clone(),
+            header.currency_pair.clone(),
+        ));
+        let failover_exchange = strategy_settings
+            .as_ref()
+            .and_then(|x| x.failover_account_id.as_ref())
+            .and_then(|x| self.exchanges.get(x).map(|x| x.clone()));
+        let policy = strategy_settings
+            .map(|x| x.order_placement_policy)
+            .unwrap_or_default();
+
+        create_order_with_failover(
+            &exchange,
+            failover_exchange.as_deref(),
+            order_to_create,
+            policy,
+            cancellation_token,
+        )
+        .await
+    }
+
+    /// Order of any exchange account with specified exchange order id.
+    /// Orders already pruned from orders pool aren't found
+    pub fn order_by_exchange_id(
+        &self,
+        exchange_order_id: &ExchangeOrderId,
+    ) -> Option<OrderSnapshot> {
+        self.exchanges.iter().find_map(|exchange| {
+            exchange
+                .orders
+                .cache_by_exchange_id
+                .get(exchange_order_id)
+                .map(|x| x.deep_clone())
+        })
+    }
+}
+
+fn exchanges_by_id(settings: &Value) -> Vec<(String, &Value)> {
+    settings
+        .get("core")
+        .and_then(|x| x.get("exchanges"))
+        .and_then(|x| x.as_array())
+        .map(|exchanges| {
+            exchanges
+                .iter()
+                .map(|x| {
+                    let exchange_account_id = x
+                        .get(EXCHANGE_ACCOUNT_ID)
+                        .and_then(|x| x.as_str())
+                        .unwrap_or_default()
+                        .to_owned();
+                    (exchange_account_id, x)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Keys of tables which values differ
+fn changed_keys(current: &Value, new: &Value) -> Vec<String> {
+    let empty = toml::value::Table::new();
+    let current = current.as_table().unwrap_or(&empty);
+    let new = new.as_table().unwrap_or(&empty);
+
+    let keys: BTreeSet<_> = current.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter(|key| current.get(*key) != new.get(*key))
+        .cloned()
+        .collect()
+}
+
+fn settings_requiring_restart(current: &Value, new: &Value) -> Vec<String> {
+    let mut changes = Vec::new();
+    for key in changed_keys(current, new) {
+        // strategy settings are applied live, its trade place is checked by typed settings
+        if key == "strategy" {
+            continue;
+        }
+
+        if key != "core" {
+            changes.push(key);
+            continue;
+        }
+
+        let empty = Value::Table(Default::default());
+        let current_core = current.get("core").unwrap_or(&empty);
+        let new_core = new.get("core").unwrap_or(&empty);
+        for core_key in changed_keys(current_core, new_core) {
+            if core_key != "exchanges" {
+                changes.push(format!("core.{}", core_key));
+            }
+        }
+
+        let current_exchanges = exchanges_by_id(current);
+        let new_exchanges = exchanges_by_id(new);
+        let exchange_account_ids: BTreeSet<_> = current_exchanges
+            .iter()
+            .chain(new_exchanges.iter())
+            .map(|(id, _)| id)
+            .collect();
+        for exchange_account_id in exchange_account_ids {
+            let find = |exchanges: &[(String, &'_ Value)]| {
+                exchanges
+                    .iter()
+                    .find(|(id, _)| id == exchange_account_id)
+                    .map(|(_, x)| (*x).clone())
+            };
+            match (find(&current_exchanges), find(&new_exchanges)) {
+                (Some(current_exchange), Some(new_exchange)) => {
+                    for exchange_key in changed_keys(&current_exchange, &new_exchange) {
+                        if !LIVE_EXCHANGE_SETTINGS.contains(&exchange_key.as_str()) {
+                            changes.push(format!(
+                                "core.exchanges.{}.{}",
+                                exchange_account_id, exchange_key
+                            ));
+                        }
+                    }
+                }
+                _ => changes.push(format!("core.exchanges.{}", exchange_account_id)),
+            }
+        }
+    }
+
+    changes
 }
 
 async fn cancel_opened_orders(exchanges: &DashMap<ExchangeAccountId, Arc<Exchange>>) {
@@ -124,22 +330,86 @@ async fn cancel_opened_orders(exchanges: &DashMap<ExchangeAccountId, Arc<Exchang
     info!("Canceling opened orders finished");
 }
 
+async fn flatten_opened_positions(exchanges: &DashMap<ExchangeAccountId, Arc<Exchange>>) {
+    info!("Flattening positions started");
+
+    join_all(exchanges.iter().map(|x| flatten_positions(x.clone()))).await;
+
+    info!("Flattening positions finished");
+}
+
 pub struct TradingEngine {
     context: Arc<EngineContext>,
     finished_graceful_shutdown: oneshot::Receiver<()>,
+    // settings engine is running with, including credentials
+    settings: Mutex<Value>,
 }
 
 impl TradingEngine {
     pub fn new(
         context: Arc<EngineContext>,
         finished_graceful_shutdown: oneshot::Receiver<()>,
+        settings: Value,
     ) -> Self {
         TradingEngine {
             context,
             finished_graceful_shutdown,
+            settings: Mutex::new(settings),
         }
     }
 
+    /// Re-read config and credentials and apply settings which can be changed live: exchange settings
+    /// from `LIVE_EXCHANGE_SETTINGS` and strategy settings (see `LiveStrategySettings`).
+    /// If any other setting is changed nothing is applied and changed settings requiring restart
+    /// of engine are listed in returned error
+    pub fn reload_settings<TSettings>(
+        &self,
+        config_path: &str,
+        credentials_path: &str,
+    ) -> Result<()>
+    where
+        TSettings: BaseStrategySettings + Clone + Debug + DeserializeOwned + Serialize,
+    {
+        let new_settings: AppSettings<TSettings> = load_settings(config_path, credentials_path)
+            .with_context(|| format!("Unable to read settings from {}", config_path))?;
+        new_settings.core.validate()?;
+        let new_settings_value = Value::try_from(&new_settings)?;
+
+        let current_settings_value = self.settings.lock().clone();
+        let current_settings: AppSettings<TSettings> = current_settings_value.clone().try_into()?;
+        let mut settings_requiring_restart =
+            settings_requiring_restart(&current_settings_value, &new_settings_value);
+        if current_settings.strategy.exchange_account_id()
+            != new_settings.strategy.exchange_account_id()
+        {
+            settings_requiring_restart.push("strategy.exchange_account_id".to_owned());
+        }
+        if current_settings.strategy.currency_pair() != new_settings.strategy.currency_pair() {
+            settings_requiring_restart.push("strategy.currency_pair".to_owned());
+        }
+        if !settings_requiring_restart.is_empty() {
+            bail!(
+                "Settings require restart of trading engine to be applied: {}",
+                settings_requiring_restart.join(", ")
+            )
+        }
+
+        for exchange_settings in &new_settings.core.exchanges {
+            if let Some(exchange) = self
+                .context
+                .exchanges
+                .get(&exchange_settings.exchange_account_id)
+            {
+                apply_live_settings(&exchange, exchange_settings);
+            }
+        }
+        self.context.set_strategy_settings(&new_settings.strategy);
+
+        *self.settings.lock() = new_settings_value;
+        info!("Settings reloaded from {}", config_path);
+        Ok(())
+    }
+
     pub fn context(&self) -> Arc<EngineContext> {
         self.context.clone()
     }
@@ -148,3 +418,341 @@ impl TradingEngine {
         let _ = self.finished_graceful_shutdown.await;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::general::features::ExchangeCapabilities;
+    use crate::core::exchanges::general::test_helper::{
+        add_filled_order, get_test_exchange, ScriptedExchangeBuilder,
+    };
+    use crate::core::lifecycle::application_manager::ApplicationManager;
+    use crate::core::lifecycle::shutdown_policy::FLATTENING_STRATEGY_NAME;
+    use crate::core::orders::fill::{EventSourceType, OrderFill, OrderFillType};
+    use crate::core::orders::order::{OrderHeader, OrderSide, OrderStatus};
+    use crate::core::orders::store::InMemoryOrderStore;
+    use crate::core::settings::ExchangeSettings;
+    use awc::http::StatusCode;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    struct TestStrategySettings {
+        spread: Decimal,
+        max_amount: Amount,
+        #[serde(default)]
+        failover_account_id: Option<ExchangeAccountId>,
+    }
+
+    impl BaseStrategySettings for TestStrategySettings {
+        fn exchange_account_id(&self) -> ExchangeAccountId {
+            "Binance0".parse().expect("in test")
+        }
+
+        fn currency_pair(&self) -> CurrencyPair {
+            CurrencyPair::from_codes("phb".into(), "btc".into())
+        }
+
+        fn max_amount(&self) -> Amount {
+            self.max_amount
+        }
+
+        fn failover_account_id(&self) -> Option<ExchangeAccountId> {
+            self.failover_account_id.clone()
+        }
+    }
+
+    fn config(max_amount: &str, rest_host: &str, max_position: &str) -> String {
+        format!(
+            r#"[strategy]
+spread = "0.1"
+max_amount = "{}"
+
+[[core.exchanges]]
+exchange_account_id = "Binance0"
+is_margin_trading = false
+web_socket_host = ""
+web_socket2_host = ""
+rest_host = "{}"
+websocket_channels = ["depth20"]
+subscribe_to_market_data = true
+currency_pairs = [{{ base = "phb", quote = "btc" }}]
+
+[core.exchanges.max_positions]
+"phb/btc" = "{}"
+"#,
+            max_amount, rest_host, max_position
+        )
+    }
+
+    #[actix_rt::test]
+    async fn reload_live_settings_and_report_requiring_restart() {
+        let temp_path = |name: &str| {
+            std::env::temp_dir()
+                .join(format!("mmb_reload_{}_{}.toml", name, uuid::Uuid::new_v4()))
+                .to_string_lossy()
+                .to_string()
+        };
+        let config_path = temp_path("config");
+        let credentials_path = temp_path("credentials");
+        std::fs::write(
+            &credentials_path,
+            r#"[Binance0]
+api_key = "test_api_key"
+secret_key = "test_secret_key""#,
+        )
+        .expect("in test");
+
+        std::fs::write(&config_path, config("1", "", "5")).expect("in test");
+        let settings: AppSettings<TestStrategySettings> =
+            load_settings(&config_path, &credentials_path).expect("in test");
+        let settings_value = Value::try_from(&settings).expect("in test");
+
+        let (exchange, _rx) = get_test_exchange(false);
+        let exchanges = DashMap::new();
+        let _ = exchanges.insert(settings.strategy.exchange_account_id(), exchange.clone());
+        let (events_sender, _) = broadcast::channel(10);
+        let (finish_graceful_shutdown_sender, finish_graceful_shutdown_receiver) =
+            oneshot::channel();
+        let context = EngineContext::new(
+            settings.core.clone(),
+            exchanges,
+            ExchangeEvents::new(events_sender),
+            finish_graceful_shutdown_sender,
+            TimeoutManager::new(HashMap::new()),
+            ApplicationManager::new(CancellationToken::new()),
+            Arc::new(InMemoryOrderStore::default()),
+        );
+        context.set_strategy_settings(&settings.strategy);
+        let engine = TradingEngine::new(
+            context.clone(),
+            finish_graceful_shutdown_receiver,
+            settings_value,
+        );
+        let currency_pair = CurrencyPair::from_codes("phb".into(), "btc".into());
+        let trade_place = TradePlaceAccount::new(
+            settings.strategy.exchange_account_id(),
+            currency_pair.clone(),
+        );
+        let max_amount = || {
+            context
+                .strategy_settings(&trade_place)
+                .expect("in test")
+                .max_amount
+        };
+
+        std::fs::write(&config_path, config("2", "", "7")).expect("in test");
+        engine
+            .reload_settings::<TestStrategySettings>(&config_path, &credentials_path)
+            .expect("in test");
+        assert_eq!(exchange.max_position(&currency_pair), Some(dec!(7)));
+        assert_eq!(max_amount(), dec!(2));
+
+        std::fs::write(&config_path, config("3", "https://api.new", "9")).expect("in test");
+        let error = engine
+            .reload_settings::<TestStrategySettings>(&config_path, &credentials_path)
+            .expect_err("in test");
+        let _ = std::fs::remove_file(config_path);
+        let _ = std::fs::remove_file(credentials_path);
+
+        assert_eq!(
+            error.to_string(),
+            "Settings require restart of trading engine to be applied: core.exchanges.Binance0.rest_host"
+        );
+        assert_eq!(exchange.max_position(&currency_pair), Some(dec!(7)));
+        assert_eq!(max_amount(), dec!(2));
+    }
+
+    fn context_with_exchange(exchange: &Arc<Exchange>) -> Arc<EngineContext> {
+        context_with_exchanges(&[exchange])
+    }
+
+    fn context_with_exchanges(exchanges: &[&Arc<Exchange>]) -> Arc<EngineContext> {
+        let exchanges: DashMap<_, _> = exchanges
+            .iter()
+            .map(|x| (x.exchange_account_id.clone(), (*x).clone()))
+            .collect();
+        let (finish_graceful_shutdown_sender, _) = oneshot::channel();
+        EngineContext::new(
+            CoreSettings::default(),
+            exchanges,
+            ExchangeEvents::new(broadcast::channel(10).0),
+            finish_graceful_shutdown_sender,
+            TimeoutManager::new(HashMap::new()),
+            ApplicationManager::new(CancellationToken::new()),
+            Arc::new(InMemoryOrderStore::default()),
+        )
+    }
+
+    #[actix_rt::test]
+    async fn net_position_by_filled_buy_and_partially_filled_sell() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let context = context_with_exchange(&exchange);
+
+        let filled_buy = add_filled_order(&exchange, OrderSide::Buy, dec!(3), dec!(3));
+        let funding = OrderFill::test_default()
+            .with_fill_type(OrderFillType::Funding)
+            .with_price_and_amount(dec!(0.1), dec!(5))
+            .with_side(OrderSide::Buy);
+        exchange.add_order_fill(&filled_buy, funding);
+        let _ = add_filled_order(&exchange, OrderSide::Sell, dec!(2), dec!(1));
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        assert_eq!(context.net_position(&currency_pair), dec!(2));
+        assert_eq!(
+            context.net_position(&CurrencyPair::from_codes("eth".into(), "btc".into())),
+            dec!(0)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn find_order_by_assigned_exchange_order_id() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let context = context_with_exchange(&exchange);
+        let mut header = OrderHeader::test_default();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("phb".into(), "btc".into());
+        let client_order_id = header.client_order_id.clone();
+        let _ = exchange
+            .orders
+            .add_simple_initial(Arc::new(header), Some(dec!(0.1)));
+        let exchange_order_id = ExchangeOrderId::new("exchange_order_id".into());
+
+        assert!(context.order_by_exchange_id(&exchange_order_id).is_none());
+
+        exchange
+            .handle_create_order_succeeded(
+                &exchange.exchange_account_id,
+                &client_order_id,
+                &exchange_order_id,
+                &EventSourceType::WebSocket,
+            )
+            .expect("in test");
+
+        let order = context
+            .order_by_exchange_id(&exchange_order_id)
+            .expect("in test");
+        assert_eq!(order.header.client_order_id, client_order_id);
+        assert_eq!(order.status(), OrderStatus::Created);
+        assert!(context
+            .order_by_exchange_id(&ExchangeOrderId::new("unknown".into()))
+            .is_none());
+    }
+
+    #[actix_rt::test]
+    async fn strategy_order_fails_over_to_account_from_settings() {
+        let primary_account_id = ExchangeAccountId::new("Binance".into(), 0);
+        let secondary_account_id = ExchangeAccountId::new("Binance".into(), 1);
+        let (primary, _primary_rx) = ScriptedExchangeBuilder::new(primary_account_id.clone())
+            .create_order_status(StatusCode::TOO_MANY_REQUESTS)
+            .build();
+        let (secondary, _secondary_rx) =
+            ScriptedExchangeBuilder::new(secondary_account_id.clone()).build();
+        let context = context_with_exchanges(&[&primary, &secondary]);
+        context.set_strategy_settings(&TestStrategySettings {
+            spread: dec!(0.1),
+            max_amount: dec!(1),
+            failover_account_id: Some(secondary_account_id.clone()),
+        });
+
+        let mut header = (*OrderSnapshot::test_default().header).clone();
+        header.exchange_account_id = primary_account_id;
+        header.currency_pair = CurrencyPair::from_codes("phb".into(), "btc".into());
+        let order_to_create = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.1),
+        };
+
+        let order_ref = context
+            .create_strategy_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test");
+
+        assert_eq!(order_ref.exchange_account_id(), secondary_account_id);
+        assert_eq!(order_ref.status(), OrderStatus::Created);
+    }
+
+    /// Records flattening orders which were created before shutdown of the service
+    struct FlatteningOrdersProbe {
+        exchange: Arc<Exchange>,
+        flattening_orders_count: Mutex<Option<usize>>,
+    }
+
+    impl Service for FlatteningOrdersProbe {
+        fn name(&self) -> &str {
+            "FlatteningOrdersProbe"
+        }
+
+        fn graceful_shutdown(self: Arc<Self>) -> Option<oneshot::Receiver<Result<()>>> {
+            let count = self
+                .exchange
+                .orders
+                .cache_by_client_id
+                .iter()
+                .filter(|x| x.fn_ref(|x| x.header.strategy_name == FLATTENING_STRATEGY_NAME))
+                .count();
+            *self.flattening_orders_count.lock() = Some(count);
+            None
+        }
+    }
+
+    #[actix_rt::test]
+    async fn flatten_positions_before_services_shutdown() {
+        let (exchange, _rx) = ScriptedExchangeBuilder::new("Binance0".parse().expect("in test"))
+            .capabilities(ExchangeCapabilities {
+                reduce_only: true,
+                ..Default::default()
+            })
+            .build();
+        let _ = add_filled_order(&exchange, OrderSide::Buy, dec!(5), dec!(5));
+
+        let exchanges = DashMap::new();
+        let _ = exchanges.insert(exchange.exchange_account_id.clone(), exchange.clone());
+        let (finish_graceful_shutdown_sender, finish_graceful_shutdown_receiver) =
+            oneshot::channel();
+        let settings = CoreSettings {
+            exchanges: vec![ExchangeSettings::new_short(
+                exchange.exchange_account_id.clone(),
+                "test_api_key".into(),
+                "test_secret_key".into(),
+                false,
+            )],
+            ..CoreSettings::default()
+        };
+        let context = EngineContext::new(
+            settings,
+            exchanges,
+            ExchangeEvents::new(broadcast::channel(10).0),
+            finish_graceful_shutdown_sender,
+            TimeoutManager::new(HashMap::new()),
+            ApplicationManager::new(CancellationToken::new()),
+            Arc::new(InMemoryOrderStore::default()),
+        );
+        context.set_shutdown_policy(ShutdownPolicy {
+            cancel_all_on_shutdown: false,
+            flatten_on_shutdown: true,
+        });
+        let probe = Arc::new(FlatteningOrdersProbe {
+            exchange: exchange.clone(),
+            flattening_orders_count: Mutex::new(None),
+        });
+        context.shutdown_service.register_service(probe.clone());
+
+        context.clone().graceful_shutdown().await;
+
+        finish_graceful_shutdown_receiver.await.expect("in test");
+        assert_eq!(*probe.flattening_orders_count.lock(), Some(1));
+        let flattening_order = exchange
+            .orders
+            .cache_by_client_id
+            .iter()
+            .find(|x| x.fn_ref(|x| x.header.strategy_name == FLATTENING_STRATEGY_NAME))
+            .expect("in test")
+            .clone();
+        assert_eq!(flattening_order.side(), OrderSide::Sell);
+        assert_eq!(flattening_order.amount(), dec!(5));
+        assert_eq!(flattening_order.status(), OrderStatus::Created);
+    }
+}
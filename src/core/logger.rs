@@ -1,12 +1,115 @@
 use chrono::Utc;
-use log::LevelFilter;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
 use std::sync::Once;
 
+use crate::core::DateTime;
+
+pub const DEFAULT_RECENT_LOGS_CAPACITY: usize = 1000;
+
+static RECENT_LOGS: Lazy<RecentLogs> = Lazy::new(|| RecentLogs::new(DEFAULT_RECENT_LOGS_CAPACITY));
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub time: DateTime,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+struct RecentLogsState {
+    capacity: usize,
+    records: VecDeque<LogRecord>,
+}
+
+/// Ring buffer with the most recent log records for in-process diagnostics
+pub struct RecentLogs {
+    state: Mutex<RecentLogsState>,
+}
+
+impl RecentLogs {
+    pub fn new(capacity: usize) -> Self {
+        RecentLogs {
+            state: Mutex::new(RecentLogsState {
+                capacity,
+                records: VecDeque::with_capacity(capacity),
+            }),
+        }
+    }
+
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut state = self.state.lock();
+        state.capacity = capacity;
+        while state.records.len() > capacity {
+            let _ = state.records.pop_front();
+        }
+    }
+
+    pub fn push(&self, record: LogRecord) {
+        let mut state = self.state.lock();
+        if state.capacity == 0 {
+            return;
+        }
+
+        if state.records.len() == state.capacity {
+            let _ = state.records.pop_front();
+        }
+        state.records.push_back(record);
+    }
+
+    /// Records starting from the oldest one
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.state.lock().records.iter().cloned().collect()
+    }
+}
+
+impl Log for RecentLogs {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.push(LogRecord {
+            time: Utc::now(),
+            level: record.level(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+struct GlobalRecentLogs;
+
+impl Log for GlobalRecentLogs {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        RECENT_LOGS.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        RECENT_LOGS.log(record)
+    }
+
+    fn flush(&self) {}
+}
+
+/// The most recent log records retained by logger set up in `init_logger`
+pub fn recent_logs() -> Vec<LogRecord> {
+    RECENT_LOGS.records()
+}
+
+pub fn set_recent_logs_capacity(capacity: usize) {
+    RECENT_LOGS.set_capacity(capacity)
+}
+
 pub fn init_logger() {
     static INIT_LOGGER: Once = Once::new();
 
     INIT_LOGGER.call_once(|| {
-        let _ = fern::Dispatch::new()
+        let formatted = fern::Dispatch::new()
             .format(|out, message, record| {
                 out.finish(format_args!(
                     "[{}][{}][{}] {}",
@@ -37,8 +140,48 @@ pub fn init_logger() {
                             .open("log.txt")
                             .expect("Unable to open log file"),
                     ),
-            )
+            );
+
+        // recent logs keep raw messages without formatting
+        let recent = fern::Dispatch::new()
+            .level(LevelFilter::Info)
+            .chain(Box::new(GlobalRecentLogs) as Box<dyn Log>);
+
+        fern::Dispatch::new()
+            .chain(formatted)
+            .chain(recent)
             .apply()
             .expect("Unable to set up logger");
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn retain_only_latest_records() {
+        let recent_logs = RecentLogs::new(3);
+
+        for i in 0..5 {
+            recent_logs.log(
+                &Record::builder()
+                    .args(format_args!("message {}", i))
+                    .level(Level::Info)
+                    .target("test")
+                    .build(),
+            );
+        }
+
+        let messages = recent_logs
+            .records()
+            .into_iter()
+            .map(|x| x.message)
+            .collect::<Vec<_>>();
+        assert_eq!(messages, vec!["message 2", "message 3", "message 4"]);
+
+        recent_logs.set_capacity(1);
+        assert_eq!(recent_logs.records()[0].message, "message 4");
+        assert_eq!(recent_logs.records().len(), 1);
+    }
+}
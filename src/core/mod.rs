@@ -1,5 +1,6 @@
 use chrono::Utc;
 
+pub mod clock;
 pub mod connectivity;
 pub mod exchanges;
 pub mod infrastructure;
@@ -8,6 +9,7 @@ pub mod orders;
 pub mod utils;
 
 pub mod config;
+pub mod credentials_encryption;
 pub mod disposition_execution;
 pub(crate) mod events;
 pub mod explanation;
@@ -2,6 +2,11 @@ use crate::core::exchanges::common::*;
 use crate::core::order_book::order_book_data::OrderBookData;
 use crate::core::orders::order::*;
 use crate::core::DateTime;
+use itertools::Itertools;
+use rust_decimal_macros::dec;
+
+/// Count of price levels of each side included into order book checksum
+pub const CHECKSUM_DEPTH: usize = 25;
 
 /// Fields from OrderSnapshot for exclude order
 pub struct DataToExcludeOrder {
@@ -78,6 +83,60 @@ impl LocalOrderBookSnapshot {
         }
     }
 
+    /// Return middle between top ask and top bid, price of the only existing side if other one is
+    /// empty or fallback if order book is empty
+    pub fn mid_price_or(&self, fallback: Price) -> Price {
+        match (self.get_top_ask(), self.get_top_bid()) {
+            (Some((ask, _)), Some((bid, _))) => (ask + bid) / dec!(2),
+            (Some((price, _)), None) | (None, Some((price, _))) => price,
+            (None, None) => fallback,
+        }
+    }
+
+    /// Top bid is not lower than top ask, so locked book is crossed too
+    pub fn is_crossed(&self) -> bool {
+        self.top_prices()
+            .map(|(ask, bid)| bid >= ask)
+            .unwrap_or(false)
+    }
+
+    /// Top bid equals top ask
+    pub fn is_locked(&self) -> bool {
+        self.top_prices()
+            .map(|(ask, bid)| bid == ask)
+            .unwrap_or(false)
+    }
+
+    fn top_prices(&self) -> Option<(Price, Price)> {
+        Some((self.get_top_ask()?.0, self.get_top_bid()?.0))
+    }
+
+    /// Volume weighted price of filling order with specified side and amount by levels of opposite
+    /// book side starting from the best price. None if there isn't enough depth for the whole amount
+    pub fn expected_fill_price(&self, side: OrderSide, amount: Amount) -> Option<Price> {
+        if amount <= dec!(0) {
+            return None;
+        }
+
+        let levels: Box<dyn Iterator<Item = (&Price, &Amount)>> = match side {
+            OrderSide::Buy => Box::new(self.get_asks_price_levels()),
+            OrderSide::Sell => Box::new(self.get_bids_price_levels()),
+        };
+
+        let mut remaining_amount = amount;
+        let mut cost = dec!(0);
+        for (price, level_amount) in levels {
+            let filled_amount = remaining_amount.min(*level_amount);
+            cost += filled_amount * price;
+            remaining_amount -= filled_amount;
+            if remaining_amount.is_zero() {
+                return Some(cost / amount);
+            }
+        }
+
+        None
+    }
+
     /// Return all asks values starting from the lowest price
     pub fn get_asks_price_levels(&self) -> impl Iterator<Item = (&Price, &Amount)> {
         self.asks.iter()
@@ -88,6 +147,27 @@ impl LocalOrderBookSnapshot {
         self.bids.iter().rev()
     }
 
+    /// Checksum of OKX order book: CRC32 as signed 32-bit integer of top `CHECKSUM_DEPTH` levels
+    /// interleaved as `bid_price:bid_amount:ask_price:ask_amount:...` starting from best prices.
+    /// Side with less levels is just skipped after its end. Prices and amounts are formatted
+    /// with scale they were received with, so they match raw strings of exchange messages
+    pub fn okx_checksum(&self) -> i64 {
+        let format_level = |(price, amount): (&Price, &Amount)| format!("{}:{}", price, amount);
+        let bids = self
+            .get_bids_price_levels()
+            .take(CHECKSUM_DEPTH)
+            .map(format_level);
+        let asks = self
+            .get_asks_price_levels()
+            .take(CHECKSUM_DEPTH)
+            .map(format_level);
+
+        let checksum_data = bids.interleave(asks).join(":");
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(checksum_data.as_bytes());
+        i64::from(hasher.finalize() as i32)
+    }
+
     fn try_remove_order(&mut self, order: DataToExcludeOrder) {
         let book_side = self.get_order_book_side(order.side);
 
@@ -124,7 +204,6 @@ impl LocalOrderBookSnapshot {
 mod tests {
     use super::*;
     use chrono::Utc;
-    use rust_decimal_macros::*;
 
     #[test]
     fn get_top_ask() {
@@ -184,6 +263,103 @@ mod tests {
         assert_eq!(iter.next().expect("in test"), (&dec!(1.0), &dec!(0.1)));
     }
 
+    #[test]
+    fn mid_price_for_both_sides() {
+        let mut asks = SortedOrderData::new();
+        asks.insert(dec!(3.0), dec!(0.1));
+        asks.insert(dec!(4.0), dec!(4.2));
+        let mut bids = SortedOrderData::new();
+        bids.insert(dec!(1.0), dec!(0.1));
+        bids.insert(dec!(2.0), dec!(4.2));
+
+        let order_book_snapshot = LocalOrderBookSnapshot::new(asks, bids, Utc::now());
+
+        assert_eq!(order_book_snapshot.mid_price_or(dec!(10)), dec!(2.5));
+    }
+
+    #[test]
+    fn mid_price_for_one_side() {
+        let mut asks = SortedOrderData::new();
+        asks.insert(dec!(3.0), dec!(0.1));
+        asks.insert(dec!(4.0), dec!(4.2));
+        let bids = SortedOrderData::new();
+
+        let order_book_snapshot = LocalOrderBookSnapshot::new(asks, bids, Utc::now());
+
+        assert_eq!(order_book_snapshot.mid_price_or(dec!(10)), dec!(3.0));
+    }
+
+    #[test]
+    fn mid_price_for_empty_book() {
+        let order_book_snapshot =
+            LocalOrderBookSnapshot::new(SortedOrderData::new(), SortedOrderData::new(), Utc::now());
+
+        assert_eq!(order_book_snapshot.mid_price_or(dec!(10)), dec!(10));
+    }
+
+    fn snapshot_with_tops(top_ask: Price, top_bid: Price) -> LocalOrderBookSnapshot {
+        let mut asks = SortedOrderData::new();
+        asks.insert(top_ask, dec!(0.1));
+        asks.insert(top_ask + dec!(1), dec!(4.2));
+        let mut bids = SortedOrderData::new();
+        bids.insert(top_bid, dec!(0.1));
+        bids.insert(top_bid - dec!(1), dec!(4.2));
+
+        LocalOrderBookSnapshot::new(asks, bids, Utc::now())
+    }
+
+    #[test]
+    fn normal_book_is_neither_crossed_nor_locked() {
+        let order_book_snapshot = snapshot_with_tops(dec!(3.0), dec!(2.0));
+
+        assert!(!order_book_snapshot.is_crossed());
+        assert!(!order_book_snapshot.is_locked());
+    }
+
+    #[test]
+    fn locked_book() {
+        let order_book_snapshot = snapshot_with_tops(dec!(3.0), dec!(3.0));
+
+        assert!(order_book_snapshot.is_locked());
+        assert!(order_book_snapshot.is_crossed());
+    }
+
+    #[test]
+    fn crossed_book() {
+        let order_book_snapshot = snapshot_with_tops(dec!(3.0), dec!(3.5));
+
+        assert!(order_book_snapshot.is_crossed());
+        assert!(!order_book_snapshot.is_locked());
+    }
+
+    #[test]
+    fn expected_fill_price_by_several_levels() {
+        let mut asks = SortedOrderData::new();
+        asks.insert(dec!(10), dec!(1));
+        asks.insert(dec!(11), dec!(2));
+        asks.insert(dec!(12), dec!(5));
+        let mut bids = SortedOrderData::new();
+        bids.insert(dec!(9), dec!(1));
+        bids.insert(dec!(8), dec!(3));
+
+        let order_book_snapshot = LocalOrderBookSnapshot::new(asks, bids, Utc::now());
+
+        // 1 * 10 + 2 * 11 + 1 * 12 = 44
+        assert_eq!(
+            order_book_snapshot.expected_fill_price(OrderSide::Buy, dec!(4)),
+            Some(dec!(11))
+        );
+        // 1 * 9 + 1 * 8 = 17
+        assert_eq!(
+            order_book_snapshot.expected_fill_price(OrderSide::Sell, dec!(2)),
+            Some(dec!(8.5))
+        );
+        assert_eq!(
+            order_book_snapshot.expected_fill_price(OrderSide::Sell, dec!(4.1)),
+            None
+        );
+    }
+
     #[test]
     fn get_empty() {
         let asks = SortedOrderData::new();
@@ -287,4 +463,20 @@ mod tests {
         // Still exists
         assert_eq!(asks.next().expect("in test"), (&dec!(3.0), &dec!(4.2)));
     }
+
+    #[test]
+    fn okx_checksum_of_published_sample() {
+        // sample from OKX API docs: asks [["3366.8", "9"], ["3368", "8"]], bids [["3366.1", "7"], ["3366", "6"]]
+        let mut asks = SortedOrderData::new();
+        asks.insert(dec!(3366.8), dec!(9));
+        asks.insert(dec!(3368), dec!(8));
+        let mut bids = SortedOrderData::new();
+        bids.insert(dec!(3366.1), dec!(7));
+        bids.insert(dec!(3366), dec!(6));
+
+        let order_book_snapshot = LocalOrderBookSnapshot::new(asks, bids, Utc::now());
+
+        // crc32 of "3366.1:7:3366.8:9:3366:6:3368:8"
+        assert_eq!(order_book_snapshot.okx_checksum(), -1881014294);
+    }
 }
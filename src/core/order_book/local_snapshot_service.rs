@@ -1,16 +1,29 @@
 use crate::core::exchanges::common::*;
 use crate::core::order_book::local_order_book_snapshot::LocalOrderBookSnapshot;
 use crate::core::order_book::*;
+use log::warn;
 use std::collections::HashMap;
 
 /// Produce and actualize current logical state of order book snapshot according to logical time of handled order book events
 pub struct LocalSnapshotsService {
     local_snapshots: HashMap<TradePlace, LocalOrderBookSnapshot>,
+    resubscribe_callback: Box<dyn FnMut(TradePlaceAccount) + Send + Sync>,
 }
 
 impl LocalSnapshotsService {
     pub fn new(local_snapshots: HashMap<TradePlace, LocalOrderBookSnapshot>) -> Self {
-        Self { local_snapshots }
+        Self {
+            local_snapshots,
+            resubscribe_callback: Box::new(|_| {}),
+        }
+    }
+
+    /// Callback is called when order book is desynchronized and should be received again from exchange
+    pub fn set_resubscribe_callback(
+        &mut self,
+        callback: Box<dyn FnMut(TradePlaceAccount) + Send + Sync>,
+    ) {
+        self.resubscribe_callback = callback;
     }
 
     pub fn get_snapshot(&self, trade_place: TradePlace) -> Option<&LocalOrderBookSnapshot> {
@@ -19,6 +32,7 @@ impl LocalSnapshotsService {
 
     /// Create snapshot if it does not exist
     /// Update snapshot if suitable data arrive
+    /// Snapshot with checksum mismatch is dropped until next full snapshot arrives
     pub fn update(&mut self, order_book_event: event::OrderBookEvent) -> Option<TradePlaceAccount> {
         // Extract all field
         let (_, creation_time, exchange_account_id, currency_pair, _, event_type, event_data) =
@@ -28,23 +42,38 @@ impl LocalSnapshotsService {
             exchange_account_id.exchange_id.clone(),
             currency_pair.clone(),
         );
+        let checksum = event_data.checksum;
 
-        match event_type {
+        let snapshot = match event_type {
             event::EventType::Snapshot => {
                 let _ = self.local_snapshots.insert(
                     trade_place.clone(),
                     event_data.to_local_order_book_snapshot(),
                 );
-
-                Some(TradePlaceAccount::new(exchange_account_id, currency_pair))
+                self.local_snapshots.get(&trade_place)?
             }
             event::EventType::Update => {
-                self.local_snapshots.get_mut(&trade_place).map(|snapshot| {
-                    snapshot.apply_update(event_data, creation_time);
-                    TradePlaceAccount::new(exchange_account_id, currency_pair)
-                })
+                let snapshot = self.local_snapshots.get_mut(&trade_place)?;
+                snapshot.apply_update(event_data, creation_time);
+                snapshot
+            }
+        };
+
+        let trade_place_account = TradePlaceAccount::new(exchange_account_id, currency_pair);
+        if let Some(checksum) = checksum {
+            let actual_checksum = (checksum.calculate)(snapshot);
+            if actual_checksum != checksum.expected {
+                warn!(
+                    "Order book checksum mismatch for {:?}: expected {} but got {}",
+                    trade_place_account, checksum.expected, actual_checksum
+                );
+                let _ = self.local_snapshots.remove(&trade_place);
+                (self.resubscribe_callback)(trade_place_account);
+                return None;
             }
         }
+
+        Some(trade_place_account)
     }
 }
 
@@ -57,8 +86,11 @@ impl Default for LocalSnapshotsService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::order_book::order_book_data::{OrderBookChecksum, OrderBookData};
     use chrono::Utc;
+    use parking_lot::Mutex;
     use rust_decimal_macros::*;
+    use std::sync::Arc;
 
     fn create_order_book_event_for_tests(
         exchange_id: ExchangeId,
@@ -227,4 +259,62 @@ mod tests {
             None
         );
     }
+
+    fn checksum_book() -> OrderBookData {
+        let mut asks = SortedOrderData::new();
+        asks.insert(dec!(3.5), dec!(1.5));
+        asks.insert(dec!(4), dec!(2));
+        let mut bids = SortedOrderData::new();
+        bids.insert(dec!(3), dec!(0.5));
+        OrderBookData::new(asks, bids)
+    }
+
+    #[test]
+    fn resubscribe_on_checksum_mismatch() {
+        let resubscribed = Arc::new(Mutex::new(Vec::new()));
+        let mut snapshot_service = LocalSnapshotsService::default();
+        {
+            let resubscribed = resubscribed.clone();
+            snapshot_service.set_resubscribe_callback(Box::new(move |trade_place_account| {
+                resubscribed.lock().push(trade_place_account)
+            }));
+        }
+
+        let exchange_id: ExchangeId = "exchange_id".into();
+        let currency_pair = CurrencyPair::from_codes("base".into(), "quote".into());
+        let snapshot = checksum_book();
+        let checksum = OrderBookChecksum::new(
+            snapshot
+                .clone()
+                .to_local_order_book_snapshot()
+                .okx_checksum(),
+            LocalOrderBookSnapshot::okx_checksum,
+        );
+        let trade_place_account = snapshot_service
+            .update(create_order_book_event_for_tests(
+                exchange_id.clone(),
+                currency_pair.clone(),
+                event::EventType::Snapshot,
+                snapshot.with_checksum(checksum),
+            ))
+            .expect("in test");
+        assert!(resubscribed.lock().is_empty());
+
+        // local book is corrupted: update doesn't lead to book from which exchange computed checksum
+        let mut asks = SortedOrderData::new();
+        asks.insert(dec!(4), dec!(1));
+        let update = OrderBookData::new(asks, SortedOrderData::new()).with_checksum(checksum);
+        let update_result = snapshot_service.update(create_order_book_event_for_tests(
+            exchange_id,
+            currency_pair,
+            event::EventType::Update,
+            update,
+        ));
+
+        assert!(update_result.is_none());
+        assert_eq!(*resubscribed.lock(), vec![trade_place_account.clone()]);
+        assert!(snapshot_service
+            .get_snapshot(trade_place_account.trade_place())
+            .is_none());
+    }
 }
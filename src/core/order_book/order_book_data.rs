@@ -2,16 +2,43 @@ use crate::core::exchanges::common::*;
 use crate::core::order_book::local_order_book_snapshot::LocalOrderBookSnapshot;
 use chrono::Utc;
 
+/// Checksum of order book provided by exchange and exchange specific way to calculate it for local book
+#[derive(Debug, Clone, Copy)]
+pub struct OrderBookChecksum {
+    pub expected: i64,
+    pub calculate: fn(&LocalOrderBookSnapshot) -> i64,
+}
+
+impl OrderBookChecksum {
+    pub fn new(expected: i64, calculate: fn(&LocalOrderBookSnapshot) -> i64) -> Self {
+        Self {
+            expected,
+            calculate,
+        }
+    }
+}
+
 /// Main asks and bids storage
 #[derive(Debug, Clone)]
 pub struct OrderBookData {
     pub asks: SortedOrderData,
     pub bids: SortedOrderData,
+    /// Checksum of order book after applying this data, provided by some exchanges
+    pub checksum: Option<OrderBookChecksum>,
 }
 
 impl OrderBookData {
     pub fn new(asks: SortedOrderData, bids: SortedOrderData) -> Self {
-        Self { asks, bids }
+        Self {
+            asks,
+            bids,
+            checksum: None,
+        }
+    }
+
+    pub fn with_checksum(mut self, checksum: OrderBookChecksum) -> Self {
+        self.checksum = Some(checksum);
+        self
     }
 
     /// Transform to LocalOrderBookSnapshot
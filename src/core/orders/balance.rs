@@ -0,0 +1,66 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::order::{OrderHeader, OrderSide};
+use crate::core::exchanges::common::{CurrencyCode, Price};
+
+/// Currency and its amount which should be available on balance to place order:
+/// quote currency for buy order including fee and base currency for sell order
+pub fn required_balance(
+    header: &OrderHeader,
+    price: Price,
+    fee_bps: Decimal,
+) -> (CurrencyCode, Decimal) {
+    let (base_currency_code, quote_currency_code) = header.currency_pair.to_codes();
+
+    match header.side {
+        OrderSide::Buy => {
+            let cost = header.amount * price;
+            let fee = cost * fee_bps / dec!(10000);
+            (quote_currency_code, cost + fee)
+        }
+        OrderSide::Sell => (base_currency_code, header.amount),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+    use crate::core::orders::order::{ClientOrderId, OrderExecutionType, OrderType};
+    use chrono::Utc;
+    use std::sync::Arc;
+
+    fn header(side: OrderSide) -> Arc<OrderHeader> {
+        OrderHeader::new(
+            ClientOrderId::unique_id(),
+            Utc::now(),
+            ExchangeAccountId::new("Binance".into(), 0),
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            OrderType::Limit,
+            side,
+            dec!(2),
+            OrderExecutionType::None,
+            None,
+            None,
+            "StrategyInUnitTests".to_owned(),
+        )
+    }
+
+    #[test]
+    fn buy_requires_quote_with_fee() {
+        let (currency_code, amount) = required_balance(&header(OrderSide::Buy), dec!(5), dec!(10));
+
+        assert_eq!(currency_code, "BTC".into());
+        // 2 * 5 + 0.1%
+        assert_eq!(amount, dec!(10.01));
+    }
+
+    #[test]
+    fn sell_requires_base() {
+        let (currency_code, amount) = required_balance(&header(OrderSide::Sell), dec!(5), dec!(10));
+
+        assert_eq!(currency_code, "PHB".into());
+        assert_eq!(amount, dec!(2));
+    }
+}
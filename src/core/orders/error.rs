@@ -0,0 +1,193 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use chrono::Duration;
+
+use crate::core::exchanges::common::{Amount, CurrencyPair, ExchangeErrorType, Price};
+use crate::core::orders::order::{ClientOrderId, OrderStatus, OrderType};
+use crate::core::DateTime;
+
+/// Failure modes of order construction and submission.
+/// Converts to `anyhow::Error` with `?`, so callers can downcast it back to match on variant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    InvalidAmount {
+        client_order_id: ClientOrderId,
+        amount: Amount,
+    },
+    InvalidPrice {
+        client_order_id: ClientOrderId,
+        price: Option<Price>,
+    },
+    UnsupportedOrderType {
+        client_order_id: ClientOrderId,
+        order_type: OrderType,
+        reason: String,
+    },
+    Overfilled {
+        client_order_id: ClientOrderId,
+        filled_amount: Amount,
+        amount: Amount,
+    },
+    ExchangeRejected {
+        client_order_id: ClientOrderId,
+        error_type: ExchangeErrorType,
+        message: String,
+    },
+    OutsideTradingWindow {
+        client_order_id: ClientOrderId,
+        time: DateTime,
+    },
+    ClockDriftExceeded {
+        client_order_id: ClientOrderId,
+        drift: Option<Duration>,
+    },
+    PositionLimitExceeded {
+        client_order_id: ClientOrderId,
+        position: Amount,
+        max_position: Amount,
+    },
+    PairPaused {
+        client_order_id: ClientOrderId,
+        currency_pair: CurrencyPair,
+    },
+    BelowMinNotional {
+        client_order_id: ClientOrderId,
+        notional: Price,
+        min_notional: Price,
+    },
+    MissingExchangeOrderId {
+        client_order_id: ClientOrderId,
+    },
+    MissingPrice {
+        client_order_id: ClientOrderId,
+    },
+    NotFound {
+        client_order_id: ClientOrderId,
+    },
+}
+
+impl Display for OrderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::InvalidAmount {
+                client_order_id,
+                amount,
+            } => write!(
+                f,
+                "Order {} amount should be positive but got {}",
+                client_order_id, amount
+            ),
+            OrderError::InvalidPrice {
+                client_order_id,
+                price,
+            } => write!(
+                f,
+                "Order {} price should be positive but got {:?}",
+                client_order_id, price
+            ),
+            OrderError::UnsupportedOrderType {
+                client_order_id,
+                order_type,
+                reason,
+            } => write!(
+                f,
+                "Unable to create {:?} order {}: {}",
+                order_type, client_order_id, reason
+            ),
+            OrderError::Overfilled {
+                client_order_id,
+                filled_amount,
+                amount,
+            } => write!(
+                f,
+                "Order {} filled amount {} is greater than order amount {}",
+                client_order_id, filled_amount, amount
+            ),
+            OrderError::ExchangeRejected {
+                client_order_id,
+                error_type,
+                message,
+            } => write!(
+                f,
+                "Order {} was rejected by exchange with {:?}: {}",
+                client_order_id, error_type, message
+            ),
+            OrderError::OutsideTradingWindow {
+                client_order_id,
+                time,
+            } => write!(
+                f,
+                "Order {} is suppressed because {} is outside of trading window",
+                client_order_id, time
+            ),
+            OrderError::ClockDriftExceeded {
+                client_order_id,
+                drift,
+            } => write!(
+                f,
+                "Order {} is suppressed because clock drift {:?}ms exceeds maximum",
+                client_order_id,
+                drift.map(|x| x.num_milliseconds())
+            ),
+            OrderError::PositionLimitExceeded {
+                client_order_id,
+                position,
+                max_position,
+            } => write!(
+                f,
+                "Order {} is rejected because position {} would exceed maximum {}",
+                client_order_id, position, max_position
+            ),
+            OrderError::PairPaused {
+                client_order_id,
+                currency_pair,
+            } => write!(
+                f,
+                "Order {} is rejected because placement on {} is paused",
+                client_order_id, currency_pair
+            ),
+            OrderError::BelowMinNotional {
+                client_order_id,
+                notional,
+                min_notional,
+            } => write!(
+                f,
+                "Order {} is rejected because notional {} is below minimum {}",
+                client_order_id, notional, min_notional
+            ),
+            OrderError::MissingExchangeOrderId { client_order_id } => {
+                write!(f, "Order {} has no exchange order id", client_order_id)
+            }
+            OrderError::MissingPrice { client_order_id } => {
+                write!(f, "Order {} has no price", client_order_id)
+            }
+            OrderError::NotFound { client_order_id } => write!(
+                f,
+                "Order {} is not in the local orders pool",
+                client_order_id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+/// Order status change not allowed by order lifecycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidStatusTransition {
+    pub from: OrderStatus,
+    pub to: OrderStatus,
+}
+
+impl Display for InvalidStatusTransition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid order status transition from {:?} to {:?}",
+            self.from, self.to
+        )
+    }
+}
+
+impl std::error::Error for InvalidStatusTransition {}
@@ -13,6 +13,8 @@ pub enum OrderEventType {
     OrderCompleted { cloned_order: Arc<OrderSnapshot> },
     CancelOrderSucceeded,
     CancelOrderFailed,
+    // order is still live after configured number of failed cancellation attempts
+    CancelAttemptsExhausted,
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,149 @@
+use std::io::Write;
+
+use anyhow::Result;
+
+use super::order::OrderSnapshot;
+
+const ORDERS_CSV_HEADER: &str = "client_order_id,exchange_order_id,exchange_account_id,currency_pair,side,order_type,price,amount,filled_amount,average_fill_price,status,init_time,finished_time";
+
+/// Write orders as CSV with one row per order. Missing optional values are written as empty cells
+pub fn export_orders_csv(orders: &[OrderSnapshot], mut writer: impl Write) -> Result<()> {
+    writeln!(writer, "{}", ORDERS_CSV_HEADER)?;
+
+    for order in orders {
+        let row = [
+            order.header.client_order_id.to_string(),
+            order
+                .props
+                .exchange_order_id
+                .as_ref()
+                .map(|x| x.as_str().to_owned())
+                .unwrap_or_default(),
+            order.header.exchange_account_id.to_string(),
+            order.header.currency_pair.to_string(),
+            order.header.side.to_string(),
+            format!("{:?}", order.header.order_type),
+            order
+                .props
+                .raw_price
+                .map(|x| x.to_string())
+                .unwrap_or_default(),
+            order.amount().to_string(),
+            order.filled_amount().to_string(),
+            order
+                .average_fill_price()
+                .map(|x| x.normalize().to_string())
+                .unwrap_or_default(),
+            format!("{:?}", order.status()),
+            order.header.init_time.to_rfc3339(),
+            order
+                .props
+                .finished_time
+                .map(|x| x.to_rfc3339())
+                .unwrap_or_default(),
+        ];
+
+        writeln!(
+            writer,
+            "{}",
+            row.iter()
+                .map(|x| escape_csv_field(x))
+                .collect::<Vec<_>>()
+                .join(",")
+        )?;
+    }
+
+    Ok(())
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::orders::fill::{EventSourceType, OrderFill, OrderFillType};
+    use crate::core::orders::order::{OrderFillRole, OrderSide, OrderStatus};
+    use chrono::{TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn add_fill(order: &mut OrderSnapshot, price: Decimal, amount: Decimal) {
+        order.add_fill(OrderFill::new(
+            Uuid::new_v4(),
+            Utc::now(),
+            OrderFillType::UserTrade,
+            None,
+            price,
+            amount,
+            price * amount,
+            OrderFillRole::Maker,
+            "BTC".into(),
+            dec!(0),
+            dec!(0),
+            "BTC".into(),
+            dec!(0),
+            dec!(0),
+            false,
+            Some(EventSourceType::WebSocket),
+            Some(OrderSide::Buy),
+        ));
+    }
+
+    #[test]
+    fn export_two_orders() {
+        let init_time = Utc.ymd(2021, 1, 4).and_hms(12, 0, 0);
+
+        let mut completed = OrderSnapshot::test_default();
+        completed.header = {
+            let mut header = (*completed.header).clone();
+            header.client_order_id = "completed".into();
+            header.init_time = init_time;
+            header.amount = dec!(2);
+            header.into()
+        };
+        completed.props.exchange_order_id = Some("100".into());
+        add_fill(&mut completed, dec!(1), dec!(1));
+        add_fill(&mut completed, dec!(2), dec!(1));
+        completed.set_status(
+            OrderStatus::Completed,
+            Utc.ymd(2021, 1, 4).and_hms(12, 0, 5),
+        );
+
+        let mut creating = OrderSnapshot::test_default();
+        creating.header = {
+            let mut header = (*creating.header).clone();
+            header.client_order_id = "creating".into();
+            header.init_time = init_time;
+            header.into()
+        };
+
+        let mut buffer = Vec::new();
+        export_orders_csv(&[completed, creating], &mut buffer).expect("in test");
+        let csv = String::from_utf8(buffer).expect("in test");
+        let lines: Vec<_> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], ORDERS_CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "completed,100,Binance0,eos/btc,Buy,Limit,1,2,2,1.5,Completed,2021-01-04T12:00:00+00:00,2021-01-04T12:00:05+00:00"
+        );
+        assert_eq!(
+            lines[2],
+            "creating,,Binance0,eos/btc,Buy,Limit,1,1,0,,Creating,2021-01-04T12:00:00+00:00,"
+        );
+    }
+
+    #[test]
+    fn escape_field_with_comma() {
+        assert_eq!(escape_csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+        assert_eq!(escape_csv_field("plain"), "plain");
+    }
+}
@@ -1,9 +1,11 @@
 use crate::core::{
     exchanges::common::CurrencyCode,
     orders::order::{OrderFillRole, OrderSide},
+    utils::deserialize_normalized_decimal,
     DateTime,
 };
 use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -30,8 +32,11 @@ pub struct OrderFill {
     fill_type: OrderFillType,
 
     trade_id: Option<String>,
+    #[serde(deserialize_with = "deserialize_normalized_decimal")]
     price: Decimal,
+    #[serde(deserialize_with = "deserialize_normalized_decimal")]
     amount: Decimal,
+    #[serde(deserialize_with = "deserialize_normalized_decimal")]
     cost: Decimal,
     role: OrderFillRole,
     commission_currency_code: CurrencyCode,
@@ -142,4 +147,208 @@ impl OrderFill {
     pub fn side(&self) -> Option<OrderSide> {
         self.side
     }
+
+    /// User trade fill with unique trade id of buy order for 1 by price 1 received by websocket
+    /// without commission
+    #[cfg(test)]
+    pub fn test_default() -> Self {
+        Self::new(
+            Uuid::new_v4(),
+            chrono::Utc::now(),
+            OrderFillType::UserTrade,
+            Some(Uuid::new_v4().to_string()),
+            dec!(1),
+            dec!(1),
+            dec!(1),
+            OrderFillRole::Maker,
+            "BTC".into(),
+            dec!(0),
+            dec!(0),
+            "BTC".into(),
+            dec!(0),
+            dec!(0),
+            false,
+            Some(EventSourceType::WebSocket),
+            Some(OrderSide::Buy),
+        )
+    }
+
+    #[cfg(test)]
+    pub fn with_price_and_amount(mut self, price: Decimal, amount: Decimal) -> Self {
+        self.price = price;
+        self.amount = amount;
+        self.cost = price * amount;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_fill_type(mut self, fill_type: OrderFillType) -> Self {
+        self.fill_type = fill_type;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_role(mut self, role: OrderFillRole) -> Self {
+        self.role = role;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_side(mut self, side: OrderSide) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_commission(mut self, currency_code: CurrencyCode, amount: Decimal) -> Self {
+        self.commission_currency_code = currency_code;
+        self.commission_amount = amount;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_event_source_type(mut self, event_source_type: Option<EventSourceType>) -> Self {
+        self.event_source_type = event_source_type;
+        self
+    }
+
+    /// Signed slippage of fill price relative to reference price (for example arrival mid price).
+    /// Positive value means that fill was adverse for specified side
+    pub fn slippage_vs(&self, reference: Decimal, side: OrderSide) -> Decimal {
+        match side {
+            OrderSide::Buy => self.price - reference,
+            OrderSide::Sell => reference - self.price,
+        }
+    }
+}
+
+/// Net PnL from funding payments of perpetual swaps.
+/// Funding fill cost is received for sell side and paid for buy side.
+/// See `OrderFills::funding_pnl` to include summarized fills of order
+pub fn compute_funding_pnl(fills: &[OrderFill]) -> Decimal {
+    fills
+        .iter()
+        .filter(|fill| fill.fill_type == OrderFillType::Funding)
+        .map(funding_pnl_of)
+        .fold(dec!(0), |pnl, x| pnl + x)
+}
+
+pub(crate) fn funding_pnl_of(fill: &OrderFill) -> Decimal {
+    match fill.side {
+        Some(OrderSide::Buy) => -fill.cost,
+        Some(OrderSide::Sell) | None => fill.cost,
+    }
+}
+
+/// Net position from historical fills: buys are positive, sells are negative.
+/// Funding fills don't change position so they are skipped
+pub fn position_from_fills(
+    fills: &[OrderFill],
+    side_of: impl Fn(&OrderFill) -> OrderSide,
+) -> Decimal {
+    fills
+        .iter()
+        .filter(|fill| fill.fill_type != OrderFillType::Funding)
+        .map(|fill| match side_of(fill) {
+            OrderSide::Buy => fill.amount,
+            OrderSide::Sell => -fill.amount,
+        })
+        .fold(dec!(0), |position, x| position + x)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::orders::order::OrderSnapshot;
+    use chrono::Utc;
+
+    fn create_fill(price: Decimal, amount: Decimal, side: OrderSide) -> OrderFill {
+        create_fill_with_type(OrderFillType::UserTrade, price, amount, side)
+    }
+
+    fn create_fill_with_type(
+        fill_type: OrderFillType,
+        price: Decimal,
+        amount: Decimal,
+        side: OrderSide,
+    ) -> OrderFill {
+        OrderFill::new(
+            Uuid::new_v4(),
+            Utc::now(),
+            fill_type,
+            Some("trade_id".into()),
+            price,
+            amount,
+            price * amount,
+            OrderFillRole::Taker,
+            "BTC".into(),
+            dec!(0),
+            dec!(0),
+            "BTC".into(),
+            dec!(0),
+            dec!(0),
+            false,
+            Some(EventSourceType::WebSocket),
+            Some(side),
+        )
+    }
+
+    #[test]
+    fn slippage_of_buy_filled_above_reference_is_positive() {
+        let fill = create_fill(dec!(101), dec!(1), OrderSide::Buy);
+
+        assert_eq!(fill.slippage_vs(dec!(100), OrderSide::Buy), dec!(1));
+    }
+
+    #[test]
+    fn slippage_of_sell_filled_above_reference_is_negative() {
+        let fill = create_fill(dec!(101), dec!(1), OrderSide::Sell);
+
+        assert_eq!(fill.slippage_vs(dec!(100), OrderSide::Sell), dec!(-1));
+    }
+
+    #[test]
+    fn funding_pnl_and_filled_amount() {
+        let mut order = OrderSnapshot::test_default();
+        order.add_fill(create_fill(dec!(1), dec!(0.4), OrderSide::Buy));
+        // received 0.03 and paid 0.01
+        order.add_fill(create_fill_with_type(
+            OrderFillType::Funding,
+            dec!(0.03),
+            dec!(1),
+            OrderSide::Sell,
+        ));
+        order.add_fill(create_fill_with_type(
+            OrderFillType::Funding,
+            dec!(0.01),
+            dec!(1),
+            OrderSide::Buy,
+        ));
+
+        assert_eq!(compute_funding_pnl(&order.fills.fills), dec!(0.02));
+        assert_eq!(order.filled_amount(), dec!(0.4));
+
+        // funding PnL is kept for summarized fills
+        order.fills.summarize_older_fills(1);
+        assert_eq!(order.fills.funding_pnl(), dec!(0.02));
+    }
+
+    #[test]
+    fn position_from_buy_and_sell_fills() {
+        let fills = vec![
+            create_fill(dec!(1), dec!(3), OrderSide::Buy),
+            create_fill(dec!(1.1), dec!(1.2), OrderSide::Sell),
+            create_fill_with_type(OrderFillType::Funding, dec!(0.03), dec!(5), OrderSide::Buy),
+            create_fill_with_type(
+                OrderFillType::Liquidation,
+                dec!(0.9),
+                dec!(0.5),
+                OrderSide::Sell,
+            ),
+        ];
+
+        let position = position_from_fills(&fills, |fill| fill.side().expect("in test"));
+
+        assert_eq!(position, dec!(1.3));
+    }
 }
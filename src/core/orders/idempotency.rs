@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use parking_lot::Mutex;
+
+use crate::core::DateTime;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SubmissionCheck<TOutcome> {
+    /// Key wasn't seen during TTL, submission can be performed
+    New,
+    /// Key was already submitted during TTL. Contains outcome of previous submission if it is known
+    Duplicate(Option<TOutcome>),
+}
+
+struct Entry<TOutcome> {
+    submitted_time: DateTime,
+    outcome: Option<TOutcome>,
+}
+
+/// Short-lived cache of submitted idempotency keys for preventing duplicate submissions on retries
+pub struct IdempotencyCache<TKey, TOutcome> {
+    ttl: chrono::Duration,
+    entries: Mutex<HashMap<TKey, Entry<TOutcome>>>,
+}
+
+impl<TKey, TOutcome> IdempotencyCache<TKey, TOutcome>
+where
+    TKey: Hash + Eq,
+    TOutcome: Clone,
+{
+    pub fn new(ttl: chrono::Duration) -> Self {
+        IdempotencyCache {
+            ttl,
+            entries: Default::default(),
+        }
+    }
+
+    /// Register submission with specified key if it wasn't seen during TTL
+    pub fn try_submit(&self, key: TKey, now: DateTime) -> SubmissionCheck<TOutcome> {
+        let mut entries = self.entries.lock();
+
+        if let Some(entry) = entries.get(&key) {
+            if now - entry.submitted_time < self.ttl {
+                return SubmissionCheck::Duplicate(entry.outcome.clone());
+            }
+        }
+
+        let _ = entries.insert(
+            key,
+            Entry {
+                submitted_time: now,
+                outcome: None,
+            },
+        );
+
+        SubmissionCheck::New
+    }
+
+    /// Save outcome of submission for returning it to duplicates
+    pub fn set_outcome(&self, key: &TKey, outcome: TOutcome) {
+        if let Some(entry) = self.entries.lock().get_mut(key) {
+            entry.outcome = Some(outcome);
+        }
+    }
+
+    pub fn remove_expired(&self, now: DateTime) {
+        let ttl = self.ttl;
+        self.entries
+            .lock()
+            .retain(|_, entry| now - entry.submitted_time < ttl);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::orders::order::ClientOrderId;
+    use chrono::Utc;
+
+    #[test]
+    fn dedup_same_key_within_ttl() {
+        let cache = IdempotencyCache::<ClientOrderId, String>::new(chrono::Duration::seconds(5));
+        let key = ClientOrderId::unique_id();
+        let now = Utc::now();
+
+        assert_eq!(cache.try_submit(key.clone(), now), SubmissionCheck::New);
+        assert_eq!(
+            cache.try_submit(key.clone(), now + chrono::Duration::seconds(1)),
+            SubmissionCheck::Duplicate(None)
+        );
+
+        cache.set_outcome(&key, "created".to_owned());
+        assert_eq!(
+            cache.try_submit(key, now + chrono::Duration::seconds(2)),
+            SubmissionCheck::Duplicate(Some("created".to_owned()))
+        );
+    }
+
+    #[test]
+    fn allow_same_key_after_ttl() {
+        let cache = IdempotencyCache::<ClientOrderId, String>::new(chrono::Duration::seconds(5));
+        let key = ClientOrderId::unique_id();
+        let now = Utc::now();
+
+        assert_eq!(cache.try_submit(key.clone(), now), SubmissionCheck::New);
+        assert_eq!(
+            cache.try_submit(key.clone(), now + chrono::Duration::seconds(5)),
+            SubmissionCheck::New
+        );
+
+        cache.remove_expired(now + chrono::Duration::seconds(20));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use log::{error, info};
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+
+use crate::core::exchanges::common::ExchangeErrorType;
+use crate::core::lifecycle::trading_engine::Service;
+use crate::core::orders::error::OrderError;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum CreationFailureReason {
+    InsufficientFunds,
+    InvalidOrder,
+    RateLimit,
+    // order parameters are not supported by exchange (e.g. post-only)
+    NotSupported,
+    Other,
+}
+
+impl From<&ExchangeErrorType> for CreationFailureReason {
+    fn from(error_type: &ExchangeErrorType) -> Self {
+        match error_type {
+            ExchangeErrorType::InsufficientFunds => CreationFailureReason::InsufficientFunds,
+            ExchangeErrorType::InvalidOrder => CreationFailureReason::InvalidOrder,
+            ExchangeErrorType::RateLimit => CreationFailureReason::RateLimit,
+            _ => CreationFailureReason::Other,
+        }
+    }
+}
+
+/// Reason of order rejected by local checks before sending to exchange
+impl From<&OrderError> for CreationFailureReason {
+    fn from(error: &OrderError) -> Self {
+        match error {
+            OrderError::UnsupportedOrderType { .. } => CreationFailureReason::NotSupported,
+            OrderError::InvalidAmount { .. }
+            | OrderError::InvalidPrice { .. }
+            | OrderError::PositionLimitExceeded { .. }
+            | OrderError::BelowMinNotional { .. } => CreationFailureReason::InvalidOrder,
+            OrderError::ExchangeRejected { error_type, .. } => error_type.into(),
+            _ => CreationFailureReason::Other,
+        }
+    }
+}
+
+/// Hook for monitoring of orders lifecycle
+pub trait OrderMetricsHook: Send + Sync {
+    fn on_order_rejected(&self, reason: CreationFailureReason);
+}
+
+/// In-memory aggregator of order rejections counts by reason
+#[derive(Default)]
+pub struct OrderRejectionMetrics {
+    counts: Mutex<HashMap<CreationFailureReason, u64>>,
+}
+
+impl OrderRejectionMetrics {
+    pub fn count(&self, reason: CreationFailureReason) -> u64 {
+        self.counts.lock().get(&reason).copied().unwrap_or_default()
+    }
+
+    pub fn counts(&self) -> HashMap<CreationFailureReason, u64> {
+        self.counts.lock().clone()
+    }
+}
+
+impl OrderMetricsHook for OrderRejectionMetrics {
+    fn on_order_rejected(&self, reason: CreationFailureReason) {
+        *self.counts.lock().entry(reason).or_default() += 1;
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    pub rejections_by_reason: HashMap<CreationFailureReason, u64>,
+}
+
+/// Destination where metrics are exported
+pub trait MetricsSink: Send + Sync {
+    fn flush(&self, snapshot: &MetricsSnapshot) -> Result<()>;
+}
+
+/// Writes metrics to log
+pub struct LogMetricsSink;
+
+impl MetricsSink for LogMetricsSink {
+    fn flush(&self, snapshot: &MetricsSnapshot) -> Result<()> {
+        info!("Metrics snapshot: {:?}", snapshot);
+        Ok(())
+    }
+}
+
+/// Flushes final state of metrics to sink on graceful shutdown
+pub struct MetricsFlushService {
+    rejection_metrics: Arc<OrderRejectionMetrics>,
+    sink: Arc<dyn MetricsSink>,
+}
+
+impl MetricsFlushService {
+    pub fn new(
+        rejection_metrics: Arc<OrderRejectionMetrics>,
+        sink: Arc<dyn MetricsSink>,
+    ) -> Arc<Self> {
+        Arc::new(MetricsFlushService {
+            rejection_metrics,
+            sink,
+        })
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            rejections_by_reason: self.rejection_metrics.counts(),
+        }
+    }
+}
+
+impl Service for MetricsFlushService {
+    fn name(&self) -> &str {
+        "MetricsFlushService"
+    }
+
+    fn graceful_shutdown(self: Arc<Self>) -> Option<oneshot::Receiver<Result<()>>> {
+        if let Err(error) = self.sink.flush(&self.snapshot()) {
+            error!("Unable to flush metrics on graceful shutdown: {:?}", error);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::lifecycle::shutdown::ShutdownService;
+
+    #[test]
+    fn counters_increment_independently() {
+        let metrics = OrderRejectionMetrics::default();
+
+        metrics.on_order_rejected(CreationFailureReason::InsufficientFunds);
+        metrics.on_order_rejected(CreationFailureReason::InvalidOrder);
+        metrics.on_order_rejected(CreationFailureReason::InsufficientFunds);
+
+        assert_eq!(metrics.count(CreationFailureReason::InsufficientFunds), 2);
+        assert_eq!(metrics.count(CreationFailureReason::InvalidOrder), 1);
+        assert_eq!(metrics.count(CreationFailureReason::RateLimit), 0);
+    }
+
+    #[derive(Default)]
+    struct RecordingSink {
+        flushed: Mutex<Vec<MetricsSnapshot>>,
+    }
+
+    impl MetricsSink for RecordingSink {
+        fn flush(&self, snapshot: &MetricsSnapshot) -> Result<()> {
+            self.flushed.lock().push(snapshot.clone());
+            Ok(())
+        }
+    }
+
+    #[actix_rt::test]
+    async fn flush_metrics_on_graceful_shutdown() {
+        let metrics = Arc::new(OrderRejectionMetrics::default());
+        let sink = Arc::new(RecordingSink::default());
+        let shutdown_service = Arc::new(ShutdownService::default());
+        shutdown_service.register_service(MetricsFlushService::new(metrics.clone(), sink.clone()));
+
+        metrics.on_order_rejected(CreationFailureReason::RateLimit);
+        metrics.on_order_rejected(CreationFailureReason::RateLimit);
+        assert!(sink.flushed.lock().is_empty());
+
+        let not_dropped_services = shutdown_service.graceful_shutdown().await;
+
+        assert!(not_dropped_services.is_empty());
+        let flushed = sink.flushed.lock();
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(
+            flushed[0].rejections_by_reason[&CreationFailureReason::RateLimit],
+            2
+        );
+    }
+}
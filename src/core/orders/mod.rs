@@ -1,4 +1,17 @@
+pub mod balance;
+pub mod error;
 pub mod event;
+pub mod export;
 pub mod fill;
+pub mod idempotency;
+pub mod metrics;
+pub mod oco;
 pub mod order;
+pub mod order_migrations;
 pub mod pool;
+pub mod pruning;
+pub mod reservation;
+pub mod sla_monitor;
+pub mod split;
+pub mod statistics;
+pub mod store;
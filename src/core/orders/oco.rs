@@ -0,0 +1,370 @@
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::order::{ClientOrderId, OrderStatus};
+use super::pool::OrderRef;
+
+/// Pair of orders with one-cancels-other semantics:
+/// when one of the orders is completed, the other one should be canceled
+pub struct OcoGroup {
+    first: OrderRef,
+    second: OrderRef,
+    is_triggered: Mutex<bool>,
+}
+
+impl OcoGroup {
+    pub fn new(first: OrderRef, second: OrderRef) -> Arc<Self> {
+        Arc::new(Self {
+            first,
+            second,
+            is_triggered: Mutex::new(false),
+        })
+    }
+
+    pub fn orders(&self) -> (&OrderRef, &OrderRef) {
+        (&self.first, &self.second)
+    }
+
+    pub fn contains(&self, client_order_id: &ClientOrderId) -> bool {
+        self.sibling_of(client_order_id).is_some()
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.is_triggered.lock()
+    }
+
+    /// Returns the other order of the group which has to be canceled.
+    /// Only the first completed order triggers the group, so if both orders are filled
+    /// near-simultaneously the second completion returns None and nothing is canceled twice.
+    /// Also returns None if the other order is already finished
+    pub fn on_order_completed(&self, client_order_id: &ClientOrderId) -> Option<OrderRef> {
+        let sibling = self.sibling_of(client_order_id)?;
+
+        let mut is_triggered = self.is_triggered.lock();
+        if *is_triggered {
+            return None;
+        }
+        *is_triggered = true;
+
+        if sibling.is_finished() || sibling.status() == OrderStatus::Canceling {
+            return None;
+        }
+
+        Some(sibling.clone())
+    }
+
+    fn sibling_of(&self, client_order_id: &ClientOrderId) -> Option<&OrderRef> {
+        if &self.first.client_order_id() == client_order_id {
+            Some(&self.second)
+        } else if &self.second.client_order_id() == client_order_id {
+            Some(&self.first)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Default)]
+struct OcoRegistryState {
+    members_by_group: HashMap<Uuid, Vec<ClientOrderId>>,
+    group_by_order: HashMap<ClientOrderId, Uuid>,
+}
+
+/// Links any number of orders by `oco_group_id` so fill of any order of group cancels the other ones.
+/// Group is removed once it's triggered by the first fill or any of its orders is finished
+#[derive(Default)]
+pub struct OcoRegistry {
+    state: Mutex<OcoRegistryState>,
+}
+
+impl OcoRegistry {
+    pub fn register(&self, group_id: Uuid, client_order_id: ClientOrderId) {
+        let mut state = self.state.lock();
+        let members = state.members_by_group.entry(group_id).or_default();
+        if !members.contains(&client_order_id) {
+            members.push(client_order_id.clone());
+        }
+        let _ = state.group_by_order.insert(client_order_id, group_id);
+    }
+
+    pub fn members(&self, group_id: &Uuid) -> Vec<ClientOrderId> {
+        self.state
+            .lock()
+            .members_by_group
+            .get(group_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns other orders of filled order group which should be canceled now.
+    /// Empty if order is out of any group or its group is triggered already
+    pub fn on_fill(&self, client_order_id: &ClientOrderId) -> Vec<ClientOrderId> {
+        self.remove_group_of(client_order_id)
+            .into_iter()
+            .filter(|x| x != client_order_id)
+            .collect()
+    }
+
+    /// Remove group of order which is finished without fills, e.g. canceled or failed to create
+    pub fn on_finished(&self, client_order_id: &ClientOrderId) {
+        let _ = self.remove_group_of(client_order_id);
+    }
+
+    fn remove_group_of(&self, client_order_id: &ClientOrderId) -> Vec<ClientOrderId> {
+        let mut state = self.state.lock();
+        let group_id = match state.group_by_order.get(client_order_id) {
+            Some(group_id) => *group_id,
+            None => return Vec::new(),
+        };
+
+        let members = state.members_by_group.remove(&group_id).unwrap_or_default();
+        for member in &members {
+            let _ = state.group_by_order.remove(member);
+        }
+
+        members
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+    use crate::core::exchanges::general::handlers::handle_order_filled::FillEventData;
+    use crate::core::exchanges::general::test_helper::{create_order_ref, ScriptedExchangeBuilder};
+    use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use crate::core::orders::fill::{EventSourceType, OrderFill, OrderFillType};
+    use crate::core::orders::order::{
+        OrderCreating, OrderFillRole, OrderRole, OrderSide, OrderSnapshot,
+    };
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn create_oco_group() -> (Arc<OcoGroup>, OrderRef, OrderRef) {
+        let exchange_account_id = ExchangeAccountId::new("local_exchange_account_id".into(), 0);
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+
+        let take_profit = create_order_ref(
+            &ClientOrderId::unique_id(),
+            Some(OrderRole::Maker),
+            &exchange_account_id,
+            &currency_pair,
+            dec!(1.2),
+            dec!(10),
+            OrderSide::Sell,
+        );
+        let stop_loss = create_order_ref(
+            &ClientOrderId::unique_id(),
+            Some(OrderRole::Maker),
+            &exchange_account_id,
+            &currency_pair,
+            dec!(0.8),
+            dec!(10),
+            OrderSide::Sell,
+        );
+        take_profit.fn_mut(|order| order.set_status(OrderStatus::Created, Utc::now()));
+        stop_loss.fn_mut(|order| order.set_status(OrderStatus::Created, Utc::now()));
+
+        let group = OcoGroup::new(take_profit.clone(), stop_loss.clone());
+        (group, take_profit, stop_loss)
+    }
+
+    fn fill_completely(order_ref: &OrderRef) {
+        order_ref.fn_mut(|order| {
+            let fill = OrderFill::new(
+                Uuid::new_v4(),
+                Utc::now(),
+                OrderFillType::UserTrade,
+                Some("trade_id".into()),
+                order.price(),
+                order.amount(),
+                order.price() * order.amount(),
+                OrderFillRole::Maker,
+                "BTC".into(),
+                dec!(0),
+                dec!(0),
+                "BTC".into(),
+                dec!(0),
+                dec!(0),
+                false,
+                None,
+                None,
+            );
+            order.add_fill(fill);
+            order.set_status(OrderStatus::Completed, Utc::now());
+        });
+    }
+
+    #[test]
+    fn filling_take_profit_cancels_stop_loss() {
+        let (group, take_profit, stop_loss) = create_oco_group();
+
+        fill_completely(&take_profit);
+        let order_to_cancel = group
+            .on_order_completed(&take_profit.client_order_id())
+            .expect("in test");
+
+        assert_eq!(
+            order_to_cancel.client_order_id(),
+            stop_loss.client_order_id()
+        );
+        assert!(group.is_triggered());
+    }
+
+    #[test]
+    fn both_filled_near_simultaneously() {
+        let (group, take_profit, stop_loss) = create_oco_group();
+
+        fill_completely(&take_profit);
+        fill_completely(&stop_loss);
+
+        // stop loss is already completed so there is nothing to cancel
+        assert!(group
+            .on_order_completed(&take_profit.client_order_id())
+            .is_none());
+        // group was triggered already, so the second completion shouldn't cancel take profit
+        assert!(group
+            .on_order_completed(&stop_loss.client_order_id())
+            .is_none());
+    }
+
+    #[test]
+    fn order_out_of_group_is_ignored() {
+        let (group, _, _) = create_oco_group();
+
+        assert!(group
+            .on_order_completed(&ClientOrderId::unique_id())
+            .is_none());
+        assert!(!group.is_triggered());
+    }
+
+    #[actix_rt::test]
+    async fn fill_of_order_cancels_other_orders_with_same_oco_group_id() {
+        let (exchange, _rx) = ScriptedExchangeBuilder::new(ExchangeAccountId::new(
+            "local_exchange_account_id".into(),
+            0,
+        ))
+        .build();
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let oco_group_id = Uuid::new_v4();
+        let create_order = |price| {
+            let mut header = (*OrderSnapshot::test_default().header).clone();
+            header.exchange_account_id = exchange.exchange_account_id.clone();
+            header.currency_pair = currency_pair.clone();
+            header.side = OrderSide::Sell;
+            header.oco_group_id = Some(oco_group_id);
+            let order_to_create = OrderCreating {
+                header: Arc::new(header),
+                price,
+            };
+            let exchange = exchange.clone();
+            async move {
+                exchange
+                    .create_order(&order_to_create, CancellationToken::default())
+                    .await
+                    .expect("in test")
+            }
+        };
+        let take_profit = create_order(dec!(1.2)).await;
+        let stop_loss = create_order(dec!(0.8)).await;
+        let trailing_stop = create_order(dec!(0.9)).await;
+        assert_eq!(exchange.oco_registry.members(&oco_group_id).len(), 3);
+
+        exchange
+            .handle_order_filled(FillEventData {
+                source_type: EventSourceType::Rest,
+                trade_id: "take_profit_trade".to_owned(),
+                client_order_id: Some(take_profit.client_order_id()),
+                exchange_order_id: take_profit.exchange_order_id().expect("in test"),
+                fill_price: take_profit.price(),
+                fill_amount: take_profit.amount(),
+                is_diff: true,
+                total_filled_amount: None,
+                order_role: Some(OrderRole::Maker),
+                commission_currency_code: None,
+                commission_rate: None,
+                commission_amount: None,
+                fill_type: OrderFillType::UserTrade,
+                trade_currency_pair: Some(currency_pair.clone()),
+                order_side: Some(OrderSide::Sell),
+                order_amount: Some(take_profit.amount()),
+            })
+            .expect("in test");
+        assert_eq!(take_profit.status(), OrderStatus::Completed);
+
+        // siblings are canceled in spawned futures
+        for _ in 0..100 {
+            if stop_loss.status() == OrderStatus::Canceled
+                && trailing_stop.status() == OrderStatus::Canceled
+            {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(stop_loss.status(), OrderStatus::Canceled);
+        assert_eq!(trailing_stop.status(), OrderStatus::Canceled);
+        assert!(exchange.oco_registry.members(&oco_group_id).is_empty());
+    }
+
+    #[test]
+    fn fill_of_registered_order_returns_siblings_once() {
+        let registry = OcoRegistry::default();
+        let group_id = Uuid::new_v4();
+        let take_profit = ClientOrderId::unique_id();
+        let stop_loss = ClientOrderId::unique_id();
+        registry.register(group_id, take_profit.clone());
+        registry.register(group_id, stop_loss.clone());
+        registry.register(Uuid::new_v4(), ClientOrderId::unique_id());
+
+        assert_eq!(
+            registry.members(&group_id),
+            vec![take_profit.clone(), stop_loss.clone()]
+        );
+        assert_eq!(registry.on_fill(&stop_loss), vec![take_profit.clone()]);
+
+        // group is triggered already, so fill of sibling doesn't cancel anything
+        assert!(registry.on_fill(&take_profit).is_empty());
+        assert!(registry.members(&group_id).is_empty());
+    }
+
+    #[test]
+    fn fill_of_order_out_of_registry_is_ignored() {
+        let registry = OcoRegistry::default();
+        registry.register(Uuid::new_v4(), ClientOrderId::unique_id());
+
+        assert!(registry.on_fill(&ClientOrderId::unique_id()).is_empty());
+    }
+
+    #[test]
+    fn fill_of_one_leg_returns_all_other_legs() {
+        let registry = OcoRegistry::default();
+        let group_id = Uuid::new_v4();
+        let legs: Vec<_> = (0..3).map(|_| ClientOrderId::unique_id()).collect();
+        for leg in &legs {
+            registry.register(group_id, leg.clone());
+        }
+
+        assert_eq!(
+            registry.on_fill(&legs[1]),
+            vec![legs[0].clone(), legs[2].clone()]
+        );
+    }
+
+    #[test]
+    fn group_is_removed_when_order_is_finished() {
+        let registry = OcoRegistry::default();
+        let group_id = Uuid::new_v4();
+        let take_profit = ClientOrderId::unique_id();
+        let stop_loss = ClientOrderId::unique_id();
+        registry.register(group_id, take_profit.clone());
+        registry.register(group_id, stop_loss.clone());
+
+        registry.on_finished(&take_profit);
+
+        assert!(registry.members(&group_id).is_empty());
+        assert!(registry.on_fill(&stop_loss).is_empty());
+    }
+}
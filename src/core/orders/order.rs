@@ -1,20 +1,32 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
-use chrono::Utc;
+use anyhow::{bail, Result};
+use chrono::{Duration, Utc};
 use enum_map::Enum;
+use itertools::Itertools;
+use log::{error, warn};
 use nanoid::nanoid;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use rust_decimal_macros::dec;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 use smallstr::SmallString;
 use uuid::Uuid;
 
 use crate::core::exchanges::common::{
-    Amount, CurrencyPair, ExchangeAccountId, ExchangeErrorType, Price,
+    Amount, CurrencyCode, CurrencyPair, ExchangeAccountId, ExchangeErrorType, Price,
 };
-use crate::core::orders::fill::{EventSourceType, OrderFill};
+use crate::core::orders::error::{InvalidStatusTransition, OrderError};
+use crate::core::orders::fill::{
+    compute_funding_pnl, funding_pnl_of, EventSourceType, OrderFill, OrderFillType,
+};
+use crate::core::utils::{deserialize_normalized_decimal, deserialize_normalized_option_decimal};
 use crate::core::DateTime;
 
 type String16 = SmallString<[u8; 16]>;
@@ -99,15 +111,67 @@ pub enum OrderExecutionType {
     MakerOnly = 1,
 }
 
+// prefixed ids should fit inline capacity of String16
+const CLIENT_ORDER_ID_CAPACITY: usize = 16;
+const CLIENT_ORDER_ID_ENV_PREFIX_SEPARATOR: char = '-';
+// random part of prefixed id should stay long enough to keep ids unique
+const MIN_CLIENT_ORDER_ID_RANDOM_LENGTH: usize = 8;
+
+static CLIENT_ORDER_ID_ENV_PREFIX: Lazy<RwLock<String>> = Lazy::new(Default::default);
+
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone, Serialize, Deserialize, Hash)]
 #[serde(transparent)]
 pub struct ClientOrderId(String16);
 
 impl ClientOrderId {
+    /// Generate id prefixed with environment tag set by `set_env_prefix`
     pub fn unique_id() -> Self {
-        let client_order_id_length = 15;
-        let generated = nanoid!(client_order_id_length);
-        ClientOrderId(generated.into())
+        Self::unique_id_with_prefix(&CLIENT_ORDER_ID_ENV_PREFIX.read())
+    }
+
+    // prefix should be checked by `validate_env_prefix` to leave place for random part
+    fn unique_id_with_prefix(env_prefix: &str) -> Self {
+        if env_prefix.is_empty() {
+            let client_order_id_length = 15;
+            let generated = nanoid!(client_order_id_length);
+            return ClientOrderId(generated.into());
+        }
+
+        let random_length = CLIENT_ORDER_ID_CAPACITY - env_prefix.len() - 1;
+        let generated = format!(
+            "{}{}{}",
+            env_prefix,
+            CLIENT_ORDER_ID_ENV_PREFIX_SEPARATOR,
+            nanoid!(random_length)
+        );
+        ClientOrderId(generated.as_str().into())
+    }
+
+    pub fn validate_env_prefix(env_prefix: &str) -> Result<()> {
+        if !env_prefix.chars().all(|x| x.is_ascii_alphanumeric()) {
+            bail!(
+                "Client order id environment prefix '{}' should contain only ASCII letters and digits",
+                env_prefix
+            )
+        }
+
+        let max_length = CLIENT_ORDER_ID_CAPACITY - MIN_CLIENT_ORDER_ID_RANDOM_LENGTH - 1;
+        if env_prefix.len() > max_length {
+            bail!(
+                "Client order id environment prefix '{}' is longer than {} symbols",
+                env_prefix,
+                max_length
+            )
+        }
+
+        Ok(())
+    }
+
+    /// Set environment tag for all ids generated by `unique_id`, empty prefix disables tagging
+    pub fn set_env_prefix(env_prefix: &str) -> Result<()> {
+        Self::validate_env_prefix(env_prefix)?;
+        *CLIENT_ORDER_ID_ENV_PREFIX.write() = env_prefix.to_owned();
+        Ok(())
     }
 
     #[inline]
@@ -180,6 +244,8 @@ pub enum OrderStatus {
     Canceled = 5,
     FailedToCancel = 6,
     Completed = 7,
+    // creation was refused by exchange because of order itself, e.g. post only order would cross
+    Rejected = 8,
 }
 
 impl Default for OrderStatus {
@@ -188,28 +254,102 @@ impl Default for OrderStatus {
     }
 }
 
+/// Status of order reported by exchange. Status which isn't recognized is kept raw for diagnostics
+/// instead of guessing corresponding local status
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub enum ExchangeOrderStatus {
+    Known(OrderStatus),
+    UnknownStatus(String),
+}
+
+impl From<OrderStatus> for ExchangeOrderStatus {
+    fn from(status: OrderStatus) -> Self {
+        ExchangeOrderStatus::Known(status)
+    }
+}
+
 impl OrderStatus {
     pub fn is_finished(&self) -> bool {
         use OrderStatus::*;
-        matches!(*self, FailedToCreate | Canceled | Completed)
+        matches!(*self, FailedToCreate | Canceled | Completed | Rejected)
+    }
+
+    /// Finished statuses can't be changed. Order can be cancelled or filled
+    /// before its creation or cancellation request is confirmed.
+    /// Canceling can be set again because each cancellation attempt sets it
+    pub fn can_change_to(&self, new_status: OrderStatus) -> bool {
+        use OrderStatus::*;
+        match *self {
+            Creating => matches!(
+                new_status,
+                Created | FailedToCreate | Rejected | Canceling | Canceled | Completed
+            ),
+            Created => matches!(
+                new_status,
+                Canceling | Canceled | FailedToCancel | Completed
+            ),
+            Canceling => matches!(
+                new_status,
+                Canceling | Canceled | FailedToCancel | Completed
+            ),
+            FailedToCancel => matches!(new_status, Canceling | Canceled | Completed),
+            FailedToCreate | Canceled | Completed | Rejected => false,
+        }
     }
 }
 
 /// Id for reserved amount
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct ReservationId(u64);
 
+// next reservation id to generate
+static RESERVATION_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
+
 impl ReservationId {
     pub fn generate() -> Self {
-        static RESERVATION_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
-
         let new_id = RESERVATION_ID_COUNTER.fetch_add(1, Ordering::AcqRel);
         ReservationId(new_id)
     }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// The highest generated id, 0 if nothing was generated yet
+    pub fn current() -> u64 {
+        RESERVATION_ID_COUNTER.load(Ordering::Acquire) - 1
+    }
+
+    /// Make next generated ids greater than `value`, e.g. ids of orders restored after restart.
+    /// Counter is never decreased
+    pub fn set_floor(value: u64) {
+        let mut next_id = RESERVATION_ID_COUNTER.load(Ordering::Acquire);
+        while next_id <= value {
+            match RESERVATION_ID_COUNTER.compare_exchange_weak(
+                next_id,
+                value + 1,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return,
+                Err(actual) => next_id = actual,
+            }
+        }
+    }
+}
+
+/// How long order stays active on exchange
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeInForce {
+    #[default]
+    GoodTillCancel,
+    ImmediateOrCancel,
+    FillOrKill,
+    GoodTillTime(DateTime),
 }
 
-pub const CURRENT_ORDER_VERSION: u32 = 1;
+pub const CURRENT_ORDER_VERSION: u32 = 5;
 
 /// Immutable part of order
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -227,6 +367,7 @@ pub struct OrderHeader {
     pub order_type: OrderType,
 
     pub side: OrderSide,
+    #[serde(deserialize_with = "deserialize_normalized_decimal")]
     pub amount: Amount,
 
     pub execution_type: OrderExecutionType,
@@ -235,6 +376,29 @@ pub struct OrderHeader {
 
     pub signal_id: Option<String>,
     pub strategy_name: String,
+
+    // set for child orders which were split from parent order
+    #[serde(default)]
+    pub parent_client_order_id: Option<ClientOrderId>,
+
+    // order can only reduce current position
+    #[serde(default)]
+    pub reduce_only: bool,
+
+    #[serde(default)]
+    pub time_in_force: TimeInForce,
+
+    // order is canceled if it's still not finished after this time since creation
+    #[serde(default)]
+    pub auto_cancel_after: Option<std::time::Duration>,
+
+    // cost basis of position closed by order, realized PnL of fills is accounted against it
+    #[serde(deserialize_with = "deserialize_normalized_option_decimal", default)]
+    pub entry_price: Option<Price>,
+
+    // orders of the same one-cancels-other group, see `OcoRegistry`
+    #[serde(default)]
+    pub oco_group_id: Option<Uuid>,
 }
 
 impl OrderHeader {
@@ -264,21 +428,66 @@ impl OrderHeader {
             reservation_id,
             signal_id,
             strategy_name,
+            parent_client_order_id: None,
+            reduce_only: false,
+            time_in_force: TimeInForce::default(),
+            auto_cancel_after: None,
+            entry_price: None,
+            oco_group_id: None,
         })
     }
 
+    /// Header of limit buy order for 1 eos/btc, the same as in `OrderSnapshot::test_default`
+    #[cfg(test)]
+    pub fn test_default() -> Self {
+        Self {
+            version: CURRENT_ORDER_VERSION,
+            client_order_id: ClientOrderId::unique_id(),
+            init_time: Utc::now(),
+            exchange_account_id: ExchangeAccountId::new("Binance".into(), 0),
+            currency_pair: CurrencyPair::from_codes("eos".into(), "btc".into()),
+            order_type: OrderType::Limit,
+            side: OrderSide::Buy,
+            amount: dec!(1),
+            execution_type: OrderExecutionType::None,
+            reservation_id: None,
+            signal_id: None,
+            strategy_name: "StrategyInUnitTests".to_owned(),
+            parent_client_order_id: None,
+            reduce_only: false,
+            time_in_force: TimeInForce::default(),
+            auto_cancel_after: None,
+            entry_price: None,
+            oco_group_id: None,
+        }
+    }
+
     pub fn version(&self) -> u32 {
         self.version
     }
+
+    /// Only `GoodTillTime` orders expire, other ones are active until exchange finishes them
+    pub fn is_expired(&self, now: DateTime) -> bool {
+        match self.time_in_force {
+            TimeInForce::GoodTillTime(deadline) => now > deadline,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderSimpleProps {
+    #[serde(deserialize_with = "deserialize_normalized_option_decimal", default)]
     pub raw_price: Option<Price>,
     pub role: Option<OrderRole>,
     pub exchange_order_id: Option<ExchangeOrderId>,
+    #[serde(deserialize_with = "deserialize_normalized_decimal")]
     pub stop_loss_price: Decimal,
+    #[serde(deserialize_with = "deserialize_normalized_decimal")]
     pub trailing_stop_delta: Decimal,
+    // trailing stop starts moving only after market price crosses this price
+    #[serde(deserialize_with = "deserialize_normalized_option_decimal", default)]
+    pub activation_price: Option<Price>,
 
     pub status: OrderStatus,
 
@@ -301,6 +510,7 @@ impl OrderSimpleProps {
             exchange_order_id,
             stop_loss_price,
             trailing_stop_delta,
+            activation_price: None,
             status,
             finished_time,
         }
@@ -313,6 +523,7 @@ impl OrderSimpleProps {
             exchange_order_id: None,
             stop_loss_price: Default::default(),
             trailing_stop_delta: Default::default(),
+            activation_price: None,
             status: Default::default(),
             finished_time: None,
         }
@@ -341,13 +552,218 @@ impl From<OrderRole> for OrderFillRole {
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct OrderFills {
     pub fills: Vec<OrderFill>,
+    #[serde(deserialize_with = "deserialize_normalized_decimal")]
     pub filled_amount: Decimal,
+    #[serde(deserialize_with = "deserialize_normalized_decimal", default)]
+    pub realized_pnl: Decimal,
+    // oldest fills which aren't retained individually
+    #[serde(default)]
+    pub summarized: SummarizedFills,
+}
+
+/// Aggregate of fills removed from order to limit memory usage
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SummarizedFills {
+    pub count: usize,
+    // amount and notionals don't include funding fills like other fills aggregates
+    pub amount: Amount,
+    pub maker_notional: Decimal,
+    pub taker_notional: Decimal,
+    pub cost: Decimal,
+    pub commissions: Vec<(CurrencyCode, Decimal)>,
+    // needed to detect redelivered trades after their fills are summarized
+    #[serde(default)]
+    pub trade_ids: HashSet<String>,
+    #[serde(default)]
+    pub amount_by_source: HashMap<EventSourceType, Amount>,
+    #[serde(default)]
+    pub funding_pnl: Decimal,
+}
+
+impl SummarizedFills {
+    fn add(&mut self, fill: &OrderFill) {
+        self.count += 1;
+        self.cost += fill.cost();
+        add_commission(
+            &mut self.commissions,
+            fill.commission_currency_code(),
+            fill.commission_amount(),
+        );
+        if let Some(trade_id) = fill.trade_id() {
+            let _ = self.trade_ids.insert(trade_id.clone());
+        }
+
+        if fill.fill_type() == OrderFillType::Funding {
+            self.funding_pnl += funding_pnl_of(fill);
+            return;
+        }
+
+        self.amount += fill.amount();
+        if let Some(source_type) = fill.event_source_type() {
+            *self.amount_by_source.entry(source_type).or_default() += fill.amount();
+        }
+        let notional = fill.price() * fill.amount();
+        match fill.role() {
+            OrderFillRole::Maker => self.maker_notional += notional,
+            OrderFillRole::Taker => self.taker_notional += notional,
+        }
+    }
+
+    /// Amount weighted price of summarized fills
+    pub fn average_price(&self) -> Option<Decimal> {
+        if self.amount.is_zero() {
+            return None;
+        }
+
+        Some((self.maker_notional + self.taker_notional) / self.amount)
+    }
+}
+
+fn add_commission(
+    commissions: &mut Vec<(CurrencyCode, Decimal)>,
+    currency_code: &CurrencyCode,
+    commission_amount: Decimal,
+) {
+    if commission_amount.is_zero() {
+        return;
+    }
+
+    match commissions.iter_mut().find(|(x, _)| x == currency_code) {
+        Some((_, amount)) => *amount += commission_amount,
+        None => commissions.push((currency_code.clone(), commission_amount)),
+    }
 }
 
 impl OrderFills {
+    pub fn fills(&self) -> &[OrderFill] {
+        &self.fills
+    }
+
     pub fn last_fill_received_time(&self) -> Option<DateTime> {
         self.fills.last().map(|x| x.receive_time())
     }
+
+    /// Whether fill with `trade_id` was added, including summarized fills
+    pub fn contains_trade(&self, trade_id: &str) -> bool {
+        self.summarized.trade_ids.contains(trade_id)
+            || self
+                .fills
+                .iter()
+                .any(|fill| fill.trade_id().map(|x| x.as_str()) == Some(trade_id))
+    }
+
+    /// Retained fills grouped by source they were received from. Fills with unknown source are skipped.
+    /// See `filled_amount_by_source` for amounts including summarized fills
+    pub fn fills_by_source(&self) -> HashMap<EventSourceType, Vec<&OrderFill>> {
+        let mut fills_by_source: HashMap<_, Vec<_>> = HashMap::new();
+        for fill in &self.fills {
+            if let Some(source_type) = fill.event_source_type() {
+                fills_by_source.entry(source_type).or_default().push(fill);
+            }
+        }
+
+        fills_by_source
+    }
+
+    /// Filled amount of all fills including summarized ones grouped by source they were received from.
+    /// Fills with unknown source and funding fills are skipped
+    pub fn filled_amount_by_source(&self) -> HashMap<EventSourceType, Amount> {
+        let mut amount_by_source = self.summarized.amount_by_source.clone();
+        for fill in &self.fills {
+            if fill.fill_type() == OrderFillType::Funding {
+                continue;
+            }
+            if let Some(source_type) = fill.event_source_type() {
+                *amount_by_source.entry(source_type).or_default() += fill.amount();
+            }
+        }
+
+        amount_by_source
+    }
+
+    /// Net PnL from funding payments including summarized fills, see `compute_funding_pnl`
+    pub fn funding_pnl(&self) -> Decimal {
+        self.summarized.funding_pnl + compute_funding_pnl(&self.fills)
+    }
+
+    /// Keep only the last `max_retained` fills aggregating older ones into `summarized`
+    pub fn summarize_older_fills(&mut self, max_retained: usize) {
+        if self.fills.len() <= max_retained {
+            return;
+        }
+
+        let excess = self.fills.len() - max_retained;
+        for fill in self.fills.drain(..excess) {
+            self.summarized.add(&fill);
+        }
+    }
+
+    /// Sum of costs of all fills including summarized ones
+    pub fn total_cost(&self) -> Decimal {
+        self.summarized.cost + self.fills.iter().map(|fill| fill.cost()).sum::<Decimal>()
+    }
+
+    /// Amount weighted average price of fills. Funding fills are skipped
+    pub fn average_fill_price(&self) -> Option<Decimal> {
+        let summarized = &self.summarized;
+        let (notional, amount) = self
+            .fills
+            .iter()
+            .filter(|fill| fill.fill_type() != OrderFillType::Funding)
+            .fold(
+                (
+                    summarized.maker_notional + summarized.taker_notional,
+                    summarized.amount,
+                ),
+                |(notional, amount), fill| {
+                    (
+                        notional + fill.price() * fill.amount(),
+                        amount + fill.amount(),
+                    )
+                },
+            );
+
+        if amount.is_zero() {
+            return None;
+        }
+
+        Some(notional / amount)
+    }
+
+    /// Filled notional `price * amount` split by fill role as (maker, taker). Funding fills are skipped
+    pub fn notional_by_role(&self) -> (Decimal, Decimal) {
+        self.fills
+            .iter()
+            .filter(|fill| fill.fill_type() != OrderFillType::Funding)
+            .fold(
+                (
+                    self.summarized.maker_notional,
+                    self.summarized.taker_notional,
+                ),
+                |(maker, taker), fill| {
+                    let notional = fill.price() * fill.amount();
+                    match fill.role() {
+                        OrderFillRole::Maker => (maker + notional, taker),
+                        OrderFillRole::Taker => (maker, taker + notional),
+                    }
+                },
+            )
+    }
+
+    /// Commission of fills summed separately for each currency in order of their first appearance.
+    /// Fills without commission are skipped
+    pub fn total_commission(&self) -> Vec<(CurrencyCode, Decimal)> {
+        let mut total_commission = self.summarized.commissions.clone();
+        for fill in &self.fills {
+            add_commission(
+                &mut total_commission,
+                fill.commission_currency_code(),
+                fill.commission_amount(),
+            );
+        }
+
+        total_commission
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -362,6 +778,32 @@ pub struct OrderStatusHistory {
     status_changes: Vec<OrderStatusChange>,
 }
 
+impl OrderStatusHistory {
+    /// Sum of intervals between change to `status` and the next status change.
+    /// Only recorded changes are considered, so initial `Creating` status before the first change
+    /// isn't accounted (see `OrderSnapshot::creation_latency` for it). Interval of current status
+    /// isn't counted up to now as it isn't finished yet, so `None` is returned if order never left `status`
+    pub fn time_in_status(&self, status: OrderStatus) -> Option<Duration> {
+        self.status_changes
+            .iter()
+            .tuple_windows()
+            .filter(|(change, _)| change.status == status)
+            .map(|(change, next_change)| next_change.time - change.time)
+            .reduce(|total, interval| total + interval)
+    }
+
+    pub fn first_transition_to(&self, status: OrderStatus) -> Option<DateTime> {
+        self.status_changes
+            .iter()
+            .find(|x| x.status == status)
+            .map(|x| x.time)
+    }
+
+    pub fn last_status(&self) -> Option<OrderStatus> {
+        self.status_changes.last().map(|x| x.status)
+    }
+}
+
 /// Helping properties for trading engine internal use
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct SystemInternalOrderProps {
@@ -369,24 +811,36 @@ pub struct SystemInternalOrderProps {
     pub last_order_creation_status_request_time: Option<DateTime>,
     pub last_creation_error_type: Option<ExchangeErrorType>,
     pub last_creation_error_message: String,
+    // message of exchange if order was rejected
+    #[serde(default)]
+    pub rejection_reason: Option<String>,
 
     pub cancellation_event_source_type: Option<EventSourceType>,
     pub last_order_cancellation_status_request_time: Option<DateTime>,
     pub last_cancellation_error: Option<ExchangeErrorType>,
 
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub is_canceling_from_wait_cancel_order: bool,
 
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub canceled_not_from_wait_cancel_order: bool,
 
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub was_cancellation_event_raised: bool,
 
     pub last_order_trades_request_time: Option<DateTime>,
 
     pub handled_by_balance_recovery: bool,
+    #[serde(deserialize_with = "deserialize_normalized_option_decimal", default)]
     pub filled_amount_after_cancellation: Option<Amount>,
+
+    // failed cancellation attempts
+    #[serde(default)]
+    pub cancellation_attempts: u32,
+
+    // last status reported by exchange which isn't recognized
+    #[serde(default)]
+    pub unknown_exchange_status: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -395,7 +849,7 @@ pub struct OrderInfo {
     pub exchange_order_id: ExchangeOrderId,
     pub client_order_id: ClientOrderId,
     pub order_side: OrderSide,
-    pub order_status: OrderStatus,
+    pub order_status: ExchangeOrderStatus,
     pub price: Price,
     pub amount: Amount,
     pub average_fill_price: Decimal,
@@ -411,7 +865,7 @@ impl OrderInfo {
         exchange_order_id: ExchangeOrderId,
         client_order_id: ClientOrderId,
         order_side: OrderSide,
-        order_status: OrderStatus,
+        order_status: ExchangeOrderStatus,
         price: Price,
         amount: Amount,
         average_fill_price: Decimal,
@@ -449,8 +903,44 @@ pub struct OrderCancelling {
     pub exchange_order_id: ExchangeOrderId,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderAmending {
+    pub header: Arc<OrderHeader>,
+    pub exchange_order_id: ExchangeOrderId,
+    pub new_price: Price,
+    pub new_amount: Amount,
+}
+
+pub const CURRENT_SNAPSHOT_VERSION: u32 = 1;
+
+fn first_snapshot_version() -> u32 {
+    1
+}
+
+/// Snapshots persisted before versioning have no version and are treated as the first one
+fn deserialize_snapshot_version<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let version = u32::deserialize(deserializer)?;
+    if version == 0 || version > CURRENT_SNAPSHOT_VERSION {
+        return Err(D::Error::custom(format!(
+            "Unsupported order snapshot version {}, supported versions are 1..={}",
+            version, CURRENT_SNAPSHOT_VERSION
+        )));
+    }
+
+    Ok(version)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderSnapshot {
+    // for migrations of whole snapshot layout started from 1
+    #[serde(
+        default = "first_snapshot_version",
+        deserialize_with = "deserialize_snapshot_version"
+    )]
+    snapshot_version: u32,
     pub header: Arc<OrderHeader>,
     pub props: OrderSimpleProps,
     pub fills: OrderFills,
@@ -458,6 +948,20 @@ pub struct OrderSnapshot {
     pub internal_props: SystemInternalOrderProps,
 }
 
+/// Part of order snapshot which is worth to persist, internal props are transient
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistableOrder {
+    #[serde(
+        default = "first_snapshot_version",
+        deserialize_with = "deserialize_snapshot_version"
+    )]
+    snapshot_version: u32,
+    pub header: Arc<OrderHeader>,
+    pub props: OrderSimpleProps,
+    pub fills: OrderFills,
+    pub status_history: OrderStatusHistory,
+}
+
 impl OrderSnapshot {
     pub fn new(
         header: Arc<OrderHeader>,
@@ -467,6 +971,7 @@ impl OrderSnapshot {
         internal_props: SystemInternalOrderProps,
     ) -> Self {
         OrderSnapshot {
+            snapshot_version: CURRENT_SNAPSHOT_VERSION,
             header,
             props,
             fills,
@@ -513,20 +1018,207 @@ impl OrderSnapshot {
         )
     }
 
+    /// Minimal valid snapshot for tests: limit buy order for 1 eos/btc by price 1 in Creating status
+    #[cfg(test)]
+    pub fn test_default() -> Self {
+        Self::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            Some(OrderRole::Maker),
+            ExchangeAccountId::new("Binance".into(), 0),
+            CurrencyPair::from_codes("eos".into(), "btc".into()),
+            dec!(1),
+            dec!(1),
+            OrderSide::Buy,
+            None,
+            "StrategyInUnitTests",
+        )
+    }
+
+    pub fn to_persistable(&self) -> PersistableOrder {
+        PersistableOrder {
+            snapshot_version: self.snapshot_version,
+            header: self.header.clone(),
+            props: self.props.clone(),
+            fills: self.fills.clone(),
+            status_history: self.status_history.clone(),
+        }
+    }
+
+    /// Restore snapshot with reset internal props
+    pub fn from_persistable(persistable: PersistableOrder) -> Self {
+        OrderSnapshot {
+            snapshot_version: persistable.snapshot_version,
+            header: persistable.header,
+            props: persistable.props,
+            fills: persistable.fills,
+            status_history: persistable.status_history,
+            internal_props: SystemInternalOrderProps::default(),
+        }
+    }
+
+    pub fn snapshot_version(&self) -> u32 {
+        self.snapshot_version
+    }
+
+    pub fn validate(&self) -> Result<(), OrderError> {
+        let client_order_id = &self.header.client_order_id;
+
+        if self.header.amount <= dec!(0) {
+            return Err(OrderError::InvalidAmount {
+                client_order_id: client_order_id.clone(),
+                amount: self.header.amount,
+            });
+        }
+
+        if self.header.order_type == OrderType::Limit {
+            match self.props.raw_price {
+                Some(price) if price > dec!(0) => {}
+                price => {
+                    return Err(OrderError::InvalidPrice {
+                        client_order_id: client_order_id.clone(),
+                        price,
+                    })
+                }
+            }
+        }
+
+        if self.is_overfilled() {
+            return Err(OrderError::Overfilled {
+                client_order_id: client_order_id.clone(),
+                filled_amount: self.fills.filled_amount,
+                amount: self.header.amount,
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn add_fill(&mut self, fill: OrderFill) {
-        self.fills.filled_amount += fill.amount();
+        // funding payments doesn't change filled amount of order
+        if fill.fill_type() != OrderFillType::Funding {
+            let was_overfilled = self.is_overfilled();
+            self.fills.filled_amount += fill.amount();
+            if !was_overfilled && self.is_overfilled() {
+                error!(
+                    "Order {} is overfilled: filled amount {} exceeds order amount {}",
+                    self.header.client_order_id, self.fills.filled_amount, self.header.amount
+                );
+            }
+
+            if let Some(entry_price) = self.header.entry_price {
+                let price_difference = match fill.side().unwrap_or(self.header.side) {
+                    OrderSide::Buy => entry_price - fill.price(),
+                    OrderSide::Sell => fill.price() - entry_price,
+                };
+                self.fills.realized_pnl += price_difference * fill.amount();
+            }
+        }
         self.fills.fills.push(fill);
     }
 
-    pub fn set_status(&mut self, new_status: OrderStatus, time: DateTime) {
+    /// Running PnL of fills against `entry_price` from header without commissions,
+    /// zero if entry price isn't specified
+    pub fn realized_pnl(&self) -> Decimal {
+        self.fills.realized_pnl
+    }
+
+    pub fn total_commission(&self) -> Vec<(CurrencyCode, Decimal)> {
+        self.fills.total_commission()
+    }
+
+    /// Move stop loss price after market price at `trailing_stop_delta` distance, only in direction of profit.
+    /// If activation price is set, trailing starts once market price crosses it.
+    /// Returns true if stop loss price was changed
+    pub fn update_trailing_stop(&mut self, market_price: Price) -> bool {
+        let side = self.header.side;
+        let props = &mut self.props;
+        if props.trailing_stop_delta <= dec!(0) {
+            return false;
+        }
+
+        if let Some(activation_price) = props.activation_price {
+            let is_activated = match side {
+                OrderSide::Sell => market_price >= activation_price,
+                OrderSide::Buy => market_price <= activation_price,
+            };
+            if !is_activated {
+                return false;
+            }
+
+            props.activation_price = None;
+        }
+
+        let (new_stop_price, is_improved) = match side {
+            OrderSide::Sell => {
+                let new_stop_price = market_price - props.trailing_stop_delta;
+                (new_stop_price, new_stop_price > props.stop_loss_price)
+            }
+            OrderSide::Buy => {
+                let new_stop_price = market_price + props.trailing_stop_delta;
+                (
+                    new_stop_price,
+                    props.stop_loss_price.is_zero() || new_stop_price < props.stop_loss_price,
+                )
+            }
+        };
+
+        if is_improved {
+            props.stop_loss_price = new_stop_price;
+        }
+
+        is_improved
+    }
+
+    pub fn is_overfilled(&self) -> bool {
+        self.fills.filled_amount > self.header.amount
+    }
+
+    /// Change status if it's allowed by order lifecycle, see `OrderStatus::can_change_to`
+    pub fn try_set_status(
+        &mut self,
+        new_status: OrderStatus,
+        time: DateTime,
+    ) -> Result<(), InvalidStatusTransition> {
+        if !self.props.status.can_change_to(new_status) {
+            return Err(InvalidStatusTransition {
+                from: self.props.status,
+                to: new_status,
+            });
+        }
+
         self.props.status = new_status;
+        if new_status.is_finished() {
+            self.props.finished_time = Some(time);
+        }
         self.status_history.status_changes.push(OrderStatusChange {
             id: Uuid::default(),
             status: new_status,
             time,
-        })
+        });
+
+        Ok(())
+    }
+
+    /// Same as `try_set_status` but invalid transition is only logged
+    pub fn set_status(&mut self, new_status: OrderStatus, time: DateTime) {
+        if let Err(error) = self.try_set_status(new_status, time) {
+            error!("{} for order {}", error, self.header.client_order_id);
+        }
+    }
+
+    /// Time from order initialization to its first confirmation (`Created` or finished status).
+    /// `None` if order is still waiting for creation confirmation
+    pub fn creation_latency(&self) -> Option<Duration> {
+        self.status_history
+            .status_changes
+            .iter()
+            .find(|x| x.status == OrderStatus::Created || x.status.is_finished())
+            .map(|x| x.time - self.header.init_time)
     }
 
+    /// Price of limit order. Panics if order has no price which is normal for market orders,
+    /// so use `try_price` or `price_or_error` where order type isn't known
     pub fn price(&self) -> Price {
         let error_msg = format!(
             "Cannot get price from order {}",
@@ -535,13 +1227,765 @@ impl OrderSnapshot {
         self.props.raw_price.expect(&error_msg)
     }
 
+    pub fn try_price(&self) -> Option<Price> {
+        self.props.raw_price
+    }
+
+    pub fn price_or_error(&self) -> Result<Price, OrderError> {
+        self.try_price().ok_or_else(|| OrderError::MissingPrice {
+            client_order_id: self.header.client_order_id.clone(),
+        })
+    }
+
     pub fn amount(&self) -> Amount {
         self.header.amount
     }
     pub fn filled_amount(&self) -> Amount {
         self.fills.filled_amount
     }
+    pub fn average_fill_price(&self) -> Option<Decimal> {
+        self.fills.average_fill_price()
+    }
+    /// Average fill price of order as position entry shifted by fees for entering and exiting position:
+    /// above average price for buy order and below it for sell one.
+    /// Returns `None` if order has no fills
+    pub fn break_even_price(&self, fee_bps: Decimal) -> Option<Decimal> {
+        let average_price = self.average_fill_price()?;
+        let round_trip_fee = dec!(2) * fee_bps / dec!(10000);
+        Some(match self.header.side {
+            OrderSide::Buy => average_price * (dec!(1) + round_trip_fee),
+            OrderSide::Sell => average_price * (dec!(1) - round_trip_fee),
+        })
+    }
+    /// Amount left to fill, zero for overfilled order
+    pub fn remaining_amount(&self) -> Amount {
+        let remaining_amount = self.header.amount - self.fills.filled_amount;
+        if remaining_amount < dec!(0) {
+            warn!(
+                "Order {} filled amount {} exceeds order amount {}, remaining amount is considered zero",
+                self.header.client_order_id, self.fills.filled_amount, self.header.amount
+            );
+            return dec!(0);
+        }
+
+        remaining_amount
+    }
     pub fn status(&self) -> OrderStatus {
         self.props.status
     }
 }
+
+/// Average price of orders weighted by their remaining amounts. Orders without price are skipped.
+/// Returns `None` if total remaining amount is zero
+pub fn weighted_average_price(orders: &[&OrderSnapshot]) -> Option<Decimal> {
+    let (notional, remaining_amount) = orders
+        .iter()
+        .filter_map(|order| Some((order.props.raw_price?, order.remaining_amount())))
+        .fold((dec!(0), dec!(0)), |(notional, total), (price, amount)| {
+            (notional + price * amount, total + amount)
+        });
+
+    if remaining_amount.is_zero() {
+        return None;
+    }
+
+    Some(notional / remaining_amount)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::logger::{init_logger, recent_logs};
+
+    fn create_snapshot(price: Price, amount: Amount) -> OrderSnapshot {
+        OrderSnapshot::with_params(
+            ClientOrderId::new("test".into()),
+            OrderType::Limit,
+            Some(OrderRole::Maker),
+            ExchangeAccountId::new("Binance".into(), 0),
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            price,
+            amount,
+            OrderSide::Buy,
+            None,
+            "StrategyInUnitTests",
+        )
+    }
+
+    #[test]
+    fn generate_client_order_id_with_env_prefix() {
+        let client_order_id = ClientOrderId::unique_id_with_prefix("stg");
+
+        assert!(client_order_id.as_str().starts_with("stg-"));
+        assert_eq!(client_order_id.as_str().len(), CLIENT_ORDER_ID_CAPACITY);
+        assert_ne!(client_order_id, ClientOrderId::unique_id_with_prefix("stg"));
+    }
+
+    #[test]
+    fn reject_too_long_client_order_id_env_prefix() {
+        assert!(ClientOrderId::validate_env_prefix("prod").is_ok());
+        assert!(ClientOrderId::validate_env_prefix("production").is_err());
+        assert!(ClientOrderId::validate_env_prefix("st-g").is_err());
+    }
+
+    #[test]
+    fn weighted_average_price_by_remaining_amount() {
+        let first = create_snapshot(dec!(10), dec!(1));
+        let mut second = create_snapshot(dec!(12), dec!(4));
+        second.add_fill(create_fill(dec!(1), Some(EventSourceType::WebSocket)));
+
+        // remaining amounts are 1 and 3
+        assert_eq!(weighted_average_price(&[&first, &second]), Some(dec!(11.5)));
+        assert_eq!(weighted_average_price(&[]), None);
+    }
+
+    #[test]
+    fn normalize_decimals_on_deserialize() {
+        let snapshot = create_snapshot(dec!(0.50), dec!(2.500));
+        let serialized_props = serde_json::to_string(&snapshot.props).expect("in test");
+        let serialized_header = serde_json::to_string(&snapshot.header).expect("in test");
+        assert!(serialized_props.contains("\"0.50\""));
+
+        let props: OrderSimpleProps = serde_json::from_str(&serialized_props).expect("in test");
+        let header: OrderHeader = serde_json::from_str(&serialized_header).expect("in test");
+
+        let price = props.raw_price.expect("in test");
+        assert_eq!(price, dec!(0.5));
+        assert_eq!(price.to_string(), "0.5");
+        assert_eq!(header.amount.to_string(), "2.5");
+
+        let expected = create_snapshot(dec!(0.5), dec!(2.5));
+        assert_eq!(
+            serde_json::to_value(&props).expect("in test"),
+            serde_json::to_value(&expected.props).expect("in test")
+        );
+        assert_eq!(
+            serde_json::to_value(header.amount).expect("in test"),
+            serde_json::to_value(expected.header.amount).expect("in test")
+        );
+    }
+
+    #[test]
+    fn time_in_force_round_trip() {
+        let deadline = Utc::now();
+        for time_in_force in [
+            TimeInForce::GoodTillCancel,
+            TimeInForce::ImmediateOrCancel,
+            TimeInForce::FillOrKill,
+            TimeInForce::GoodTillTime(deadline),
+        ] {
+            let mut header = (*OrderSnapshot::test_default().header).clone();
+            header.time_in_force = time_in_force;
+
+            let serialized = serde_json::to_string(&header).expect("in test");
+            let deserialized: OrderHeader = serde_json::from_str(&serialized).expect("in test");
+
+            assert_eq!(deserialized.time_in_force, time_in_force);
+        }
+    }
+
+    #[test]
+    fn deserialize_header_without_time_in_force() {
+        let header = OrderSnapshot::test_default().header;
+        let mut value = serde_json::to_value(&header).expect("in test");
+        let _ = value
+            .as_object_mut()
+            .expect("in test")
+            .remove("time_in_force");
+
+        let deserialized: OrderHeader = serde_json::from_value(value).expect("in test");
+
+        assert_eq!(deserialized.time_in_force, TimeInForce::GoodTillCancel);
+        assert!(!deserialized.is_expired(Utc::now()));
+    }
+
+    #[test]
+    fn good_till_time_order_expires_after_deadline() {
+        let deadline = Utc::now();
+        let mut header = (*OrderSnapshot::test_default().header).clone();
+        header.time_in_force = TimeInForce::GoodTillTime(deadline);
+
+        assert!(!header.is_expired(deadline));
+        assert!(header.is_expired(deadline + Duration::milliseconds(1)));
+    }
+
+    #[test]
+    fn deserialize_snapshot_without_version_as_first() {
+        let snapshot = OrderSnapshot::test_default();
+        let mut value = serde_json::to_value(&snapshot).expect("in test");
+        let _ = value
+            .as_object_mut()
+            .expect("in test")
+            .remove("snapshot_version");
+
+        let deserialized: OrderSnapshot = serde_json::from_value(value).expect("in test");
+
+        assert_eq!(deserialized.snapshot_version(), 1);
+        assert_eq!(
+            deserialized.header.client_order_id,
+            snapshot.header.client_order_id
+        );
+    }
+
+    #[test]
+    fn deserialize_snapshot_with_current_version() {
+        let snapshot = OrderSnapshot::test_default();
+        let serialized = serde_json::to_string(&snapshot).expect("in test");
+        assert!(serialized.contains(&format!(
+            "\"snapshot_version\":{}",
+            CURRENT_SNAPSHOT_VERSION
+        )));
+
+        let deserialized: OrderSnapshot = serde_json::from_str(&serialized).expect("in test");
+
+        assert_eq!(deserialized.snapshot_version(), CURRENT_SNAPSHOT_VERSION);
+        assert_eq!(deserialized.status(), snapshot.status());
+    }
+
+    #[test]
+    fn persistable_order_round_trip_without_internal_props() {
+        let mut snapshot = OrderSnapshot::test_default();
+        snapshot.set_status(OrderStatus::Created, Utc::now());
+        snapshot.internal_props.last_creation_error_message = "Timeout".to_owned();
+        snapshot.internal_props.handled_by_balance_recovery = true;
+
+        let value = serde_json::to_value(snapshot.to_persistable()).expect("in test");
+        assert!(value.get("internal_props").is_none());
+
+        let persistable: PersistableOrder = serde_json::from_value(value).expect("in test");
+        let restored = OrderSnapshot::from_persistable(persistable);
+
+        assert_eq!(
+            restored.header.client_order_id,
+            snapshot.header.client_order_id
+        );
+        assert_eq!(restored.status(), OrderStatus::Created);
+        assert_eq!(restored.snapshot_version(), CURRENT_SNAPSHOT_VERSION);
+        assert!(restored
+            .internal_props
+            .last_creation_error_message
+            .is_empty());
+        assert!(!restored.internal_props.handled_by_balance_recovery);
+    }
+
+    #[test]
+    fn error_on_unsupported_snapshot_version() {
+        let snapshot = OrderSnapshot::test_default();
+        let mut value = serde_json::to_value(&snapshot).expect("in test");
+        value["snapshot_version"] = (CURRENT_SNAPSHOT_VERSION + 1).into();
+
+        let error = serde_json::from_value::<OrderSnapshot>(value).expect_err("in test");
+
+        assert!(error
+            .to_string()
+            .contains("Unsupported order snapshot version"));
+    }
+
+    fn create_fill(amount: Amount, source_type: Option<EventSourceType>) -> OrderFill {
+        create_fill_with_params(dec!(1), amount, OrderFillRole::Maker, source_type)
+    }
+
+    fn create_fill_with_role(price: Price, amount: Amount, role: OrderFillRole) -> OrderFill {
+        create_fill_with_params(price, amount, role, Some(EventSourceType::WebSocket))
+    }
+
+    fn create_fill_with_params(
+        price: Price,
+        amount: Amount,
+        role: OrderFillRole,
+        source_type: Option<EventSourceType>,
+    ) -> OrderFill {
+        OrderFill::test_default()
+            .with_price_and_amount(price, amount)
+            .with_role(role)
+            .with_event_source_type(source_type)
+    }
+
+    #[test]
+    fn accumulate_realized_pnl_against_entry_price() {
+        let mut snapshot = OrderSnapshot::test_default();
+        assert_eq!(snapshot.realized_pnl(), dec!(0));
+        Arc::make_mut(&mut snapshot.header).entry_price = Some(dec!(100));
+
+        let fill = |price: Price, amount: Amount, side| {
+            OrderFill::test_default()
+                .with_price_and_amount(price, amount)
+                .with_side(side)
+        };
+        snapshot.add_fill(fill(dec!(98), dec!(2), OrderSide::Buy));
+        assert_eq!(snapshot.realized_pnl(), dec!(4));
+
+        // (103 - 100) * 1 + (100 - 101) * 0.5
+        snapshot.add_fill(fill(dec!(103), dec!(1), OrderSide::Sell));
+        snapshot.add_fill(fill(dec!(101), dec!(0.5), OrderSide::Buy));
+        assert_eq!(snapshot.realized_pnl(), dec!(6.5));
+    }
+
+    #[test]
+    fn total_commission_by_currency() {
+        let mut snapshot = OrderSnapshot::test_default();
+        assert!(snapshot.total_commission().is_empty());
+
+        let fill = |commission_currency_code: &str, commission_amount| {
+            OrderFill::test_default()
+                .with_commission(commission_currency_code.into(), commission_amount)
+        };
+        snapshot.add_fill(fill("BNB", dec!(0.01)));
+        snapshot.add_fill(fill("BTC", dec!(0.002)));
+        snapshot.add_fill(fill("BNB", dec!(0.03)));
+        snapshot.add_fill(fill("ETH", dec!(0)));
+
+        assert_eq!(
+            snapshot.total_commission(),
+            vec![("BNB".into(), dec!(0.04)), ("BTC".into(), dec!(0.002))]
+        );
+    }
+
+    #[test]
+    fn summarize_fills_beyond_retained_count() {
+        let mut snapshot = OrderSnapshot::test_default();
+        for (price, amount) in [
+            (dec!(1), dec!(1)),
+            (dec!(2), dec!(1)),
+            (dec!(4), dec!(2)),
+            (dec!(5), dec!(1)),
+            (dec!(6), dec!(1)),
+        ] {
+            snapshot.add_fill(create_fill_with_role(price, amount, OrderFillRole::Maker));
+            snapshot.fills.summarize_older_fills(2);
+        }
+
+        let fills = &snapshot.fills;
+        assert_eq!(
+            fills.fills().iter().map(|x| x.price()).collect::<Vec<_>>(),
+            vec![dec!(5), dec!(6)]
+        );
+        assert_eq!(fills.summarized.count, 3);
+        assert_eq!(fills.summarized.amount, dec!(4));
+        // (1 + 2 + 4 * 2) / 4
+        assert_eq!(fills.summarized.average_price(), Some(dec!(2.75)));
+        assert_eq!(fills.filled_amount, dec!(6));
+        assert_eq!(fills.total_cost(), dec!(22));
+        assert_eq!(
+            fills.average_fill_price().expect("in test").round_dp(4),
+            dec!(3.6667)
+        );
+        assert_eq!(fills.notional_by_role(), (dec!(22), dec!(0)));
+    }
+
+    #[test]
+    fn summarized_fills_are_accounted() {
+        let mut snapshot = OrderSnapshot::test_default();
+        snapshot.add_fill(create_fill(dec!(0.1), Some(EventSourceType::WebSocket)));
+        snapshot.add_fill(create_fill(dec!(0.2), Some(EventSourceType::Rest)));
+        snapshot.add_fill(create_fill(dec!(0.3), Some(EventSourceType::WebSocket)));
+        let trade_ids = snapshot
+            .fills
+            .fills()
+            .iter()
+            .map(|x| x.trade_id().expect("in test").clone())
+            .collect::<Vec<_>>();
+
+        snapshot.fills.summarize_older_fills(1);
+
+        for trade_id in &trade_ids {
+            assert!(snapshot.fills.contains_trade(trade_id));
+        }
+        assert!(!snapshot.fills.contains_trade("unknown_trade_id"));
+        let amount_by_source = snapshot.fills.filled_amount_by_source();
+        assert_eq!(amount_by_source[&EventSourceType::WebSocket], dec!(0.4));
+        assert_eq!(amount_by_source[&EventSourceType::Rest], dec!(0.2));
+    }
+
+    #[test]
+    fn break_even_price_of_buy_order() {
+        let mut snapshot = OrderSnapshot::test_default();
+        assert_eq!(snapshot.break_even_price(dec!(10)), None);
+
+        snapshot.add_fill(create_fill_with_role(
+            dec!(100),
+            dec!(1),
+            OrderFillRole::Maker,
+        ));
+        snapshot.add_fill(create_fill_with_role(
+            dec!(110),
+            dec!(3),
+            OrderFillRole::Maker,
+        ));
+        snapshot.fills.summarize_older_fills(1);
+
+        // average entry price is 107.5, round-trip fee is 0.2%
+        assert_eq!(snapshot.break_even_price(dec!(10)), Some(dec!(107.715)));
+    }
+
+    #[test]
+    fn fills_grouped_by_source() {
+        let mut snapshot = OrderSnapshot::test_default();
+        snapshot.add_fill(create_fill(dec!(0.1), Some(EventSourceType::WebSocket)));
+        snapshot.add_fill(create_fill(dec!(0.2), Some(EventSourceType::Rest)));
+        snapshot.add_fill(create_fill(dec!(0.3), Some(EventSourceType::WebSocket)));
+        snapshot.add_fill(create_fill(dec!(0.4), None));
+
+        let fills_by_source = snapshot.fills.fills_by_source();
+
+        let amounts = |source_type| {
+            fills_by_source[&source_type]
+                .iter()
+                .map(|fill| fill.amount())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fills_by_source.len(), 2);
+        assert_eq!(
+            amounts(EventSourceType::WebSocket),
+            vec![dec!(0.1), dec!(0.3)]
+        );
+        assert_eq!(amounts(EventSourceType::Rest), vec![dec!(0.2)]);
+    }
+
+    #[test]
+    fn notional_split_by_role() {
+        let mut snapshot = OrderSnapshot::test_default();
+        snapshot.add_fill(create_fill_with_role(
+            dec!(2),
+            dec!(0.1),
+            OrderFillRole::Maker,
+        ));
+        snapshot.add_fill(create_fill_with_role(
+            dec!(3),
+            dec!(0.2),
+            OrderFillRole::Taker,
+        ));
+        snapshot.add_fill(create_fill_with_role(
+            dec!(4),
+            dec!(0.3),
+            OrderFillRole::Maker,
+        ));
+
+        assert_eq!(snapshot.fills.notional_by_role(), (dec!(1.4), dec!(0.6)));
+    }
+
+    #[test]
+    fn average_fill_price_weighted_by_amount() {
+        let mut snapshot = OrderSnapshot::test_default();
+        assert_eq!(snapshot.average_fill_price(), None);
+
+        snapshot.add_fill(create_fill_with_role(
+            dec!(10),
+            dec!(0.1),
+            OrderFillRole::Maker,
+        ));
+        snapshot.add_fill(create_fill_with_role(
+            dec!(12),
+            dec!(0.3),
+            OrderFillRole::Taker,
+        ));
+        snapshot.add_fill(create_fill_with_role(
+            dec!(11),
+            dec!(0.6),
+            OrderFillRole::Maker,
+        ));
+
+        assert_eq!(snapshot.average_fill_price(), Some(dec!(11.2)));
+    }
+
+    #[test]
+    fn trailing_stop_starts_after_activation_price() {
+        let mut snapshot = create_snapshot(dec!(100), dec!(1));
+        let mut header = (*snapshot.header).clone();
+        header.side = OrderSide::Sell;
+        snapshot.header = Arc::new(header);
+        snapshot.props.stop_loss_price = dec!(95);
+        snapshot.props.trailing_stop_delta = dec!(5);
+        snapshot.props.activation_price = Some(dec!(110));
+
+        assert!(!snapshot.update_trailing_stop(dec!(108)));
+        assert_eq!(snapshot.props.stop_loss_price, dec!(95));
+
+        assert!(snapshot.update_trailing_stop(dec!(111)));
+        assert_eq!(snapshot.props.stop_loss_price, dec!(106));
+
+        // keeps trailing after activation even below activation price
+        assert!(!snapshot.update_trailing_stop(dec!(109.5)));
+        assert!(snapshot.update_trailing_stop(dec!(112)));
+        assert_eq!(snapshot.props.stop_loss_price, dec!(107));
+    }
+
+    #[test]
+    fn detect_and_log_overfill() {
+        init_logger();
+        let mut snapshot = OrderSnapshot::test_default();
+        let client_order_id = snapshot.header.client_order_id.clone();
+
+        snapshot.add_fill(create_fill(dec!(0.6), Some(EventSourceType::WebSocket)));
+        assert!(!snapshot.is_overfilled());
+
+        snapshot.add_fill(create_fill(dec!(0.6), Some(EventSourceType::WebSocket)));
+        assert!(snapshot.is_overfilled());
+
+        let expected_message = format!(
+            "Order {} is overfilled: filled amount 1.2 exceeds order amount 1",
+            client_order_id
+        );
+        assert!(recent_logs()
+            .iter()
+            .any(|x| x.level == log::Level::Error && x.message == expected_message));
+    }
+
+    #[test]
+    fn remaining_amount_of_not_filled_order() {
+        let snapshot = OrderSnapshot::test_default();
+
+        assert_eq!(snapshot.filled_amount(), dec!(0));
+        assert_eq!(snapshot.remaining_amount(), dec!(1));
+        assert!(snapshot.fills.fills().is_empty());
+    }
+
+    #[test]
+    fn remaining_amount_of_half_filled_order() {
+        let mut snapshot = OrderSnapshot::test_default();
+        snapshot.add_fill(create_fill(dec!(0.5), Some(EventSourceType::WebSocket)));
+
+        assert_eq!(snapshot.filled_amount(), dec!(0.5));
+        assert_eq!(snapshot.remaining_amount(), dec!(0.5));
+        assert_eq!(snapshot.fills.fills().len(), 1);
+    }
+
+    #[test]
+    fn remaining_amount_of_overfilled_order_is_zero() {
+        init_logger();
+        let mut snapshot = OrderSnapshot::test_default();
+        snapshot.add_fill(create_fill(dec!(0.7), Some(EventSourceType::WebSocket)));
+        snapshot.add_fill(create_fill(dec!(0.7), Some(EventSourceType::WebSocket)));
+
+        assert_eq!(snapshot.filled_amount(), dec!(1.4));
+        assert_eq!(snapshot.remaining_amount(), dec!(0));
+        assert!(recent_logs().iter().any(|x| x.level == log::Level::Warn
+            && x.message.contains("remaining amount is considered zero")));
+    }
+
+    #[test]
+    fn test_default_is_valid() {
+        let snapshot = OrderSnapshot::test_default();
+
+        snapshot.validate().expect("in test");
+        assert_eq!(snapshot.status(), OrderStatus::Creating);
+        assert_eq!(
+            snapshot.header.exchange_account_id,
+            ExchangeAccountId::new("Binance".into(), 0)
+        );
+    }
+
+    #[test]
+    fn validate_rejects_non_positive_amount() {
+        let mut snapshot = OrderSnapshot::test_default();
+        Arc::make_mut(&mut snapshot.header).amount = dec!(0);
+
+        match snapshot.validate() {
+            Err(OrderError::InvalidAmount { amount, .. }) => assert_eq!(amount, dec!(0)),
+            result => panic!("Expected InvalidAmount error but got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn legal_status_transitions() {
+        let mut snapshot = OrderSnapshot::test_default();
+        let time = Utc::now();
+
+        for status in [
+            OrderStatus::Created,
+            OrderStatus::Canceling,
+            OrderStatus::FailedToCancel,
+            OrderStatus::Canceling,
+            OrderStatus::Canceling,
+            OrderStatus::Canceled,
+        ] {
+            snapshot.try_set_status(status, time).expect("in test");
+        }
+
+        assert_eq!(snapshot.status(), OrderStatus::Canceled);
+        assert_eq!(snapshot.status_history.status_changes.len(), 6);
+        assert_eq!(snapshot.props.finished_time, Some(time));
+    }
+
+    #[test]
+    fn generate_unique_reservation_ids_above_floor() {
+        let floor = ReservationId::current() + 1_000;
+        ReservationId::set_floor(floor);
+        // lower floor doesn't decrease counter
+        ReservationId::set_floor(1);
+        assert!(ReservationId::current() >= floor);
+
+        let threads = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    (0..100)
+                        .map(|_| ReservationId::generate().value())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let ids = threads
+            .into_iter()
+            .flat_map(|x| x.join().expect("in test"))
+            .collect::<Vec<_>>();
+
+        assert!(ids.iter().all(|x| *x > floor));
+        assert_eq!(ids.iter().unique().count(), 400);
+        assert!(ReservationId::current() >= floor + 400);
+    }
+
+    #[test]
+    fn rejected_status_is_terminal() {
+        let mut snapshot = OrderSnapshot::test_default();
+        let time = Utc::now();
+
+        snapshot
+            .try_set_status(OrderStatus::Rejected, time)
+            .expect("in test");
+
+        assert!(snapshot.status().is_finished());
+        assert_eq!(snapshot.props.finished_time, Some(time));
+        for status in [
+            OrderStatus::Created,
+            OrderStatus::FailedToCreate,
+            OrderStatus::Canceled,
+            OrderStatus::Completed,
+        ] {
+            assert!(snapshot.try_set_status(status, time).is_err());
+        }
+        assert!(!OrderStatus::Created.can_change_to(OrderStatus::Rejected));
+    }
+
+    #[test]
+    fn deserialize_order_statuses() {
+        let status: OrderStatus = serde_json::from_str("\"FailedToCreate\"").expect("in test");
+        assert_eq!(status, OrderStatus::FailedToCreate);
+
+        let serialized = serde_json::to_string(&OrderStatus::Rejected).expect("in test");
+        assert_eq!(serialized, "\"Rejected\"");
+        let status: OrderStatus = serde_json::from_str(&serialized).expect("in test");
+        assert_eq!(status, OrderStatus::Rejected);
+    }
+
+    #[test]
+    fn reject_illegal_status_transitions() {
+        let mut snapshot = OrderSnapshot::test_default();
+        let time = Utc::now();
+
+        assert_eq!(
+            snapshot.try_set_status(OrderStatus::FailedToCancel, time),
+            Err(InvalidStatusTransition {
+                from: OrderStatus::Creating,
+                to: OrderStatus::FailedToCancel,
+            })
+        );
+
+        snapshot
+            .try_set_status(OrderStatus::Created, time)
+            .expect("in test");
+        assert!(snapshot
+            .try_set_status(OrderStatus::Creating, time)
+            .is_err());
+
+        snapshot
+            .try_set_status(OrderStatus::Completed, time)
+            .expect("in test");
+        for status in [
+            OrderStatus::Creating,
+            OrderStatus::Created,
+            OrderStatus::Canceling,
+            OrderStatus::Canceled,
+        ] {
+            assert!(snapshot.try_set_status(status, time).is_err());
+        }
+
+        // set_status keeps status unchanged on invalid transition
+        snapshot.set_status(OrderStatus::Creating, time);
+        assert_eq!(snapshot.status(), OrderStatus::Completed);
+        assert_eq!(
+            InvalidStatusTransition {
+                from: OrderStatus::Completed,
+                to: OrderStatus::Creating,
+            }
+            .to_string(),
+            "Invalid order status transition from Completed to Creating"
+        );
+    }
+
+    #[test]
+    fn price_of_limit_order() {
+        let snapshot = OrderSnapshot::test_default();
+
+        assert_eq!(snapshot.try_price(), Some(snapshot.price()));
+        assert_eq!(snapshot.price_or_error(), Ok(snapshot.price()));
+    }
+
+    #[test]
+    fn no_price_of_market_order() {
+        let mut snapshot = OrderSnapshot::test_default();
+        let mut header = (*snapshot.header).clone();
+        header.order_type = OrderType::Market;
+        snapshot.header = Arc::new(header);
+        snapshot.props.raw_price = None;
+
+        assert_eq!(snapshot.try_price(), None);
+        assert_eq!(
+            snapshot.price_or_error(),
+            Err(OrderError::MissingPrice {
+                client_order_id: snapshot.header.client_order_id.clone()
+            })
+        );
+    }
+
+    #[test]
+    fn query_status_history() {
+        let mut snapshot = OrderSnapshot::test_default();
+        let init_time = snapshot.header.init_time;
+        let history = &snapshot.status_history;
+        assert_eq!(history.last_status(), None);
+        assert_eq!(history.time_in_status(OrderStatus::Created), None);
+
+        snapshot.set_status(OrderStatus::Created, init_time + Duration::seconds(1));
+        snapshot.set_status(OrderStatus::Canceling, init_time + Duration::seconds(4));
+        snapshot.set_status(
+            OrderStatus::FailedToCancel,
+            init_time + Duration::seconds(5),
+        );
+        snapshot.set_status(OrderStatus::Canceling, init_time + Duration::seconds(7));
+
+        let history = &snapshot.status_history;
+        assert_eq!(history.last_status(), Some(OrderStatus::Canceling));
+        assert_eq!(
+            history.first_transition_to(OrderStatus::Canceling),
+            Some(init_time + Duration::seconds(4))
+        );
+        assert_eq!(history.first_transition_to(OrderStatus::Canceled), None);
+        assert_eq!(
+            history.time_in_status(OrderStatus::Created),
+            Some(Duration::seconds(3))
+        );
+        // current status isn't counted until the next change
+        assert_eq!(
+            history.time_in_status(OrderStatus::Canceling),
+            Some(Duration::seconds(1))
+        );
+    }
+
+    #[test]
+    fn creation_latency_from_first_confirmation() {
+        let mut snapshot = OrderSnapshot::test_default();
+        let init_time = snapshot.header.init_time;
+        assert_eq!(snapshot.creation_latency(), None);
+
+        snapshot.set_status(
+            OrderStatus::Created,
+            init_time + Duration::milliseconds(120),
+        );
+        snapshot.set_status(OrderStatus::Canceled, init_time + Duration::seconds(3));
+
+        assert_eq!(
+            snapshot.creation_latency(),
+            Some(Duration::milliseconds(120))
+        );
+    }
+}
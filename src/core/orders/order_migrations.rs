@@ -0,0 +1,172 @@
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{Map, Value};
+
+use crate::core::orders::order::{OrderHeader, OrderSnapshot, CURRENT_ORDER_VERSION};
+
+const VERSION: &str = "version";
+const HEADER: &str = "header";
+
+/// Upgrades fields of persisted header by one version
+type OrderHeaderMigration = fn(&mut Map<String, Value>) -> Result<()>;
+
+/// Migration with index `i` upgrades header from version `i + 1` to `i + 2`
+const ORDER_HEADER_MIGRATIONS: &[OrderHeaderMigration] = &[
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+];
+
+// version 2 introduced time in force, child orders and reduce only orders
+fn migrate_v1_to_v2(header: &mut Map<String, Value>) -> Result<()> {
+    let _ = header
+        .entry("time_in_force")
+        .or_insert_with(|| "GoodTillCancel".into());
+    let _ = header
+        .entry("parent_client_order_id")
+        .or_insert(Value::Null);
+    let _ = header.entry("reduce_only").or_insert(false.into());
+    Ok(())
+}
+
+// version 3 introduced auto cancellation of orders
+fn migrate_v2_to_v3(header: &mut Map<String, Value>) -> Result<()> {
+    let _ = header.entry("auto_cancel_after").or_insert(Value::Null);
+    Ok(())
+}
+
+// version 4 introduced entry price for realized PnL
+fn migrate_v3_to_v4(header: &mut Map<String, Value>) -> Result<()> {
+    let _ = header.entry("entry_price").or_insert(Value::Null);
+    Ok(())
+}
+
+// version 5 introduced one-cancels-other groups
+fn migrate_v4_to_v5(header: &mut Map<String, Value>) -> Result<()> {
+    let _ = header.entry("oco_group_id").or_insert(Value::Null);
+    Ok(())
+}
+
+/// Deserialize persisted header of any supported version applying migrations up to current one
+pub fn migrate_order_header(mut value: Value) -> Result<OrderHeader> {
+    upgrade_order_header(&mut value)?;
+    serde_json::from_value(value).context("Unable to deserialize migrated order header")
+}
+
+/// Deserialize persisted order snapshot migrating its header up to current version
+pub fn migrate_order_snapshot(mut value: Value) -> Result<OrderSnapshot> {
+    let header = value
+        .get_mut(HEADER)
+        .ok_or(anyhow!("Persisted order snapshot has no header"))?;
+    upgrade_order_header(header)?;
+    serde_json::from_value(value).context("Unable to deserialize migrated order snapshot")
+}
+
+fn upgrade_order_header(value: &mut Value) -> Result<()> {
+    let header = value
+        .as_object_mut()
+        .ok_or(anyhow!("Persisted order header should be an object"))?;
+
+    let version = header
+        .get(VERSION)
+        .and_then(|x| x.as_u64())
+        .ok_or(anyhow!("Persisted order header has no version"))? as u32;
+    if version == 0 || version > CURRENT_ORDER_VERSION {
+        bail!(
+            "Unsupported order header version {}, supported versions are 1..={}",
+            version,
+            CURRENT_ORDER_VERSION
+        )
+    }
+
+    for from_version in version..CURRENT_ORDER_VERSION {
+        let migration = ORDER_HEADER_MIGRATIONS[(from_version - 1) as usize];
+        migration(header).with_context(|| {
+            format!(
+                "Unable to migrate order header from version {} to {}",
+                from_version,
+                from_version + 1
+            )
+        })?;
+        let _ = header.insert(VERSION.to_owned(), (from_version + 1).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::orders::order::TimeInForce;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[test]
+    fn migrations_registered_for_all_versions() {
+        assert_eq!(
+            ORDER_HEADER_MIGRATIONS.len() as u32,
+            CURRENT_ORDER_VERSION - 1
+        );
+    }
+
+    #[test]
+    fn migrate_v1_header() {
+        let v1_header = json!({
+            "version": 1,
+            "client_order_id": "v1OrderId",
+            "init_time": "2021-01-04T12:00:00Z",
+            "exchange_account_id": "Binance0",
+            "currency_pair": "eos/btc",
+            "order_type": "Limit",
+            "side": "Buy",
+            "amount": "1.50",
+            "execution_type": "None",
+            "reservation_id": null,
+            "signal_id": null,
+            "strategy_name": "StrategyInUnitTests"
+        });
+
+        let header = migrate_order_header(v1_header).expect("in test");
+
+        assert_eq!(header.version(), CURRENT_ORDER_VERSION);
+        assert_eq!(header.client_order_id.as_str(), "v1OrderId");
+        assert_eq!(header.amount, dec!(1.5));
+        assert_eq!(header.time_in_force, TimeInForce::GoodTillCancel);
+        assert_eq!(header.parent_client_order_id, None);
+        assert!(!header.reduce_only);
+        assert_eq!(header.auto_cancel_after, None);
+        assert_eq!(header.entry_price, None);
+        assert_eq!(header.oco_group_id, None);
+    }
+
+    #[test]
+    fn migrate_header_of_persisted_snapshot() {
+        let snapshot = OrderSnapshot::test_default();
+        let mut value = serde_json::to_value(&snapshot).expect("in test");
+        let header = value[HEADER].as_object_mut().expect("in test");
+        let _ = header.insert(VERSION.to_owned(), 2.into());
+        let _ = header.remove("auto_cancel_after");
+        let _ = header.remove("entry_price");
+        let _ = header.remove("oco_group_id");
+
+        let migrated = migrate_order_snapshot(value).expect("in test");
+
+        assert_eq!(migrated.header.version(), CURRENT_ORDER_VERSION);
+        assert_eq!(
+            migrated.header.client_order_id,
+            snapshot.header.client_order_id
+        );
+        assert_eq!(migrated.header.oco_group_id, None);
+    }
+
+    #[test]
+    fn reject_header_of_unknown_version() {
+        let error = migrate_order_header(json!({ "version": CURRENT_ORDER_VERSION + 1 }))
+            .expect_err("in test");
+
+        assert_eq!(
+            error.to_string(),
+            "Unsupported order header version 6, supported versions are 1..=5"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use std::borrow::{Borrow, BorrowMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use dashmap::DashMap;
@@ -14,6 +15,7 @@ use crate::core::exchanges::common::{Amount, CurrencyPair, ExchangeAccountId, Tr
 use crate::core::orders::order::{
     ClientOrderId, ExchangeOrderId, OrderHeader, OrderSimpleProps, OrderSnapshot, OrderStatus,
 };
+use crate::core::DateTime;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -42,6 +44,9 @@ impl OrderRef {
     pub fn price(&self) -> Decimal {
         self.fn_ref(|x| x.price())
     }
+    pub fn try_price(&self) -> Option<Decimal> {
+        self.fn_ref(|x| x.try_price())
+    }
     pub fn amount(&self) -> Decimal {
         self.fn_ref(|x| x.header.amount)
     }
@@ -113,6 +118,7 @@ pub struct OrdersPool {
     pub cache_by_client_id: DashMap<ClientOrderId, OrderRef>,
     pub cache_by_exchange_id: DashMap<ExchangeOrderId, OrderRef>,
     pub not_finished: DashMap<ClientOrderId, OrderRef>,
+    pruned_count: AtomicUsize,
     _private: (), // field base constructor shouldn't be accessible from other modules
 }
 
@@ -124,37 +130,191 @@ impl OrdersPool {
             cache_by_client_id: DashMap::with_capacity(ORDERS_INIT_CAPACITY),
             cache_by_exchange_id: DashMap::with_capacity(ORDERS_INIT_CAPACITY),
             not_finished: DashMap::with_capacity(ORDERS_INIT_CAPACITY),
+            pruned_count: AtomicUsize::new(0),
             _private: (),
         })
     }
 
     /// Insert specified `OrderSnapshot` in order pool.
+    /// Snapshot with already assigned exchange order id (e.g. restored one) is indexed by it too
     pub fn add_snapshot_initial(&self, snapshot: Arc<RwLock<OrderSnapshot>>) -> OrderRef {
-        let client_order_id = snapshot.read().header.client_order_id.clone();
+        let (client_order_id, exchange_order_id) = {
+            let snapshot = snapshot.read();
+            (
+                snapshot.header.client_order_id.clone(),
+                snapshot.props.exchange_order_id.clone(),
+            )
+        };
         let order_ref = OrderRef(snapshot.clone());
         let _ = self
             .cache_by_client_id
             .insert(client_order_id.clone(), order_ref.clone());
         let _ = self.not_finished.insert(client_order_id, order_ref.clone());
+        if let Some(exchange_order_id) = exchange_order_id {
+            self.index_by_exchange_id(exchange_order_id, &order_ref);
+        }
 
         order_ref
     }
 
+    /// Index order by exchange order id assigned to it, already indexed order isn't replaced
+    pub fn index_by_exchange_id(&self, exchange_order_id: ExchangeOrderId, order_ref: &OrderRef) {
+        let _ = self
+            .cache_by_exchange_id
+            .entry(exchange_order_id)
+            .or_insert_with(|| order_ref.clone());
+    }
+
     /// Create `OrderSnapshot` by specified `OrderHeader` + order price with default other properties and insert it in order pool.
     pub fn add_simple_initial(&self, header: Arc<OrderHeader>, price: Option<Decimal>) -> OrderRef {
         match self.cache_by_client_id.get(&header.client_order_id) {
             None => {
-                let snapshot = Arc::new(RwLock::new(OrderSnapshot {
-                    props: OrderSimpleProps::from_price(price),
+                let snapshot = Arc::new(RwLock::new(OrderSnapshot::new(
                     header,
-                    fills: Default::default(),
-                    status_history: Default::default(),
-                    internal_props: Default::default(),
-                }));
+                    OrderSimpleProps::from_price(price),
+                    Default::default(),
+                    Default::default(),
+                    Default::default(),
+                )));
 
                 self.add_snapshot_initial(snapshot)
             }
             Some(order_ref) => order_ref.clone(),
         }
     }
+
+    /// Remove from pool finished orders which were finished before `now - retention`.
+    /// Orders should be already saved to persistent storage because they can't be found in pool after pruning.
+    /// Returns count of pruned orders
+    pub fn prune_finished_orders(&self, retention: chrono::Duration, now: DateTime) -> usize {
+        let threshold = now - retention;
+
+        let orders_to_prune = self
+            .cache_by_client_id
+            .iter()
+            .filter(|order_ref| {
+                order_ref.fn_ref(move |order| {
+                    order.props.is_finished()
+                        && order
+                            .props
+                            .finished_time
+                            .is_some_and(|finished_time| finished_time <= threshold)
+                })
+            })
+            .map(|order_ref| order_ref.value().clone())
+            .collect::<Vec<_>>();
+
+        for order_ref in &orders_to_prune {
+            let _ = self.cache_by_client_id.remove(&order_ref.client_order_id());
+            let _ = self.not_finished.remove(&order_ref.client_order_id());
+            if let Some(exchange_order_id) = order_ref.exchange_order_id() {
+                let _ = self.cache_by_exchange_id.remove(&exchange_order_id);
+            }
+        }
+
+        let pruned = orders_to_prune.len();
+        self.pruned_count.fetch_add(pruned, Ordering::Relaxed);
+
+        pruned
+    }
+
+    /// Total count of orders pruned from pool
+    pub fn pruned_count(&self) -> usize {
+        self.pruned_count.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::orders::order::OrderType;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    fn add_order(orders_pool: &OrdersPool, exchange_order_id: &str) -> OrderRef {
+        let order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            Some(OrderRole::Maker),
+            ExchangeAccountId::new("local_exchange_account_id".into(), 0),
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            dec!(1),
+            dec!(10),
+            OrderSide::Buy,
+            None,
+            "StrategyInUnitTests",
+        );
+
+        let order_ref = orders_pool.add_snapshot_initial(Arc::new(RwLock::new(order)));
+        order_ref.fn_mut(|order| order.props.exchange_order_id = Some(exchange_order_id.into()));
+        let _ = orders_pool
+            .cache_by_exchange_id
+            .insert(exchange_order_id.into(), order_ref.clone());
+
+        order_ref
+    }
+
+    #[test]
+    fn prune_only_old_finished_orders() {
+        let orders_pool = OrdersPool::new();
+        let now = Utc::now();
+        let retention = chrono::Duration::hours(1);
+
+        let old_completed = add_order(&orders_pool, "1");
+        old_completed.fn_mut(|order| {
+            order.set_status(OrderStatus::Completed, now - chrono::Duration::hours(2))
+        });
+
+        let old_canceled = add_order(&orders_pool, "2");
+        old_canceled.fn_mut(|order| {
+            order.set_status(OrderStatus::Canceled, now - chrono::Duration::hours(3))
+        });
+
+        let recently_completed = add_order(&orders_pool, "3");
+        recently_completed.fn_mut(|order| {
+            order.set_status(OrderStatus::Completed, now - chrono::Duration::minutes(5))
+        });
+
+        let live = add_order(&orders_pool, "4");
+        live.fn_mut(|order| {
+            order.set_status(OrderStatus::Created, now - chrono::Duration::hours(5))
+        });
+
+        let pruned = orders_pool.prune_finished_orders(retention, now);
+
+        assert_eq!(pruned, 2);
+        assert_eq!(orders_pool.pruned_count(), 2);
+
+        let remaining = [&recently_completed, &live];
+        assert_eq!(orders_pool.cache_by_client_id.len(), remaining.len());
+        assert_eq!(orders_pool.cache_by_exchange_id.len(), remaining.len());
+        for order_ref in remaining.iter() {
+            assert!(orders_pool
+                .cache_by_client_id
+                .contains_key(&order_ref.client_order_id()));
+        }
+        assert!(orders_pool
+            .not_finished
+            .contains_key(&live.client_order_id()));
+
+        let pruned = orders_pool.prune_finished_orders(retention, now);
+        assert_eq!(pruned, 0);
+        assert_eq!(orders_pool.pruned_count(), 2);
+    }
+
+    #[test]
+    fn index_restored_snapshot_by_exchange_order_id() {
+        let orders_pool = OrdersPool::new();
+        let mut order = OrderSnapshot::test_default();
+        order.props.exchange_order_id = Some("restored".into());
+
+        let order_ref = orders_pool.add_snapshot_initial(Arc::new(RwLock::new(order)));
+
+        let indexed = orders_pool
+            .cache_by_exchange_id
+            .get(&"restored".into())
+            .expect("in test")
+            .client_order_id();
+        assert_eq!(indexed, order_ref.client_order_id());
+    }
 }
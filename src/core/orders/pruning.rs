@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OrdersPruningSettings {
+    // finished orders are kept in orders pool during this time since finishing
+    pub retention_ms: i64,
+    pub prune_period_ms: u64,
+}
+
+/// Periodically remove from orders pools of exchanges finished orders older than retention
+pub async fn prune_finished_orders_periodically(
+    exchanges: Vec<Arc<Exchange>>,
+    settings: OrdersPruningSettings,
+    cancellation_token: CancellationToken,
+) -> Result<()> {
+    let retention = chrono::Duration::milliseconds(settings.retention_ms);
+    let prune_period = Duration::from_millis(settings.prune_period_ms);
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(prune_period) => {}
+            _ = cancellation_token.when_cancelled() => return Ok(()),
+        }
+
+        for exchange in &exchanges {
+            let pruned = exchange
+                .orders
+                .prune_finished_orders(retention, exchange.now());
+            if pruned > 0 {
+                info!(
+                    "Pruned {} finished orders from orders pool of {}, {} in total",
+                    pruned,
+                    exchange.exchange_account_id,
+                    exchange.orders.pruned_count()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::general::test_helper::get_test_exchange;
+    use crate::core::orders::order::{OrderSnapshot, OrderStatus};
+    use parking_lot::RwLock;
+
+    #[actix_rt::test]
+    async fn prune_old_finished_orders_periodically() {
+        let (exchange, _rx) = get_test_exchange(false);
+        let add_order = |status, finished_ago| {
+            let mut order = OrderSnapshot::test_default();
+            order.set_status(status, exchange.now() - finished_ago);
+            exchange
+                .orders
+                .add_snapshot_initial(Arc::new(RwLock::new(order)))
+        };
+        let old_completed = add_order(OrderStatus::Completed, chrono::Duration::hours(2));
+        let recently_canceled = add_order(OrderStatus::Canceled, chrono::Duration::minutes(5));
+
+        let cancellation_token = CancellationToken::new();
+        let handle = tokio::spawn(prune_finished_orders_periodically(
+            vec![exchange.clone()],
+            OrdersPruningSettings {
+                retention_ms: chrono::Duration::hours(1).num_milliseconds(),
+                prune_period_ms: 10,
+            },
+            cancellation_token.clone(),
+        ));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancellation_token.cancel();
+        handle.await.expect("in test").expect("in test");
+
+        let orders = &exchange.orders.cache_by_client_id;
+        assert!(!orders.contains_key(&old_completed.client_order_id()));
+        assert!(orders.contains_key(&recently_canceled.client_order_id()));
+        assert_eq!(exchange.orders.pruned_count(), 1);
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use log::warn;
+use parking_lot::Mutex;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use super::order::ReservationId;
+use crate::core::exchanges::common::Amount;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ReservationSettings {
+    // Outstanding reservations count above which probably some reservations are never released
+    pub leak_warning_threshold: usize,
+}
+
+impl Default for ReservationSettings {
+    fn default() -> Self {
+        ReservationSettings {
+            leak_warning_threshold: 1000,
+        }
+    }
+}
+
+/// Tracks outstanding (not released yet) reservations to detect reservation leaks
+pub struct ReservationRegistry {
+    reservations: Mutex<HashMap<ReservationId, Amount>>,
+    leak_warning_threshold: usize,
+}
+
+impl ReservationRegistry {
+    pub fn new(settings: &ReservationSettings) -> Arc<Self> {
+        Arc::new(ReservationRegistry {
+            reservations: Default::default(),
+            leak_warning_threshold: settings.leak_warning_threshold,
+        })
+    }
+
+    pub fn reserve(&self, amount: Amount) -> ReservationId {
+        let reservation_id = ReservationId::generate();
+
+        let outstanding_count = {
+            let mut reservations = self.reservations.lock();
+            let _ = reservations.insert(reservation_id.clone(), amount);
+            reservations.len()
+        };
+
+        if outstanding_count > self.leak_warning_threshold {
+            warn!(
+                "Outstanding reservations count {} exceeds threshold {}. Probably some reservations are never released",
+                outstanding_count, self.leak_warning_threshold
+            );
+        }
+
+        reservation_id
+    }
+
+    /// Returns amount of released reservation or None if reservation is unknown
+    pub fn release(&self, reservation_id: &ReservationId) -> Option<Amount> {
+        self.reservations.lock().remove(reservation_id)
+    }
+
+    pub fn outstanding_count(&self) -> usize {
+        self.reservations.lock().len()
+    }
+
+    pub fn outstanding_total(&self) -> Amount {
+        self.reservations
+            .lock()
+            .values()
+            .fold(dec!(0), |total, amount| total + amount)
+    }
+
+    pub fn is_leak_suspected(&self) -> bool {
+        self.outstanding_count() > self.leak_warning_threshold
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reserve_without_release() {
+        let registry = ReservationRegistry::new(&ReservationSettings {
+            leak_warning_threshold: 2,
+        });
+
+        let _ = registry.reserve(dec!(1));
+        let _ = registry.reserve(dec!(2.5));
+        assert_eq!(registry.outstanding_count(), 2);
+        assert!(!registry.is_leak_suspected());
+
+        let _ = registry.reserve(dec!(3));
+        assert_eq!(registry.outstanding_count(), 3);
+        assert_eq!(registry.outstanding_total(), dec!(6.5));
+        assert!(registry.is_leak_suspected());
+    }
+
+    #[test]
+    fn release_reservation() {
+        let registry = ReservationRegistry::new(&ReservationSettings::default());
+
+        let first = registry.reserve(dec!(1));
+        let second = registry.reserve(dec!(2));
+
+        assert_eq!(registry.release(&first), Some(dec!(1)));
+        assert_eq!(registry.release(&first), None);
+        assert_eq!(registry.outstanding_count(), 1);
+        assert_eq!(registry.outstanding_total(), dec!(2));
+
+        assert_eq!(registry.release(&second), Some(dec!(2)));
+        assert_eq!(registry.outstanding_count(), 0);
+        assert_eq!(registry.outstanding_total(), dec!(0));
+    }
+}
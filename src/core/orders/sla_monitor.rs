@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Duration;
+use log::warn;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::core::exchanges::common::ExchangeAccountId;
+use crate::core::exchanges::events::ExchangeEvent;
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::lifecycle::trading_engine::Service;
+use crate::core::orders::order::ClientOrderId;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OrderSlaSettings {
+    // max time order can stay live before alert is raised
+    pub max_lifetime_ms: i64,
+    pub check_period_ms: u64,
+}
+
+/// Order stays live longer than allowed by SLA
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderSlaAlert {
+    pub exchange_account_id: ExchangeAccountId,
+    pub client_order_id: ClientOrderId,
+    pub lifetime: Duration,
+    pub max_lifetime: Duration,
+}
+
+/// Periodically scans live orders and raises alert once for each order exceeding SLA.
+/// Alerts are published to events channel of exchange. Orders are not cancelled by monitor
+pub struct OrderSlaMonitor {
+    max_lifetime: Duration,
+    alerted_orders: Mutex<HashMap<ExchangeAccountId, HashSet<ClientOrderId>>>,
+    work_finished_receiver: Mutex<Option<oneshot::Receiver<Result<()>>>>,
+}
+
+impl OrderSlaMonitor {
+    pub fn new(max_lifetime: Duration) -> Arc<Self> {
+        Arc::new(OrderSlaMonitor {
+            max_lifetime,
+            alerted_orders: Default::default(),
+            work_finished_receiver: Default::default(),
+        })
+    }
+
+    pub fn check(&self, exchange: &Exchange) -> Vec<OrderSlaAlert> {
+        let now = exchange.now();
+        let mut alerted_orders_by_exchange = self.alerted_orders.lock();
+        let alerted_orders = alerted_orders_by_exchange
+            .entry(exchange.exchange_account_id.clone())
+            .or_default();
+        alerted_orders.retain(|x| exchange.orders.not_finished.contains_key(x));
+
+        let mut alerts = Vec::new();
+        for order in exchange.orders.not_finished.iter() {
+            if order.is_finished() || alerted_orders.contains(order.key()) {
+                continue;
+            }
+
+            let lifetime = now - order.fn_ref(|x| x.header.init_time);
+            if lifetime <= self.max_lifetime {
+                continue;
+            }
+
+            let alert = OrderSlaAlert {
+                exchange_account_id: exchange.exchange_account_id.clone(),
+                client_order_id: order.key().clone(),
+                lifetime,
+                max_lifetime: self.max_lifetime,
+            };
+            warn!(
+                "Order {} on {} is live for {}ms exceeding SLA {}ms",
+                alert.client_order_id,
+                alert.exchange_account_id,
+                lifetime.num_milliseconds(),
+                self.max_lifetime.num_milliseconds()
+            );
+
+            let _ = alerted_orders.insert(alert.client_order_id.clone());
+            // there can be no subscribers to events
+            let _ = exchange
+                .events_channel
+                .send(ExchangeEvent::OrderSlaAlert(alert.clone()));
+            alerts.push(alert);
+        }
+
+        alerts
+    }
+
+    pub async fn start(
+        self: Arc<Self>,
+        exchanges: Vec<Arc<Exchange>>,
+        check_period: std::time::Duration,
+        cancellation_token: CancellationToken,
+    ) -> Result<()> {
+        let (work_finished_sender, receiver) = oneshot::channel();
+        *self.work_finished_receiver.lock() = Some(receiver);
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(check_period) => {}
+                _ = cancellation_token.when_cancelled() => {
+                    let _ = work_finished_sender.send(Ok(()));
+                    return Ok(());
+                }
+            }
+
+            for exchange in &exchanges {
+                let _ = self.check(exchange);
+            }
+        }
+    }
+}
+
+impl Service for OrderSlaMonitor {
+    fn name(&self) -> &str {
+        "OrderSlaMonitor"
+    }
+
+    fn graceful_shutdown(self: Arc<Self>) -> Option<oneshot::Receiver<Result<()>>> {
+        let work_finished_receiver = self.work_finished_receiver.lock().take();
+        if work_finished_receiver.is_none() {
+            warn!("'work_finished_receiver' wasn't created when started graceful shutdown in OrderSlaMonitor");
+        }
+
+        work_finished_receiver
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::clock::SimulatedClock;
+    use crate::core::exchanges::general::test_helper::{
+        get_test_exchange, ScriptedExchangeBuilder,
+    };
+    use crate::core::orders::order::OrderSnapshot;
+    use crate::core::orders::pool::OrderRef;
+    use chrono::{TimeZone, Utc};
+    use parking_lot::RwLock;
+
+    fn add_order_initiated_at(exchange: &Exchange, init_time: chrono::DateTime<Utc>) -> OrderRef {
+        let mut order = OrderSnapshot::test_default();
+        let mut header = (*order.header).clone();
+        header.init_time = init_time;
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        order.header = Arc::new(header);
+        exchange
+            .orders
+            .add_snapshot_initial(Arc::new(RwLock::new(order)))
+    }
+
+    #[test]
+    fn alert_once_for_stale_order() {
+        let (exchange, mut events_rx) = get_test_exchange(false);
+        let start = Utc.ymd(2021, 1, 4).and_hms(12, 0, 0);
+        let clock = Arc::new(SimulatedClock::new(start));
+        exchange.set_clock(clock.clone());
+
+        let order_ref = add_order_initiated_at(&exchange, start);
+
+        let monitor = OrderSlaMonitor::new(Duration::seconds(30));
+
+        clock.advance(Duration::seconds(10));
+        assert!(monitor.check(&exchange).is_empty());
+
+        clock.advance(Duration::seconds(25));
+        let alerts = monitor.check(&exchange);
+        assert_eq!(alerts.len(), 1);
+        let alert = match events_rx.try_recv().expect("in test") {
+            ExchangeEvent::OrderSlaAlert(alert) => alert,
+            event => panic!("Unexpected event {:?}", event),
+        };
+        assert_eq!(alert.client_order_id, order_ref.client_order_id());
+        assert_eq!(alert.lifetime, Duration::seconds(35));
+
+        clock.advance(Duration::seconds(10));
+        assert!(monitor.check(&exchange).is_empty());
+        // monitor doesn't cancel stale orders
+        assert!(!order_ref.is_finished());
+    }
+
+    #[test]
+    fn alert_once_for_stale_orders_of_several_exchanges() {
+        let start = Utc.ymd(2021, 1, 4).and_hms(12, 0, 0);
+        let clock = Arc::new(SimulatedClock::new(start));
+        let exchanges = ["Binance0", "Binance1"]
+            .iter()
+            .map(|x| {
+                let (exchange, _rx) =
+                    ScriptedExchangeBuilder::new(x.parse().expect("in test")).build();
+                exchange.set_clock(clock.clone());
+                let _ = add_order_initiated_at(&exchange, start);
+                exchange
+            })
+            .collect::<Vec<_>>();
+
+        let monitor = OrderSlaMonitor::new(Duration::seconds(30));
+        clock.advance(Duration::seconds(35));
+        for exchange in &exchanges {
+            assert_eq!(monitor.check(exchange).len(), 1);
+        }
+
+        // checking one exchange shouldn't forget alerts raised for another one
+        clock.advance(Duration::seconds(10));
+        for exchange in &exchanges {
+            assert!(monitor.check(exchange).is_empty());
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::{bail, Result};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::order::{ClientOrderId, OrderHeader};
+
+/// Split parent order into child orders with amount not greater than chunk.
+/// The last child takes the remainder, so children amounts sum up to parent amount.
+/// Each child gets unique client order id and reference to parent order
+pub fn split_order(header: &OrderHeader, chunk: Decimal) -> Result<Vec<OrderHeader>> {
+    if chunk <= dec!(0) {
+        bail!(
+            "Unable to split order {}: chunk should be positive but got {}",
+            header.client_order_id,
+            chunk
+        )
+    }
+
+    let mut children = Vec::new();
+    let mut remaining_amount = header.amount;
+    while remaining_amount > dec!(0) {
+        let amount = remaining_amount.min(chunk);
+        remaining_amount -= amount;
+
+        let mut child = header.clone();
+        child.client_order_id = ClientOrderId::unique_id();
+        child.amount = amount;
+        child.parent_client_order_id = Some(header.client_order_id.clone());
+
+        children.push(child);
+    }
+
+    Ok(children)
+}
+
+/// Split parent order into `children_count` child orders with equal amounts rounded down to `amount_step`.
+/// Rounding remainder goes to the last child, so children amounts sum up to parent amount exactly
+pub fn split_order_evenly(
+    header: &OrderHeader,
+    children_count: usize,
+    amount_step: Decimal,
+) -> Result<Vec<OrderHeader>> {
+    if children_count == 0 || amount_step <= dec!(0) {
+        bail!(
+            "Unable to split order {} into {} children with amount step {}",
+            header.client_order_id,
+            children_count,
+            amount_step
+        )
+    }
+
+    let child_amount =
+        (header.amount / Decimal::from(children_count) / amount_step).floor() * amount_step;
+    if child_amount <= dec!(0) {
+        bail!(
+            "Unable to split order {}: amount {} is too small for {} children with amount step {}",
+            header.client_order_id,
+            header.amount,
+            children_count,
+            amount_step
+        )
+    }
+
+    let last_child_amount = header.amount - child_amount * Decimal::from(children_count - 1);
+    let children = (0..children_count)
+        .map(|index| {
+            let mut child = header.clone();
+            child.client_order_id = ClientOrderId::unique_id();
+            child.amount = match index == children_count - 1 {
+                true => last_child_amount,
+                false => child_amount,
+            };
+            child.parent_client_order_id = Some(header.client_order_id.clone());
+            child
+        })
+        .collect();
+
+    Ok(children)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+    use crate::core::orders::order::{OrderExecutionType, OrderSide, OrderType};
+    use chrono::Utc;
+    use itertools::Itertools;
+    use std::sync::Arc;
+
+    fn create_header(amount: Decimal) -> Arc<OrderHeader> {
+        OrderHeader::new(
+            ClientOrderId::unique_id(),
+            Utc::now(),
+            ExchangeAccountId::new("local_exchange_account_id".into(), 0),
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            OrderType::Limit,
+            OrderSide::Buy,
+            amount,
+            OrderExecutionType::None,
+            None,
+            None,
+            "StrategyInUnitTests".to_owned(),
+        )
+    }
+
+    #[test]
+    fn split_with_remainder() {
+        let header = create_header(dec!(1.0));
+
+        let children = split_order(&header, dec!(0.3)).expect("in test");
+
+        let amounts = children.iter().map(|x| x.amount).collect_vec();
+        assert_eq!(amounts, vec![dec!(0.3), dec!(0.3), dec!(0.3), dec!(0.1)]);
+        assert_eq!(amounts.iter().sum::<Decimal>(), header.amount);
+
+        let client_order_ids = children.iter().map(|x| &x.client_order_id).unique();
+        assert_eq!(client_order_ids.count(), children.len());
+        assert!(children
+            .iter()
+            .all(|x| x.client_order_id != header.client_order_id
+                && x.parent_client_order_id == Some(header.client_order_id.clone())));
+    }
+
+    #[test]
+    fn split_with_non_positive_chunk() {
+        let header = create_header(dec!(1.0));
+
+        assert!(split_order(&header, dec!(0)).is_err());
+    }
+
+    #[test]
+    fn split_evenly_preserves_total_amount() {
+        let header = create_header(dec!(1.005));
+
+        let children = split_order_evenly(&header, 3, dec!(0.01)).expect("in test");
+
+        let amounts = children.iter().map(|x| x.amount).collect_vec();
+        assert_eq!(amounts, vec![dec!(0.33), dec!(0.33), dec!(0.345)]);
+        assert_eq!(amounts.iter().sum::<Decimal>(), header.amount);
+        assert!(amounts[..2]
+            .iter()
+            .all(|x| (x / dec!(0.01)).fract().is_zero()));
+        // last child bears rounding remainder which is less than step
+        let last_remainder = amounts[2] - (amounts[2] / dec!(0.01)).floor() * dec!(0.01);
+        assert!(last_remainder < dec!(0.01));
+    }
+
+    #[test]
+    fn split_evenly_with_too_small_amount() {
+        let header = create_header(dec!(0.02));
+
+        assert!(split_order_evenly(&header, 3, dec!(0.01)).is_err());
+        assert!(split_order_evenly(&header, 0, dec!(0.01)).is_err());
+    }
+}
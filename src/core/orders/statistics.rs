@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::fill::OrderFillType;
+use super::order::{OrderSnapshot, OrderStatus};
+
+/// Aggregated summary over set of orders
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderStats {
+    pub counts_by_status: HashMap<OrderStatus, usize>,
+    /// Sum of fills cost in quote currency (funding payments are not included)
+    pub filled_notional: Decimal,
+    /// Count of orders which are not finished yet
+    pub live_count: usize,
+    /// Mean of filled_amount / amount over orders with positive amount
+    pub average_fill_ratio: Decimal,
+}
+
+pub fn order_statistics(orders: &[OrderSnapshot]) -> OrderStats {
+    let mut stats = OrderStats::default();
+    let mut fill_ratio_sum = dec!(0);
+    let mut fill_ratio_count = 0u32;
+
+    for order in orders {
+        let status = order.status();
+        *stats.counts_by_status.entry(status).or_default() += 1;
+
+        if !status.is_finished() {
+            stats.live_count += 1;
+        }
+
+        stats.filled_notional += order
+            .fills
+            .fills
+            .iter()
+            .filter(|fill| fill.fill_type() != OrderFillType::Funding)
+            .map(|fill| fill.cost())
+            .sum::<Decimal>();
+
+        if order.amount() > dec!(0) {
+            fill_ratio_sum += order.filled_amount() / order.amount();
+            fill_ratio_count += 1;
+        }
+    }
+
+    if fill_ratio_count > 0 {
+        stats.average_fill_ratio = fill_ratio_sum / Decimal::from(fill_ratio_count);
+    }
+
+    stats
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::orders::fill::{EventSourceType, OrderFill};
+    use crate::core::orders::order::{OrderFillRole, OrderSide};
+    use chrono::Utc;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn create_order(status: OrderStatus, amount: Decimal, filled_amount: Decimal) -> OrderSnapshot {
+        let mut order = OrderSnapshot::test_default();
+        let mut header = (*order.header).clone();
+        header.amount = amount;
+        order.header = Arc::new(header);
+        order.set_status(status, Utc::now());
+
+        if filled_amount > dec!(0) {
+            let price = dec!(2);
+            order.add_fill(OrderFill::new(
+                Uuid::new_v4(),
+                Utc::now(),
+                OrderFillType::UserTrade,
+                Some("trade_id".into()),
+                price,
+                filled_amount,
+                price * filled_amount,
+                OrderFillRole::Maker,
+                "BTC".into(),
+                dec!(0),
+                dec!(0),
+                "BTC".into(),
+                dec!(0),
+                dec!(0),
+                false,
+                Some(EventSourceType::WebSocket),
+                Some(OrderSide::Buy),
+            ));
+        }
+
+        order
+    }
+
+    #[test]
+    fn aggregate_over_mixed_orders() {
+        let orders = vec![
+            create_order(OrderStatus::Created, dec!(4), dec!(1)),
+            create_order(OrderStatus::Created, dec!(2), dec!(0)),
+            create_order(OrderStatus::Completed, dec!(2), dec!(2)),
+            create_order(OrderStatus::Canceled, dec!(4), dec!(0)),
+        ];
+
+        let stats = order_statistics(&orders);
+
+        assert_eq!(stats.counts_by_status[&OrderStatus::Created], 2);
+        assert_eq!(stats.counts_by_status[&OrderStatus::Completed], 1);
+        assert_eq!(stats.counts_by_status[&OrderStatus::Canceled], 1);
+        assert_eq!(stats.counts_by_status.get(&OrderStatus::Creating), None);
+        // price of each fill is 2
+        assert_eq!(stats.filled_notional, dec!(6));
+        assert_eq!(stats.live_count, 2);
+        // (0.25 + 0 + 1 + 0) / 4
+        assert_eq!(stats.average_fill_ratio, dec!(0.3125));
+    }
+
+    #[test]
+    fn empty_orders() {
+        let stats = order_statistics(&[]);
+
+        assert_eq!(stats, OrderStats::default());
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+
+use super::order::{ClientOrderId, OrderSnapshot};
+use super::order_migrations::migrate_order_snapshot;
+
+/// Persistent storage of order snapshots
+pub trait OrderStore: Send + Sync {
+    /// Insert or replace snapshot of order with the same client order id
+    fn save(&self, order: &OrderSnapshot) -> Result<()>;
+
+    fn load_all(&self) -> Result<Vec<OrderSnapshot>>;
+
+    /// Removing of absent order is not an error
+    fn remove(&self, client_order_id: &ClientOrderId) -> Result<()>;
+}
+
+/// Keeps orders in memory only, so they are lost on restart
+#[derive(Default)]
+pub struct InMemoryOrderStore {
+    orders: Mutex<HashMap<ClientOrderId, OrderSnapshot>>,
+}
+
+impl OrderStore for InMemoryOrderStore {
+    fn save(&self, order: &OrderSnapshot) -> Result<()> {
+        let _ = self
+            .orders
+            .lock()
+            .insert(order.header.client_order_id.clone(), order.clone());
+        Ok(())
+    }
+
+    fn load_all(&self) -> Result<Vec<OrderSnapshot>> {
+        Ok(self.orders.lock().values().cloned().collect())
+    }
+
+    fn remove(&self, client_order_id: &ClientOrderId) -> Result<()> {
+        let _ = self.orders.lock().remove(client_order_id);
+        Ok(())
+    }
+}
+
+/// Stores each order as separate JSON file in directory
+pub struct FileOrderStore {
+    directory: PathBuf,
+}
+
+impl FileOrderStore {
+    pub fn new(directory: impl AsRef<Path>) -> Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory).with_context(|| {
+            format!("Unable to create orders directory {}", directory.display())
+        })?;
+
+        Ok(FileOrderStore { directory })
+    }
+
+    fn order_path(&self, client_order_id: &ClientOrderId) -> PathBuf {
+        self.directory.join(format!("{}.json", client_order_id))
+    }
+}
+
+impl OrderStore for FileOrderStore {
+    fn save(&self, order: &OrderSnapshot) -> Result<()> {
+        let path = self.order_path(&order.header.client_order_id);
+        let content = serde_json::to_string(order)?;
+        fs::write(&path, content)
+            .with_context(|| format!("Unable to save order to {}", path.display()))
+    }
+
+    fn load_all(&self) -> Result<Vec<OrderSnapshot>> {
+        let mut orders = Vec::new();
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.extension().and_then(|x| x.to_str()) != Some("json") {
+                continue;
+            }
+
+            let content = fs::read_to_string(&path)?;
+            let order = serde_json::from_str(&content)
+                .map_err(anyhow::Error::from)
+                .and_then(migrate_order_snapshot)
+                .with_context(|| format!("Unable to parse order from {}", path.display()))?;
+            orders.push(order);
+        }
+
+        Ok(orders)
+    }
+
+    fn remove(&self, client_order_id: &ClientOrderId) -> Result<()> {
+        let path = self.order_path(client_order_id);
+        match fs::remove_file(&path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(error)
+                .with_context(|| format!("Unable to remove order file {}", path.display())),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+    use crate::core::exchanges::general::test_helper::ScriptedExchangeBuilder;
+    use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use crate::core::orders::order::{OrderCreating, OrderStatus, CURRENT_ORDER_VERSION};
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    fn check_round_trip(store: &dyn OrderStore) {
+        let first = OrderSnapshot::test_default();
+        let second = OrderSnapshot::test_default();
+        let first_id = first.header.client_order_id.clone();
+        let second_id = second.header.client_order_id.clone();
+
+        store.save(&first).expect("in test");
+        store.save(&second).expect("in test");
+        // saving again replaces snapshot
+        store.save(&first).expect("in test");
+
+        let mut loaded_ids: Vec<_> = store
+            .load_all()
+            .expect("in test")
+            .into_iter()
+            .map(|x| x.header.client_order_id.clone())
+            .collect();
+        loaded_ids.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        let mut expected_ids = vec![first_id.clone(), second_id.clone()];
+        expected_ids.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        assert_eq!(loaded_ids, expected_ids);
+
+        store.remove(&first_id).expect("in test");
+        store.remove(&first_id).expect("in test");
+
+        let loaded = store.load_all().expect("in test");
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].header.client_order_id, second_id);
+        assert_eq!(loaded[0].amount(), OrderSnapshot::test_default().amount());
+    }
+
+    #[test]
+    fn in_memory_round_trip() {
+        check_round_trip(&InMemoryOrderStore::default());
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let directory =
+            std::env::temp_dir().join(format!("mmb_order_store_{}", uuid::Uuid::new_v4()));
+        let store = FileOrderStore::new(&directory).expect("in test");
+
+        check_round_trip(&store);
+
+        let _ = fs::remove_dir_all(directory);
+    }
+
+    #[test]
+    fn file_store_migrates_header_of_loaded_order() {
+        let directory =
+            std::env::temp_dir().join(format!("mmb_order_store_{}", uuid::Uuid::new_v4()));
+        let store = FileOrderStore::new(&directory).expect("in test");
+        let order = OrderSnapshot::test_default();
+        let mut value = serde_json::to_value(&order).expect("in test");
+        let header = value["header"].as_object_mut().expect("in test");
+        let _ = header.insert("version".to_owned(), 1.into());
+        let _ = header.remove("time_in_force");
+        let _ = header.remove("oco_group_id");
+        fs::write(
+            store.order_path(&order.header.client_order_id),
+            value.to_string(),
+        )
+        .expect("in test");
+
+        let loaded = store.load_all().expect("in test");
+
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].header.version(), CURRENT_ORDER_VERSION);
+        assert_eq!(
+            loaded[0].header.client_order_id,
+            order.header.client_order_id
+        );
+
+        let _ = fs::remove_dir_all(directory);
+    }
+
+    #[actix_rt::test]
+    async fn exchange_saves_changed_orders_and_removes_finished() {
+        let (exchange, _rx) = ScriptedExchangeBuilder::new(ExchangeAccountId::new(
+            "local_exchange_account_id".into(),
+            0,
+        ))
+        .build();
+        let store = Arc::new(InMemoryOrderStore::default());
+        exchange.set_order_store(store.clone());
+
+        let mut header = (*OrderSnapshot::test_default().header).clone();
+        header.exchange_account_id = exchange.exchange_account_id.clone();
+        header.currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order_to_create = OrderCreating {
+            header: Arc::new(header),
+            price: dec!(0.1),
+        };
+
+        let order_ref = exchange
+            .create_order(&order_to_create, CancellationToken::default())
+            .await
+            .expect("in test");
+        let saved_orders = store.load_all().expect("in test");
+        assert_eq!(saved_orders.len(), 1);
+        assert_eq!(saved_orders[0].status(), OrderStatus::Created);
+
+        let _ = exchange
+            .start_cancel_order(&order_ref, CancellationToken::default())
+            .await
+            .expect("in test");
+        assert_eq!(order_ref.status(), OrderStatus::Canceled);
+        assert!(store.load_all().expect("in test").is_empty());
+    }
+}
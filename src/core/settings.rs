@@ -1,12 +1,72 @@
+use std::collections::HashMap;
+
 use crate::core::exchanges::common::{CurrencyCode, CurrencyPair, ExchangeAccountId};
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 
-use super::exchanges::common::Amount;
+use super::connectivity::reconnect_backoff::ReconnectBackoffSettings;
+use super::exchanges::common::{Amount, Price};
+use super::exchanges::events::CreationConfirmationSource;
+use super::exchanges::general::currency_pair_metadata::RoundingSettings;
+use super::exchanges::general::handlers::handle_cancel_order_succeeded::CancelFillReconciliation;
+use super::exchanges::general::order::cancel_escalation::CancelEscalationSettings;
+use super::exchanges::general::order::limit_then_market::OrderPlacementPolicy;
+use super::exchanges::general::order::modify::OrderModificationPreference;
+use super::exchanges::general::trading_window::TradingWindow;
+use super::exchanges::rest_retry::RestRetrySettings;
+use super::exchanges::timeouts::rate_limit_rules::RateLimitRules;
+use super::lifecycle::heartbeat::HeartbeatSettings;
+use super::lifecycle::kill_switch::KillSwitchSettings;
+use super::lifecycle::shutdown::ShutdownSettings;
+use super::orders::order::ClientOrderId;
+use super::orders::pruning::OrdersPruningSettings;
+use super::orders::reservation::ReservationSettings;
+use super::orders::sla_monitor::OrderSlaSettings;
 
 pub trait BaseStrategySettings {
     fn exchange_account_id(&self) -> ExchangeAccountId;
     fn currency_pair(&self) -> CurrencyPair;
     fn max_amount(&self) -> Amount;
+
+    /// Secondary account for orders which failed on primary one with retryable error
+    fn failover_account_id(&self) -> Option<ExchangeAccountId> {
+        None
+    }
+
+    fn order_placement_policy(&self) -> OrderPlacementPolicy {
+        OrderPlacementPolicy::default()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.max_amount() <= dec!(0) {
+            bail!(
+                "Strategy max_amount should be positive but got {}",
+                self.max_amount()
+            )
+        }
+
+        Ok(())
+    }
+}
+
+/// Strategy settings which are applied without restart of trading engine
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveStrategySettings {
+    pub max_amount: Amount,
+    pub failover_account_id: Option<ExchangeAccountId>,
+    pub order_placement_policy: OrderPlacementPolicy,
+}
+
+impl LiveStrategySettings {
+    pub fn new(settings: &dyn BaseStrategySettings) -> Self {
+        LiveStrategySettings {
+            max_amount: settings.max_amount(),
+            failover_account_id: settings.failover_account_id(),
+            order_placement_policy: settings.order_placement_policy(),
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
@@ -18,9 +78,115 @@ where
     pub core: CoreSettings,
 }
 
+impl<TStrategySettings> AppSettings<TStrategySettings>
+where
+    TStrategySettings: BaseStrategySettings + Clone,
+{
+    pub fn validate(&self) -> Result<()> {
+        self.core.validate()?;
+        self.core.validate_strategy(&self.strategy)
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CoreSettings {
     pub exchanges: Vec<ExchangeSettings>,
+    #[serde(default)]
+    pub reservations: ReservationSettings,
+    #[serde(default)]
+    pub shutdown: ShutdownSettings,
+    // how many exchanges can connect and subscribe to websocket streams simultaneously on startup,
+    // all at once if not specified
+    #[serde(default)]
+    pub max_concurrent_subscriptions: Option<usize>,
+    // alert about orders which stay live too long, disabled if not specified
+    #[serde(default)]
+    pub order_sla: Option<OrderSlaSettings>,
+    // removing of old finished orders from orders pools, they are kept forever if not specified
+    #[serde(default)]
+    pub orders_pruning: Option<OrdersPruningSettings>,
+    // periodic summary of engine state in logs, disabled if not specified
+    #[serde(default)]
+    pub heartbeat: Option<HeartbeatSettings>,
+    // graceful shutdown when specified file appears, disabled if not specified
+    #[serde(default)]
+    pub kill_switch: Option<KillSwitchSettings>,
+    // environment tag (e.g. `stg`, `prod`) prepended to every generated client order id
+    #[serde(default)]
+    pub client_order_id_env_prefix: Option<String>,
+}
+
+impl CoreSettings {
+    pub fn validate(&self) -> Result<()> {
+        let duplicates = self
+            .exchanges
+            .iter()
+            .map(|x| &x.exchange_account_id)
+            .duplicates()
+            .collect_vec();
+
+        if !duplicates.is_empty() {
+            bail!(
+                "Exchange accounts specified more than once in core.exchanges: {}",
+                duplicates.iter().join(", ")
+            )
+        }
+
+        if self.max_concurrent_subscriptions == Some(0) {
+            bail!("core.max_concurrent_subscriptions should be greater than 0")
+        }
+
+        if let Some(env_prefix) = &self.client_order_id_env_prefix {
+            ClientOrderId::validate_env_prefix(env_prefix)
+                .context("Invalid core.client_order_id_env_prefix")?;
+        }
+
+        for exchange_settings in &self.exchanges {
+            exchange_settings
+                .credentials()
+                .validate()
+                .with_context(|| {
+                    format!(
+                        "Invalid credentials for {}",
+                        exchange_settings.exchange_account_id
+                    )
+                })?;
+
+            exchange_settings.rate_limits.validate().with_context(|| {
+                format!(
+                    "Invalid rate limits for {}",
+                    exchange_settings.exchange_account_id
+                )
+            })?;
+
+            if exchange_settings.max_retained_fills_per_order == Some(0) {
+                bail!(
+                    "max_retained_fills_per_order for {} should be greater than 0",
+                    exchange_settings.exchange_account_id
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_strategy(&self, strategy: &dyn BaseStrategySettings) -> Result<()> {
+        strategy.validate()?;
+
+        let strategy_exchange_account_id = strategy.exchange_account_id();
+        if !self
+            .exchanges
+            .iter()
+            .any(|x| x.exchange_account_id == strategy_exchange_account_id)
+        {
+            bail!(
+                "Exchange {} used by strategy is not specified in core.exchanges",
+                strategy_exchange_account_id
+            )
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -49,9 +215,92 @@ pub struct ExchangeSettings {
     pub subscribe_to_market_data: bool,
     pub websocket_channels: Vec<String>,
     pub currency_pairs: Option<Vec<CurrencyPairSetting>>,
+    #[serde(default)]
+    pub rounding: RoundingSettings,
+    #[serde(default)]
+    pub websocket_reconnect_backoff: ReconnectBackoffSettings,
+    // retries of REST requests failed with server or network errors
+    #[serde(default)]
+    pub rest_retry: RestRetrySettings,
+    #[serde(default)]
+    pub creation_confirmation_source: CreationConfirmationSource,
+    // confirmation from non-preferred source is applied if preferred one isn't received during this timeout,
+    // without timeout order waits for confirmation from preferred source
+    #[serde(default)]
+    pub creation_confirmation_timeout_ms: Option<i64>,
+    #[serde(default)]
+    pub order_modification: OrderModificationPreference,
+    #[serde(default)]
+    pub cancel_fill_reconciliation: CancelFillReconciliation,
+    // UTC time ranges when orders can be placed, trading is not restricted if empty
+    #[serde(default)]
+    pub trading_window: TradingWindow,
+    // new orders are not placed while local clock drifts from exchange one more than this value
+    #[serde(default)]
+    pub max_clock_drift_ms: Option<i64>,
+    // shift of exchange time relative to system time, useful for simulation
+    #[serde(default)]
+    pub time_offset_ms: i64,
+    // broker/referral id attached to each created order
+    #[serde(default)]
+    pub broker_id: Option<String>,
+    // absolute net position by pair which orders can't exceed, not limited for other pairs
+    #[serde(default)]
+    pub max_positions: HashMap<CurrencyPair, Amount>,
+    // minimum `amount * price` of orders by pair, smaller orders are rejected before sending to exchange
+    #[serde(default)]
+    pub min_order_notionals: HashMap<CurrencyPair, Price>,
+    // local orders absent on exchange are considered orphaned only after this period since creation
+    #[serde(default = "default_orphan_grace_period_ms")]
+    pub orphan_grace_period_ms: i64,
+    // rate limit buckets of exchange account, requests are not limited if empty
+    #[serde(default)]
+    pub rate_limits: RateLimitRules,
+    // escalation of orders which can't be canceled, disabled if not specified
+    #[serde(default)]
+    pub cancel_escalation: Option<CancelEscalationSettings>,
+    // cancel-replace of order is deferred until this interval elapses since previous replacement
+    // of order with the same pair and side, not limited if zero
+    #[serde(default)]
+    pub min_replace_interval_ms: i64,
+    // older fills of order are aggregated into summary above this count, all fills are kept if not specified
+    #[serde(default)]
+    pub max_retained_fills_per_order: Option<usize>,
+}
+
+fn default_orphan_grace_period_ms() -> i64 {
+    5_000
+}
+
+/// API credentials of exchange account
+#[derive(Debug, Clone, PartialEq)]
+pub struct Credentials<'a> {
+    pub api_key: &'a str,
+    pub secret_key: &'a str,
+}
+
+impl Credentials<'_> {
+    pub fn validate(&self) -> Result<()> {
+        if self.api_key.trim().is_empty() {
+            bail!("api_key should not be empty")
+        }
+
+        if self.secret_key.trim().is_empty() {
+            bail!("secret_key should not be empty")
+        }
+
+        Ok(())
+    }
 }
 
 impl ExchangeSettings {
+    pub fn credentials(&self) -> Credentials<'_> {
+        Credentials {
+            api_key: &self.api_key,
+            secret_key: &self.secret_key,
+        }
+    }
+
     // only for tests
     pub fn new_short(
         exchange_account_id: ExchangeAccountId,
@@ -70,6 +319,24 @@ impl ExchangeSettings {
             websocket_channels: vec![],
             currency_pairs: None,
             subscribe_to_market_data: true,
+            rounding: RoundingSettings::default(),
+            websocket_reconnect_backoff: ReconnectBackoffSettings::default(),
+            rest_retry: RestRetrySettings::default(),
+            creation_confirmation_source: CreationConfirmationSource::default(),
+            creation_confirmation_timeout_ms: None,
+            order_modification: OrderModificationPreference::default(),
+            cancel_fill_reconciliation: CancelFillReconciliation::default(),
+            trading_window: TradingWindow::default(),
+            max_clock_drift_ms: None,
+            orphan_grace_period_ms: default_orphan_grace_period_ms(),
+            rate_limits: RateLimitRules::default(),
+            cancel_escalation: None,
+            min_replace_interval_ms: 0,
+            max_retained_fills_per_order: None,
+            broker_id: None,
+            max_positions: HashMap::new(),
+            min_order_notionals: HashMap::new(),
+            time_offset_ms: 0,
         }
     }
 }
@@ -87,6 +354,24 @@ impl Default for ExchangeSettings {
             websocket_channels: vec![],
             currency_pairs: None,
             subscribe_to_market_data: true,
+            rounding: RoundingSettings::default(),
+            websocket_reconnect_backoff: ReconnectBackoffSettings::default(),
+            rest_retry: RestRetrySettings::default(),
+            creation_confirmation_source: CreationConfirmationSource::default(),
+            creation_confirmation_timeout_ms: None,
+            order_modification: OrderModificationPreference::default(),
+            cancel_fill_reconciliation: CancelFillReconciliation::default(),
+            trading_window: TradingWindow::default(),
+            max_clock_drift_ms: None,
+            orphan_grace_period_ms: default_orphan_grace_period_ms(),
+            rate_limits: RateLimitRules::default(),
+            cancel_escalation: None,
+            min_replace_interval_ms: 0,
+            max_retained_fills_per_order: None,
+            broker_id: None,
+            max_positions: HashMap::new(),
+            min_order_notionals: HashMap::new(),
+            time_offset_ms: 0,
         }
     }
 }
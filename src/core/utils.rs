@@ -1,3 +1,10 @@
+use anyhow::{bail, Context, Result};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{de, Deserialize, Deserializer};
+use std::fmt;
+use std::str::FromStr;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub(crate) fn get_current_milliseconds() -> u128 {
@@ -6,3 +13,161 @@ pub(crate) fn get_current_milliseconds() -> u128 {
         .expect("Unable to get time since unix epoch started")
         .as_millis()
 }
+
+/// Deserialize `Decimal` with normalized scale (`0.50` -> `0.5`)
+pub(crate) fn deserialize_normalized_decimal<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Decimal, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    <Decimal as Deserialize>::deserialize(deserializer).map(|value| value.normalize())
+}
+
+/// Deserialize `Option<Decimal>` with normalized scale (`0.50` -> `0.5`)
+pub(crate) fn deserialize_normalized_option_decimal<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Decimal>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<Decimal>::deserialize(deserializer).map(|value| value.map(|x| x.normalize()))
+}
+
+/// Parse decimal with optional unit suffix: `k` (thousands), `m` (millions) or `%` (hundredths).
+/// E.g. `1.5k` -> `1500`, `0.1%` -> `0.001`
+pub fn parse_decimal_with_units(value: &str) -> Result<Decimal> {
+    let value = value.trim();
+    let (number, multiplier) = match value.chars().last() {
+        Some('k') | Some('K') => (&value[..value.len() - 1], dec!(1000)),
+        Some('m') | Some('M') => (&value[..value.len() - 1], dec!(1000000)),
+        Some('%') => (&value[..value.len() - 1], dec!(0.01)),
+        Some(last) if !last.is_ascii_digit() && last != '.' => {
+            bail!(
+                "Unknown unit suffix '{}' in decimal value '{}'",
+                last,
+                value
+            )
+        }
+        _ => (value, dec!(1)),
+    };
+
+    let number = Decimal::from_str(number.trim())
+        .with_context(|| format!("Unable to parse decimal value '{}'", value))?;
+
+    Ok((number * multiplier).normalize())
+}
+
+/// Deserialize `Decimal` from number or from string with unit suffix supported by `parse_decimal_with_units`
+pub fn deserialize_decimal_with_units<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Decimal, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct DecimalWithUnitsVisitor;
+
+    impl<'de> de::Visitor<'de> for DecimalWithUnitsVisitor {
+        type Value = Decimal;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a decimal number or a string with optional unit suffix")
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<Decimal, E> {
+            Ok(Decimal::from(value))
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<Decimal, E> {
+            Ok(Decimal::from(value))
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> std::result::Result<Decimal, E> {
+            Decimal::from_f64(value)
+                .map(|x| x.normalize())
+                .ok_or_else(|| E::custom(format!("Unable to convert {} to decimal", value)))
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<Decimal, E> {
+            parse_decimal_with_units(value).map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_any(DecimalWithUnitsVisitor)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_decimal_with_multiplier() {
+        assert_eq!(
+            parse_decimal_with_units("1.5k").expect("in test"),
+            dec!(1500)
+        );
+        assert_eq!(
+            parse_decimal_with_units("2m").expect("in test"),
+            dec!(2000000)
+        );
+        assert_eq!(
+            parse_decimal_with_units("0.25").expect("in test"),
+            dec!(0.25)
+        );
+    }
+
+    #[test]
+    fn parse_decimal_with_percent() {
+        assert_eq!(
+            parse_decimal_with_units("0.1%").expect("in test"),
+            dec!(0.001)
+        );
+    }
+
+    #[test]
+    fn parse_decimal_with_invalid_suffix() {
+        let error = parse_decimal_with_units("1.5x").expect_err("in test");
+        assert_eq!(
+            error.to_string(),
+            "Unknown unit suffix 'x' in decimal value '1.5x'"
+        );
+    }
+
+    #[test]
+    fn deserialize_settings_with_units() {
+        #[derive(Deserialize)]
+        struct Settings {
+            #[serde(deserialize_with = "deserialize_decimal_with_units")]
+            max_amount: Decimal,
+            #[serde(deserialize_with = "deserialize_decimal_with_units")]
+            spread: Decimal,
+            #[serde(deserialize_with = "deserialize_decimal_with_units")]
+            fee: Decimal,
+        }
+
+        let settings: Settings =
+            toml::from_str("max_amount = \"1.5k\"\nspread = \"0.1%\"\nfee = 0.5").expect("in test");
+
+        assert_eq!(settings.max_amount, dec!(1500));
+        assert_eq!(settings.spread, dec!(0.001));
+        assert_eq!(settings.fee, dec!(0.5));
+    }
+
+    #[test]
+    fn deserialize_numbers_with_units_without_precision_loss() {
+        #[derive(Deserialize)]
+        struct Settings {
+            #[serde(deserialize_with = "deserialize_decimal_with_units")]
+            max_amount: Decimal,
+            #[serde(deserialize_with = "deserialize_decimal_with_units")]
+            fee: Decimal,
+        }
+
+        let settings: Settings =
+            serde_json::from_str(r#"{"max_amount": 12345678901234567, "fee": 0.1}"#)
+                .expect("in test");
+
+        assert_eq!(settings.max_amount, dec!(12345678901234567));
+        assert_eq!(settings.fee, dec!(0.1));
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use log::warn;
 use rust_decimal::Decimal;
 
 use crate::core::disposition_execution::{
@@ -11,11 +12,15 @@ use crate::core::exchanges::common::{
 };
 use crate::core::explanation::{Explanation, WithExplanation};
 use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::lifecycle::shutdown_policy::ShutdownPolicy;
 use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
 use crate::core::orders::order::{OrderRole, OrderSide, OrderSnapshot};
 use crate::core::DateTime;
 
 pub trait DispositionStrategy: Send + Sync + 'static {
+    /// Called once by disposition executor before handling of events with trade place of strategy
+    fn init(&mut self, _exchange_account_id: &ExchangeAccountId, _currency_pair: &CurrencyPair) {}
+
     fn calculate_trading_context(
         &mut self,
         max_amount: Decimal,
@@ -31,6 +36,10 @@ pub trait DispositionStrategy: Send + Sync + 'static {
         target_eai: &ExchangeAccountId,
         cancellation_token: CancellationToken,
     ) -> Result<()>;
+
+    fn shutdown_policy(&self) -> ShutdownPolicy {
+        ShutdownPolicy::default()
+    }
 }
 
 pub struct ExampleStrategy {
@@ -96,6 +105,16 @@ impl DispositionStrategy for ExampleStrategy {
         local_snapshots_service: &LocalSnapshotsService,
         explanation: &mut Explanation,
     ) -> Option<TradingContext> {
+        let snapshot = local_snapshots_service.get_snapshot(self.trade_place())?;
+        if snapshot.is_crossed() || snapshot.is_locked() {
+            // quoting on stale or bad data is dangerous
+            warn!(
+                "Order book for {:?} is crossed or locked, so orders are not placed",
+                self.trade_place()
+            );
+            return None;
+        }
+
         let buy_trading_ctx = self.calc_trading_context_by_side(
             OrderSide::Buy,
             max_amount,
@@ -1 +1,2 @@
 pub mod disposition_strategy;
+pub mod signal_mapping;
@@ -0,0 +1,146 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use crate::core::exchanges::common::{Amount, CurrencyPair, ExchangeAccountId, Price};
+use crate::core::orders::order::{
+    ClientOrderId, OrderCreating, OrderExecutionType, OrderHeader, OrderSide, OrderType,
+};
+use crate::core::utils::deserialize_decimal_with_units;
+use crate::core::DateTime;
+
+/// External trading signal. Positive strength means buying, negative one means selling
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signal {
+    pub signal_id: String,
+    pub strength: Decimal,
+}
+
+/// How signals are mapped to parameters of orders
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SignalMapping {
+    // order amount per unit of signal strength, unit suffixes like `1.5k` are supported
+    #[serde(deserialize_with = "deserialize_decimal_with_units")]
+    pub amount_multiplier: Decimal,
+    // distance of order price from reference price away from market
+    #[serde(default, deserialize_with = "deserialize_decimal_with_units")]
+    pub price_offset_bps: Decimal,
+}
+
+impl SignalMapping {
+    pub fn side(signal: &Signal) -> Option<OrderSide> {
+        if signal.strength > dec!(0) {
+            Some(OrderSide::Buy)
+        } else if signal.strength < dec!(0) {
+            Some(OrderSide::Sell)
+        } else {
+            None
+        }
+    }
+
+    pub fn amount(&self, signal: &Signal) -> Amount {
+        signal.strength.abs() * self.amount_multiplier
+    }
+
+    pub fn price(&self, reference_price: Price, side: OrderSide) -> Price {
+        let offset = reference_price * self.price_offset_bps / dec!(10000);
+        match side {
+            OrderSide::Buy => reference_price - offset,
+            OrderSide::Sell => reference_price + offset,
+        }
+    }
+
+    /// Limit order for signal marked with its `signal_id`. Signal with zero strength produces no order
+    pub fn create_order(
+        &self,
+        signal: &Signal,
+        reference_price: Price,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+        strategy_name: &str,
+        init_time: DateTime,
+    ) -> Option<OrderCreating> {
+        let side = Self::side(signal)?;
+
+        let header = OrderHeader::new(
+            ClientOrderId::unique_id(),
+            init_time,
+            exchange_account_id,
+            currency_pair,
+            OrderType::Limit,
+            side,
+            self.amount(signal),
+            OrderExecutionType::None,
+            None,
+            Some(signal.signal_id.clone()),
+            strategy_name.to_owned(),
+        );
+
+        Some(OrderCreating {
+            header,
+            price: self.price(reference_price, side),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Utc;
+
+    fn mapping() -> SignalMapping {
+        SignalMapping {
+            amount_multiplier: dec!(0.5),
+            price_offset_bps: dec!(10),
+        }
+    }
+
+    fn create_order(strength: Decimal) -> Option<OrderCreating> {
+        let signal = Signal {
+            signal_id: "signal1".into(),
+            strength,
+        };
+
+        mapping().create_order(
+            &signal,
+            dec!(100),
+            ExchangeAccountId::new("Binance".into(), 0),
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            "StrategyInUnitTests",
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    fn map_sell_signal_to_order() {
+        let order = create_order(dec!(-3)).expect("in test");
+
+        assert_eq!(order.header.side, OrderSide::Sell);
+        assert_eq!(order.header.amount, dec!(1.5));
+        assert_eq!(order.price, dec!(100.1));
+        assert_eq!(order.header.signal_id, Some("signal1".to_owned()));
+    }
+
+    #[test]
+    fn map_buy_signal_to_order() {
+        let order = create_order(dec!(2)).expect("in test");
+
+        assert_eq!(order.header.side, OrderSide::Buy);
+        assert_eq!(order.header.amount, dec!(1));
+        assert_eq!(order.price, dec!(99.9));
+    }
+
+    #[test]
+    fn no_order_for_zero_signal() {
+        assert!(create_order(dec!(0)).is_none());
+    }
+
+    #[test]
+    fn deserialize_mapping_with_units() {
+        let mapping: SignalMapping =
+            toml::from_str("amount_multiplier = \"1.5k\"\nprice_offset_bps = 10").expect("in test");
+
+        assert_eq!(mapping.amount_multiplier, dec!(1500));
+        assert_eq!(mapping.price_offset_bps, dec!(10));
+    }
+}
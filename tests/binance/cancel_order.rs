@@ -53,6 +53,7 @@ async fn cancelled_successfully() {
             true,
             AllowedEventSourceType::default(),
             AllowedEventSourceType::default(),
+            ExchangeCapabilities::default(),
         ),
         tx,
         application_manager,
@@ -157,6 +158,7 @@ async fn nothing_to_cancel() {
             true,
             AllowedEventSourceType::default(),
             AllowedEventSourceType::default(),
+            ExchangeCapabilities::default(),
         ),
         tx,
         application_manager,
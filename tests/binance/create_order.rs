@@ -56,6 +56,7 @@ async fn create_successfully() {
             true,
             AllowedEventSourceType::default(),
             AllowedEventSourceType::default(),
+            ExchangeCapabilities::default(),
         ),
         tx,
         application_manager,
@@ -164,6 +165,7 @@ async fn should_fail() {
             true,
             AllowedEventSourceType::default(),
             AllowedEventSourceType::default(),
+            ExchangeCapabilities::default(),
         ),
         tx,
         application_manager,
@@ -54,6 +54,7 @@ async fn open_orders_exists() {
             true,
             AllowedEventSourceType::default(),
             AllowedEventSourceType::default(),
+            ExchangeCapabilities::default(),
         ),
         tx,
         application_manager,
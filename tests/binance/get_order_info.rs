@@ -53,6 +53,7 @@ async fn get_order_info() {
             true,
             AllowedEventSourceType::default(),
             AllowedEventSourceType::default(),
+            ExchangeCapabilities::default(),
         ),
         tx,
         application_manager,
@@ -11,6 +11,7 @@ use mmb_lib::core::{
     exchanges::events::AllowedEventSourceType,
     exchanges::general::commission::Commission,
     exchanges::general::exchange::Exchange,
+    exchanges::general::features::ExchangeCapabilities,
     exchanges::general::features::ExchangeFeatures,
     exchanges::general::features::OpenOrdersType,
     exchanges::timeouts::timeout_manager::TimeoutManager,
@@ -55,6 +56,7 @@ pub async fn should_connect_and_reconnect_normally() {
             true,
             AllowedEventSourceType::default(),
             AllowedEventSourceType::default(),
+            ExchangeCapabilities::default(),
         ),
         tx,
         application_manager,
@@ -55,6 +55,7 @@ async fn cancellation_waited_successfully() {
             true,
             AllowedEventSourceType::default(),
             AllowedEventSourceType::default(),
+            ExchangeCapabilities::default(),
         ),
         tx,
         application_manager,
@@ -152,6 +153,7 @@ async fn cancellation_waited_failed_fallback() {
             true,
             AllowedEventSourceType::default(),
             AllowedEventSourceType::FallbackOnly,
+            ExchangeCapabilities::default(),
         ),
         tx,
         application_manager,
@@ -10,7 +10,10 @@ use mmb_lib::core::orders::order::OrderSnapshot;
 use mmb_lib::core::settings::BaseStrategySettings;
 use mmb_lib::core::{
     exchanges::common::Amount,
-    lifecycle::launcher::{launch_trading_engine, EngineBuildConfig, InitSettings},
+    lifecycle::launcher::{
+        launch_trading_engine, launch_trading_engine_with_strategies, EngineBuildConfig,
+        InitSettings, StrategyRegistration,
+    },
     DateTime,
 };
 use mmb_lib::core::{
@@ -18,6 +21,7 @@ use mmb_lib::core::{
     infrastructure::spawn_future,
 };
 use mmb_lib::strategies::disposition_strategy::DispositionStrategy;
+use parking_lot::Mutex;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
@@ -28,6 +32,25 @@ use tokio::time::sleep;
 #[derive(Default, Clone, Debug, Deserialize, Serialize)]
 pub struct TestStrategySettings {}
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PairStrategySettings {
+    currency_pair: CurrencyPair,
+}
+
+impl BaseStrategySettings for PairStrategySettings {
+    fn exchange_account_id(&self) -> ExchangeAccountId {
+        "Binance0".parse().expect("for testing")
+    }
+
+    fn currency_pair(&self) -> CurrencyPair {
+        self.currency_pair.clone()
+    }
+
+    fn max_amount(&self) -> Amount {
+        dec!(1)
+    }
+}
+
 impl BaseStrategySettings for TestStrategySettings {
     fn exchange_account_id(&self) -> ExchangeAccountId {
         "Binance0".parse().expect("for testing")
@@ -98,3 +121,95 @@ async fn launch_engine() -> Result<()> {
 
     Ok(())
 }
+
+#[actix_rt::test]
+async fn launch_engine_with_two_strategies() -> Result<()> {
+    struct InitRecordingStrategy {
+        initialized: Arc<Mutex<Vec<(ExchangeAccountId, CurrencyPair)>>>,
+    }
+
+    impl DispositionStrategy for InitRecordingStrategy {
+        fn init(&mut self, exchange_account_id: &ExchangeAccountId, currency_pair: &CurrencyPair) {
+            self.initialized
+                .lock()
+                .push((exchange_account_id.clone(), currency_pair.clone()));
+        }
+
+        fn calculate_trading_context(
+            &mut self,
+            _max_amount: Decimal,
+            _now: DateTime,
+            _local_snapshots_service: &LocalSnapshotsService,
+            _explanation: &mut Explanation,
+        ) -> Option<TradingContext> {
+            None
+        }
+
+        fn handle_order_fill(
+            &self,
+            _cloned_order: &Arc<OrderSnapshot>,
+            _price_slot: &PriceSlot,
+            _target_eai: &ExchangeAccountId,
+            _cancellation_token: CancellationToken,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    let config = EngineBuildConfig::standard();
+
+    let settings = parse_settings::<TestStrategySettings>(
+        include_str!("lifecycle.toml"),
+        include_str!("lifecycle.cred.toml"),
+    )?;
+    let init_settings = InitSettings::Directly(settings);
+    let initialized = Arc::new(Mutex::new(Vec::new()));
+    let currency_pairs = [
+        CurrencyPair::from_codes("eos".into(), "btc".into()),
+        CurrencyPair::from_codes("eth".into(), "btc".into()),
+    ];
+    let engine = launch_trading_engine_with_strategies(&config, init_settings, |_| {
+        currency_pairs
+            .iter()
+            .map(|currency_pair| {
+                StrategyRegistration::new(
+                    PairStrategySettings {
+                        currency_pair: currency_pair.clone(),
+                    },
+                    Box::new(InitRecordingStrategy {
+                        initialized: initialized.clone(),
+                    }),
+                )
+            })
+            .collect()
+    })
+    .await?;
+
+    let context = engine.context();
+    let action = async move {
+        sleep(Duration::from_millis(200)).await;
+        context
+            .application_manager
+            .run_graceful_shutdown("test")
+            .await;
+
+        Ok(())
+    };
+    spawn_future(
+        "run graceful_shutdown in launch_engine_with_two_strategies test",
+        true,
+        action.boxed(),
+    );
+
+    engine.run().await;
+
+    let mut initialized_pairs = initialized
+        .lock()
+        .iter()
+        .map(|(_, currency_pair)| currency_pair.clone())
+        .collect::<Vec<_>>();
+    initialized_pairs.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    assert_eq!(initialized_pairs, currency_pairs);
+
+    Ok(())
+}